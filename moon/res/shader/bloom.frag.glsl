@@ -0,0 +1,33 @@
+#version 300 es
+precision highp float;
+
+uniform sampler2D uTex0;
+uniform vec2 uResolution;
+uniform float uThreshold;
+uniform float uIntensity;
+uniform float uRadius;
+
+in vec2 vTexCoord;
+
+out vec4 color;
+
+vec3 bright(vec2 uv) {
+    vec3 sampled = texture(uTex0, uv).rgb;
+    float luminance = dot(sampled, vec3(0.2126, 0.7152, 0.0722));
+    return sampled * max(luminance - uThreshold, 0.0) / max(luminance, 0.0001);
+}
+
+void main() {
+    vec2 texel = uRadius / uResolution;
+    vec3 glow = vec3(0.0);
+    float total = 0.0;
+    for (int x = -2; x <= 2; x++) {
+        for (int y = -2; y <= 2; y++) {
+            float weight = 1.0 / (1.0 + float(x * x + y * y));
+            glow += bright(vTexCoord + vec2(float(x), float(y)) * texel) * weight;
+            total += weight;
+        }
+    }
+    vec4 base = texture(uTex0, vTexCoord);
+    color = vec4(base.rgb + glow / total * uIntensity, base.a);
+}
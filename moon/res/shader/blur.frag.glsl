@@ -0,0 +1,23 @@
+#version 300 es
+precision highp float;
+
+uniform sampler2D uTex0;
+uniform vec2 uResolution;
+uniform vec2 uDirection;
+uniform float uRadius;
+
+in vec2 vTexCoord;
+
+out vec4 color;
+
+const float WEIGHTS[5] = float[](0.227027, 0.1945946, 0.1216216, 0.054054, 0.016216);
+
+void main() {
+    vec2 texel = uDirection * uRadius / uResolution;
+    vec4 result = texture(uTex0, vTexCoord) * WEIGHTS[0];
+    for (int i = 1; i < 5; i++) {
+        result += texture(uTex0, vTexCoord + texel * float(i)) * WEIGHTS[i];
+        result += texture(uTex0, vTexCoord - texel * float(i)) * WEIGHTS[i];
+    }
+    color = result;
+}
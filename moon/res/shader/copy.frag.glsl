@@ -0,0 +1,12 @@
+#version 300 es
+precision highp float;
+
+uniform sampler2D uTex0;
+
+in vec2 vTexCoord;
+
+out vec4 color;
+
+void main() {
+    color = texture(uTex0, vTexCoord);
+}
@@ -0,0 +1,25 @@
+#version 300 es
+precision highp float;
+
+uniform sampler2D uTex0;
+uniform vec2 uResolution;
+uniform float uScanlines;
+uniform float uScanlineIntensity;
+uniform float uCurvature;
+
+in vec2 vTexCoord;
+
+out vec4 color;
+
+void main() {
+    vec2 centered = vTexCoord * 2.0 - 1.0;
+    centered *= 1.0 + uCurvature * dot(centered.yx, centered.yx);
+    vec2 uv = centered * 0.5 + 0.5;
+    if (uv.x < 0.0 || uv.x > 1.0 || uv.y < 0.0 || uv.y > 1.0) {
+        color = vec4(0.0, 0.0, 0.0, 1.0);
+        return;
+    }
+    vec4 base = texture(uTex0, uv);
+    float scanline = sin(uv.y * uScanlines * 3.14159265) * 0.5 + 0.5;
+    color = vec4(base.rgb * mix(1.0, scanline, uScanlineIntensity), base.a);
+}
@@ -1,6 +1,8 @@
 #version 300 es
 precision highp float;
 
+#define MAX_LIGHTS 16
+
 uniform float uTime;
 uniform mat4 uModel;
 uniform mat4 uView;
@@ -10,12 +12,55 @@ uniform sampler2D uTex1;
 uniform vec3 uCamPos;
 uniform vec4 uColor;
 
+uniform vec4 uAmbient;
+uniform int uLightCount;
+uniform vec3 uLightPosition[MAX_LIGHTS];
+uniform vec4 uLightColor[MAX_LIGHTS];
+uniform vec4 uLightParams[MAX_LIGHTS];
+uniform vec2 uLightDirection[MAX_LIGHTS];
+uniform bool uUseNormalMap;
+
 in vec2 vPosition;
 in vec2 vTexCoord;
 in vec4 vColor;
 
 out vec4 color;
 
+vec3 lighting() {
+    vec3 normal = vec3(0.0, 0.0, 1.0);
+    if (uUseNormalMap) {
+        normal = normalize(texture(uTex1, vTexCoord).rgb * 2.0 - 1.0);
+    }
+
+    vec3 total = uAmbient.rgb * uAmbient.a;
+    for (int i = 0; i < MAX_LIGHTS; i++) {
+        if (i >= uLightCount) {
+            break;
+        }
+        // Params are (radius, falloff, cosine of inner angle, cosine of outer angle)
+        vec4 params = uLightParams[i];
+        vec2 offset = uLightPosition[i].xy - vPosition;
+        float distance = length(offset);
+        float attenuation = pow(clamp(1.0 - distance / params.x, 0.0, 1.0), params.y);
+
+        // Point lights have an outer angle cosine below -1.0, and are never cut off
+        if (params.w >= -1.0) {
+            float cosine = dot(-offset / max(distance, 0.0001), uLightDirection[i]);
+            attenuation *= smoothstep(params.w, params.z, cosine);
+        }
+
+        float diffuse = 1.0;
+        if (uUseNormalMap) {
+            vec3 direction = normalize(vec3(offset, uLightPosition[i].z));
+            diffuse = max(dot(normal, direction), 0.0);
+        }
+
+        total += uLightColor[i].rgb * uLightColor[i].a * attenuation * diffuse;
+    }
+    return total;
+}
+
 void main() {
-    color = texture(uTex0, vTexCoord) * vColor * uColor;
+    vec4 base = texture(uTex0, vTexCoord) * vColor * uColor;
+    color = vec4(base.rgb * lighting(), base.a);
 }
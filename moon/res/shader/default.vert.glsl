@@ -16,6 +16,7 @@ out vec4 vColor;
 
 void main() {
     gl_Position = uProj * uView * vec4(aPosition, 0.0, 1.0);
+    vPosition = aPosition;
     vTexCoord = aTexCoord;
     vColor = aColor;
 }
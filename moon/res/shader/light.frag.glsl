@@ -0,0 +1,26 @@
+#version 300 es
+precision highp float;
+
+uniform vec3 uLightPosition;
+uniform vec4 uLightColor;
+uniform vec4 uLightParams;
+uniform vec2 uLightDirection;
+
+in vec2 vPosition;
+
+out vec4 color;
+
+void main() {
+    // Params are (radius, falloff, cosine of inner angle, cosine of outer angle)
+    vec2 offset = uLightPosition.xy - vPosition;
+    float distance = length(offset);
+    float attenuation = pow(clamp(1.0 - distance / uLightParams.x, 0.0, 1.0), uLightParams.y);
+
+    // Point lights have an outer angle cosine below -1.0, and are never cut off
+    if (uLightParams.w >= -1.0) {
+        float cosine = dot(-offset / max(distance, 0.0001), uLightDirection);
+        attenuation *= smoothstep(uLightParams.w, uLightParams.z, cosine);
+    }
+
+    color = vec4(uLightColor.rgb * uLightColor.a * attenuation, 1.0);
+}
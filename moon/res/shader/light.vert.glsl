@@ -0,0 +1,13 @@
+#version 300 es
+
+layout (location = 0) in vec2 aPosition;
+
+uniform mat4 uView;
+uniform mat4 uProj;
+
+out vec2 vPosition;
+
+void main() {
+    gl_Position = uProj * uView * vec4(aPosition, 0.0, 1.0);
+    vPosition = aPosition;
+}
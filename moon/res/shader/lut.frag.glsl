@@ -0,0 +1,30 @@
+#version 300 es
+precision highp float;
+
+uniform sampler2D uTex0;
+uniform sampler2D uTex1;
+uniform float uLutSize;
+uniform float uIntensity;
+
+in vec2 vTexCoord;
+
+out vec4 color;
+
+// The LUT is laid out as a horizontal strip of `uLutSize` slices, each `uLutSize` pixels square.
+vec3 lookup(vec3 graded) {
+    float size = uLutSize;
+    float blue = graded.b * (size - 1.0);
+    float slice0 = floor(blue);
+    float slice1 = min(slice0 + 1.0, size - 1.0);
+    vec2 texel = vec2(1.0 / (size * size), 1.0 / size);
+    vec2 offset = vec2(graded.r * (size - 1.0), graded.g * (size - 1.0)) * texel + texel * 0.5;
+    vec3 color0 = texture(uTex1, vec2(slice0 / size, 0.0) + offset).rgb;
+    vec3 color1 = texture(uTex1, vec2(slice1 / size, 0.0) + offset).rgb;
+    return mix(color0, color1, blue - slice0);
+}
+
+void main() {
+    vec4 base = texture(uTex0, vTexCoord);
+    vec3 graded = lookup(clamp(base.rgb, 0.0, 1.0));
+    color = vec4(mix(base.rgb, graded, uIntensity), base.a);
+}
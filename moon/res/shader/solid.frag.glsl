@@ -0,0 +1,10 @@
+#version 300 es
+precision highp float;
+
+uniform vec4 uColor;
+
+out vec4 color;
+
+void main() {
+    color = uColor;
+}
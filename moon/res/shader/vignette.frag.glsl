@@ -0,0 +1,18 @@
+#version 300 es
+precision highp float;
+
+uniform sampler2D uTex0;
+uniform float uRadius;
+uniform float uSoftness;
+uniform float uIntensity;
+
+in vec2 vTexCoord;
+
+out vec4 color;
+
+void main() {
+    vec4 base = texture(uTex0, vTexCoord);
+    float distance = length(vTexCoord - 0.5);
+    float vignette = smoothstep(uRadius, uRadius - uSoftness, distance);
+    color = vec4(base.rgb * mix(1.0, vignette, uIntensity), base.a);
+}
@@ -0,0 +1,360 @@
+//! The [`ActionMap`] struct, used to map [`InputBindings`](InputBinding) to named actions and axes.
+
+use std::collections::BTreeMap;
+use std::fmt;
+use std::str::FromStr;
+
+/// Value above which an [`InputBinding`] counts as pressed.
+pub const ACTION_THRESHOLD: f32 = 0.5;
+
+/// A single physical input that can be bound to an action or axis.
+///
+/// Every [`InputBinding`] has a value from `0.0` to `1.0`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum InputBinding {
+    /// A keyboard key, by its key code.
+    Key(u8),
+    /// A mouse button, where `0` is the main button.
+    MouseButton(u8),
+    /// A gamepad button, by its index in the standard mapping.
+    GamepadButton(u8),
+    /// One direction of a gamepad axis, by its index in the standard mapping.
+    GamepadAxis {
+        /// Index of the axis.
+        axis: u8,
+        /// Whether the positive or negative half of the axis is used.
+        positive: bool,
+    },
+}
+
+impl fmt::Display for InputBinding {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Key(key_code) => write!(f, "key:{}", key_code),
+            Self::MouseButton(button) => write!(f, "mouse:{}", button),
+            Self::GamepadButton(button) => write!(f, "button:{}", button),
+            Self::GamepadAxis { axis, positive } => {
+                write!(f, "axis:{}{}", axis, if *positive { '+' } else { '-' })
+            }
+        }
+    }
+}
+
+impl FromStr for InputBinding {
+    type Err = String;
+
+    /// Parse an [`InputBinding`] in the same format it is displayed in, such as `key:87` or `axis:0-`.
+    fn from_str(binding: &str) -> Result<Self, Self::Err> {
+        let (kind, value) = binding
+            .split_once(':')
+            .ok_or_else(|| format!("Invalid binding '{}'.", binding))?;
+        let index = |value: &str| {
+            value
+                .parse::<u8>()
+                .map_err(|_| format!("Invalid index in binding '{}'.", binding))
+        };
+        match kind {
+            "key" => Ok(Self::Key(index(value)?)),
+            "mouse" => Ok(Self::MouseButton(index(value)?)),
+            "button" => Ok(Self::GamepadButton(index(value)?)),
+            "axis" => {
+                let positive = match value.chars().last() {
+                    Some('+') => true,
+                    Some('-') => false,
+                    _ => return Err(format!("Missing axis direction in '{}'.", binding)),
+                };
+                Ok(Self::GamepadAxis {
+                    axis: index(&value[..value.len() - 1])?,
+                    positive,
+                })
+            }
+            _ => Err(format!("Unknown binding type '{}'.", kind)),
+        }
+    }
+}
+
+/// The [`InputBindings`](InputBinding) of an axis, pulling it in the positive and negative directions.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct AxisBinding {
+    /// [`InputBindings`](InputBinding) that move the axis towards `1.0`.
+    pub positive: Vec<InputBinding>,
+    /// [`InputBindings`](InputBinding) that move the axis towards `-1.0`.
+    pub negative: Vec<InputBinding>,
+}
+
+/// Maps named actions and axes to one or more [`InputBindings`](InputBinding).
+///
+/// Bindings can be changed at runtime, and saved to or loaded from a text profile.
+///
+/// # Examples
+/// ```
+/// use moon_engine::action::{ActionMap, InputBinding};
+///
+/// let mut actions = ActionMap::new();
+/// actions.bind_action("jump", InputBinding::Key(b' '));
+/// actions.bind_action("jump", InputBinding::GamepadButton(0));
+///
+/// let profile = actions.to_profile();
+/// assert_eq!(ActionMap::from_profile(&profile), Ok(actions));
+/// ```
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct ActionMap {
+    actions: BTreeMap<String, Vec<InputBinding>>,
+    axes: BTreeMap<String, AxisBinding>,
+}
+
+impl ActionMap {
+    /// Create a new, empty [`ActionMap`].
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Add an [`InputBinding`] to an action, creating the action if needed.
+    pub fn bind_action(&mut self, action: &str, binding: InputBinding) {
+        let bindings = self.actions.entry(action.to_string()).or_default();
+        if !bindings.contains(&binding) {
+            bindings.push(binding);
+        }
+    }
+
+    /// Remove an [`InputBinding`] from an action, returning whether it was found.
+    pub fn unbind_action(&mut self, action: &str, binding: InputBinding) -> bool {
+        match self.actions.get_mut(action) {
+            Some(bindings) => {
+                let count = bindings.len();
+                bindings.retain(|bound| *bound != binding);
+                bindings.len() != count
+            }
+            None => false,
+        }
+    }
+
+    /// Replace all the [`InputBindings`](InputBinding) of an action.
+    pub fn set_action_bindings(&mut self, action: &str, bindings: Vec<InputBinding>) {
+        self.actions.insert(action.to_string(), bindings);
+    }
+
+    /// Get the [`InputBindings`](InputBinding) of an action.
+    pub fn action_bindings(&self, action: &str) -> &[InputBinding] {
+        self.actions
+            .get(action)
+            .map(|bindings| bindings.as_slice())
+            .unwrap_or(&[])
+    }
+
+    /// Remove an action, returning its [`InputBindings`](InputBinding) if it was found.
+    pub fn remove_action(&mut self, action: &str) -> Option<Vec<InputBinding>> {
+        self.actions.remove(action)
+    }
+
+    /// Add a pair of [`InputBindings`](InputBinding) to an axis, creating the axis if needed.
+    pub fn bind_axis(&mut self, axis: &str, positive: InputBinding, negative: InputBinding) {
+        let bindings = self.axes.entry(axis.to_string()).or_default();
+        if !bindings.positive.contains(&positive) {
+            bindings.positive.push(positive);
+        }
+        if !bindings.negative.contains(&negative) {
+            bindings.negative.push(negative);
+        }
+    }
+
+    /// Replace all the [`InputBindings`](InputBinding) of an axis.
+    pub fn set_axis_bindings(&mut self, axis: &str, bindings: AxisBinding) {
+        self.axes.insert(axis.to_string(), bindings);
+    }
+
+    /// Get the [`AxisBinding`] of an axis.
+    pub fn axis_bindings(&self, axis: &str) -> Option<&AxisBinding> {
+        self.axes.get(axis)
+    }
+
+    /// Remove an axis, returning its [`AxisBinding`] if it was found.
+    pub fn remove_axis(&mut self, axis: &str) -> Option<AxisBinding> {
+        self.axes.remove(axis)
+    }
+
+    /// Replace one [`InputBinding`] with another, wherever it is used by an action or axis with the given name.
+    ///
+    /// Fails if there is no such action or axis, or if it does not use the old [`InputBinding`].
+    pub fn rebind(
+        &mut self,
+        name: &str,
+        old: InputBinding,
+        new: InputBinding,
+    ) -> Result<(), String> {
+        let action = self.actions.get_mut(name).into_iter().flatten();
+        let axis = self
+            .axes
+            .get_mut(name)
+            .into_iter()
+            .flat_map(|axis| axis.positive.iter_mut().chain(axis.negative.iter_mut()));
+        let mut found = false;
+        for binding in action.chain(axis).filter(|binding| **binding == old) {
+            *binding = new;
+            found = true;
+        }
+        if found {
+            Ok(())
+        } else {
+            Err(format!("'{}' is not bound to {}.", name, old))
+        }
+    }
+
+    /// Get an iterator over the names of all actions.
+    pub fn actions(&self) -> impl Iterator<Item = &str> {
+        self.actions.keys().map(|action| action.as_str())
+    }
+
+    /// Get an iterator over the names of all axes.
+    pub fn axes(&self) -> impl Iterator<Item = &str> {
+        self.axes.keys().map(|axis| axis.as_str())
+    }
+
+    /// Get the value of an action, as the strongest of its [`InputBindings`](InputBinding).
+    ///
+    /// The value of each [`InputBinding`] is read using a given function.
+    pub fn action_value(&self, action: &str, value: impl Fn(InputBinding) -> f32) -> f32 {
+        self.action_bindings(action)
+            .iter()
+            .map(|binding| value(*binding))
+            .fold(0.0, f32::max)
+    }
+
+    /// Get the value of an axis, from `-1.0` to `1.0`.
+    ///
+    /// The value of each [`InputBinding`] is read using a given function.
+    pub fn axis_value(&self, axis: &str, value: impl Fn(InputBinding) -> f32) -> f32 {
+        let strongest = |bindings: &[InputBinding]| {
+            bindings
+                .iter()
+                .map(|binding| value(*binding))
+                .fold(0.0, f32::max)
+        };
+        self.axes
+            .get(axis)
+            .map(|axis| (strongest(&axis.positive) - strongest(&axis.negative)).clamp(-1.0, 1.0))
+            .unwrap_or(0.0)
+    }
+
+    /// Save the [`ActionMap`] as a text profile, with one action or axis on each line.
+    ///
+    /// Actions are written as `action jump = key:32 button:0`, and axes as `axis move_x = key:68 axis:0+ | key:65 axis:0-`.
+    /// Whitespace, `=`, `#` and `%` in names are escaped as `%` followed by the hexadecimal value of each byte, such as `%20` for a space.
+    /// An empty name is written as a single `%`.
+    pub fn to_profile(&self) -> String {
+        let join = |bindings: &[InputBinding]| {
+            bindings
+                .iter()
+                .map(|binding| binding.to_string())
+                .collect::<Vec<String>>()
+                .join(" ")
+        };
+        let mut profile = String::new();
+        for (action, bindings) in self.actions.iter() {
+            profile.push_str(&format!(
+                "action {} = {}\n",
+                escape_name(action),
+                join(bindings)
+            ));
+        }
+        for (axis, bindings) in self.axes.iter() {
+            profile.push_str(&format!(
+                "axis {} = {} | {}\n",
+                escape_name(axis),
+                join(&bindings.positive),
+                join(&bindings.negative)
+            ));
+        }
+        profile
+    }
+
+    /// Load an [`ActionMap`] from a text profile, as written by [`to_profile`](ActionMap::to_profile).
+    ///
+    /// Empty lines, and lines starting with `#` are ignored.
+    pub fn from_profile(profile: &str) -> Result<Self, String> {
+        let parse = |bindings: &str| {
+            bindings
+                .split_whitespace()
+                .map(InputBinding::from_str)
+                .collect::<Result<Vec<InputBinding>, String>>()
+        };
+        let mut map = Self::new();
+        for (number, line) in profile.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let error = |message: String| format!("Line {}: {}", number + 1, message);
+            let (declaration, bindings) = line
+                .split_once('=')
+                .ok_or_else(|| error(String::from("Missing '='.")))?;
+            match declaration.split_whitespace().collect::<Vec<&str>>()[..] {
+                ["action", name] => {
+                    let name = unescape_name(name).map_err(error)?;
+                    map.set_action_bindings(&name, parse(bindings).map_err(error)?);
+                }
+                ["axis", name] => {
+                    let name = unescape_name(name).map_err(error)?;
+                    let (positive, negative) = bindings.split_once('|').unwrap_or((bindings, ""));
+                    map.set_axis_bindings(
+                        &name,
+                        AxisBinding {
+                            positive: parse(positive).map_err(error)?,
+                            negative: parse(negative).map_err(error)?,
+                        },
+                    );
+                }
+                _ => {
+                    return Err(error(format!(
+                        "Invalid declaration '{}'.",
+                        declaration.trim()
+                    )))
+                }
+            }
+        }
+        Ok(map)
+    }
+}
+
+/// Escape the characters of an action or axis name that would be misread in a profile.
+fn escape_name(name: &str) -> String {
+    if name.is_empty() {
+        return String::from("%");
+    }
+    let mut escaped = String::with_capacity(name.len());
+    for character in name.chars() {
+        if character.is_whitespace() || matches!(character, '=' | '#' | '%') {
+            let mut bytes = [0; 4];
+            for byte in character.encode_utf8(&mut bytes).bytes() {
+                escaped.push_str(&format!("%{:02X}", byte));
+            }
+        } else {
+            escaped.push(character);
+        }
+    }
+    escaped
+}
+
+/// Reverse [`escape_name`], failing on malformed escapes.
+fn unescape_name(name: &str) -> Result<String, String> {
+    if name == "%" {
+        return Ok(String::new());
+    }
+    let mut bytes = Vec::with_capacity(name.len());
+    let mut rest = name.as_bytes();
+    while let Some((&byte, tail)) = rest.split_first() {
+        if byte == b'%' {
+            let value = tail
+                .get(..2)
+                .and_then(|hex| std::str::from_utf8(hex).ok())
+                .and_then(|hex| u8::from_str_radix(hex, 16).ok())
+                .ok_or_else(|| format!("Invalid escape in name '{}'.", name))?;
+            bytes.push(value);
+            rest = &tail[2..];
+        } else {
+            bytes.push(byte);
+            rest = tail;
+        }
+    }
+    String::from_utf8(bytes).map_err(|_| format!("Invalid escape in name '{}'.", name))
+}
@@ -0,0 +1,533 @@
+//! The [`AssetServer`], which loads assets by path and hands out reference counted [`Handles`](Handle) to them.
+//!
+//! An asset is freed, along with any GPU resources it uses, as soon as the last [`Handle`] to it is dropped.
+
+use std::any::{Any, TypeId};
+use std::cell::RefCell;
+use std::collections::BTreeMap;
+use std::fmt;
+use std::rc::{Rc, Weak};
+
+use wasm_bindgen::prelude::*;
+use wasm_bindgen::JsCast;
+use web_sys::{HtmlImageElement, Response};
+
+use crate::audio::{Audio, SoundId};
+use crate::gl::GL;
+use crate::image::Image;
+use crate::texture::Texture;
+
+/// A unique identifier for a loaded asset.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct AssetId(pub u32);
+
+/// The loading state of an asset.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AssetState {
+    /// The asset is still being fetched or decoded.
+    Loading,
+    /// The asset is ready to be used.
+    Loaded,
+    /// The asset could not be loaded, with the reason why.
+    Failed(String),
+}
+
+/// How the data of an asset is fetched.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AssetKind {
+    /// The raw bytes of the file.
+    Bytes,
+    /// An image, decoded by the browser.
+    Image,
+}
+
+/// The data of an asset, as fetched by an [`AssetSource`].
+#[derive(Debug)]
+pub enum AssetData {
+    /// The raw bytes of the file.
+    Bytes(Vec<u8>),
+    /// An image, decoded by the browser.
+    Image(HtmlImageElement),
+}
+
+impl AssetData {
+    /// Get the raw bytes of the file, or an error if the data is an image.
+    pub fn into_bytes(self) -> Result<Vec<u8>, String> {
+        match self {
+            AssetData::Bytes(bytes) => Ok(bytes),
+            AssetData::Image(_) => Err(String::from("Expected bytes, found an image.")),
+        }
+    }
+}
+
+/// Resources that are needed to turn fetched data into assets.
+///
+/// Assets that need a missing resource fail to load.
+#[derive(Default)]
+pub struct LoadContext<'a> {
+    /// The WebGL context, used to create textures.
+    pub gl: Option<&'a GL>,
+    /// The [`Audio`] mixer, used to decode sounds.
+    pub audio: Option<&'a mut Audio>,
+}
+
+/// Frees what an asset holds outside of itself, such as a sound in the [`Audio`] mixer.
+pub type Release = Box<dyn FnOnce(&mut LoadContext)>;
+
+/// Trait for types that can be loaded by an [`AssetServer`].
+pub trait Asset: Sized + 'static {
+    /// How the data of the asset is fetched.
+    fn kind() -> AssetKind {
+        AssetKind::Bytes
+    }
+
+    /// Create the asset from its fetched data.
+    fn from_data(path: &str, data: AssetData, context: &mut LoadContext) -> Result<Self, String>;
+
+    /// Get a [`Release`] to call once the asset is freed, if it holds anything outside of itself.
+    ///
+    /// It is called by the next [`AssetServer::update`] after the asset is freed.
+    fn release(&self) -> Option<Release> {
+        None
+    }
+}
+
+impl Asset for Vec<u8> {
+    fn from_data(_path: &str, data: AssetData, _context: &mut LoadContext) -> Result<Self, String> {
+        data.into_bytes()
+    }
+}
+
+impl Asset for String {
+    fn from_data(path: &str, data: AssetData, _context: &mut LoadContext) -> Result<Self, String> {
+        String::from_utf8(data.into_bytes()?).map_err(|_| format!("{} is not valid UTF-8.", path))
+    }
+}
+
+/// The source code of a vertex or fragment shader.
+///
+/// A [`Shader`](crate::shader::Shader) can be created once both of its sources have loaded.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ShaderSource(pub String);
+
+impl Asset for ShaderSource {
+    fn from_data(path: &str, data: AssetData, context: &mut LoadContext) -> Result<Self, String> {
+        String::from_data(path, data, context).map(ShaderSource)
+    }
+}
+
+/// A sound decoded by the [`Audio`] mixer, named after its path.
+///
+/// Once freed, the sound is also freed from the mixer by the next [`AssetServer::update`], which should be given the mixer.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Sound(pub SoundId);
+
+impl Asset for Sound {
+    fn from_data(path: &str, data: AssetData, context: &mut LoadContext) -> Result<Self, String> {
+        let audio = context
+            .audio
+            .as_mut()
+            .ok_or_else(|| format!("No audio mixer to decode {} with.", path))?;
+        Ok(Sound(audio.load_sound(path, &data.into_bytes()?)))
+    }
+
+    fn release(&self) -> Option<Release> {
+        let sound = self.0;
+        Some(Box::new(move |context| {
+            if let Some(audio) = context.audio.as_mut() {
+                audio.free_sound(sound);
+            }
+        }))
+    }
+}
+
+impl Asset for Image {
+    fn from_data(_path: &str, data: AssetData, _context: &mut LoadContext) -> Result<Self, String> {
+        Image::decode(&data.into_bytes()?)
+    }
+}
+
+impl Asset for Texture {
+    fn kind() -> AssetKind {
+        AssetKind::Image
+    }
+
+    fn from_data(path: &str, data: AssetData, context: &mut LoadContext) -> Result<Self, String> {
+        let gl = context
+            .gl
+            .ok_or_else(|| format!("No WebGL context to create {} with.", path))?;
+        match data {
+            AssetData::Image(image) => Ok(Texture::new(gl, &image)),
+            AssetData::Bytes(bytes) => Texture::new_from_bytes(gl, &bytes),
+        }
+    }
+}
+
+/// The shared state of an asset, owned by its [`Handles`](Handle).
+#[derive(Debug)]
+struct AssetSlot<T> {
+    id: AssetId,
+    path: String,
+    state: RefCell<AssetState>,
+    value: RefCell<Option<Rc<T>>>,
+}
+
+/// A reference counted handle to an asset loaded by an [`AssetServer`].
+///
+/// The asset is freed when the last [`Handle`] to it is dropped, along with any [`Rc`] returned by [`get`](Handle::get).
+#[derive(Debug)]
+pub struct Handle<T> {
+    slot: Rc<AssetSlot<T>>,
+}
+
+impl<T> Clone for Handle<T> {
+    fn clone(&self) -> Self {
+        Self {
+            slot: self.slot.clone(),
+        }
+    }
+}
+
+impl<T> PartialEq for Handle<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.slot.id == other.slot.id
+    }
+}
+
+impl<T> Handle<T> {
+    /// Get the [`AssetId`] of the asset.
+    pub fn id(&self) -> AssetId {
+        self.slot.id
+    }
+
+    /// Get the path the asset was loaded from.
+    pub fn path(&self) -> &str {
+        &self.slot.path
+    }
+
+    /// Get the current [`AssetState`] of the asset.
+    pub fn state(&self) -> AssetState {
+        self.slot.state.borrow().clone()
+    }
+
+    /// Check whether the asset is ready to be used.
+    pub fn is_loaded(&self) -> bool {
+        *self.slot.state.borrow() == AssetState::Loaded
+    }
+
+    /// Get the asset, if it has loaded.
+    pub fn get(&self) -> Option<Rc<T>> {
+        self.slot.value.borrow().clone()
+    }
+
+    /// Get the number of [`Handles`](Handle) to the asset.
+    pub fn handle_count(&self) -> usize {
+        Rc::strong_count(&self.slot)
+    }
+}
+
+/// A callback given the fetched data of an asset, or an error.
+pub type FetchCallback = Box<dyn FnOnce(Result<AssetData, String>)>;
+
+/// Trait for the place assets are fetched from.
+pub trait AssetSource: fmt::Debug {
+    /// Start fetching the data at a path, calling a callback once it has been fetched.
+    fn fetch(&mut self, path: &str, kind: AssetKind, done: FetchCallback);
+}
+
+/// An [`AssetSource`] that fetches assets from the web server using the Fetch API.
+#[derive(Debug, Default)]
+pub struct WebAssetSource;
+
+impl WebAssetSource {
+    fn fetch_bytes(path: &str, done: FetchCallback) {
+        let window = match web_sys::window() {
+            Some(window) => window,
+            None => return done(Err(String::from("No window to fetch with."))),
+        };
+        let done = Rc::new(RefCell::new(Some(done)));
+        let finish = |done: &Rc<RefCell<Option<FetchCallback>>>, result| {
+            if let Some(done) = done.borrow_mut().take() {
+                done(result);
+            }
+        };
+
+        let path_name = path.to_string();
+        let on_error = {
+            let done = done.clone();
+            Closure::once(move |_: JsValue| {
+                finish(&done, Err(format!("Could not fetch {}.", path_name)))
+            })
+        };
+        let on_bytes = {
+            let done = done.clone();
+            Closure::once(move |buffer: JsValue| {
+                let bytes = js_sys::Uint8Array::new(&buffer).to_vec();
+                finish(&done, Ok(AssetData::Bytes(bytes)))
+            })
+        };
+        let path_name = path.to_string();
+        let on_response = Closure::once(move |response: JsValue| {
+            let response = response.unchecked_into::<Response>();
+            let body = match response.ok() {
+                true => response.array_buffer().ok(),
+                false => None,
+            };
+            match body {
+                Some(body) => {
+                    let _ = body.then2(&on_bytes, &on_error);
+                    on_bytes.forget();
+                    on_error.forget();
+                }
+                None => finish(
+                    &done,
+                    Err(format!(
+                        "Could not fetch {}, status {}.",
+                        path_name,
+                        response.status()
+                    )),
+                ),
+            }
+        });
+        let _ = window.fetch_with_str(path).then(&on_response);
+        on_response.forget();
+    }
+
+    fn fetch_image(path: &str, done: FetchCallback) {
+        let image = match HtmlImageElement::new() {
+            Ok(image) => image,
+            Err(_) => return done(Err(String::from("Could not create an image element."))),
+        };
+        let done = Rc::new(RefCell::new(Some(done)));
+
+        let on_load = {
+            let (done, image) = (done.clone(), image.clone());
+            Closure::once(move || {
+                if let Some(done) = done.borrow_mut().take() {
+                    done(Ok(AssetData::Image(image)));
+                }
+            })
+        };
+        let path_name = path.to_string();
+        let on_error = Closure::once(move || {
+            if let Some(done) = done.borrow_mut().take() {
+                done(Err(format!("Could not load image {}.", path_name)));
+            }
+        });
+        image.set_onload(Some(on_load.as_ref().unchecked_ref()));
+        image.set_onerror(Some(on_error.as_ref().unchecked_ref()));
+        on_load.forget();
+        on_error.forget();
+        image.set_src(path);
+    }
+}
+
+impl AssetSource for WebAssetSource {
+    fn fetch(&mut self, path: &str, kind: AssetKind, done: FetchCallback) {
+        match kind {
+            AssetKind::Bytes => Self::fetch_bytes(path, done),
+            AssetKind::Image => Self::fetch_image(path, done),
+        }
+    }
+}
+
+/// An [`AssetSource`] that serves files from memory.
+///
+/// Images cannot be decoded, so always fail to load.
+#[derive(Debug, Default, Clone)]
+pub struct MemoryAssetSource {
+    /// The contents of each file, by path.
+    pub files: BTreeMap<String, Vec<u8>>,
+}
+
+impl MemoryAssetSource {
+    /// Create a new, empty [`MemoryAssetSource`].
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Add a file to the [`MemoryAssetSource`].
+    pub fn insert(&mut self, path: &str, bytes: &[u8]) {
+        self.files.insert(path.to_string(), bytes.to_vec());
+    }
+}
+
+impl AssetSource for MemoryAssetSource {
+    fn fetch(&mut self, path: &str, kind: AssetKind, done: FetchCallback) {
+        let result = match (kind, self.files.get(path)) {
+            (AssetKind::Image, _) => Err(format!("Cannot decode image {} from memory.", path)),
+            (AssetKind::Bytes, Some(bytes)) => Ok(AssetData::Bytes(bytes.clone())),
+            (AssetKind::Bytes, None) => Err(format!("{} was not found.", path)),
+        };
+        done(result);
+    }
+}
+
+/// Fetched data of assets, waiting for the next [`AssetServer::update`].
+type FetchedQueue = Rc<RefCell<Vec<(AssetId, Result<AssetData, String>)>>>;
+
+/// A loaded asset, and the [`Release`] to call once it is freed.
+type Releasable = (Weak<dyn Any>, Release);
+
+/// Turns the fetched data of an asset into the asset, if any [`Handles`](Handle) to it are left.
+type PendingAsset =
+    Box<dyn FnOnce(Result<AssetData, String>, &mut LoadContext) -> Option<Releasable>>;
+
+/// Loads assets by path, and keeps track of their [`AssetState`].
+///
+/// Loading the same path twice as the same type returns a [`Handle`] to the same asset.
+///
+/// # Examples
+/// ```
+/// use moon_engine::asset::{AssetServer, AssetState, LoadContext, MemoryAssetSource};
+///
+/// let mut source = MemoryAssetSource::new();
+/// source.insert("level.txt", b"Hello");
+/// let mut assets = AssetServer::new_with_source(Box::new(source));
+///
+/// let level = assets.load::<String>("level.txt");
+/// assert_eq!(level.state(), AssetState::Loading);
+///
+/// assets.update(&mut LoadContext::default());
+/// assert_eq!(level.get().unwrap().as_str(), "Hello");
+/// ```
+pub struct AssetServer {
+    source: Box<dyn AssetSource>,
+    paths: BTreeMap<(TypeId, String), Weak<dyn Any>>,
+    pending: BTreeMap<AssetId, PendingAsset>,
+    fetched: FetchedQueue,
+    releases: Vec<Releasable>,
+    next_id: u32,
+}
+
+impl fmt::Debug for AssetServer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("AssetServer")
+            .field("source", &self.source)
+            .field("pending", &self.pending.len())
+            .finish()
+    }
+}
+
+impl Default for AssetServer {
+    fn default() -> Self {
+        Self::new_with_source(Box::new(WebAssetSource))
+    }
+}
+
+impl AssetServer {
+    /// Create a new [`AssetServer`] that fetches assets from the web server.
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Create a new [`AssetServer`] with an [`AssetSource`].
+    pub fn new_with_source(source: Box<dyn AssetSource>) -> Self {
+        Self {
+            source,
+            paths: BTreeMap::new(),
+            pending: BTreeMap::new(),
+            fetched: Default::default(),
+            releases: Vec::new(),
+            next_id: 0,
+        }
+    }
+
+    /// Start loading an asset from a path, returning a [`Handle`] to it immediately.
+    ///
+    /// Loading the same path again shares the asset, unless it failed to load, in which case it is retried with a new [`Handle`].
+    pub fn load<T: Asset>(&mut self, path: &str) -> Handle<T> {
+        let key = (TypeId::of::<T>(), path.to_string());
+        let existing = self.paths.get(&key).and_then(Weak::upgrade);
+        if let Some(slot) = existing.and_then(|slot| slot.downcast::<AssetSlot<T>>().ok()) {
+            // Assets that failed to load are fetched again, instead of sharing the error
+            if !matches!(*slot.state.borrow(), AssetState::Failed(_)) {
+                return Handle { slot };
+            }
+        }
+
+        let id = AssetId(self.next_id);
+        self.next_id += 1;
+        let slot = Rc::new(AssetSlot {
+            id,
+            path: path.to_string(),
+            state: RefCell::new(AssetState::Loading),
+            value: RefCell::new(None),
+        });
+        let any: Rc<dyn Any> = slot.clone();
+        self.paths.insert(key, Rc::downgrade(&any));
+
+        let weak = Rc::downgrade(&slot);
+        self.pending.insert(
+            id,
+            Box::new(move |result, context| {
+                // Nothing is created if every handle was dropped while loading
+                let slot = weak.upgrade()?;
+                let result = result.and_then(|data| T::from_data(&slot.path, data, context));
+                match result {
+                    Ok(value) => {
+                        let release = value.release();
+                        let value = Rc::new(value);
+                        *slot.value.borrow_mut() = Some(value.clone());
+                        *slot.state.borrow_mut() = AssetState::Loaded;
+                        let any: Rc<dyn Any> = value;
+                        release.map(|release| (Rc::downgrade(&any), release))
+                    }
+                    Err(error) => {
+                        *slot.state.borrow_mut() = AssetState::Failed(error);
+                        None
+                    }
+                }
+            }),
+        );
+
+        let fetched = self.fetched.clone();
+        self.source.fetch(
+            path,
+            T::kind(),
+            Box::new(move |result| fetched.borrow_mut().push((id, result))),
+        );
+        Handle { slot }
+    }
+
+    /// Finish loading any assets that have been fetched, and forget or [`release`](Asset::release) assets that have been freed.
+    pub fn update(&mut self, context: &mut LoadContext) {
+        let fetched = std::mem::take(&mut *self.fetched.borrow_mut());
+        for (id, result) in fetched {
+            if let Some(releasable) = self
+                .pending
+                .remove(&id)
+                .and_then(|pending| pending(result, context))
+            {
+                self.releases.push(releasable);
+            }
+        }
+        self.paths.retain(|_, slot| slot.strong_count() > 0);
+
+        let (freed, kept) = std::mem::take(&mut self.releases)
+            .into_iter()
+            .partition::<Vec<_>, _>(|(value, _)| value.strong_count() == 0);
+        self.releases = kept;
+        for (_, release) in freed {
+            release(context);
+        }
+    }
+
+    /// Get the number of assets that are still loading.
+    pub fn loading_count(&self) -> usize {
+        self.pending.len()
+    }
+
+    /// Check whether every asset has finished loading.
+    pub fn is_idle(&self) -> bool {
+        self.pending.is_empty()
+    }
+
+    /// Get the number of assets that are loading or loaded, and still have [`Handles`](Handle).
+    pub fn asset_count(&self) -> usize {
+        self.paths
+            .values()
+            .filter(|slot| slot.strong_count() > 0)
+            .count()
+    }
+}
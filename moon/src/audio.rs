@@ -0,0 +1,876 @@
+//! The [`Audio`] mixer, which plays sound effects and music through an [`AudioBackend`].
+
+use std::cell::RefCell;
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt;
+use std::rc::Rc;
+use std::str::FromStr;
+
+use wasm_bindgen::prelude::*;
+use wasm_bindgen::JsCast;
+use web_sys::{
+    AudioBuffer, AudioBufferSourceNode, AudioContext, AudioScheduledSourceNode, GainNode,
+    HtmlAudioElement, MediaElementAudioSourceNode, StereoPannerNode,
+};
+
+use crate::scene::{EntityId, SceneGraph};
+use crate::spatial::{Emitter, Listener};
+use crate::{Camera, Vec2};
+
+/// A handle to a sound asset, loaded with [`Audio::load_sound`] or [`Audio::load_stream`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct SoundId(pub u32);
+
+/// A handle to a single playing instance of a sound.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct VoiceId(pub u32);
+
+/// A mixer bus, with its own volume that applies to every sound played on it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Bus {
+    /// Applies to every sound.
+    Master,
+    /// Looping background music.
+    Music,
+    /// One-shot sound effects.
+    Sfx,
+}
+
+impl fmt::Display for Bus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Bus::Master => write!(f, "master"),
+            Bus::Music => write!(f, "music"),
+            Bus::Sfx => write!(f, "sfx"),
+        }
+    }
+}
+
+impl FromStr for Bus {
+    type Err = String;
+
+    fn from_str(name: &str) -> Result<Self, Self::Err> {
+        match name {
+            "master" => Ok(Bus::Master),
+            "music" => Ok(Bus::Music),
+            "sfx" => Ok(Bus::Sfx),
+            _ => Err(format!("Unknown audio bus {}.", name)),
+        }
+    }
+}
+
+/// The volume of a [`Bus`], and whether it is muted.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BusSettings {
+    /// Volume of the [`Bus`], where 1 is unchanged.
+    pub volume: f32,
+    /// A muted [`Bus`] is silent, but keeps its volume.
+    pub muted: bool,
+}
+
+impl Default for BusSettings {
+    fn default() -> Self {
+        Self {
+            volume: 1.0,
+            muted: false,
+        }
+    }
+}
+
+impl BusSettings {
+    /// Get the gain applied by the [`Bus`].
+    pub fn gain(&self) -> f32 {
+        if self.muted {
+            0.0
+        } else {
+            self.volume
+        }
+    }
+}
+
+/// Trait for the platform audio output used by the [`Audio`] mixer.
+///
+/// The mixer works out the final gain of every voice, so a backend only has to play, stop and change the gain of them.
+pub trait AudioBackend: fmt::Debug {
+    /// Start decoding encoded audio data, such as a WAV, MP3 or OGG file, into a sound.
+    ///
+    /// Decoding may finish later, at which point [`duration`](AudioBackend::duration) returns the length of the sound.
+    fn decode(&mut self, sound: SoundId, bytes: &[u8]);
+
+    /// Prepare a sound that is streamed from a URL while it plays, instead of being decoded up front.
+    fn stream(&mut self, sound: SoundId, url: &str);
+
+    /// Get the length of a sound in seconds, or [`None`] if it is not ready to be played yet.
+    ///
+    /// Streamed sounds have an unknown length, given as infinite, and are reported by [`finished_streams`](AudioBackend::finished_streams) when they end.
+    fn duration(&self, sound: SoundId) -> Option<f32>;
+
+    /// Get the voices playing a streamed sound that have reached its end, since the last call.
+    ///
+    /// This is called by the [`Audio`] mixer once each frame, with the time since the last frame.
+    fn finished_streams(&mut self, _delta_time: f32) -> Vec<VoiceId> {
+        Vec::new()
+    }
+
+    /// Free a sound that will not be played again, such as when another sound is loaded with its name.
+    ///
+    /// Voices that are already playing it may keep playing until they finish.
+    fn free(&mut self, _sound: SoundId) {}
+
+    /// Start playing a sound as a new voice.
+    fn play(
+        &mut self,
+        voice: VoiceId,
+        sound: SoundId,
+        gain: f32,
+        pitch: f32,
+        looping: bool,
+    ) -> Result<(), String>;
+
+    /// Change the gain of a playing voice.
+    fn set_gain(&mut self, voice: VoiceId, gain: f32);
+
+    /// Change the stereo pan of a playing voice, from -1 for fully left to 1 for fully right.
+    fn set_pan(&mut self, voice: VoiceId, pan: f32);
+
+    /// Stop a voice, and free anything used by it.
+    fn stop(&mut self, voice: VoiceId);
+
+    /// Resume audio output, which browsers only allow after the user has interacted with the page.
+    fn resume(&mut self) {}
+}
+
+/// An [`AudioBackend`] that does not output any sound.
+///
+/// Every sound is decoded immediately, and lasts the same amount of time.
+/// Streamed sounds report an unknown length, but still end after that time unless they loop.
+#[derive(Debug, Clone)]
+pub struct NullBackend {
+    /// The length of every decoded sound, in seconds.
+    pub sound_duration: f32,
+    sounds: BTreeMap<SoundId, f32>,
+    streams: BTreeMap<VoiceId, f32>,
+}
+
+impl Default for NullBackend {
+    fn default() -> Self {
+        Self::new_with_duration(1.0)
+    }
+}
+
+impl NullBackend {
+    /// Create a new [`NullBackend`], where every sound lasts a given number of seconds.
+    pub fn new_with_duration(sound_duration: f32) -> Self {
+        Self {
+            sound_duration,
+            sounds: BTreeMap::new(),
+            streams: BTreeMap::new(),
+        }
+    }
+}
+
+impl AudioBackend for NullBackend {
+    fn decode(&mut self, sound: SoundId, _bytes: &[u8]) {
+        self.sounds.insert(sound, self.sound_duration);
+    }
+
+    fn stream(&mut self, sound: SoundId, _url: &str) {
+        self.sounds.insert(sound, f32::INFINITY);
+    }
+
+    fn duration(&self, sound: SoundId) -> Option<f32> {
+        self.sounds.get(&sound).copied()
+    }
+
+    fn free(&mut self, sound: SoundId) {
+        self.sounds.remove(&sound);
+    }
+
+    fn finished_streams(&mut self, delta_time: f32) -> Vec<VoiceId> {
+        let mut finished = Vec::new();
+        self.streams.retain(|voice, remaining| {
+            *remaining -= delta_time;
+            if *remaining <= 0.0 {
+                finished.push(*voice);
+            }
+            *remaining > 0.0
+        });
+        finished
+    }
+
+    fn play(
+        &mut self,
+        voice: VoiceId,
+        sound: SoundId,
+        _: f32,
+        pitch: f32,
+        looping: bool,
+    ) -> Result<(), String> {
+        match self.sounds.get(&sound) {
+            Some(duration) => {
+                if duration.is_infinite() && !looping {
+                    self.streams.insert(voice, self.sound_duration / pitch);
+                }
+                Ok(())
+            }
+            None => Err(format!("Sound {} has not been decoded.", sound.0)),
+        }
+    }
+
+    fn set_gain(&mut self, _voice: VoiceId, _gain: f32) {}
+
+    fn set_pan(&mut self, _voice: VoiceId, _pan: f32) {}
+
+    fn stop(&mut self, voice: VoiceId) {
+        self.streams.remove(&voice);
+    }
+}
+
+/// The audio node playing a voice of a [`WebAudioBackend`].
+#[derive(Debug)]
+enum WebSource {
+    Buffer(AudioBufferSourceNode),
+    Stream(HtmlAudioElement, MediaElementAudioSourceNode),
+}
+
+/// The audio nodes of a voice of a [`WebAudioBackend`], connected in order.
+#[derive(Debug)]
+struct WebVoice {
+    source: WebSource,
+    panner: StereoPannerNode,
+    gain: GainNode,
+}
+
+/// An [`AudioBackend`] using the Web Audio API.
+#[derive(Debug)]
+pub struct WebAudioBackend {
+    context: AudioContext,
+    buffers: Rc<RefCell<BTreeMap<SoundId, AudioBuffer>>>,
+    decoding: Rc<RefCell<BTreeSet<SoundId>>>,
+    streams: BTreeMap<SoundId, String>,
+    voices: BTreeMap<VoiceId, WebVoice>,
+}
+
+impl WebAudioBackend {
+    /// Create a new [`WebAudioBackend`] with its own [`AudioContext`].
+    pub fn new() -> Result<Self, String> {
+        Ok(Self {
+            context: AudioContext::new().map_err(|_| "Could not create an AudioContext.")?,
+            buffers: Default::default(),
+            decoding: Default::default(),
+            streams: BTreeMap::new(),
+            voices: BTreeMap::new(),
+        })
+    }
+
+    fn create_source(
+        &self,
+        sound: SoundId,
+        pitch: f32,
+        looping: bool,
+    ) -> Result<WebSource, JsValue> {
+        if let Some(url) = self.streams.get(&sound) {
+            let element = HtmlAudioElement::new_with_src(url)?;
+            element.set_loop(looping);
+            element.set_playback_rate(pitch as f64);
+            let node = self.context.create_media_element_source(&element)?;
+            return Ok(WebSource::Stream(element, node));
+        }
+        let buffers = self.buffers.borrow();
+        let buffer = buffers
+            .get(&sound)
+            .ok_or_else(|| JsValue::from(format!("Sound {} has not been decoded.", sound.0)))?;
+        let node = self.context.create_buffer_source()?;
+        node.set_buffer(Some(buffer));
+        node.set_loop(looping);
+        node.playback_rate().set_value(pitch);
+        Ok(WebSource::Buffer(node))
+    }
+}
+
+impl AudioBackend for WebAudioBackend {
+    fn decode(&mut self, sound: SoundId, bytes: &[u8]) {
+        // The data is copied, since decoding takes ownership of the ArrayBuffer
+        let data = js_sys::Uint8Array::from(bytes).buffer();
+        let promise = match self.context.decode_audio_data(&data) {
+            Ok(promise) => promise,
+            Err(_) => return crate::console_log!("Could not decode sound {}.", sound.0),
+        };
+        self.decoding.borrow_mut().insert(sound);
+        let (buffers, decoding) = (self.buffers.clone(), self.decoding.clone());
+        let on_decoded = Closure::once(move |buffer: JsValue| {
+            // The sound may have been freed while it was being decoded
+            if !decoding.borrow_mut().remove(&sound) {
+                return;
+            }
+            if let Ok(buffer) = buffer.dyn_into::<AudioBuffer>() {
+                buffers.borrow_mut().insert(sound, buffer);
+            }
+        });
+        let decoding = self.decoding.clone();
+        let on_error = Closure::once(move |_: JsValue| {
+            decoding.borrow_mut().remove(&sound);
+            crate::console_log!("Could not decode sound {}.", sound.0);
+        });
+        let _ = promise.then2(&on_decoded, &on_error);
+        on_decoded.forget();
+        on_error.forget();
+    }
+
+    fn stream(&mut self, sound: SoundId, url: &str) {
+        self.streams.insert(sound, url.to_string());
+    }
+
+    fn free(&mut self, sound: SoundId) {
+        self.decoding.borrow_mut().remove(&sound);
+        self.buffers.borrow_mut().remove(&sound);
+        self.streams.remove(&sound);
+    }
+
+    fn duration(&self, sound: SoundId) -> Option<f32> {
+        if self.streams.contains_key(&sound) {
+            return Some(f32::INFINITY);
+        }
+        self.buffers
+            .borrow()
+            .get(&sound)
+            .map(|buffer| buffer.duration() as f32)
+    }
+
+    fn finished_streams(&mut self, _delta_time: f32) -> Vec<VoiceId> {
+        self.voices
+            .iter()
+            .filter_map(|(voice, web_voice)| match &web_voice.source {
+                WebSource::Stream(element, _) if element.ended() => Some(*voice),
+                _ => None,
+            })
+            .collect()
+    }
+
+    fn play(
+        &mut self,
+        voice: VoiceId,
+        sound: SoundId,
+        gain: f32,
+        pitch: f32,
+        looping: bool,
+    ) -> Result<(), String> {
+        let play = || -> Result<WebVoice, JsValue> {
+            let source = self.create_source(sound, pitch, looping)?;
+            let panner = self.context.create_stereo_panner()?;
+            let gain_node = self.context.create_gain()?;
+            gain_node.gain().set_value(gain);
+            panner.connect_with_audio_node(&gain_node)?;
+            gain_node.connect_with_audio_node(&self.context.destination())?;
+            match &source {
+                WebSource::Buffer(node) => {
+                    node.connect_with_audio_node(&panner)?;
+                    AudioScheduledSourceNode::start(node)?;
+                }
+                WebSource::Stream(element, node) => {
+                    node.connect_with_audio_node(&panner)?;
+                    let _ = element.play()?;
+                }
+            }
+            Ok(WebVoice {
+                source,
+                panner,
+                gain: gain_node,
+            })
+        };
+        let web_voice = play().map_err(|error| {
+            error
+                .as_string()
+                .unwrap_or_else(|| format!("Could not play sound {}.", sound.0))
+        })?;
+        self.voices.insert(voice, web_voice);
+        Ok(())
+    }
+
+    fn set_gain(&mut self, voice: VoiceId, gain: f32) {
+        if let Some(web_voice) = self.voices.get(&voice) {
+            web_voice.gain.gain().set_value(gain);
+        }
+    }
+
+    fn set_pan(&mut self, voice: VoiceId, pan: f32) {
+        if let Some(web_voice) = self.voices.get(&voice) {
+            web_voice.panner.pan().set_value(pan);
+        }
+    }
+
+    fn stop(&mut self, voice: VoiceId) {
+        if let Some(web_voice) = self.voices.remove(&voice) {
+            match web_voice.source {
+                WebSource::Buffer(node) => {
+                    let _ = AudioScheduledSourceNode::stop(&node);
+                }
+                WebSource::Stream(element, _) => {
+                    let _ = element.pause();
+                }
+            }
+            let _ = web_voice.gain.disconnect();
+        }
+    }
+
+    fn resume(&mut self) {
+        let _ = self.context.resume();
+    }
+}
+
+/// A change in volume of a voice over time.
+#[derive(Debug, Clone, Copy)]
+struct Fade {
+    from: f32,
+    to: f32,
+    duration: f32,
+    elapsed: f32,
+    stop_when_done: bool,
+}
+
+impl Fade {
+    fn value(&self) -> f32 {
+        if self.duration <= 0.0 {
+            return self.to;
+        }
+        let factor = (self.elapsed / self.duration).min(1.0);
+        self.from + (self.to - self.from) * factor
+    }
+
+    fn is_done(&self) -> bool {
+        self.elapsed >= self.duration
+    }
+}
+
+/// A single playing instance of a sound.
+#[derive(Debug, Clone)]
+struct Voice {
+    bus: Bus,
+    volume: f32,
+    pitch: f32,
+    looping: bool,
+    duration: f32,
+    time: f32,
+    fade_volume: f32,
+    fade: Option<Fade>,
+    spatial: Option<SpatialVoice>,
+    gain: f32,
+    pan: f32,
+}
+
+impl Voice {
+    /// Get the gain and pan of the [`Voice`], after fades, [`Buses`](Bus) and its position are applied.
+    fn mix(&self, buses: &BTreeMap<Bus, BusSettings>, listener: &Listener) -> (f32, f32) {
+        let bus_gain = |bus| buses.get(&bus).copied().unwrap_or_default().gain();
+        let gain = bus_gain(Bus::Master) * bus_gain(self.bus) * self.volume * self.fade_volume;
+        match &self.spatial {
+            Some(spatial) => (
+                gain * spatial.emitter.gain(listener, spatial.position),
+                spatial.emitter.pan(listener, spatial.position),
+            ),
+            None => (gain, 0.0),
+        }
+    }
+}
+
+/// The position of a [`Voice`] played by an [`Emitter`].
+#[derive(Debug, Clone, Copy)]
+struct SpatialVoice {
+    emitter: Emitter,
+    position: Vec2,
+    entity: Option<EntityId>,
+}
+
+/// Mixes sound effects and music on named [`Buses`](Bus), and plays them through an [`AudioBackend`].
+///
+/// # Examples
+/// ```
+/// use moon_engine::audio::{Audio, Bus};
+///
+/// let mut audio = Audio::new();
+/// let jump = audio.load_sound("JUMP", &[]);
+///
+/// let voice = audio.play_sfx(jump, 0.5, 1.0).unwrap();
+/// audio.set_bus_volume(Bus::Sfx, 0.5);
+/// audio.update(0.1);
+///
+/// assert_eq!(audio.voice_gain(voice), Some(0.25));
+/// ```
+#[derive(Debug)]
+pub struct Audio {
+    backend: Box<dyn AudioBackend>,
+    sounds: BTreeMap<String, SoundId>,
+    buses: BTreeMap<Bus, BusSettings>,
+    voices: BTreeMap<VoiceId, Voice>,
+    music: Option<VoiceId>,
+    listener: Listener,
+    listener_entity: Option<EntityId>,
+    next_sound: u32,
+    next_voice: u32,
+}
+
+impl Default for Audio {
+    fn default() -> Self {
+        Self::new_with_backend(Box::new(NullBackend::default()))
+    }
+}
+
+impl Audio {
+    /// Create a new [`Audio`] mixer that does not output any sound.
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Create a new [`Audio`] mixer with an [`AudioBackend`].
+    pub fn new_with_backend(backend: Box<dyn AudioBackend>) -> Self {
+        Self {
+            backend,
+            sounds: BTreeMap::new(),
+            buses: [Bus::Master, Bus::Music, Bus::Sfx]
+                .into_iter()
+                .map(|bus| (bus, BusSettings::default()))
+                .collect(),
+            voices: BTreeMap::new(),
+            music: None,
+            listener: Listener::default(),
+            listener_entity: None,
+            next_sound: 0,
+            next_voice: 0,
+        }
+    }
+
+    /// Replace the [`AudioBackend`], stopping every voice.
+    ///
+    /// Sounds need to be loaded again.
+    pub fn set_backend(&mut self, backend: Box<dyn AudioBackend>) {
+        self.stop_all();
+        self.sounds.clear();
+        self.backend = backend;
+    }
+
+    /// Resume audio output, which browsers only allow after the user has interacted with the page.
+    pub fn resume(&mut self) {
+        self.backend.resume();
+    }
+
+    /// Load a sound from encoded audio data, and give it a name.
+    ///
+    /// Any sound previously loaded with the same name is freed.
+    ///
+    /// The sound can be played once it has been decoded, see [`is_sound_ready`](Audio::is_sound_ready).
+    pub fn load_sound(&mut self, name: &str, bytes: &[u8]) -> SoundId {
+        let sound = self.add_sound(name);
+        self.backend.decode(sound, bytes);
+        sound
+    }
+
+    /// Load a sound that is streamed from a URL while it plays, and give it a name.
+    ///
+    /// This suits long music tracks, which would take a lot of memory to decode up front.
+    pub fn load_stream(&mut self, name: &str, url: &str) -> SoundId {
+        let sound = self.add_sound(name);
+        self.backend.stream(sound, url);
+        sound
+    }
+
+    /// Free a sound using its name, returning whether it was found.
+    ///
+    /// Voices that are already playing it may keep playing until they finish.
+    pub fn unload_sound(&mut self, name: &str) -> bool {
+        match self.sounds.remove(name) {
+            Some(sound) => {
+                self.backend.free(sound);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Free a sound, forgetting its name if it still refers to it.
+    ///
+    /// Voices that are already playing it may keep playing until they finish.
+    pub fn free_sound(&mut self, sound: SoundId) {
+        self.sounds.retain(|_, named| *named != sound);
+        self.backend.free(sound);
+    }
+
+    /// Get a loaded sound using its name.
+    pub fn get_sound(&self, name: &str) -> Option<SoundId> {
+        self.sounds.get(name).copied()
+    }
+
+    /// Check whether a sound has been decoded, and can be played.
+    pub fn is_sound_ready(&self, sound: SoundId) -> bool {
+        self.backend.duration(sound).is_some()
+    }
+
+    /// Play a sound once on the [`Bus::Sfx`] bus, with a volume and pitch.
+    ///
+    /// A pitch of 1 plays the sound at its normal speed, and 2 plays it an octave higher in half the time.
+    pub fn play_sfx(&mut self, sound: SoundId, volume: f32, pitch: f32) -> Result<VoiceId, String> {
+        self.play(sound, Bus::Sfx, volume, pitch, false, None, None)
+    }
+
+    /// Play a sound once on the [`Bus::Sfx`] bus from a position in the world, using an [`Emitter`].
+    ///
+    /// The sound is attenuated and panned relative to the [`Listener`].
+    pub fn play_sfx_at(
+        &mut self,
+        sound: SoundId,
+        volume: f32,
+        pitch: f32,
+        emitter: Emitter,
+        position: Vec2,
+    ) -> Result<VoiceId, String> {
+        let spatial = SpatialVoice {
+            emitter,
+            position,
+            entity: None,
+        };
+        self.play(sound, Bus::Sfx, volume, pitch, false, None, Some(spatial))
+    }
+
+    /// Move a voice played from a position in the world.
+    pub fn set_voice_position(&mut self, voice: VoiceId, position: Vec2) {
+        if let Some(spatial) = self.spatial_mut(voice) {
+            spatial.position = position;
+        }
+    }
+
+    /// Make a voice played from a position in the world follow an entity of a [`SceneGraph`].
+    ///
+    /// The position is updated by [`follow_scene`](Audio::follow_scene).
+    pub fn attach_to_entity(&mut self, voice: VoiceId, entity: EntityId) {
+        if let Some(spatial) = self.spatial_mut(voice) {
+            spatial.entity = Some(entity);
+        }
+    }
+
+    /// Get the [`Listener`] that positional sounds are heard from.
+    pub fn listener(&self) -> &Listener {
+        &self.listener
+    }
+
+    /// Set the [`Listener`] that positional sounds are heard from, such as one following the active [`Camera`](crate::Camera).
+    pub fn set_listener(&mut self, listener: Listener) {
+        self.listener = listener;
+    }
+
+    /// Make the [`Listener`] follow an entity of a [`SceneGraph`], or stop following one.
+    ///
+    /// The [`Listener`] is updated by [`follow_scene`](Audio::follow_scene), keeping its pan width.
+    pub fn set_listener_entity(&mut self, entity: Option<EntityId>) {
+        self.listener_entity = entity;
+    }
+
+    /// Get the entity the [`Listener`] follows, if any.
+    pub fn listener_entity(&self) -> Option<EntityId> {
+        self.listener_entity
+    }
+
+    /// Move the [`Listener`] to a [`Camera`], unless it follows an entity set with [`set_listener_entity`](Audio::set_listener_entity).
+    pub fn follow_camera(&mut self, camera: &Camera) {
+        if self.listener_entity.is_none() {
+            self.listener = Listener::new_from_camera(camera);
+        }
+    }
+
+    /// Move the [`Listener`] and any attached voices to the world positions of their entities.
+    ///
+    /// Voices whose entity has been despawned stay at its last position.
+    pub fn follow_scene(&mut self, scene: &SceneGraph) {
+        if let Some(world) = self.listener_entity.and_then(|entity| scene.world(entity)) {
+            self.listener = Listener::new_from_transform2d(&world, self.listener.pan_width);
+        }
+        for voice in self.voices.values_mut() {
+            if let Some(spatial) = voice.spatial.as_mut() {
+                if let Some(world) = spatial.entity.and_then(|entity| scene.world(entity)) {
+                    spatial.position = world.position;
+                }
+            }
+        }
+    }
+
+    /// Play a sound on a loop on the [`Bus::Music`] bus, crossfading from the current music over a number of seconds.
+    pub fn play_music(&mut self, sound: SoundId, fade_time: f32) -> Result<VoiceId, String> {
+        let fade = Fade {
+            from: 0.0,
+            to: 1.0,
+            duration: fade_time,
+            elapsed: 0.0,
+            stop_when_done: false,
+        };
+        let voice = self.play(sound, Bus::Music, 1.0, 1.0, true, Some(fade), None)?;
+        if let Some(previous) = self.music.replace(voice) {
+            self.fade_out(previous, fade_time);
+        }
+        Ok(voice)
+    }
+
+    /// Fade out the current music over a number of seconds, then stop it.
+    pub fn stop_music(&mut self, fade_time: f32) {
+        if let Some(music) = self.music.take() {
+            self.fade_out(music, fade_time);
+        }
+    }
+
+    /// Get the voice of the current music, if any.
+    pub fn music(&self) -> Option<VoiceId> {
+        self.music
+    }
+
+    /// Stop a voice immediately.
+    pub fn stop(&mut self, voice: VoiceId) {
+        if self.voices.remove(&voice).is_some() {
+            self.backend.stop(voice);
+        }
+        if self.music == Some(voice) {
+            self.music = None;
+        }
+    }
+
+    /// Stop every voice immediately.
+    pub fn stop_all(&mut self) {
+        for voice in self.voices.keys() {
+            self.backend.stop(*voice);
+        }
+        self.voices.clear();
+        self.music = None;
+    }
+
+    /// Check whether a voice is still playing.
+    pub fn is_playing(&self, voice: VoiceId) -> bool {
+        self.voices.contains_key(&voice)
+    }
+
+    /// Get the final gain of a voice, after fades and [`Buses`](Bus) are applied.
+    pub fn voice_gain(&self, voice: VoiceId) -> Option<f32> {
+        self.voices.get(&voice).map(|voice| voice.gain)
+    }
+
+    /// Get the stereo pan of a voice, from -1 for fully left to 1 for fully right.
+    pub fn voice_pan(&self, voice: VoiceId) -> Option<f32> {
+        self.voices.get(&voice).map(|voice| voice.pan)
+    }
+
+    /// Get the number of playing voices.
+    pub fn voice_count(&self) -> usize {
+        self.voices.len()
+    }
+
+    /// Get the [`BusSettings`] of a [`Bus`].
+    pub fn bus(&self, bus: Bus) -> BusSettings {
+        self.buses.get(&bus).copied().unwrap_or_default()
+    }
+
+    /// Set the volume of a [`Bus`].
+    pub fn set_bus_volume(&mut self, bus: Bus, volume: f32) {
+        self.buses.entry(bus).or_default().volume = volume.max(0.0);
+    }
+
+    /// Mute or unmute a [`Bus`].
+    pub fn set_bus_muted(&mut self, bus: Bus, muted: bool) {
+        self.buses.entry(bus).or_default().muted = muted;
+    }
+
+    /// Advance fades, remove finished voices and update the gain and pan of the others.
+    pub fn update(&mut self, delta_time: f32) {
+        let mut finished = Vec::new();
+        for (id, voice) in self.voices.iter_mut() {
+            voice.time += delta_time * voice.pitch;
+            if let Some(fade) = voice.fade.as_mut() {
+                fade.elapsed += delta_time;
+                voice.fade_volume = fade.value();
+                if fade.is_done() {
+                    if fade.stop_when_done {
+                        finished.push(*id);
+                    }
+                    voice.fade = None;
+                }
+            }
+            if !voice.looping && voice.time >= voice.duration {
+                finished.push(*id);
+            }
+
+            let (gain, pan) = voice.mix(&self.buses, &self.listener);
+            if gain != voice.gain {
+                voice.gain = gain;
+                self.backend.set_gain(*id, gain);
+            }
+            if pan != voice.pan {
+                voice.pan = pan;
+                self.backend.set_pan(*id, pan);
+            }
+        }
+        // Streams end when the backend reaches the end, since their length is not known
+        finished.extend(self.backend.finished_streams(delta_time));
+        for voice in finished {
+            self.stop(voice);
+        }
+    }
+
+    fn add_sound(&mut self, name: &str) -> SoundId {
+        let sound = SoundId(self.next_sound);
+        self.next_sound += 1;
+        if let Some(previous) = self.sounds.insert(name.to_string(), sound) {
+            self.backend.free(previous);
+        }
+        sound
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn play(
+        &mut self,
+        sound: SoundId,
+        bus: Bus,
+        volume: f32,
+        pitch: f32,
+        looping: bool,
+        fade: Option<Fade>,
+        spatial: Option<SpatialVoice>,
+    ) -> Result<VoiceId, String> {
+        let duration = self
+            .backend
+            .duration(sound)
+            .ok_or_else(|| format!("Sound {} is not ready to be played.", sound.0))?;
+        let mut voice = Voice {
+            bus,
+            volume,
+            pitch,
+            looping,
+            duration,
+            time: 0.0,
+            fade_volume: fade.map_or(1.0, |fade| fade.value()),
+            fade,
+            spatial,
+            gain: 0.0,
+            pan: 0.0,
+        };
+        (voice.gain, voice.pan) = voice.mix(&self.buses, &self.listener);
+
+        let id = VoiceId(self.next_voice);
+        self.backend.play(id, sound, voice.gain, pitch, looping)?;
+        if voice.pan != 0.0 {
+            self.backend.set_pan(id, voice.pan);
+        }
+        self.next_voice += 1;
+        self.voices.insert(id, voice);
+        Ok(id)
+    }
+
+    fn spatial_mut(&mut self, voice: VoiceId) -> Option<&mut SpatialVoice> {
+        self.voices.get_mut(&voice)?.spatial.as_mut()
+    }
+
+    fn fade_out(&mut self, voice: VoiceId, fade_time: f32) {
+        if let Some(voice) = self.voices.get_mut(&voice) {
+            voice.fade = Some(Fade {
+                from: voice.fade_volume,
+                to: 0.0,
+                duration: fade_time,
+                elapsed: 0.0,
+                stop_when_done: true,
+            });
+        }
+    }
+}
@@ -1,40 +1,191 @@
-//! The [`Camera`] struct.
+//! The [`Camera`], [`Viewport`] and [`CameraController`] structs, and the [`ScalingMode`] enum.
 
+use crate::collider::AABB;
 use crate::transform::Transform;
+use crate::Color32;
 use crate::Mat4;
 use crate::Ortho;
+use crate::Vec2;
 use crate::Vec3;
+use crate::Vec4;
 
 /// The 'X' component at the left and right edges of the screen
 pub const FIXED_WIDTH: f32 = 20.0;
 /// Calculate the height from the `FIXED_WIDTH` to maintain 16:9 Aspect ratio
 pub const FIXED_HEIGHT: f32 = FIXED_WIDTH / 1.77;
 
+/// How the visible area of the world is fitted to the screen.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ScalingMode {
+    /// A fixed width of the world is visible, and the height depends on the aspect ratio of the screen.
+    FixedWidth(f32),
+    /// A fixed height of the world is visible, and the width depends on the aspect ratio of the screen.
+    FixedHeight(f32),
+    /// The whole area is always visible, and more of the world is shown along one axis if the aspect ratios differ.
+    Fit {
+        /// Width of the area.
+        width: f32,
+        /// Height of the area.
+        height: f32,
+    },
+    /// The area always fills the screen, and less of the world is shown along one axis if the aspect ratios differ.
+    Fill {
+        /// Width of the area.
+        width: f32,
+        /// Height of the area.
+        height: f32,
+    },
+    /// Each unit of the world covers a whole number of pixels, picked so that at least the area is visible.
+    PixelPerfect {
+        /// Width of the area, in units.
+        width: f32,
+        /// Height of the area, in units.
+        height: f32,
+    },
+}
+
+impl Default for ScalingMode {
+    fn default() -> Self {
+        Self::Fit {
+            width: FIXED_WIDTH,
+            height: FIXED_HEIGHT,
+        }
+    }
+}
+
+impl ScalingMode {
+    /// Get the size of the visible area of the world for a screen of a given size, without any zoom.
+    pub fn view_size(&self, screen_width: f32, screen_height: f32) -> Vec2 {
+        let screen_width = screen_width.max(1.0);
+        let screen_height = screen_height.max(1.0);
+        let aspect = screen_width / screen_height;
+        match *self {
+            Self::FixedWidth(width) => Vec2::new(width, width / aspect),
+            Self::FixedHeight(height) => Vec2::new(height * aspect, height),
+            Self::Fit { width, height } => {
+                if aspect > width / height {
+                    Vec2::new(height * aspect, height)
+                } else {
+                    Vec2::new(width, width / aspect)
+                }
+            }
+            Self::Fill { width, height } => {
+                if aspect > width / height {
+                    Vec2::new(width, width / aspect)
+                } else {
+                    Vec2::new(height * aspect, height)
+                }
+            }
+            Self::PixelPerfect { width, height } => {
+                let scale = (screen_width / width)
+                    .min(screen_height / height)
+                    .floor()
+                    .max(1.0);
+                Vec2::new(screen_width / scale, screen_height / scale)
+            }
+        }
+    }
+}
+
+/// A rectangle of the screen that a [`Camera`] draws into.
+///
+/// All values are normalized, so `0.0` is the top or left edge of the screen and `1.0` is the bottom or right edge.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Viewport {
+    /// Left edge of the [`Viewport`].
+    pub x: f32,
+    /// Top edge of the [`Viewport`].
+    pub y: f32,
+    /// Width of the [`Viewport`].
+    pub width: f32,
+    /// Height of the [`Viewport`].
+    pub height: f32,
+}
+
+impl Default for Viewport {
+    fn default() -> Self {
+        Self::FULL
+    }
+}
+
+impl Viewport {
+    /// A [`Viewport`] covering the whole screen.
+    pub const FULL: Viewport = Viewport::new(0.0, 0.0, 1.0, 1.0);
+    /// A [`Viewport`] covering the left half of the screen.
+    pub const LEFT: Viewport = Viewport::new(0.0, 0.0, 0.5, 1.0);
+    /// A [`Viewport`] covering the right half of the screen.
+    pub const RIGHT: Viewport = Viewport::new(0.5, 0.0, 0.5, 1.0);
+    /// A [`Viewport`] covering the top half of the screen.
+    pub const TOP: Viewport = Viewport::new(0.0, 0.0, 1.0, 0.5);
+    /// A [`Viewport`] covering the bottom half of the screen.
+    pub const BOTTOM: Viewport = Viewport::new(0.0, 0.5, 1.0, 0.5);
+
+    /// Create a new [`Viewport`] from a normalized position and size.
+    pub const fn new(x: f32, y: f32, width: f32, height: f32) -> Self {
+        Self {
+            x,
+            y,
+            width,
+            height,
+        }
+    }
+
+    /// Get the rectangle covered by the [`Viewport`] on a screen of a given size, in pixels.
+    ///
+    /// The rectangle is returned as `(x, y, width, height)` starting from the bottom-left corner, as used by WebGL.
+    pub fn pixel_rect(&self, screen_width: f32, screen_height: f32) -> (i32, i32, i32, i32) {
+        let left = (self.x * screen_width).round() as i32;
+        let right = ((self.x + self.width) * screen_width).round() as i32;
+        let top = (self.y * screen_height).round() as i32;
+        let bottom = ((self.y + self.height) * screen_height).round() as i32;
+        (
+            left,
+            screen_height.round() as i32 - bottom,
+            right - left,
+            bottom - top,
+        )
+    }
+
+    /// Check whether a normalized position on the screen is inside the [`Viewport`].
+    pub fn contains(&self, x: f32, y: f32) -> bool {
+        x >= self.x && x < self.x + self.width && y >= self.y && y < self.y + self.height
+    }
+}
+
 /// A [`Camera`] represents a Virtual Camera, that has a view and Orthographic projection matrices
+///
+/// The [`Transform`] of the [`Camera`] places it in the world, and the view matrix is its inverse.
 #[derive(Debug)]
 pub struct Camera {
     /// [`Transform`] for the Camera
     pub transform: Transform,
     orthographic: Ortho,
+    scaling: ScalingMode,
+    viewport: Viewport,
     width: f32,
     height: f32,
+    zoom: f32,
+    /// A bitmask of the render layers the `Camera` draws. Layer `n` is drawn if bit `n` is set.
+    pub layers: u32,
+    /// The [`Color32`] the `Camera` clears its [`Viewport`] with before drawing, if any.
+    pub clear_color: Option<Color32>,
 }
 
 impl Default for Camera {
     fn default() -> Self {
-        Self {
+        let mut camera = Self {
             transform: Transform::new(),
+            orthographic: Ortho::new(-1.0, 1.0, 1.0, -1.0, 0f32, 1000.0f32),
+            scaling: ScalingMode::default(),
+            viewport: Viewport::FULL,
             width: FIXED_WIDTH,
             height: FIXED_HEIGHT,
-            orthographic: Ortho::new(
-                -FIXED_WIDTH / 2.0,
-                FIXED_WIDTH / 2.0,
-                FIXED_HEIGHT / 2.0,
-                -FIXED_HEIGHT / 2.0,
-                0f32,
-                1000.0f32,
-            ),
-        }
+            zoom: 1.0,
+            layers: u32::MAX,
+            clear_color: None,
+        };
+        camera.update_projection();
+        camera
     }
 }
 
@@ -58,26 +209,137 @@ impl Camera {
         }
     }
     /// Create a new `Camera` with an initial width and height.
+    ///
+    /// The whole area is visible, on a screen of the same size.
     pub fn with_width_and_height(width: f32, height: f32) -> Self {
-        Self {
+        let mut camera = Self {
+            scaling: ScalingMode::Fit { width, height },
             width,
             height,
-            orthographic: Ortho::new(
-                -width / 2.0,
-                width / 2.0,
-                height / 2.0,
-                -height / 2.0,
-                0f32,
-                1000.0f32,
-            ),
             ..Default::default()
-        }
+        };
+        camera.update_projection();
+        camera
+    }
+    /// Create a new `Camera` with a given [`ScalingMode`].
+    pub fn with_scaling(scaling: ScalingMode) -> Self {
+        let mut camera = Self {
+            scaling,
+            ..Default::default()
+        };
+        camera.update_projection();
+        camera
+    }
+
+    /// Create a new `Camera` that draws into a given [`Viewport`].
+    pub fn with_viewport(viewport: Viewport) -> Self {
+        let mut camera = Self {
+            viewport,
+            ..Default::default()
+        };
+        camera.update_projection();
+        camera
+    }
+
+    /// Get the [`Viewport`] of the `Camera`.
+    pub fn viewport(&self) -> Viewport {
+        self.viewport
+    }
+
+    /// Set the [`Viewport`] of the `Camera`, and update the Projection Matrix to match.
+    pub fn set_viewport(&mut self, viewport: Viewport) {
+        self.viewport = viewport;
+        self.update_projection();
+    }
+
+    /// Get the rectangle the `Camera` draws into, in pixels, as used by WebGL.
+    pub fn viewport_rect(&self) -> (i32, i32, i32, i32) {
+        self.viewport.pixel_rect(self.width, self.height)
     }
 
-    /// Set the width and height of the camera plane, and update the Projection Matrix to match.
+    /// Check whether the `Camera` draws a given render layer.
+    pub fn draws_layer(&self, layer: u32) -> bool {
+        layer < 32 && self.layers & (1 << layer) != 0
+    }
+
+    /// Check whether a position in screen co-ordinates is inside the [`Viewport`] of the `Camera`.
+    pub fn contains_screen_point(&self, screen_x: f32, screen_y: f32) -> bool {
+        self.viewport
+            .contains(screen_x / self.width, screen_y / self.height)
+    }
+
+    /// Set the width and height of the screen in pixels, and update the Projection Matrix to match.
     pub fn set_width_and_height(&mut self, width: f32, height: f32) {
         self.width = width;
         self.height = height;
+        self.update_projection();
+    }
+
+    /// Get the width and height of the screen in pixels.
+    pub fn screen_size(&self) -> Vec2 {
+        Vec2::new(self.width, self.height)
+    }
+
+    /// Get the [`ScalingMode`] of the `Camera`.
+    pub fn scaling(&self) -> ScalingMode {
+        self.scaling
+    }
+
+    /// Set the [`ScalingMode`] of the `Camera`, and update the Projection Matrix to match.
+    pub fn set_scaling(&mut self, scaling: ScalingMode) {
+        self.scaling = scaling;
+        self.update_projection();
+    }
+
+    /// Get the zoom of the `Camera`.
+    pub fn zoom(&self) -> f32 {
+        self.zoom
+    }
+
+    /// Set the zoom of the `Camera`, and update the Projection Matrix to match.
+    ///
+    /// A zoom greater than `1.0` makes everything appear larger.
+    pub fn set_zoom(&mut self, zoom: f32) {
+        if zoom <= 0.0 {
+            return;
+        }
+        self.zoom = zoom;
+        self.update_projection();
+    }
+
+    /// Get the rotation of the `Camera`, in radians.
+    pub fn rotation(&self) -> f32 {
+        self.transform.get_rotation()
+    }
+
+    /// Set the rotation of the `Camera`, in radians.
+    pub fn set_rotation(&mut self, rotation: f32) {
+        self.transform.set_rotation(rotation);
+    }
+
+    /// Get the position in the world the `Camera` is centered on.
+    pub fn position(&self) -> Vec2 {
+        self.transform.position.xy()
+    }
+
+    /// Move the `Camera` so that it is centered on a position in the world.
+    pub fn focus_on(&mut self, position: Vec2) {
+        self.transform
+            .set_position(Vec3::new(position.x, position.y, self.transform.position.z));
+    }
+
+    /// Get the size of the area of the world that is visible, at the current zoom.
+    pub fn view_size(&self) -> Vec2 {
+        self.scaling.view_size(
+            self.width * self.viewport.width,
+            self.height * self.viewport.height,
+        ) / self.zoom
+    }
+
+    /// Rebuild the Projection Matrix from the [`ScalingMode`], screen size and zoom.
+    fn update_projection(&mut self) {
+        let half = self.view_size() / 2.0;
+        self.orthographic = Ortho::new(-half.x, half.x, half.y, -half.y, 0f32, 1000.0f32);
     }
 
     /// Return the Projection Matrix of the `Camera` as a slice of `f32` so it can be used by WebGL.
@@ -85,18 +347,218 @@ impl Camera {
         self.orthographic.as_matrix().as_slice()
     }
 
+    /// Return the Projection Matrix of the `Camera` as a [`Mat4`].
+    pub fn projection_matrix(&self) -> Mat4 {
+        *self.orthographic.as_matrix()
+    }
+
+    /// Return the View Matrix of the `Camera` as a [`Mat4`].
+    ///
+    /// This is the inverse of the matrix of its [`Transform`].
+    pub fn view_matrix(&self) -> Mat4 {
+        self.transform
+            .matrix()
+            .try_inverse()
+            .unwrap_or_else(Mat4::identity)
+    }
+
     /// Return the calculated and combined view-projection matrix as a [`Mat4`].
     pub fn view_projection_matrix(&self) -> Mat4 {
-        self.transform.matrix() * self.orthographic.as_matrix()
+        self.orthographic.as_matrix() * self.view_matrix()
     }
 
-    /// Get a position in screen co-ordinates to a range within the world.
+    /// Convert a position in screen co-ordinates to a position in the world.
     ///
-    /// This works by first converting it into a `-1.0 to 1.0` range, and then multiplying its components by the [`FIXED_WIDTH`] and [`FIXED_HEIGHT`].
+    /// Screen co-ordinates are in pixels, starting from the top-left corner of the screen, and are relative to the [`Viewport`] of the `Camera`.
     pub fn screen_to_world_coordinates(&self, screen_x: f32, screen_y: f32) -> (f32, f32) {
-        let clipped_x = screen_x / self.width - 0.5;
-        let clipped_y = screen_y / self.height - 0.5;
+        let viewport = &self.viewport;
+        let local_x = (screen_x / self.width - viewport.x) / viewport.width;
+        let local_y = (screen_y / self.height - viewport.y) / viewport.height;
+        let clip = Vec4::new(local_x * 2.0 - 1.0, 1.0 - local_y * 2.0, 0.0, 1.0);
+        let world = self
+            .view_projection_matrix()
+            .try_inverse()
+            .unwrap_or_else(Mat4::identity)
+            * clip;
+        (world.x, world.y)
+    }
+
+    /// Convert a position in the world to a position in screen co-ordinates.
+    ///
+    /// Screen co-ordinates are in pixels, starting from the top-left corner of the screen, and are relative to the [`Viewport`] of the `Camera`.
+    pub fn world_to_screen_coordinates(&self, world_x: f32, world_y: f32) -> (f32, f32) {
+        let viewport = &self.viewport;
+        let clip = self.view_projection_matrix() * Vec4::new(world_x, world_y, 0.0, 1.0);
+        let local_x = (clip.x + 1.0) / 2.0;
+        let local_y = (1.0 - clip.y) / 2.0;
+        (
+            (viewport.x + local_x * viewport.width) * self.width,
+            (viewport.y + local_y * viewport.height) * self.height,
+        )
+    }
+}
+
+/// A [`CameraController`] moves a [`Camera`] to follow a target, and can shake and zoom it.
+///
+/// All of its behaviours only depend on the values passed to [`update`](CameraController::update), so they are deterministic.
+#[derive(Debug, Clone)]
+pub struct CameraController {
+    /// The position the [`Camera`] is centered on, without any shake.
+    pub position: Vec2,
+    /// The position the [`Camera`] is following.
+    pub target: Vec2,
+    /// How quickly the [`Camera`] catches up with its target. A value of `0.0` or less follows it instantly.
+    pub smoothing: f32,
+    /// Size of the area around the center of the [`Camera`] in which the target can move without the [`Camera`] following it.
+    pub dead_zone: Vec2,
+    /// An [`AABB`] that the visible area of the [`Camera`] is kept inside, if any.
+    pub bounds: Option<AABB>,
+    /// The current zoom of the [`Camera`].
+    pub zoom: f32,
+    /// The zoom the [`Camera`] is moving towards.
+    pub target_zoom: f32,
+    /// How quickly the zoom catches up with its target. A value of `0.0` or less zooms instantly.
+    pub zoom_smoothing: f32,
+    /// How much the [`Camera`] is shaking, from `0.0` to `1.0`.
+    pub trauma: f32,
+    /// How much trauma is lost each second.
+    pub trauma_decay: f32,
+    /// The largest offset the [`Camera`] can be shaken by.
+    pub max_shake: Vec2,
+    /// How quickly the [`Camera`] shakes.
+    pub shake_frequency: f32,
+    seed: u32,
+    time: f32,
+    shake: Vec2,
+}
+
+impl Default for CameraController {
+    fn default() -> Self {
+        Self {
+            position: Vec2::zeros(),
+            target: Vec2::zeros(),
+            smoothing: 5.0,
+            dead_zone: Vec2::zeros(),
+            bounds: None,
+            zoom: 1.0,
+            target_zoom: 1.0,
+            zoom_smoothing: 5.0,
+            trauma: 0.0,
+            trauma_decay: 1.0,
+            max_shake: Vec2::new(0.5, 0.5),
+            shake_frequency: 15.0,
+            seed: 0,
+            time: 0.0,
+            shake: Vec2::zeros(),
+        }
+    }
+}
 
-        (clipped_x * FIXED_WIDTH, clipped_y * FIXED_HEIGHT)
+impl CameraController {
+    /// Create a new [`CameraController`] with default values.
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Create a new [`CameraController`] with a seed for its screen shake.
+    pub fn new_with_seed(seed: u32) -> Self {
+        Self {
+            seed,
+            ..Default::default()
+        }
+    }
+
+    /// Set the position the [`Camera`] should follow.
+    pub fn follow(&mut self, target: Vec2) {
+        self.target = target;
+    }
+
+    /// Set the zoom the [`Camera`] should move towards.
+    pub fn zoom_to(&mut self, zoom: f32) {
+        self.target_zoom = zoom;
+    }
+
+    /// Add trauma to shake the [`Camera`], keeping the total between `0.0` and `1.0`.
+    pub fn add_trauma(&mut self, amount: f32) {
+        self.trauma = (self.trauma + amount).clamp(0.0, 1.0);
+    }
+
+    /// Get the current screen shake offset.
+    pub fn shake_offset(&self) -> Vec2 {
+        self.shake
     }
+
+    /// Advance the [`CameraController`] by a given time, and apply it to a [`Camera`].
+    pub fn update(&mut self, camera: &mut Camera, delta_time: f32) {
+        self.time += delta_time;
+
+        // Zoom
+        self.zoom +=
+            (self.target_zoom - self.zoom) * smoothing_factor(self.zoom_smoothing, delta_time);
+
+        // Follow the target, ignoring movement inside the dead zone
+        let half_dead_zone = self.dead_zone / 2.0;
+        let offset = self.target - self.position;
+        let desired = self.position
+            + Vec2::new(
+                offset.x - offset.x.clamp(-half_dead_zone.x, half_dead_zone.x),
+                offset.y - offset.y.clamp(-half_dead_zone.y, half_dead_zone.y),
+            );
+        self.position += (desired - self.position) * smoothing_factor(self.smoothing, delta_time);
+
+        // Keep the visible area inside the bounds
+        if let Some(bounds) = self.bounds {
+            let half_view = camera.view_size() * camera.zoom() / self.zoom.max(f32::EPSILON) / 2.0;
+            self.position.x =
+                clamp_to_bounds(self.position.x, bounds.min.x, bounds.max.x, half_view.x);
+            self.position.y =
+                clamp_to_bounds(self.position.y, bounds.min.y, bounds.max.y, half_view.y);
+        }
+
+        // Shake, scaled by the square of the trauma so small amounts are subtle
+        let shake = self.trauma * self.trauma;
+        let time = self.time * self.shake_frequency;
+        self.shake = Vec2::new(
+            self.max_shake.x * shake * noise(self.seed, time),
+            self.max_shake.y * shake * noise(self.seed.wrapping_add(1), time),
+        );
+        self.trauma = (self.trauma - self.trauma_decay * delta_time).max(0.0);
+
+        camera.set_zoom(self.zoom);
+        camera.focus_on(self.position + self.shake);
+    }
+}
+
+/// Get the fraction of the remaining distance to move this frame, independent of the frame rate.
+fn smoothing_factor(smoothing: f32, delta_time: f32) -> f32 {
+    if smoothing <= 0.0 {
+        1.0
+    } else {
+        1.0 - (-smoothing * delta_time).exp()
+    }
+}
+
+/// Clamp a position so that a half-extent around it stays inside a range, or center it if the range is too small.
+fn clamp_to_bounds(position: f32, min: f32, max: f32, half_extent: f32) -> f32 {
+    if max - min <= half_extent * 2.0 {
+        (min + max) / 2.0
+    } else {
+        position.clamp(min + half_extent, max - half_extent)
+    }
+}
+
+/// Smooth one-dimensional value noise in the range `-1.0` to `1.0`.
+fn noise(seed: u32, time: f32) -> f32 {
+    let hash = |index: i32| {
+        let mut value = (index as u32).wrapping_mul(0x9E37_79B9) ^ seed.wrapping_mul(0x85EB_CA6B);
+        value ^= value >> 16;
+        value = value.wrapping_mul(0x7FEB_352D);
+        value ^= value >> 15;
+        value as f32 / u32::MAX as f32 * 2.0 - 1.0
+    };
+    let index = time.floor();
+    let fraction = time - index;
+    let fraction = fraction * fraction * (3.0 - 2.0 * fraction);
+    let (a, b) = (hash(index as i32), hash(index as i32 + 1));
+    a + (b - a) * fraction
 }
@@ -35,7 +35,7 @@ pub trait Collide<T: Collider> {
 }
 
 /// An Axis-Aligned Bounding Box (AABB).
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy)]
 pub struct AABB {
     /// The lowest point on the X and Y axes of the [`AABB`].
     pub min: Point,
@@ -80,7 +80,7 @@ impl AABB {
 }
 
 /// A Cicle Collider.
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy)]
 pub struct Circle {
     /// The [`Point`] at which the [`Circle`] is centered.
     pub origin: Point,
@@ -114,6 +114,34 @@ impl Circle {
     }
 }
 
+/// A convex Polygon Collider.
+#[derive(Debug, Clone)]
+pub struct Polygon {
+    /// The vertices of the [`Polygon`], in order.
+    pub points: Vec<Point>,
+}
+
+impl Polygon {
+    /// Creates a new [`Polygon`] from a [`Vec`] of [`Points`](Point).
+    pub fn new(points: Vec<Point>) -> Self {
+        Self { points }
+    }
+
+    /// Get the signed area of the [`Polygon`].
+    ///
+    /// This is positive if the [`Points`](Point) are in counter-clockwise order, and negative otherwise.
+    pub fn signed_area(&self) -> f32 {
+        let count = self.points.len();
+        (0..count)
+            .map(|index| {
+                let (a, b) = (self.points[index], self.points[(index + 1) % count]);
+                a.x * b.y - b.x * a.y
+            })
+            .sum::<f32>()
+            / 2.0
+    }
+}
+
 impl Collider for Point {
     /// Get a bounding box for a [`Point`], using [`POINT_BOUNDING_SIZE`] as its size
     fn get_bounding_box(&self) -> AABB {
@@ -151,6 +179,22 @@ impl Collider for Circle {
     }
 }
 
+impl Collider for Polygon {
+    fn get_bounding_box(&self) -> AABB {
+        let mut min = Point::from_element(f32::INFINITY);
+        let mut max = Point::from_element(f32::NEG_INFINITY);
+        for point in self.points.iter() {
+            min = min.inf(point);
+            max = max.sup(point);
+        }
+        AABB { min, max }
+    }
+
+    fn get_center(&self) -> Point {
+        self.points.iter().sum::<Point>() / self.points.len().max(1) as f32
+    }
+}
+
 /// Point and Point Collsion
 impl Collide<Point> for Point {
     fn collide_with(&self, _other: &Point) -> bool {
@@ -226,3 +270,27 @@ impl Collide<AABB> for Circle {
         _other.collide_with(self)
     }
 }
+
+/// Point and Polygon Collision
+impl Collide<Polygon> for Point {
+    fn collide_with(&self, _other: &Polygon) -> bool {
+        _other.collide_with(self)
+    }
+}
+
+/// Polygon and Point Collision
+impl Collide<Point> for Polygon {
+    fn collide_with(&self, _other: &Point) -> bool {
+        let count = self.points.len();
+        let mut inside = false;
+        for index in 0..count {
+            let (a, b) = (self.points[index], self.points[(index + count - 1) % count]);
+            if (a.y > _other.y) != (b.y > _other.y)
+                && _other.x < (b.x - a.x) * (_other.y - a.y) / (b.y - a.y) + a.x
+            {
+                inside = !inside;
+            }
+        }
+        inside
+    }
+}
@@ -2,6 +2,7 @@
 
 use std::any::Any;
 
+use crate::random::Rng;
 use crate::renderer::Quad;
 
 /// The [`Component`] trait
@@ -12,6 +13,19 @@ pub trait Component {
     ///
     /// The time between frames is also provided.
     fn update(&mut self, _delta_time: f32) {}
+    /// Called once each frame instead of [`update`](Component::update) by the [`Application`](crate::Application), with its [`Rng`] for any random values.
+    ///
+    /// By default, this calls [`update`](Component::update).
+    fn update_with_rng(&mut self, delta_time: f32, _rng: &mut Rng) {
+        self.update(delta_time)
+    }
+
+    /// Get the render layer of the [`Component`].
+    ///
+    /// A [`Camera`](crate::Camera) only draws the [`Component`] if its layer mask includes this layer.
+    fn render_layer(&self) -> u32 {
+        0
+    }
 
     /// Get the [`Component`]'s drawable [`Quad`].
     fn get_quads(&self) -> Option<Vec<Quad>> {
@@ -0,0 +1,242 @@
+//! The [`RenderTarget`] struct, used for rendering into a [`Texture`].
+
+use std::rc::Rc;
+
+use wasm_bindgen::JsCast;
+
+use web_sys::{WebGlFramebuffer, WebGlRenderbuffer};
+
+use crate::gl::Bind;
+use crate::image::Image;
+use crate::texture::Texture;
+use crate::{gl, GL};
+
+/// A [`RenderTarget`] wraps a [`WebGlFramebuffer`] that can be drawn into instead of the canvas.
+///
+/// Its color attachment is a [`Texture`] that can later be sampled like any other, and it may optionally have a combined depth and stencil attachment.
+#[derive(Debug)]
+pub struct RenderTarget {
+    framebuffer: Option<WebGlFramebuffer>,
+    depth_stencil: Option<WebGlRenderbuffer>,
+    texture: Rc<Texture>,
+    /// Width of the [`RenderTarget`].
+    pub width: u32,
+    /// Height of the [`RenderTarget`].
+    pub height: u32,
+}
+
+impl Bind for RenderTarget {
+    /// Bind the [`RenderTarget`] and set the viewport to cover it.
+    fn bind(&self, gl: &GL) {
+        gl.bind_framebuffer(GL::FRAMEBUFFER, self.framebuffer.as_ref());
+        gl.viewport(0, 0, self.width as i32, self.height as i32);
+    }
+    /// Bind the default framebuffer and set the viewport to cover the canvas.
+    fn unbind(&self, gl: &GL) {
+        gl.bind_framebuffer(GL::FRAMEBUFFER, None);
+        gl.viewport(0, 0, gl.drawing_buffer_width(), gl.drawing_buffer_height());
+    }
+}
+
+impl Default for RenderTarget {
+    /// Create a 1x1 [`RenderTarget`] without any attachments, which draws into the canvas when bound.
+    fn default() -> Self {
+        Self {
+            framebuffer: None,
+            depth_stencil: None,
+            texture: Rc::new(Texture::default()),
+            width: 1,
+            height: 1,
+        }
+    }
+}
+
+impl Drop for RenderTarget {
+    fn drop(&mut self) {
+        if self.framebuffer.is_none() && self.depth_stencil.is_none() {
+            return;
+        }
+        let gl = gl::get_context();
+
+        gl.delete_framebuffer(self.framebuffer.as_ref());
+        gl.delete_renderbuffer(self.depth_stencil.as_ref());
+    }
+}
+
+impl RenderTarget {
+    /// Create a new [`RenderTarget`] with a color attachment of a given width and height.
+    pub fn new(gl: &GL, width: u32, height: u32) -> Self {
+        Self::new_with_attachments(gl, width, height, false)
+    }
+
+    /// Create a new [`RenderTarget`] with a color attachment, and a depth and stencil attachment.
+    pub fn new_with_depth_stencil(gl: &GL, width: u32, height: u32) -> Self {
+        Self::new_with_attachments(gl, width, height, true)
+    }
+
+    fn new_with_attachments(gl: &GL, width: u32, height: u32, depth_stencil: bool) -> Self {
+        let (width, height) = Self::clamp_size(width, height);
+        let texture = Texture::new_empty(gl, width, height);
+
+        let previous = bound_framebuffer(gl);
+        let framebuffer = gl.create_framebuffer();
+        gl.bind_framebuffer(GL::FRAMEBUFFER, framebuffer.as_ref());
+        gl.framebuffer_texture_2d(
+            GL::FRAMEBUFFER,
+            GL::COLOR_ATTACHMENT0,
+            GL::TEXTURE_2D,
+            texture.raw(),
+            0,
+        );
+
+        let depth_stencil = if depth_stencil {
+            let renderbuffer = gl.create_renderbuffer();
+            gl.bind_renderbuffer(GL::RENDERBUFFER, renderbuffer.as_ref());
+            gl.renderbuffer_storage(
+                GL::RENDERBUFFER,
+                GL::DEPTH24_STENCIL8,
+                width as i32,
+                height as i32,
+            );
+            gl.framebuffer_renderbuffer(
+                GL::FRAMEBUFFER,
+                GL::DEPTH_STENCIL_ATTACHMENT,
+                GL::RENDERBUFFER,
+                renderbuffer.as_ref(),
+            );
+            gl.bind_renderbuffer(GL::RENDERBUFFER, None);
+            renderbuffer
+        } else {
+            None
+        };
+
+        gl.bind_framebuffer(GL::FRAMEBUFFER, previous.as_ref());
+
+        Self {
+            framebuffer,
+            depth_stencil,
+            texture: Rc::new(texture),
+            width,
+            height,
+        }
+    }
+
+    /// Check whether the [`RenderTarget`] is complete, and can be rendered into.
+    pub fn is_complete(&self, gl: &GL) -> bool {
+        let previous = bound_framebuffer(gl);
+        gl.bind_framebuffer(GL::FRAMEBUFFER, self.framebuffer.as_ref());
+        let status = gl.check_framebuffer_status(GL::FRAMEBUFFER);
+        gl.bind_framebuffer(GL::FRAMEBUFFER, previous.as_ref());
+        status == GL::FRAMEBUFFER_COMPLETE
+    }
+
+    /// Check whether the [`RenderTarget`] has a depth and stencil attachment.
+    pub fn has_depth_stencil(&self) -> bool {
+        self.depth_stencil.is_some()
+    }
+
+    /// Get the width and height of the [`RenderTarget`].
+    pub fn size(&self) -> (u32, u32) {
+        (self.width, self.height)
+    }
+
+    /// Check whether resizing the [`RenderTarget`] to a given width and height would recreate its attachments.
+    ///
+    /// Sizes of zero are clamped to a single pixel, as an empty framebuffer can not be complete.
+    pub fn needs_resize(&self, width: u32, height: u32) -> bool {
+        Self::clamp_size(width, height) != self.size()
+    }
+
+    fn clamp_size(width: u32, height: u32) -> (u32, u32) {
+        (width.max(1), height.max(1))
+    }
+
+    /// Get the color attachment of the [`RenderTarget`] as a [`Texture`].
+    pub fn texture(&self) -> Rc<Texture> {
+        Rc::clone(&self.texture)
+    }
+
+    /// Read every pixel of the [`RenderTarget`] back as RGBA values, top row first.
+    ///
+    /// This is useful for screenshots and tests, but stalls until rendering has finished.
+    pub fn read_pixels(&self, gl: &GL) -> Result<Vec<u8>, String> {
+        self.read_region(gl, 0, 0, self.width, self.height)
+    }
+
+    /// Read a region of the [`RenderTarget`] back as RGBA values, top row first.
+    ///
+    /// The region is measured in pixels from the top-left corner.
+    pub fn read_region(
+        &self,
+        gl: &GL,
+        x: u32,
+        y: u32,
+        width: u32,
+        height: u32,
+    ) -> Result<Vec<u8>, String> {
+        if x.saturating_add(width) > self.width || y.saturating_add(height) > self.height {
+            return Err(format!(
+                "Region {}x{} at ({}, {}) is outside of a {}x{} render target.",
+                width, height, x, y, self.width, self.height
+            ));
+        }
+        read_framebuffer(
+            gl,
+            self.framebuffer.as_ref(),
+            x,
+            y,
+            width,
+            height,
+            self.height,
+        )
+    }
+
+    /// Resize the [`RenderTarget`], recreating its attachments.
+    ///
+    /// Any previous contents are lost, and [`Textures`](Texture) obtained before resizing will no longer be updated.
+    pub fn resize(&mut self, gl: &GL, width: u32, height: u32) {
+        if !self.needs_resize(width, height) {
+            return;
+        }
+        *self = Self::new_with_attachments(gl, width, height, self.has_depth_stencil());
+    }
+}
+
+/// Read a region of a framebuffer as RGBA values, flipping the rows so the top row comes first.
+///
+/// The region is measured from the top-left corner of a framebuffer with the given height.
+pub(crate) fn read_framebuffer(
+    gl: &GL,
+    framebuffer: Option<&WebGlFramebuffer>,
+    x: u32,
+    y: u32,
+    width: u32,
+    height: u32,
+    framebuffer_height: u32,
+) -> Result<Vec<u8>, String> {
+    let mut pixels = vec![0; width as usize * height as usize * 4];
+    let previous = bound_framebuffer(gl);
+    gl.bind_framebuffer(GL::FRAMEBUFFER, framebuffer);
+    let result = gl.read_pixels_with_opt_u8_array(
+        x as i32,
+        (framebuffer_height - y - height) as i32,
+        width as i32,
+        height as i32,
+        GL::RGBA,
+        GL::UNSIGNED_BYTE,
+        Some(&mut pixels),
+    );
+    gl.bind_framebuffer(GL::FRAMEBUFFER, previous.as_ref());
+    result.map_err(|error| format!("Failed to read pixels: {:?}", error))?;
+
+    let mut image = Image::new_from_pixels(width, height, pixels)?;
+    image.flip_vertically();
+    Ok(image.pixels)
+}
+
+/// Get the framebuffer currently bound to `FRAMEBUFFER`, or [`None`] for the canvas.
+pub(crate) fn bound_framebuffer(gl: &GL) -> Option<WebGlFramebuffer> {
+    gl.get_parameter(GL::FRAMEBUFFER_BINDING)
+        .ok()
+        .and_then(|framebuffer| framebuffer.dyn_into::<WebGlFramebuffer>().ok())
+}
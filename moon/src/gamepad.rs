@@ -0,0 +1,385 @@
+//! The [`Gamepads`] struct, and the [`GamepadSource`] trait used to poll them.
+
+use std::collections::BTreeMap;
+use std::fmt;
+
+use wasm_bindgen::JsCast;
+
+use crate::Vec2;
+
+/// Buttons of the standard gamepad layout, by their index.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum StandardButton {
+    /// Bottom face button, such as A or Cross.
+    South = 0,
+    /// Right face button, such as B or Circle.
+    East = 1,
+    /// Left face button, such as X or Square.
+    West = 2,
+    /// Top face button, such as Y or Triangle.
+    North = 3,
+    /// Left shoulder button.
+    LeftBumper = 4,
+    /// Right shoulder button.
+    RightBumper = 5,
+    /// Left analog trigger.
+    LeftTrigger = 6,
+    /// Right analog trigger.
+    RightTrigger = 7,
+    /// Select, Back or Share button.
+    Select = 8,
+    /// Start, or Options button.
+    Start = 9,
+    /// Pressing the left stick.
+    LeftStick = 10,
+    /// Pressing the right stick.
+    RightStick = 11,
+    /// Up on the directional pad.
+    DPadUp = 12,
+    /// Down on the directional pad.
+    DPadDown = 13,
+    /// Left on the directional pad.
+    DPadLeft = 14,
+    /// Right on the directional pad.
+    DPadRight = 15,
+    /// Home or Guide button.
+    Home = 16,
+}
+
+/// Axes of the standard gamepad layout, by their index.
+///
+/// The `Y` axes point downwards, matching the world.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum StandardAxis {
+    /// Horizontal axis of the left stick.
+    LeftStickX = 0,
+    /// Vertical axis of the left stick.
+    LeftStickY = 1,
+    /// Horizontal axis of the right stick.
+    RightStickX = 2,
+    /// Vertical axis of the right stick.
+    RightStickY = 3,
+}
+
+/// The raw state of a gamepad, as reported by a [`GamepadSource`].
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct GamepadState {
+    /// Index of the gamepad, which stays the same while it is connected.
+    pub index: u32,
+    /// Name of the gamepad, as reported by the browser.
+    pub id: String,
+    /// Whether the gamepad is still connected.
+    pub connected: bool,
+    /// Whether the buttons and axes follow the standard layout.
+    pub standard_mapping: bool,
+    /// Values of the buttons, from `0.0` to `1.0`.
+    pub buttons: Vec<f32>,
+    /// Values of the axes, from `-1.0` to `1.0`.
+    pub axes: Vec<f32>,
+}
+
+/// A [`GamepadSource`] reports the raw state of every gamepad when polled.
+pub trait GamepadSource: fmt::Debug {
+    /// Get the current state of every gamepad.
+    fn poll(&mut self) -> Vec<GamepadState>;
+}
+
+/// A [`GamepadSource`] that reads from the browser Gamepad API.
+#[derive(Debug, Default)]
+pub struct WebGamepadSource;
+
+impl GamepadSource for WebGamepadSource {
+    fn poll(&mut self) -> Vec<GamepadState> {
+        let gamepads = match web_sys::window().map(|window| window.navigator().get_gamepads()) {
+            Some(Ok(gamepads)) => gamepads,
+            _ => return Vec::new(),
+        };
+        gamepads
+            .iter()
+            .filter_map(|gamepad| gamepad.dyn_into::<web_sys::Gamepad>().ok())
+            .map(|gamepad| GamepadState {
+                index: gamepad.index(),
+                id: gamepad.id(),
+                connected: gamepad.connected(),
+                standard_mapping: gamepad.mapping() == web_sys::GamepadMappingType::Standard,
+                buttons: gamepad
+                    .buttons()
+                    .iter()
+                    .filter_map(|button| button.dyn_into::<web_sys::GamepadButton>().ok())
+                    .map(|button| button.value() as f32)
+                    .collect(),
+                axes: gamepad
+                    .axes()
+                    .iter()
+                    .filter_map(|axis| axis.as_f64())
+                    .map(|axis| axis as f32)
+                    .collect(),
+            })
+            .collect()
+    }
+}
+
+/// A [`GamepadSource`] that reports whatever it is given, for testing without a browser.
+#[derive(Debug, Default, Clone)]
+pub struct FakeGamepadSource {
+    /// The [`GamepadStates`](GamepadState) returned when polled.
+    pub gamepads: Vec<GamepadState>,
+}
+
+impl GamepadSource for FakeGamepadSource {
+    fn poll(&mut self) -> Vec<GamepadState> {
+        self.gamepads.clone()
+    }
+}
+
+/// A range of input values that are remapped, to ignore small movements and reach the maximum before the physical limit.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DeadZone {
+    /// Values below this are treated as `0.0`.
+    pub inner: f32,
+    /// Values above this are treated as `1.0`.
+    pub outer: f32,
+}
+
+impl Default for DeadZone {
+    fn default() -> Self {
+        Self {
+            inner: 0.15,
+            outer: 0.95,
+        }
+    }
+}
+
+impl DeadZone {
+    /// Remap a single value, keeping its sign.
+    pub fn apply(&self, value: f32) -> f32 {
+        let range = (self.outer - self.inner).max(f32::EPSILON);
+        let magnitude = ((value.abs() - self.inner) / range).clamp(0.0, 1.0);
+        magnitude.copysign(value)
+    }
+
+    /// Remap the length of a stick's position, keeping its direction.
+    ///
+    /// This avoids snapping to the axes, which happens when each axis is remapped separately.
+    pub fn apply_radial(&self, stick: Vec2) -> Vec2 {
+        let length = stick.norm();
+        if length <= self.inner {
+            return Vec2::zeros();
+        }
+        stick / length * self.apply(length)
+    }
+}
+
+/// A connected gamepad, with [`DeadZones`](DeadZone) applied to its sticks and triggers.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Gamepad {
+    /// Index of the gamepad.
+    pub index: u32,
+    /// Name of the gamepad, as reported by the browser.
+    pub id: String,
+    /// Whether the buttons and axes follow the standard layout.
+    ///
+    /// If not, [`StandardButton`] and [`StandardAxis`] may refer to the wrong inputs.
+    pub standard_mapping: bool,
+    buttons: Vec<f32>,
+    axes: Vec<f32>,
+}
+
+impl Gamepad {
+    /// Get the value of a button, from `0.0` to `1.0`.
+    pub fn button(&self, button: StandardButton) -> f32 {
+        self.button_value(button as usize)
+    }
+
+    /// Get the value of a button by its index, from `0.0` to `1.0`.
+    pub fn button_value(&self, index: usize) -> f32 {
+        self.buttons.get(index).copied().unwrap_or(0.0)
+    }
+
+    /// Get the value of an axis, from `-1.0` to `1.0`.
+    pub fn axis(&self, axis: StandardAxis) -> f32 {
+        self.axis_value(axis as usize)
+    }
+
+    /// Get the value of an axis by its index, from `-1.0` to `1.0`.
+    pub fn axis_value(&self, index: usize) -> f32 {
+        self.axes.get(index).copied().unwrap_or(0.0)
+    }
+
+    /// Get the position of the left stick.
+    pub fn left_stick(&self) -> Vec2 {
+        Vec2::new(
+            self.axis(StandardAxis::LeftStickX),
+            self.axis(StandardAxis::LeftStickY),
+        )
+    }
+
+    /// Get the position of the right stick.
+    pub fn right_stick(&self) -> Vec2 {
+        Vec2::new(
+            self.axis(StandardAxis::RightStickX),
+            self.axis(StandardAxis::RightStickY),
+        )
+    }
+
+    /// Get the number of buttons.
+    pub fn button_count(&self) -> usize {
+        self.buttons.len()
+    }
+
+    /// Get the number of axes.
+    pub fn axis_count(&self) -> usize {
+        self.axes.len()
+    }
+}
+
+/// A change in which gamepads are connected.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GamepadEvent {
+    /// The gamepad with this index was connected.
+    Connected(u32),
+    /// The gamepad with this index was disconnected.
+    Disconnected(u32),
+}
+
+/// Tracks every connected [`Gamepad`], polling them from a [`GamepadSource`].
+///
+/// # Examples
+/// ```
+/// use moon_engine::gamepad::{FakeGamepadSource, GamepadEvent, GamepadState, Gamepads};
+///
+/// let mut gamepads = Gamepads::new_with_source(Box::new(FakeGamepadSource {
+///     gamepads: vec![GamepadState {
+///         connected: true,
+///         ..Default::default()
+///     }],
+/// }));
+///
+/// gamepads.poll();
+///
+/// assert_eq!(gamepads.events(), &[GamepadEvent::Connected(0)]);
+/// ```
+#[derive(Debug, Default)]
+pub struct Gamepads {
+    /// [`DeadZone`] applied to the analog sticks.
+    pub stick_dead_zone: DeadZone,
+    /// [`DeadZone`] applied to the analog triggers.
+    pub trigger_dead_zone: DeadZone,
+    source: Option<Box<dyn GamepadSource>>,
+    gamepads: BTreeMap<u32, Gamepad>,
+    events: Vec<GamepadEvent>,
+    states: Vec<GamepadState>,
+}
+
+impl Gamepads {
+    /// Create a new [`Gamepads`] without a [`GamepadSource`], which never reports any [`Gamepad`].
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Create a new [`Gamepads`] with a given [`GamepadSource`].
+    pub fn new_with_source(source: Box<dyn GamepadSource>) -> Self {
+        Self {
+            source: Some(source),
+            ..Default::default()
+        }
+    }
+
+    /// Set the [`GamepadSource`] to poll.
+    pub fn set_source(&mut self, source: Box<dyn GamepadSource>) {
+        self.source = Some(source);
+    }
+
+    /// Poll the [`GamepadSource`], updating every [`Gamepad`]. This should be called once per frame.
+    ///
+    /// [`GamepadEvents`](GamepadEvent) from the previous poll are cleared.
+    pub fn poll(&mut self) {
+        let states = match self.source.as_mut() {
+            Some(source) => source.poll(),
+            None => Vec::new(),
+        };
+        self.update(states);
+    }
+
+    /// Update every [`Gamepad`] from a list of [`GamepadStates`](GamepadState), instead of polling the [`GamepadSource`].
+    ///
+    /// [`GamepadEvents`](GamepadEvent) from the previous update are cleared.
+    pub fn update(&mut self, states: Vec<GamepadState>) {
+        self.events.clear();
+        self.states = states.clone();
+
+        let mut gamepads = BTreeMap::new();
+        for state in states.into_iter().filter(|state| state.connected) {
+            gamepads.insert(state.index, self.map(state));
+        }
+        for index in self.gamepads.keys() {
+            if !gamepads.contains_key(index) {
+                self.events.push(GamepadEvent::Disconnected(*index));
+            }
+        }
+        for index in gamepads.keys() {
+            if !self.gamepads.contains_key(index) {
+                self.events.push(GamepadEvent::Connected(*index));
+            }
+        }
+        self.gamepads = gamepads;
+    }
+
+    /// Get the raw [`GamepadStates`](GamepadState) from the latest poll, before [`DeadZones`](DeadZone) were applied.
+    pub fn states(&self) -> &[GamepadState] {
+        &self.states
+    }
+
+    /// Get the [`GamepadEvents`](GamepadEvent) from the latest poll.
+    pub fn events(&self) -> &[GamepadEvent] {
+        &self.events
+    }
+
+    /// Get the requested [`Gamepad`], if it is connected.
+    pub fn get(&self, index: u32) -> Option<&Gamepad> {
+        self.gamepads.get(&index)
+    }
+
+    /// Get an iterator over every connected [`Gamepad`].
+    pub fn iter(&self) -> impl Iterator<Item = &Gamepad> {
+        self.gamepads.values()
+    }
+
+    /// Get the number of connected [`Gamepads`](Gamepad).
+    pub fn len(&self) -> usize {
+        self.gamepads.len()
+    }
+
+    /// Check if there are no connected [`Gamepads`](Gamepad).
+    pub fn is_empty(&self) -> bool {
+        self.gamepads.is_empty()
+    }
+
+    /// Apply the [`DeadZones`](DeadZone) to the raw [`GamepadState`].
+    fn map(&self, state: GamepadState) -> Gamepad {
+        let mut buttons = state.buttons;
+        let mut axes = state.axes;
+        if state.standard_mapping {
+            for trigger in [StandardButton::LeftTrigger, StandardButton::RightTrigger] {
+                if let Some(value) = buttons.get_mut(trigger as usize) {
+                    *value = self.trigger_dead_zone.apply(*value);
+                }
+            }
+        }
+        for stick in axes.chunks_mut(2) {
+            if let [x, y] = stick {
+                let value = self.stick_dead_zone.apply_radial(Vec2::new(*x, *y));
+                (*x, *y) = (value.x, value.y);
+            } else {
+                stick[0] = self.stick_dead_zone.apply(stick[0]);
+            }
+        }
+        Gamepad {
+            index: state.index,
+            id: state.id,
+            standard_mapping: state.standard_mapping,
+            buttons,
+            axes,
+        }
+    }
+}
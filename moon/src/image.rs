@@ -0,0 +1,679 @@
+//! The [`Image`] struct, which decodes PNG, QOI and BMP images into RGBA pixels.
+
+use crate::inflate::zlib_decompress_with_limit;
+
+/// The largest width or height of a decoded [`Image`], in pixels.
+pub const MAX_IMAGE_SIZE: u32 = 16384;
+/// The signature at the start of every PNG file.
+const PNG_SIGNATURE: [u8; 8] = [0x89, b'P', b'N', b'G', 0x0D, 0x0A, 0x1A, 0x0A];
+/// Starting columns, rows and spacing of the seven Adam7 interlacing passes.
+const ADAM7: [(usize, usize, usize, usize); 7] = [
+    (0, 0, 8, 8),
+    (4, 0, 8, 8),
+    (0, 4, 4, 8),
+    (2, 0, 4, 4),
+    (0, 2, 2, 4),
+    (1, 0, 2, 2),
+    (0, 1, 1, 2),
+];
+
+/// A file format that an [`Image`] can be decoded from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ImageFormat {
+    /// Portable Network Graphics.
+    Png,
+    /// The Quite OK Image format.
+    Qoi,
+    /// Windows Bitmap.
+    Bmp,
+}
+
+impl ImageFormat {
+    /// Detect the format of an encoded image from its first few bytes.
+    pub fn detect(bytes: &[u8]) -> Option<Self> {
+        if bytes.starts_with(&PNG_SIGNATURE) {
+            Some(ImageFormat::Png)
+        } else if bytes.starts_with(b"qoif") {
+            Some(ImageFormat::Qoi)
+        } else if bytes.starts_with(b"BM") {
+            Some(ImageFormat::Bmp)
+        } else {
+            None
+        }
+    }
+}
+
+/// A decoded image, stored as 8-bit RGBA pixels from the top row down.
+///
+/// # Examples
+/// ```
+/// use moon_engine::image::Image;
+///
+/// let mut image = Image::new_from_pixels(1, 1, vec![255, 0, 0, 128]).unwrap();
+/// image.premultiply_alpha();
+///
+/// assert_eq!(image.get_pixel(0, 0), Some([128, 0, 0, 128]));
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Image {
+    /// Width of the [`Image`], in pixels.
+    pub width: u32,
+    /// Height of the [`Image`], in pixels.
+    pub height: u32,
+    /// RGBA values of every pixel, row by row.
+    pub pixels: Vec<u8>,
+    /// Whether the color values are sRGB encoded, and should be converted to linear values when sampled.
+    pub srgb: bool,
+    /// Whether the color values have already been multiplied by their alpha.
+    pub premultiplied: bool,
+}
+
+impl Image {
+    /// Create a new [`Image`] from RGBA pixels, checking that there are enough of them.
+    pub fn new_from_pixels(width: u32, height: u32, pixels: Vec<u8>) -> Result<Self, String> {
+        let expected = (width as usize)
+            .checked_mul(height as usize)
+            .and_then(|count| count.checked_mul(4))
+            .ok_or_else(|| format!("A {}x{} image is too large.", width, height))?;
+        if pixels.len() != expected {
+            return Err(format!(
+                "Expected {} bytes of pixels for a {}x{} image, found {}.",
+                expected,
+                width,
+                height,
+                pixels.len()
+            ));
+        }
+        Ok(Self {
+            width,
+            height,
+            pixels,
+            srgb: false,
+            premultiplied: false,
+        })
+    }
+
+    /// Decode an [`Image`], detecting its [`ImageFormat`].
+    pub fn decode(bytes: &[u8]) -> Result<Self, String> {
+        match ImageFormat::detect(bytes) {
+            Some(ImageFormat::Png) => Self::decode_png(bytes),
+            Some(ImageFormat::Qoi) => Self::decode_qoi(bytes),
+            Some(ImageFormat::Bmp) => Self::decode_bmp(bytes),
+            None => Err(String::from("Unknown image format.")),
+        }
+    }
+
+    /// Get the RGBA value of a pixel, or [`None`] if it is outside the [`Image`].
+    pub fn get_pixel(&self, x: u32, y: u32) -> Option<[u8; 4]> {
+        if x >= self.width || y >= self.height {
+            return None;
+        }
+        let index = (y as usize * self.width as usize + x as usize) * 4;
+        let pixel = &self.pixels[index..index + 4];
+        Some([pixel[0], pixel[1], pixel[2], pixel[3]])
+    }
+
+    /// Multiply the color values of every pixel by their alpha, if that has not been done already.
+    ///
+    /// Premultiplied images blend correctly at the edges of transparent areas when filtered, using a blend function of `ONE, ONE_MINUS_SRC_ALPHA`.
+    pub fn premultiply_alpha(&mut self) {
+        if self.premultiplied {
+            return;
+        }
+        for pixel in self.pixels.chunks_exact_mut(4) {
+            let alpha = pixel[3] as u32;
+            for channel in pixel[..3].iter_mut() {
+                *channel = ((*channel as u32 * alpha + 127) / 255) as u8;
+            }
+        }
+        self.premultiplied = true;
+    }
+
+    /// Flip the [`Image`] upside down.
+    pub fn flip_vertically(&mut self) {
+        let row = self.width as usize * 4;
+        let height = self.height as usize;
+        for y in 0..height / 2 {
+            let (top, bottom) = self.pixels.split_at_mut((height - 1 - y) * row);
+            top[y * row..(y + 1) * row].swap_with_slice(&mut bottom[..row]);
+        }
+    }
+
+    /// Decode a PNG image, of any bit depth and color type.
+    ///
+    /// The [`srgb`](Image::srgb) flag is set if the file has an `sRGB` chunk.
+    pub fn decode_png(bytes: &[u8]) -> Result<Self, String> {
+        if !bytes.starts_with(&PNG_SIGNATURE) {
+            return Err(String::from("Not a PNG image."));
+        }
+        let mut reader = ByteReader::new(&bytes[PNG_SIGNATURE.len()..]);
+        let mut header = None;
+        let mut palette: &[u8] = &[];
+        let mut transparency: &[u8] = &[];
+        let mut data = Vec::new();
+        let mut srgb = false;
+        loop {
+            let length = reader.u32_be()? as usize;
+            let chunk = reader.take(length.checked_add(4).ok_or("PNG chunk is too long.")?)?;
+            let checksum = reader.u32_be()?;
+            if crc32(chunk) != checksum {
+                return Err(String::from("PNG chunk checksum does not match."));
+            }
+            let (kind, contents) = chunk.split_at(4);
+            match kind {
+                b"IHDR" => header = Some(PngHeader::new(contents)?),
+                b"PLTE" => palette = contents,
+                b"tRNS" => transparency = contents,
+                b"IDAT" => data.extend_from_slice(contents),
+                b"sRGB" => srgb = true,
+                b"IEND" => break,
+                _ => {}
+            }
+        }
+        let header = header.ok_or("PNG image has no header.")?;
+        let expected = header.data_length()?;
+        let filtered = zlib_decompress_with_limit(&data, expected)?;
+        if filtered.len() < expected {
+            return Err(String::from("PNG image data is too short."));
+        }
+
+        let mut pixels = vec![0; pixel_bytes(header.width, header.height)?];
+        if header.interlaced {
+            let mut offset = 0;
+            for (start_x, start_y, step_x, step_y) in ADAM7 {
+                let (pass_width, pass_height) = adam7_pass_size(
+                    header.width,
+                    header.height,
+                    start_x,
+                    start_y,
+                    step_x,
+                    step_y,
+                );
+                if pass_width == 0 || pass_height == 0 {
+                    continue;
+                }
+                let (rows, used) = header.unfilter(&filtered[offset..], pass_width, pass_height)?;
+                offset += used;
+                for (y, row) in rows.iter().enumerate() {
+                    for x in 0..pass_width {
+                        let pixel = header.pixel(row, x, palette, transparency);
+                        let index =
+                            ((start_y + y * step_y) * header.width + start_x + x * step_x) * 4;
+                        pixels[index..index + 4].copy_from_slice(&pixel);
+                    }
+                }
+            }
+        } else {
+            let (rows, _) = header.unfilter(&filtered, header.width, header.height)?;
+            for (y, row) in rows.iter().enumerate() {
+                for x in 0..header.width {
+                    let pixel = header.pixel(row, x, palette, transparency);
+                    let index = (y * header.width + x) * 4;
+                    pixels[index..index + 4].copy_from_slice(&pixel);
+                }
+            }
+        }
+
+        Ok(Self {
+            srgb,
+            ..Self::new_from_pixels(header.width as u32, header.height as u32, pixels)?
+        })
+    }
+
+    /// Decode a QOI image.
+    ///
+    /// The [`srgb`](Image::srgb) flag is set from the color space in the header.
+    pub fn decode_qoi(bytes: &[u8]) -> Result<Self, String> {
+        let mut reader = ByteReader::new(bytes);
+        if reader.take(4)? != b"qoif" {
+            return Err(String::from("Not a QOI image."));
+        }
+        let width = reader.u32_be()?;
+        let height = reader.u32_be()?;
+        let _channels = reader.u8()?;
+        let srgb = reader.u8()? == 0;
+
+        let length = pixel_bytes(width as usize, height as usize)?;
+        // Each operation produces at most 62 pixels
+        if (bytes.len() as u64) * 62 * 4 < length as u64 {
+            return Err(String::from("QOI image data is too short."));
+        }
+        let mut pixels = Vec::with_capacity(length);
+        let mut seen = [[0u8; 4]; 64];
+        let mut pixel = [0u8, 0, 0, 255];
+        let mut run = 0;
+        while pixels.len() < length {
+            if run > 0 {
+                run -= 1;
+            } else {
+                let op = reader.u8()?;
+                match op {
+                    0xFE => {
+                        pixel[..3].copy_from_slice(reader.take(3)?);
+                    }
+                    0xFF => {
+                        pixel.copy_from_slice(reader.take(4)?);
+                    }
+                    _ => match op >> 6 {
+                        0 => pixel = seen[op as usize],
+                        1 => {
+                            pixel[0] = pixel[0].wrapping_add((op >> 4) & 3).wrapping_sub(2);
+                            pixel[1] = pixel[1].wrapping_add((op >> 2) & 3).wrapping_sub(2);
+                            pixel[2] = pixel[2].wrapping_add(op & 3).wrapping_sub(2);
+                        }
+                        2 => {
+                            let green = (op & 0x3F).wrapping_sub(32);
+                            let next = reader.u8()?;
+                            pixel[0] = pixel[0]
+                                .wrapping_add(green)
+                                .wrapping_add(next >> 4)
+                                .wrapping_sub(8);
+                            pixel[1] = pixel[1].wrapping_add(green);
+                            pixel[2] = pixel[2]
+                                .wrapping_add(green)
+                                .wrapping_add(next & 0x0F)
+                                .wrapping_sub(8);
+                        }
+                        _ => run = (op & 0x3F) as usize,
+                    },
+                }
+                let hash = pixel[0] as usize * 3
+                    + pixel[1] as usize * 5
+                    + pixel[2] as usize * 7
+                    + pixel[3] as usize * 11;
+                seen[hash % 64] = pixel;
+            }
+            pixels.extend_from_slice(&pixel);
+        }
+
+        Ok(Self {
+            srgb,
+            ..Self::new_from_pixels(width, height, pixels)?
+        })
+    }
+
+    /// Decode an uncompressed BMP image, with 8, 24 or 32 bits per pixel.
+    pub fn decode_bmp(bytes: &[u8]) -> Result<Self, String> {
+        let mut reader = ByteReader::new(bytes);
+        if reader.take(2)? != b"BM" {
+            return Err(String::from("Not a BMP image."));
+        }
+        reader.skip(8)?;
+        let data_offset = reader.u32_le()? as usize;
+        let header_size = reader.u32_le()? as usize;
+        let width = reader.u32_le()? as i32;
+        let height = reader.u32_le()? as i32;
+        reader.skip(2)?;
+        let bits = reader.u16_le()?;
+        let compression = reader.u32_le()?;
+        if width <= 0 || height == 0 {
+            return Err(String::from("Invalid BMP size."));
+        }
+        // Rows are stored from the bottom up, unless the height is negative
+        let (width, height, top_down) =
+            (width as usize, height.unsigned_abs() as usize, height < 0);
+        let length = pixel_bytes(width, height)?;
+        let too_short = || String::from("BMP pixel data is too short.");
+
+        let palette = match bits {
+            8 => {
+                reader.skip(12)?;
+                let mut colors = reader.u32_le()? as usize;
+                if colors == 0 {
+                    colors = 256;
+                }
+                if colors > 256 {
+                    return Err(format!("BMP palette has too many colors ({}).", colors));
+                }
+                let start = header_size
+                    .checked_add(14)
+                    .ok_or("BMP palette is too short.")?;
+                bytes
+                    .get(start..)
+                    .and_then(|palette| palette.get(..colors * 4))
+                    .ok_or("BMP palette is too short.")?
+            }
+            24 | 32 => &[],
+            _ => return Err(format!("Unsupported BMP bit depth {}.", bits)),
+        };
+        // Plain RGB, or bit fields matching the usual BGRA layout
+        if compression != 0 && compression != 3 {
+            return Err(String::from("Compressed BMP images are not supported."));
+        }
+
+        let stride = (width * bits as usize / 8 + 3) & !3;
+        let data = stride
+            .checked_mul(height)
+            .and_then(|size| bytes.get(data_offset..)?.get(..size))
+            .ok_or_else(too_short)?;
+        let mut pixels = vec![0; length];
+        for y in 0..height {
+            let source_y = if top_down { y } else { height - 1 - y };
+            let row = &data[source_y * stride..];
+            for x in 0..width {
+                let bgra = match bits {
+                    8 => {
+                        let index = row[x] as usize * 4;
+                        let color = palette
+                            .get(index..index + 3)
+                            .ok_or("Invalid palette index.")?;
+                        [color[0], color[1], color[2], 255]
+                    }
+                    24 => [row[x * 3], row[x * 3 + 1], row[x * 3 + 2], 255],
+                    _ => [row[x * 4], row[x * 4 + 1], row[x * 4 + 2], row[x * 4 + 3]],
+                };
+                let index = (y * width + x) * 4;
+                pixels[index..index + 4].copy_from_slice(&[bgra[2], bgra[1], bgra[0], bgra[3]]);
+            }
+        }
+        // Many 32-bit images leave the alpha channel unused
+        if bits == 32 && pixels.chunks_exact(4).all(|pixel| pixel[3] == 0) {
+            pixels.chunks_exact_mut(4).for_each(|pixel| pixel[3] = 255);
+        }
+
+        Self::new_from_pixels(width as u32, height as u32, pixels)
+    }
+}
+
+/// The contents of a PNG `IHDR` chunk.
+struct PngHeader {
+    width: usize,
+    height: usize,
+    bit_depth: u8,
+    color_type: u8,
+    interlaced: bool,
+}
+
+impl PngHeader {
+    fn new(contents: &[u8]) -> Result<Self, String> {
+        let mut reader = ByteReader::new(contents);
+        let header = Self {
+            width: reader.u32_be()? as usize,
+            height: reader.u32_be()? as usize,
+            bit_depth: reader.u8()?,
+            color_type: reader.u8()?,
+            interlaced: {
+                reader.skip(2)?;
+                reader.u8()? == 1
+            },
+        };
+        let valid = match header.color_type {
+            0 => matches!(header.bit_depth, 1 | 2 | 4 | 8 | 16),
+            3 => matches!(header.bit_depth, 1 | 2 | 4 | 8),
+            2 | 4 | 6 => matches!(header.bit_depth, 8 | 16),
+            _ => false,
+        };
+        if !valid || header.width == 0 || header.height == 0 {
+            return Err(String::from("Unsupported PNG format."));
+        }
+        pixel_bytes(header.width, header.height)?;
+        Ok(header)
+    }
+
+    fn channels(&self) -> usize {
+        match self.color_type {
+            2 => 3,
+            4 => 2,
+            6 => 4,
+            _ => 1,
+        }
+    }
+
+    fn bits_per_pixel(&self) -> usize {
+        self.channels() * self.bit_depth as usize
+    }
+
+    /// Get the length of a filtered row of pixels, without its filter type byte.
+    fn row_length(&self, width: usize) -> Result<usize, String> {
+        width
+            .checked_mul(self.bits_per_pixel())
+            .map(|bits| bits.div_ceil(8))
+            .ok_or_else(|| String::from("PNG image is too large."))
+    }
+
+    /// Get the length of a set of filtered rows, including their filter type bytes.
+    fn rows_length(&self, width: usize, height: usize) -> Result<usize, String> {
+        self.row_length(width)?
+            .checked_add(1)
+            .and_then(|length| length.checked_mul(height))
+            .ok_or_else(|| String::from("PNG image is too large."))
+    }
+
+    /// Get the length of the decompressed image data, for every interlacing pass.
+    fn data_length(&self) -> Result<usize, String> {
+        if !self.interlaced {
+            return self.rows_length(self.width, self.height);
+        }
+        let mut length = 0usize;
+        for (start_x, start_y, step_x, step_y) in ADAM7 {
+            let (width, height) =
+                adam7_pass_size(self.width, self.height, start_x, start_y, step_x, step_y);
+            if width == 0 || height == 0 {
+                continue;
+            }
+            length = length
+                .checked_add(self.rows_length(width, height)?)
+                .ok_or("PNG image is too large.")?;
+        }
+        Ok(length)
+    }
+
+    /// Undo the filtering of a set of rows, returning them and the number of bytes used.
+    fn unfilter(
+        &self,
+        data: &[u8],
+        width: usize,
+        height: usize,
+    ) -> Result<(Vec<Vec<u8>>, usize), String> {
+        let row_length = self.row_length(width)?;
+        let distance = self.bits_per_pixel().div_ceil(8);
+        let used = self.rows_length(width, height)?;
+        if data.len() < used {
+            return Err(String::from("PNG image data is too short."));
+        }
+
+        let mut rows: Vec<Vec<u8>> = Vec::with_capacity(height);
+        let empty = vec![0; row_length];
+        for (y, line) in data[..used].chunks_exact(row_length + 1).enumerate() {
+            let (filter, line) = (line[0], &line[1..]);
+            let above = if y == 0 { &empty } else { &rows[y - 1] };
+            let mut row = line.to_vec();
+            for x in 0..row_length {
+                let left = if x >= distance { row[x - distance] } else { 0 };
+                let up = above[x];
+                let up_left = if x >= distance {
+                    above[x - distance]
+                } else {
+                    0
+                };
+                let prediction = match filter {
+                    0 => 0,
+                    1 => left,
+                    2 => up,
+                    3 => ((left as u16 + up as u16) / 2) as u8,
+                    4 => paeth(left, up, up_left),
+                    _ => return Err(format!("Invalid PNG filter type {}.", filter)),
+                };
+                row[x] = row[x].wrapping_add(prediction);
+            }
+            rows.push(row);
+        }
+        Ok((rows, used))
+    }
+
+    /// Get a sample from a row, at its full bit depth.
+    fn sample(&self, row: &[u8], index: usize) -> u16 {
+        match self.bit_depth {
+            16 => u16::from_be_bytes([row[index * 2], row[index * 2 + 1]]),
+            8 => row[index] as u16,
+            bits => {
+                let bits = bits as usize;
+                let byte = row[index * bits / 8];
+                let shift = 8 - bits - (index * bits) % 8;
+                ((byte >> shift) & ((1 << bits) - 1) as u8) as u16
+            }
+        }
+    }
+
+    /// Scale a sample to 8 bits.
+    fn to_u8(&self, sample: u16) -> u8 {
+        match self.bit_depth {
+            16 => (sample >> 8) as u8,
+            8 => sample as u8,
+            bits => (sample * 255 / ((1 << bits) - 1)) as u8,
+        }
+    }
+
+    /// Get the RGBA value of a pixel in an unfiltered row.
+    fn pixel(&self, row: &[u8], x: usize, palette: &[u8], transparency: &[u8]) -> [u8; 4] {
+        let channels = self.channels();
+        let sample = |channel| self.sample(row, x * channels + channel);
+        let key = |index: usize| {
+            transparency
+                .get(index * 2..index * 2 + 2)
+                .map(|bytes| u16::from_be_bytes([bytes[0], bytes[1]]))
+        };
+        match self.color_type {
+            0 => {
+                let gray = sample(0);
+                let value = self.to_u8(gray);
+                let alpha = if key(0) == Some(gray) { 0 } else { 255 };
+                [value, value, value, alpha]
+            }
+            2 => {
+                let (red, green, blue) = (sample(0), sample(1), sample(2));
+                let transparent =
+                    key(0) == Some(red) && key(1) == Some(green) && key(2) == Some(blue);
+                [
+                    self.to_u8(red),
+                    self.to_u8(green),
+                    self.to_u8(blue),
+                    if transparent { 0 } else { 255 },
+                ]
+            }
+            3 => {
+                let index = sample(0) as usize;
+                let color = palette.get(index * 3..index * 3 + 3).unwrap_or(&[0, 0, 0]);
+                let alpha = transparency.get(index).copied().unwrap_or(255);
+                [color[0], color[1], color[2], alpha]
+            }
+            4 => {
+                let value = self.to_u8(sample(0));
+                [value, value, value, self.to_u8(sample(1))]
+            }
+            _ => [
+                self.to_u8(sample(0)),
+                self.to_u8(sample(1)),
+                self.to_u8(sample(2)),
+                self.to_u8(sample(3)),
+            ],
+        }
+    }
+}
+
+/// Get the width and height of an Adam7 interlacing pass.
+fn adam7_pass_size(
+    width: usize,
+    height: usize,
+    start_x: usize,
+    start_y: usize,
+    step_x: usize,
+    step_y: usize,
+) -> (usize, usize) {
+    (
+        (width + step_x - start_x - 1) / step_x,
+        (height + step_y - start_y - 1) / step_y,
+    )
+}
+
+/// Get the number of bytes of RGBA pixels in a decoded image, rejecting sizes above [`MAX_IMAGE_SIZE`].
+fn pixel_bytes(width: usize, height: usize) -> Result<usize, String> {
+    if width > MAX_IMAGE_SIZE as usize || height > MAX_IMAGE_SIZE as usize {
+        return Err(format!(
+            "Image size {}x{} is larger than the maximum of {}.",
+            width, height, MAX_IMAGE_SIZE
+        ));
+    }
+    width
+        .checked_mul(height)
+        .and_then(|count| count.checked_mul(4))
+        .ok_or_else(|| format!("A {}x{} image is too large.", width, height))
+}
+
+/// The Paeth predictor used by PNG filtering.
+fn paeth(left: u8, up: u8, up_left: u8) -> u8 {
+    let estimate = left as i16 + up as i16 - up_left as i16;
+    let (to_left, to_up, to_up_left) = (
+        (estimate - left as i16).abs(),
+        (estimate - up as i16).abs(),
+        (estimate - up_left as i16).abs(),
+    );
+    if to_left <= to_up && to_left <= to_up_left {
+        left
+    } else if to_up <= to_up_left {
+        up
+    } else {
+        up_left
+    }
+}
+
+/// Get the CRC-32 checksum of some data, as used by PNG chunks.
+pub fn crc32(data: &[u8]) -> u32 {
+    let mut crc = 0xFFFF_FFFFu32;
+    for byte in data {
+        crc ^= *byte as u32;
+        for _ in 0..8 {
+            crc = if crc & 1 == 1 {
+                0xEDB8_8320 ^ (crc >> 1)
+            } else {
+                crc >> 1
+            };
+        }
+    }
+    !crc
+}
+
+/// Reads numbers from a byte slice.
+struct ByteReader<'a> {
+    bytes: &'a [u8],
+    position: usize,
+}
+
+impl<'a> ByteReader<'a> {
+    fn new(bytes: &'a [u8]) -> Self {
+        Self { bytes, position: 0 }
+    }
+
+    fn take(&mut self, count: usize) -> Result<&'a [u8], String> {
+        let bytes = self
+            .position
+            .checked_add(count)
+            .and_then(|end| self.bytes.get(self.position..end))
+            .ok_or("Unexpected end of image data.")?;
+        self.position += count;
+        Ok(bytes)
+    }
+
+    fn skip(&mut self, count: usize) -> Result<(), String> {
+        self.take(count).map(|_| ())
+    }
+
+    fn u8(&mut self) -> Result<u8, String> {
+        Ok(self.take(1)?[0])
+    }
+
+    fn u16_le(&mut self) -> Result<u16, String> {
+        let bytes = self.take(2)?;
+        Ok(u16::from_le_bytes([bytes[0], bytes[1]]))
+    }
+
+    fn u32_le(&mut self) -> Result<u32, String> {
+        let bytes = self.take(4)?;
+        Ok(u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+    }
+
+    fn u32_be(&mut self) -> Result<u32, String> {
+        let bytes = self.take(4)?;
+        Ok(u32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+    }
+}
@@ -0,0 +1,397 @@
+//! Decompression of DEFLATE, zlib and gzip data, as used by PNG images and compressed map layers.
+
+use crate::image::crc32;
+
+/// Base lengths of the length codes 257 to 285.
+const LENGTH_BASE: [u16; 29] = [
+    3, 4, 5, 6, 7, 8, 9, 10, 11, 13, 15, 17, 19, 23, 27, 31, 35, 43, 51, 59, 67, 83, 99, 115, 131,
+    163, 195, 227, 258,
+];
+/// Extra bits of the length codes 257 to 285.
+const LENGTH_EXTRA: [u8; 29] = [
+    0, 0, 0, 0, 0, 0, 0, 0, 1, 1, 1, 1, 2, 2, 2, 2, 3, 3, 3, 3, 4, 4, 4, 4, 5, 5, 5, 5, 0,
+];
+/// Base distances of the distance codes 0 to 29.
+const DISTANCE_BASE: [u16; 30] = [
+    1, 2, 3, 4, 5, 7, 9, 13, 17, 25, 33, 49, 65, 97, 129, 193, 257, 385, 513, 769, 1025, 1537,
+    2049, 3073, 4097, 6145, 8193, 12289, 16385, 24577,
+];
+/// Extra bits of the distance codes 0 to 29.
+const DISTANCE_EXTRA: [u8; 30] = [
+    0, 0, 0, 0, 1, 1, 2, 2, 3, 3, 4, 4, 5, 5, 6, 6, 7, 7, 8, 8, 9, 9, 10, 10, 11, 11, 12, 12, 13,
+    13,
+];
+/// Order in which the code lengths of the code length alphabet are stored.
+const CODE_LENGTH_ORDER: [usize; 19] = [
+    16, 17, 18, 0, 8, 7, 9, 6, 10, 5, 11, 4, 12, 3, 13, 2, 14, 1, 15,
+];
+/// Longest code allowed by DEFLATE.
+const MAX_BITS: usize = 15;
+
+/// Reads bits from a byte slice, least significant bit first.
+struct BitReader<'a> {
+    bytes: &'a [u8],
+    position: usize,
+    bit_buffer: u32,
+    bit_count: u32,
+}
+
+impl<'a> BitReader<'a> {
+    fn new(bytes: &'a [u8]) -> Self {
+        Self {
+            bytes,
+            position: 0,
+            bit_buffer: 0,
+            bit_count: 0,
+        }
+    }
+
+    fn bits(&mut self, count: u32) -> Result<u32, String> {
+        while self.bit_count < count {
+            let byte = *self
+                .bytes
+                .get(self.position)
+                .ok_or("Unexpected end of compressed data.")?;
+            self.position += 1;
+            self.bit_buffer |= (byte as u32) << self.bit_count;
+            self.bit_count += 8;
+        }
+        let value = self.bit_buffer & ((1u64 << count) - 1) as u32;
+        self.bit_buffer >>= count;
+        self.bit_count -= count;
+        Ok(value)
+    }
+
+    /// Discard the remaining bits of the current byte.
+    fn align(&mut self) {
+        self.bit_buffer = 0;
+        self.bit_count = 0;
+    }
+}
+
+/// A canonical Huffman code, stored as the number of codes of each length and the symbols in code order.
+struct Huffman {
+    counts: [u16; MAX_BITS + 1],
+    symbols: Vec<u16>,
+}
+
+impl Huffman {
+    fn new(lengths: &[u8]) -> Result<Self, String> {
+        let mut counts = [0u16; MAX_BITS + 1];
+        for length in lengths {
+            counts[*length as usize] += 1;
+        }
+        counts[0] = 0;
+
+        // Check that the code is not over-subscribed
+        let mut left = 1i32;
+        for count in counts.iter().skip(1) {
+            left = (left << 1) - *count as i32;
+            if left < 0 {
+                return Err(String::from("Invalid Huffman code lengths."));
+            }
+        }
+
+        let mut offsets = [0u16; MAX_BITS + 2];
+        for length in 1..=MAX_BITS {
+            offsets[length + 1] = offsets[length] + counts[length];
+        }
+        let mut symbols = vec![0; lengths.len()];
+        for (symbol, length) in lengths.iter().enumerate() {
+            if *length != 0 {
+                symbols[offsets[*length as usize] as usize] = symbol as u16;
+                offsets[*length as usize] += 1;
+            }
+        }
+        Ok(Self { counts, symbols })
+    }
+
+    fn decode(&self, reader: &mut BitReader) -> Result<u16, String> {
+        let (mut code, mut first, mut index) = (0i32, 0i32, 0i32);
+        for length in 1..=MAX_BITS {
+            code |= reader.bits(1)? as i32;
+            let count = self.counts[length] as i32;
+            if code - first < count {
+                return Ok(self.symbols[(index + code - first) as usize]);
+            }
+            index += count;
+            first = (first + count) << 1;
+            code <<= 1;
+        }
+        Err(String::from("Invalid Huffman code."))
+    }
+}
+
+/// Decompress raw DEFLATE data.
+///
+/// # Examples
+/// ```
+/// use moon_engine::inflate::inflate;
+///
+/// // A single stored block
+/// let data = [0x01, 0x03, 0x00, 0xFC, 0xFF, b'a', b'b', b'c'];
+/// assert_eq!(inflate(&data).unwrap(), b"abc");
+/// ```
+pub fn inflate(data: &[u8]) -> Result<Vec<u8>, String> {
+    inflate_with_limit(data, usize::MAX)
+}
+
+/// Decompress raw DEFLATE data, failing if it decompresses to more than `limit` bytes.
+///
+/// # Examples
+/// ```
+/// use moon_engine::inflate::inflate_with_limit;
+///
+/// let data = [0x01, 0x03, 0x00, 0xFC, 0xFF, b'a', b'b', b'c'];
+/// assert!(inflate_with_limit(&data, 3).is_ok());
+/// assert!(inflate_with_limit(&data, 2).is_err());
+/// ```
+pub fn inflate_with_limit(data: &[u8], limit: usize) -> Result<Vec<u8>, String> {
+    let mut reader = BitReader::new(data);
+    let mut output = Output {
+        bytes: Vec::new(),
+        limit,
+    };
+    loop {
+        let last = reader.bits(1)? == 1;
+        match reader.bits(2)? {
+            0 => stored_block(&mut reader, &mut output)?,
+            1 => {
+                let (lengths, distances) = fixed_codes()?;
+                compressed_block(&mut reader, &mut output, &lengths, &distances)?;
+            }
+            2 => {
+                let (lengths, distances) = dynamic_codes(&mut reader)?;
+                compressed_block(&mut reader, &mut output, &lengths, &distances)?;
+            }
+            _ => return Err(String::from("Invalid DEFLATE block type.")),
+        }
+        if last {
+            return Ok(output.bytes);
+        }
+    }
+}
+
+/// Decompress zlib data, checking its header and checksum.
+pub fn zlib_decompress(data: &[u8]) -> Result<Vec<u8>, String> {
+    zlib_decompress_with_limit(data, usize::MAX)
+}
+
+/// Decompress zlib data, failing if it decompresses to more than `limit` bytes.
+pub fn zlib_decompress_with_limit(data: &[u8], limit: usize) -> Result<Vec<u8>, String> {
+    if data.len() < 6 {
+        return Err(String::from("zlib data is too short."));
+    }
+    let (cmf, flags) = (data[0], data[1]);
+    if cmf & 0x0F != 8 || !((cmf as u16) << 8 | flags as u16).is_multiple_of(31) {
+        return Err(String::from("Invalid zlib header."));
+    }
+    if flags & 0x20 != 0 {
+        return Err(String::from("zlib preset dictionaries are not supported."));
+    }
+    let output = inflate_with_limit(&data[2..], limit)?;
+    let checksum = u32::from_be_bytes([
+        data[data.len() - 4],
+        data[data.len() - 3],
+        data[data.len() - 2],
+        data[data.len() - 1],
+    ]);
+    if adler32(&output) != checksum {
+        return Err(String::from("zlib checksum does not match."));
+    }
+    Ok(output)
+}
+
+/// Decompress gzip data, skipping its optional header fields and checking its checksum and size.
+pub fn gzip_decompress(data: &[u8]) -> Result<Vec<u8>, String> {
+    gzip_decompress_with_limit(data, usize::MAX)
+}
+
+/// Decompress gzip data, failing if it decompresses to more than `limit` bytes.
+pub fn gzip_decompress_with_limit(data: &[u8], limit: usize) -> Result<Vec<u8>, String> {
+    let too_short = || String::from("gzip data is too short.");
+    if data.len() < 18 {
+        return Err(too_short());
+    }
+    if data[..3] != [0x1F, 0x8B, 8] {
+        return Err(String::from("Invalid gzip header."));
+    }
+    let flags = data[3];
+    let mut offset = 10;
+    if flags & 0x04 != 0 {
+        let extra = data.get(offset..offset + 2).ok_or_else(too_short)?;
+        offset += 2 + u16::from_le_bytes([extra[0], extra[1]]) as usize;
+    }
+    // The file name and comment are zero-terminated
+    for flag in [0x08, 0x10] {
+        if flags & flag != 0 {
+            let length = data
+                .get(offset..)
+                .and_then(|rest| rest.iter().position(|byte| *byte == 0))
+                .ok_or_else(too_short)?;
+            offset += length + 1;
+        }
+    }
+    if flags & 0x02 != 0 {
+        offset += 2;
+    }
+    if offset + 8 > data.len() {
+        return Err(too_short());
+    }
+    let output = inflate_with_limit(&data[offset..data.len() - 8], limit)?;
+    let trailer = &data[data.len() - 8..];
+    let checksum = u32::from_le_bytes([trailer[0], trailer[1], trailer[2], trailer[3]]);
+    let size = u32::from_le_bytes([trailer[4], trailer[5], trailer[6], trailer[7]]);
+    if crc32(&output) != checksum || output.len() as u32 != size {
+        return Err(String::from("gzip checksum does not match."));
+    }
+    Ok(output)
+}
+
+/// Get the Adler-32 checksum of some data.
+pub fn adler32(data: &[u8]) -> u32 {
+    let (mut a, mut b) = (1u32, 0u32);
+    for chunk in data.chunks(5552) {
+        for byte in chunk {
+            a += *byte as u32;
+            b += a;
+        }
+        a %= 65521;
+        b %= 65521;
+    }
+    b << 16 | a
+}
+
+/// Decompressed bytes, which may not grow past a limit.
+struct Output {
+    bytes: Vec<u8>,
+    limit: usize,
+}
+
+impl Output {
+    /// Check that `count` more bytes fit within the limit.
+    fn reserve(&self, count: usize) -> Result<(), String> {
+        if count > self.limit - self.bytes.len() {
+            return Err(String::from("Decompressed data is larger than expected."));
+        }
+        Ok(())
+    }
+}
+
+fn stored_block(reader: &mut BitReader, output: &mut Output) -> Result<(), String> {
+    reader.align();
+    let position = reader.position;
+    let header = reader
+        .bytes
+        .get(position..position + 4)
+        .ok_or("Unexpected end of compressed data.")?;
+    let length = u16::from_le_bytes([header[0], header[1]]);
+    let complement = u16::from_le_bytes([header[2], header[3]]);
+    if length != !complement {
+        return Err(String::from("Invalid stored block length."));
+    }
+    let start = position + 4;
+    let end = start + length as usize;
+    output.reserve(length as usize)?;
+    output.bytes.extend_from_slice(
+        reader
+            .bytes
+            .get(start..end)
+            .ok_or("Unexpected end of compressed data.")?,
+    );
+    reader.position = end;
+    Ok(())
+}
+
+fn fixed_codes() -> Result<(Huffman, Huffman), String> {
+    let mut lengths = [0u8; 288];
+    lengths[..144].fill(8);
+    lengths[144..256].fill(9);
+    lengths[256..280].fill(7);
+    lengths[280..].fill(8);
+    Ok((Huffman::new(&lengths)?, Huffman::new(&[5; 30])?))
+}
+
+fn dynamic_codes(reader: &mut BitReader) -> Result<(Huffman, Huffman), String> {
+    let literal_count = reader.bits(5)? as usize + 257;
+    let distance_count = reader.bits(5)? as usize + 1;
+    let code_length_count = reader.bits(4)? as usize + 4;
+    if literal_count > 286 || distance_count > 30 {
+        return Err(String::from("Too many Huffman codes."));
+    }
+
+    let mut code_lengths = [0u8; 19];
+    for index in CODE_LENGTH_ORDER.iter().take(code_length_count) {
+        code_lengths[*index] = reader.bits(3)? as u8;
+    }
+    let code_length_code = Huffman::new(&code_lengths)?;
+
+    let mut lengths = vec![0u8; literal_count + distance_count];
+    let mut index = 0;
+    while index < lengths.len() {
+        let symbol = code_length_code.decode(reader)?;
+        let (value, repeat) = match symbol {
+            0..=15 => (symbol as u8, 1),
+            16 => {
+                let previous = *index
+                    .checked_sub(1)
+                    .and_then(|previous| lengths.get(previous))
+                    .ok_or("Repeated code length without a previous length.")?;
+                (previous, 3 + reader.bits(2)? as usize)
+            }
+            17 => (0, 3 + reader.bits(3)? as usize),
+            _ => (0, 11 + reader.bits(7)? as usize),
+        };
+        if index + repeat > lengths.len() {
+            return Err(String::from("Too many code lengths."));
+        }
+        lengths[index..index + repeat].fill(value);
+        index += repeat;
+    }
+    if lengths[256] == 0 {
+        return Err(String::from("Missing end of block code."));
+    }
+    Ok((
+        Huffman::new(&lengths[..literal_count])?,
+        Huffman::new(&lengths[literal_count..])?,
+    ))
+}
+
+fn compressed_block(
+    reader: &mut BitReader,
+    output: &mut Output,
+    lengths: &Huffman,
+    distances: &Huffman,
+) -> Result<(), String> {
+    loop {
+        let symbol = lengths.decode(reader)? as usize;
+        match symbol {
+            0..=255 => {
+                output.reserve(1)?;
+                output.bytes.push(symbol as u8);
+            }
+            256 => return Ok(()),
+            257..=285 => {
+                let code = symbol - 257;
+                let length =
+                    LENGTH_BASE[code] as usize + reader.bits(LENGTH_EXTRA[code] as u32)? as usize;
+                let code = distances.decode(reader)? as usize;
+                if code >= DISTANCE_BASE.len() {
+                    return Err(String::from("Invalid distance code."));
+                }
+                let distance = DISTANCE_BASE[code] as usize
+                    + reader.bits(DISTANCE_EXTRA[code] as u32)? as usize;
+                if distance > output.bytes.len() {
+                    return Err(String::from("Distance is too far back."));
+                }
+                output.reserve(length)?;
+                // Copy byte by byte, since the source and destination can overlap
+                let start = output.bytes.len() - distance;
+                for offset in 0..length {
+                    output.bytes.push(output.bytes[start + offset]);
+                }
+            }
+            _ => return Err(String::from("Invalid length code.")),
+        }
+    }
+}
@@ -1,12 +1,80 @@
-//! The [`InputManager`] struct.
+//! The [`InputManager`] and [`InputSnapshot`] structs.
 
-use std::collections::BTreeSet;
+use std::collections::{BTreeMap, BTreeSet};
 
+use crate::action::{ActionMap, InputBinding, ACTION_THRESHOLD};
+use crate::gamepad::{GamepadState, Gamepads};
+use crate::pointer::PointerInput;
 use crate::Vec2;
 
+/// The state of every input at a single point in time.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct InputSnapshot {
+    /// Set of Keyboard key states.
+    ///
+    /// If a key is present, then it is being pressed, and otherwise it is not.
+    keyboard_states: BTreeSet<u8>,
+    /// Set of Mouse button states.
+    mouse_button_states: BTreeSet<u8>,
+    /// Values of Gamepad buttons, from `0.0` to `1.0`.
+    gamepad_buttons: BTreeMap<u8, f32>,
+    /// Values of Gamepad axes, from `-1.0` to `1.0`.
+    gamepad_axes: BTreeMap<u8, f32>,
+}
+
+impl InputSnapshot {
+    /// Get the value of an [`InputBinding`], from `0.0` to `1.0`.
+    pub fn get_binding_value(&self, binding: InputBinding) -> f32 {
+        match binding {
+            InputBinding::Key(key_code) => self.keyboard_states.contains(&key_code) as i32 as f32,
+            InputBinding::MouseButton(button) => {
+                self.mouse_button_states.contains(&button) as i32 as f32
+            }
+            InputBinding::GamepadButton(button) => {
+                self.gamepad_buttons.get(&button).copied().unwrap_or(0.0)
+            }
+            InputBinding::GamepadAxis { axis, positive } => {
+                let value = self.gamepad_axes.get(&axis).copied().unwrap_or(0.0);
+                if positive {
+                    value.max(0.0)
+                } else {
+                    (-value).max(0.0)
+                }
+            }
+        }
+    }
+
+    /// Get an iterator over every [`InputBinding`] that is currently pressed.
+    pub fn pressed_bindings(&self) -> impl Iterator<Item = InputBinding> + '_ {
+        let keys = self
+            .keyboard_states
+            .iter()
+            .map(|key| InputBinding::Key(*key));
+        let buttons = self
+            .mouse_button_states
+            .iter()
+            .map(|button| InputBinding::MouseButton(*button));
+        let gamepad_buttons = self
+            .gamepad_buttons
+            .keys()
+            .map(|button| InputBinding::GamepadButton(*button));
+        let gamepad_axes = self.gamepad_axes.keys().flat_map(|axis| {
+            [true, false].map(|positive| InputBinding::GamepadAxis {
+                axis: *axis,
+                positive,
+            })
+        });
+        keys.chain(buttons)
+            .chain(gamepad_buttons)
+            .chain(gamepad_axes)
+            .filter(|binding| self.get_binding_value(*binding) > ACTION_THRESHOLD)
+    }
+}
+
 /// A store for Input-related data.
 ///
-/// The [`InputManager`] stores and handles the current input states.
+/// The [`InputManager`] stores and handles the current input states, along with those of the previous frame.
+/// [`advance_frame`](InputManager::advance_frame) should be called once at the end of every frame.
 ///
 /// # Examples
 /// ```
@@ -17,17 +85,42 @@ use crate::Vec2;
 /// input.key_down(b'w');
 ///
 /// assert!(input.get_key_state(b'w'));
+/// assert!(input.is_key_just_pressed(b'w'));
+///
+/// input.advance_frame(0.1);
+///
+/// assert!(!input.is_key_just_pressed(b'w'));
 /// ```
 #[derive(Default)]
 pub struct InputManager {
-    /// Set of Keyboard key states.
-    ///
-    /// If a key is present, then it is being pressed, and otherwise it is not.
-    keyboard_states: BTreeSet<u8>,
+    /// Input states of the current frame.
+    current: InputSnapshot,
+    /// Input states at the end of the previous frame.
+    previous: InputSnapshot,
+    /// [`InputBindings`](InputBinding) pressed since the previous frame, even if they were released again.
+    pressed: BTreeSet<InputBinding>,
+    /// [`InputBindings`](InputBinding) released since the previous frame.
+    released: BTreeSet<InputBinding>,
+    /// How long each held [`InputBinding`] has been held, in seconds.
+    held_time: BTreeMap<InputBinding, f32>,
+    /// Text typed since the previous frame.
+    text: String,
+    /// Mouse wheel movement since the previous frame.
+    wheel_delta: Vec2,
     /// Position of the Mouse.
     ///
     /// The Screen-Space position of the Mouse as a [`Vec2`].
     pub mouse_position: Vec2,
+    /// The [`ActionMap`] used to look up named actions and axes.
+    pub actions: ActionMap,
+    /// Touch [`Pointers`](crate::pointer::Pointer), and the [`Gestures`](crate::pointer::Gesture) made with them.
+    pub pointers: PointerInput,
+    /// Connected [`Gamepads`](crate::gamepad::Gamepad).
+    pub gamepads: Gamepads,
+    /// Gamepad button values set directly, merged with those of the [`Gamepads`].
+    manual_gamepad_buttons: BTreeMap<u8, f32>,
+    /// Gamepad axis values set directly, merged with those of the [`Gamepads`].
+    manual_gamepad_axes: BTreeMap<u8, f32>,
 }
 
 impl InputManager {
@@ -42,21 +135,52 @@ impl InputManager {
     ///
     /// Sets the key in the [`BTreeSet`].
     pub fn key_down(&mut self, key_code: u8) {
-        self.keyboard_states.insert(key_code);
+        if self.current.keyboard_states.insert(key_code) {
+            self.pressed.insert(InputBinding::Key(key_code));
+        }
     }
 
     /// Key Up State.
     ///
     /// Resets the key in the [`BTreeSet`].
     pub fn key_up(&mut self, key_code: u8) {
-        self.keyboard_states.remove(&key_code);
+        if self.current.keyboard_states.remove(&key_code) {
+            self.released.insert(InputBinding::Key(key_code));
+        }
     }
 
     /// Get the state of a key as a [`bool`].
     ///
     /// Returns true if the key is currently pressed, or false.
     pub fn get_key_state(&self, key_code: u8) -> bool {
-        self.keyboard_states.contains(&key_code)
+        self.current.keyboard_states.contains(&key_code)
+    }
+
+    /// Check whether a key was pressed this frame.
+    pub fn is_key_just_pressed(&self, key_code: u8) -> bool {
+        self.is_binding_just_pressed(InputBinding::Key(key_code))
+    }
+
+    /// Check whether a key was released this frame.
+    pub fn is_key_just_released(&self, key_code: u8) -> bool {
+        self.is_binding_just_released(InputBinding::Key(key_code))
+    }
+
+    /// Get how long a key has been held, in seconds, as of the previous frame.
+    pub fn get_key_held_time(&self, key_code: u8) -> f32 {
+        self.get_binding_held_time(InputBinding::Key(key_code))
+    }
+
+    /// Add typed text to the text input stream.
+    ///
+    /// This is separate from key states, so that text can be read with the correct case and layout.
+    pub fn text_input(&mut self, text: &str) {
+        self.text.push_str(text);
+    }
+
+    /// Get the text typed since the previous frame.
+    pub fn get_text_input(&self) -> &str {
+        &self.text
     }
 
     /// Set the mouse position.
@@ -64,4 +188,218 @@ impl InputManager {
         self.mouse_position.x = x;
         self.mouse_position.y = y;
     }
+
+    /// Mouse Button Down State.
+    pub fn mouse_button_down(&mut self, button: u8) {
+        if self.current.mouse_button_states.insert(button) {
+            self.pressed.insert(InputBinding::MouseButton(button));
+        }
+    }
+
+    /// Mouse Button Up State.
+    pub fn mouse_button_up(&mut self, button: u8) {
+        if self.current.mouse_button_states.remove(&button) {
+            self.released.insert(InputBinding::MouseButton(button));
+        }
+    }
+
+    /// Get the state of a mouse button as a [`bool`].
+    pub fn get_mouse_button_state(&self, button: u8) -> bool {
+        self.current.mouse_button_states.contains(&button)
+    }
+
+    /// Check whether a mouse button was pressed this frame.
+    pub fn is_mouse_button_just_pressed(&self, button: u8) -> bool {
+        self.is_binding_just_pressed(InputBinding::MouseButton(button))
+    }
+
+    /// Check whether a mouse button was released this frame.
+    pub fn is_mouse_button_just_released(&self, button: u8) -> bool {
+        self.is_binding_just_released(InputBinding::MouseButton(button))
+    }
+
+    /// Add to the mouse wheel movement of the current frame.
+    pub fn mouse_wheel(&mut self, delta_x: f32, delta_y: f32) {
+        self.wheel_delta += Vec2::new(delta_x, delta_y);
+    }
+
+    /// Get the mouse wheel movement since the previous frame.
+    pub fn get_wheel_delta(&self) -> Vec2 {
+        self.wheel_delta
+    }
+
+    /// Set the value of a gamepad button, from `0.0` to `1.0`.
+    ///
+    /// The value is kept until it is set again, and combined with any connected [`Gamepads`] by using the strongest value.
+    pub fn set_gamepad_button(&mut self, button: u8, value: f32) {
+        self.manual_gamepad_buttons.insert(button, value);
+        self.sync_gamepads();
+    }
+
+    /// Set the value of a gamepad axis, from `-1.0` to `1.0`.
+    ///
+    /// The value is kept until it is set again, and combined with any connected [`Gamepads`] by using the strongest value.
+    pub fn set_gamepad_axis(&mut self, axis: u8, value: f32) {
+        self.manual_gamepad_axes.insert(axis, value);
+        self.sync_gamepads();
+    }
+
+    /// Poll the [`Gamepads`], and update the gamepad inputs of the current frame. This should be called once at the start of every frame.
+    ///
+    /// When several [`Gamepads`](crate::gamepad::Gamepad) are connected, the strongest value of each button and axis is used.
+    pub fn poll_gamepads(&mut self) {
+        self.gamepads.poll();
+        self.sync_gamepads();
+    }
+
+    /// Update the [`Gamepads`] from a list of [`GamepadStates`](GamepadState) instead of polling them, such as when replaying a recording.
+    pub fn update_gamepads(&mut self, states: Vec<GamepadState>) {
+        self.gamepads.update(states);
+        self.sync_gamepads();
+    }
+
+    /// Copy the strongest value of each gamepad button and axis into the current [`InputSnapshot`], including the values that were set directly.
+    fn sync_gamepads(&mut self) {
+        let mut buttons = self.manual_gamepad_buttons.clone();
+        let mut axes = self.manual_gamepad_axes.clone();
+        for gamepad in self.gamepads.iter() {
+            for index in 0..gamepad.button_count().min(u8::MAX as usize) {
+                let value = buttons.entry(index as u8).or_insert(0.0f32);
+                *value = value.max(gamepad.button_value(index));
+            }
+            for index in 0..gamepad.axis_count().min(u8::MAX as usize) {
+                let value = axes.entry(index as u8).or_insert(0.0);
+                if gamepad.axis_value(index).abs() > value.abs() {
+                    *value = gamepad.axis_value(index);
+                }
+            }
+        }
+        self.current.gamepad_buttons = buttons;
+        self.current.gamepad_axes = axes;
+    }
+
+    /// Get the [`InputSnapshot`] of the current frame.
+    pub fn current(&self) -> &InputSnapshot {
+        &self.current
+    }
+
+    /// Get the [`InputSnapshot`] taken at the end of the previous frame.
+    pub fn previous(&self) -> &InputSnapshot {
+        &self.previous
+    }
+
+    /// Get the value of an [`InputBinding`], from `0.0` to `1.0`.
+    pub fn get_binding_value(&self, binding: InputBinding) -> f32 {
+        self.current.get_binding_value(binding)
+    }
+
+    /// Check whether an [`InputBinding`] was pressed this frame.
+    ///
+    /// This is also true for inputs that were pressed and released again within the same frame.
+    pub fn is_binding_just_pressed(&self, binding: InputBinding) -> bool {
+        self.previous.get_binding_value(binding) <= ACTION_THRESHOLD
+            && (self.pressed.contains(&binding)
+                || self.current.get_binding_value(binding) > ACTION_THRESHOLD)
+    }
+
+    /// Check whether an [`InputBinding`] was released this frame.
+    pub fn is_binding_just_released(&self, binding: InputBinding) -> bool {
+        self.current.get_binding_value(binding) <= ACTION_THRESHOLD
+            && (self.released.contains(&binding)
+                || self.previous.get_binding_value(binding) > ACTION_THRESHOLD)
+    }
+
+    /// Get how long an [`InputBinding`] has been held, in seconds, as of the previous frame.
+    pub fn get_binding_held_time(&self, binding: InputBinding) -> f32 {
+        self.held_time.get(&binding).copied().unwrap_or(0.0)
+    }
+
+    /// Get the value of a named action, from `0.0` to `1.0`.
+    pub fn get_action_value(&self, action: &str) -> f32 {
+        self.actions
+            .action_value(action, |binding| self.current.get_binding_value(binding))
+    }
+
+    /// Check whether a named action is currently pressed.
+    pub fn is_action_pressed(&self, action: &str) -> bool {
+        self.get_action_value(action) > ACTION_THRESHOLD
+    }
+
+    /// Check whether a named action was pressed this frame.
+    pub fn is_action_just_pressed(&self, action: &str) -> bool {
+        let previous = self
+            .actions
+            .action_value(action, |binding| self.previous.get_binding_value(binding));
+        previous <= ACTION_THRESHOLD
+            && (self.is_action_pressed(action)
+                || self
+                    .actions
+                    .action_bindings(action)
+                    .iter()
+                    .any(|binding| self.pressed.contains(binding)))
+    }
+
+    /// Check whether a named action was released this frame.
+    pub fn is_action_just_released(&self, action: &str) -> bool {
+        let previous = self
+            .actions
+            .action_value(action, |binding| self.previous.get_binding_value(binding));
+        !self.is_action_pressed(action)
+            && (previous > ACTION_THRESHOLD
+                || self
+                    .actions
+                    .action_bindings(action)
+                    .iter()
+                    .any(|binding| self.released.contains(binding)))
+    }
+
+    /// Get how long a named action has been held, in seconds, as of the previous frame.
+    pub fn get_action_held_time(&self, action: &str) -> f32 {
+        if !self.is_action_pressed(action) {
+            return 0.0;
+        }
+        self.actions
+            .action_bindings(action)
+            .iter()
+            .map(|binding| self.get_binding_held_time(*binding))
+            .fold(0.0, f32::max)
+    }
+
+    /// Get the value of a named axis, from `-1.0` to `1.0`.
+    pub fn get_axis(&self, axis: &str) -> f32 {
+        self.actions
+            .axis_value(axis, |binding| self.current.get_binding_value(binding))
+    }
+
+    /// Clear every input state, as if nothing has been pressed.
+    ///
+    /// The [`ActionMap`], [`Gamepads`] source and gesture settings are kept.
+    pub fn reset(&mut self) {
+        let settings = self.pointers.settings;
+        *self = Self {
+            actions: std::mem::take(&mut self.actions),
+            gamepads: std::mem::take(&mut self.gamepads),
+            ..Default::default()
+        };
+        self.pointers.settings = settings;
+        self.gamepads.update(Vec::new());
+    }
+
+    /// Move on to the next frame.
+    ///
+    /// The current input states become the previous ones, held times are increased by the frame's `delta_time`, and the text input stream, wheel movement and [`Gestures`](crate::pointer::Gesture) are cleared.
+    pub fn advance_frame(&mut self, delta_time: f32) {
+        let held: BTreeMap<InputBinding, f32> = self
+            .current
+            .pressed_bindings()
+            .map(|binding| (binding, self.get_binding_held_time(binding) + delta_time))
+            .collect();
+        self.held_time = held;
+        self.previous = self.current.clone();
+        self.pressed.clear();
+        self.released.clear();
+        self.text.clear();
+        self.wheel_delta = Vec2::zeros();
+        self.pointers.advance_frame(delta_time);
+    }
 }
@@ -0,0 +1,288 @@
+//! The [`JsonValue`] enum, and a parser for JSON documents.
+
+use std::collections::BTreeMap;
+
+use crate::asset::{Asset, AssetData, LoadContext};
+
+/// Deepest nesting of arrays and objects that will be parsed.
+const MAX_DEPTH: u32 = 128;
+
+/// A value in a JSON document.
+///
+/// # Examples
+/// ```
+/// use moon_engine::json::JsonValue;
+///
+/// let value = JsonValue::parse(r#"{"name": "Moon", "sizes": [1, 2.5]}"#).unwrap();
+/// assert_eq!(value.get("name").and_then(JsonValue::as_str), Some("Moon"));
+/// assert_eq!(value.get("sizes").and_then(|sizes| sizes.index(1)?.as_f64()), Some(2.5));
+/// ```
+#[derive(Debug, Clone, PartialEq, Default)]
+pub enum JsonValue {
+    /// The `null` value.
+    #[default]
+    Null,
+    /// `true` or `false`.
+    Bool(bool),
+    /// A number, which is always stored as an [`f64`].
+    Number(f64),
+    /// A string.
+    String(String),
+    /// An ordered list of values.
+    Array(Vec<JsonValue>),
+    /// A set of values with string keys.
+    Object(BTreeMap<String, JsonValue>),
+}
+
+impl JsonValue {
+    /// Parse a JSON document.
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let mut parser = Parser {
+            bytes: text.as_bytes(),
+            position: 0,
+        };
+        let value = parser.value(0)?;
+        parser.whitespace();
+        if parser.position != parser.bytes.len() {
+            return Err(parser.error("Unexpected data after the JSON value"));
+        }
+        Ok(value)
+    }
+
+    /// Get the value of a key, if this is an object that contains it.
+    pub fn get(&self, key: &str) -> Option<&JsonValue> {
+        self.as_object()?.get(key)
+    }
+
+    /// Get an element, if this is an array that is long enough.
+    pub fn index(&self, index: usize) -> Option<&JsonValue> {
+        self.as_array()?.get(index)
+    }
+
+    /// Get the value as a [`bool`].
+    pub fn as_bool(&self) -> Option<bool> {
+        match self {
+            JsonValue::Bool(value) => Some(*value),
+            _ => None,
+        }
+    }
+
+    /// Get the value as an [`f64`].
+    pub fn as_f64(&self) -> Option<f64> {
+        match self {
+            JsonValue::Number(value) => Some(*value),
+            _ => None,
+        }
+    }
+
+    /// Get the value as an [`i64`], if it is a whole number.
+    pub fn as_i64(&self) -> Option<i64> {
+        self.as_f64()
+            .filter(|value| value.fract() == 0.0)
+            .map(|value| value as i64)
+    }
+
+    /// Get the value as a string slice.
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            JsonValue::String(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    /// Get the elements of an array.
+    pub fn as_array(&self) -> Option<&[JsonValue]> {
+        match self {
+            JsonValue::Array(values) => Some(values),
+            _ => None,
+        }
+    }
+
+    /// Get the keys and values of an object.
+    pub fn as_object(&self) -> Option<&BTreeMap<String, JsonValue>> {
+        match self {
+            JsonValue::Object(values) => Some(values),
+            _ => None,
+        }
+    }
+
+    /// Check if the value is `null`.
+    pub fn is_null(&self) -> bool {
+        matches!(self, JsonValue::Null)
+    }
+}
+
+impl Asset for JsonValue {
+    fn from_data(path: &str, data: AssetData, context: &mut LoadContext) -> Result<Self, String> {
+        JsonValue::parse(&String::from_data(path, data, context)?)
+            .map_err(|error| format!("{}: {}", path, error))
+    }
+}
+
+/// Reads JSON values from text.
+struct Parser<'a> {
+    bytes: &'a [u8],
+    position: usize,
+}
+
+impl Parser<'_> {
+    fn error(&self, message: &str) -> String {
+        format!("{} at byte {}.", message, self.position)
+    }
+
+    fn whitespace(&mut self) {
+        while let Some(b' ' | b'\t' | b'\n' | b'\r') = self.bytes.get(self.position) {
+            self.position += 1;
+        }
+    }
+
+    fn expect(&mut self, literal: &str) -> Result<(), String> {
+        if self.bytes[self.position..].starts_with(literal.as_bytes()) {
+            self.position += literal.len();
+            Ok(())
+        } else {
+            Err(self.error(&format!("Expected `{}`", literal)))
+        }
+    }
+
+    fn value(&mut self, depth: u32) -> Result<JsonValue, String> {
+        if depth > MAX_DEPTH {
+            return Err(self.error("JSON is nested too deeply"));
+        }
+        self.whitespace();
+        match self.bytes.get(self.position) {
+            Some(b'n') => self.expect("null").map(|_| JsonValue::Null),
+            Some(b't') => self.expect("true").map(|_| JsonValue::Bool(true)),
+            Some(b'f') => self.expect("false").map(|_| JsonValue::Bool(false)),
+            Some(b'"') => self.string().map(JsonValue::String),
+            Some(b'[') => {
+                self.position += 1;
+                let mut values = Vec::new();
+                if !self.close(b']') {
+                    loop {
+                        values.push(self.value(depth + 1)?);
+                        if self.separator(b']')? {
+                            break;
+                        }
+                    }
+                }
+                Ok(JsonValue::Array(values))
+            }
+            Some(b'{') => {
+                self.position += 1;
+                let mut values = BTreeMap::new();
+                if !self.close(b'}') {
+                    loop {
+                        self.whitespace();
+                        if self.bytes.get(self.position) != Some(&b'"') {
+                            return Err(self.error("Expected a key"));
+                        }
+                        let key = self.string()?;
+                        self.whitespace();
+                        self.expect(":")?;
+                        values.insert(key, self.value(depth + 1)?);
+                        if self.separator(b'}')? {
+                            break;
+                        }
+                    }
+                }
+                Ok(JsonValue::Object(values))
+            }
+            Some(b'-' | b'0'..=b'9') => self.number(),
+            Some(_) => Err(self.error("Unexpected character")),
+            None => Err(self.error("Unexpected end of JSON")),
+        }
+    }
+
+    /// Skip a closing bracket if it comes next, returning whether it did.
+    fn close(&mut self, bracket: u8) -> bool {
+        self.whitespace();
+        let closed = self.bytes.get(self.position) == Some(&bracket);
+        if closed {
+            self.position += 1;
+        }
+        closed
+    }
+
+    /// Skip a comma or a closing bracket, returning whether it was the bracket.
+    fn separator(&mut self, bracket: u8) -> Result<bool, String> {
+        if self.close(bracket) {
+            return Ok(true);
+        }
+        self.expect(",").map(|_| false)
+    }
+
+    fn number(&mut self) -> Result<JsonValue, String> {
+        let start = self.position;
+        while let Some(b'-' | b'+' | b'.' | b'e' | b'E' | b'0'..=b'9') =
+            self.bytes.get(self.position)
+        {
+            self.position += 1;
+        }
+        std::str::from_utf8(&self.bytes[start..self.position])
+            .ok()
+            .and_then(|text| text.parse().ok())
+            .map(JsonValue::Number)
+            .ok_or_else(|| self.error("Invalid number"))
+    }
+
+    fn string(&mut self) -> Result<String, String> {
+        self.position += 1;
+        let mut bytes = Vec::new();
+        loop {
+            let byte = *self
+                .bytes
+                .get(self.position)
+                .ok_or_else(|| self.error("Unterminated string"))?;
+            self.position += 1;
+            match byte {
+                b'"' => break,
+                b'\\' => {
+                    let escape = self.bytes.get(self.position).copied();
+                    self.position += 1;
+                    let character = match escape {
+                        Some(b'"') => '"',
+                        Some(b'\\') => '\\',
+                        Some(b'/') => '/',
+                        Some(b'b') => '\u{8}',
+                        Some(b'f') => '\u{C}',
+                        Some(b'n') => '\n',
+                        Some(b'r') => '\r',
+                        Some(b't') => '\t',
+                        Some(b'u') => self.unicode_escape()?,
+                        _ => return Err(self.error("Invalid escape sequence")),
+                    };
+                    let mut buffer = [0; 4];
+                    bytes.extend_from_slice(character.encode_utf8(&mut buffer).as_bytes());
+                }
+                byte => bytes.push(byte),
+            }
+        }
+        String::from_utf8(bytes).map_err(|_| self.error("Invalid UTF-8 in string"))
+    }
+
+    /// Read the hex digits of a `\u` escape, combining surrogate pairs.
+    fn unicode_escape(&mut self) -> Result<char, String> {
+        let mut code = self.hex()?;
+        if (0xD800..0xDC00).contains(&code) {
+            self.expect("\\u")?;
+            let low = self.hex()?;
+            if !(0xDC00..0xE000).contains(&low) {
+                return Err(self.error("Invalid surrogate pair"));
+            }
+            code = 0x10000 + ((code - 0xD800) << 10) + (low - 0xDC00);
+        }
+        char::from_u32(code).ok_or_else(|| self.error("Invalid unicode escape"))
+    }
+
+    fn hex(&mut self) -> Result<u32, String> {
+        let digits = self
+            .bytes
+            .get(self.position..self.position + 4)
+            .and_then(|digits| std::str::from_utf8(digits).ok())
+            .and_then(|digits| u32::from_str_radix(digits, 16).ok())
+            .ok_or_else(|| self.error("Invalid unicode escape"))?;
+        self.position += 4;
+        Ok(digits)
+    }
+}
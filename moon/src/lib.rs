@@ -3,30 +3,57 @@
 
 //! Moon Engine
 
+pub mod action;
+pub mod asset;
+pub mod audio;
 pub mod camera;
 pub mod collider;
 pub mod component;
+pub mod framebuffer;
+pub mod gamepad;
 pub mod gl;
+pub mod image;
+pub mod inflate;
 pub mod input;
+pub mod json;
+pub mod light;
 pub mod math;
 pub mod mesh;
 pub mod particle;
+pub mod pointer;
+pub mod postprocess;
+pub mod random;
 pub mod renderer;
+pub mod replay;
+pub mod scene;
 pub mod shader;
+pub mod shadow;
+pub mod spatial;
 pub mod texture;
+pub mod tilecollider;
+pub mod tiled;
+pub mod tilemap;
 pub mod transform;
 pub mod ui;
 pub mod utils;
+// The console bindings generated by `wasm_bindgen` carry no documentation
+#[allow(missing_docs)]
 pub mod web;
+pub mod xml;
 
 use wasm_bindgen::prelude::*;
 
+use action::InputBinding;
+use asset::{AssetServer, AssetState, Handle, LoadContext};
+use audio::{Audio, Bus};
 use camera::Camera;
 use gl::GL;
 use input::InputManager;
 pub use math::*;
 use particle::ParticleSystem;
+pub use random::{seed_random, Rng};
 use renderer::Renderer;
+use replay::{InputEvent, InputPlayer, InputRecording};
 use shader::Shader;
 use texture::Texture;
 use transform::Transform;
@@ -38,6 +65,12 @@ use web::Canvas;
 pub struct Application {
     renderer: Renderer,
     input: InputManager,
+    audio: Audio,
+    assets: AssetServer,
+    loading_textures: Vec<(&'static str, Handle<Texture>)>,
+    recording: Option<InputRecording>,
+    player: Option<InputPlayer>,
+    rng: Rng,
 }
 
 impl Default for Application {
@@ -47,6 +80,12 @@ impl Default for Application {
         Self {
             renderer: Renderer::default(),
             input: InputManager::new(),
+            audio: Audio::new(),
+            assets: AssetServer::new(),
+            loading_textures: Vec::new(),
+            recording: None,
+            player: None,
+            rng: Rng::default(),
         }
     }
 }
@@ -63,6 +102,9 @@ impl Application {
     /// Set up data before render loop.
     #[wasm_bindgen]
     pub fn init(&mut self) {
+        // Seed random values differently every session
+        self.seed(Self::random_seed());
+
         let renderer = &mut self.renderer;
 
         // Initialize global WebGL state
@@ -74,15 +116,13 @@ impl Application {
         // Initialize the default Shader
         renderer.init_shader();
 
-        // Obtain Texture Slot 0 uniform location
-        let u_tex0 = renderer.program.get_uniform_location(&renderer.gl, "uTex0");
-        renderer.gl.uniform1i(u_tex0.as_ref(), 0);
-
-        // Add default Textures to the Renderer list
-        renderer.add_texture("TILEMAP", Texture::new_with_texture_id(&renderer.gl, 0));
-        renderer.add_texture("SHREK", Texture::new_with_texture_id(&renderer.gl, 1));
+        // Load default Textures, which are added to the Renderer list once loaded
+        self.loading_textures = vec![
+            ("TILEMAP", self.assets.load("texture/tilemap_packed.png")),
+            ("SHREK", self.assets.load("texture/shrek.png")),
+        ];
 
-        // Use a 1x1 pixel "WHITE" texture
+        // Use a 1x1 pixel "WHITE" texture, which the Renderer binds to a free texture unit
         renderer.use_texture("WHITE");
 
         let simple = ParticleSystem::new_from_emission_and_position(
@@ -108,32 +148,230 @@ impl Application {
 
         // Initialize Renderer components
         renderer.init_components();
+
+        // Play audio through the browser, if it is supported
+        match audio::WebAudioBackend::new() {
+            Ok(backend) => self.audio.set_backend(Box::new(backend)),
+            Err(error) => console_log!("{}", error),
+        }
+
+        // Read gamepads from the browser
+        self.input
+            .gamepads
+            .set_source(Box::new(gamepad::WebGamepadSource));
+
+        // Bind the default actions and axes
+        let actions = &mut self.input.actions;
+        actions.bind_action("reset", InputBinding::Key(b'R'));
+        actions.bind_action(
+            "reset",
+            InputBinding::GamepadButton(gamepad::StandardButton::Start as u8),
+        );
+        actions.bind_action("toggle_fire", InputBinding::Key(b'1'));
+        actions.bind_action("toggle_default", InputBinding::Key(b'2'));
+        actions.bind_action("toggle_smoke", InputBinding::Key(b'3'));
+        actions.bind_axis("move_x", InputBinding::Key(b'D'), InputBinding::Key(b'A'));
+        actions.bind_axis(
+            "move_x",
+            InputBinding::GamepadAxis {
+                axis: 0,
+                positive: true,
+            },
+            InputBinding::GamepadAxis {
+                axis: 0,
+                positive: false,
+            },
+        );
+        actions.bind_axis("move_y", InputBinding::Key(b'S'), InputBinding::Key(b'W'));
+        actions.bind_axis(
+            "move_y",
+            InputBinding::GamepadAxis {
+                axis: 1,
+                positive: true,
+            },
+            InputBinding::GamepadAxis {
+                axis: 1,
+                positive: false,
+            },
+        );
     }
 
     /// Called when window gets resized.
     #[wasm_bindgen]
     pub fn resize(&mut self, width: f32, height: f32) {
-        self.renderer.resize(width, height);
+        self.handle_event(InputEvent::Resize { width, height });
     }
 
     /// Called when a keyboard input event is generated.
     #[wasm_bindgen]
     pub fn input(&mut self, key_code: u8, is_down: bool) {
-        if is_down {
-            self.input.key_down(key_code);
-        } else {
-            self.input.key_up(key_code);
-        }
+        self.handle_event(InputEvent::Key { key_code, is_down });
+    }
+
+    /// Called when text is typed, separately from the keyboard input events.
+    #[wasm_bindgen]
+    pub fn text_input(&mut self, text: &str) {
+        self.handle_event(InputEvent::Text(text.to_string()));
     }
 
     /// Handles Mouse movement.
     #[wasm_bindgen]
     pub fn mouse_move(&mut self, mouse_x: i32, mouse_y: i32) {
-        let (x, y) = self
-            .renderer
-            .camera
-            .screen_to_world_coordinates(mouse_x as f32, mouse_y as f32);
-        self.input.mouse_position = Vec2::new(x, y);
+        self.handle_event(InputEvent::MouseMove { mouse_x, mouse_y });
+    }
+
+    /// Called when a mouse button is pressed or released.
+    #[wasm_bindgen]
+    pub fn mouse_button(&mut self, button: u8, is_down: bool) {
+        self.handle_event(InputEvent::MouseButton { button, is_down });
+    }
+
+    /// Handles Mouse wheel movement.
+    #[wasm_bindgen]
+    pub fn mouse_wheel(&mut self, delta_x: f32, delta_y: f32) {
+        self.handle_event(InputEvent::MouseWheel { delta_x, delta_y });
+    }
+
+    /// Called when a touch point touches down.
+    #[wasm_bindgen]
+    pub fn touch_start(&mut self, id: i32, touch_x: f32, touch_y: f32) {
+        self.handle_event(InputEvent::TouchStart {
+            id,
+            touch_x,
+            touch_y,
+        });
+    }
+
+    /// Called when a touch point moves.
+    #[wasm_bindgen]
+    pub fn touch_move(&mut self, id: i32, touch_x: f32, touch_y: f32) {
+        self.handle_event(InputEvent::TouchMove {
+            id,
+            touch_x,
+            touch_y,
+        });
+    }
+
+    /// Called when a touch point is lifted.
+    #[wasm_bindgen]
+    pub fn touch_end(&mut self, id: i32, touch_x: f32, touch_y: f32) {
+        self.handle_event(InputEvent::TouchEnd {
+            id,
+            touch_x,
+            touch_y,
+        });
+    }
+
+    /// Called when a touch point is interrupted.
+    #[wasm_bindgen]
+    pub fn touch_cancel(&mut self, id: i32) {
+        self.handle_event(InputEvent::TouchCancel { id });
+    }
+
+    /// Start recording the input of every frame, restarting the session with a new random seed.
+    #[wasm_bindgen]
+    pub fn start_recording(&mut self) {
+        let seed = Self::random_seed();
+        self.restart(seed);
+        self.player = None;
+
+        // Start from the current window size
+        let size = self.renderer.camera.screen_size();
+        let mut recording = InputRecording::new(seed);
+        recording.push_event(InputEvent::Resize {
+            width: size.x,
+            height: size.y,
+        });
+        self.recording = Some(recording);
+    }
+
+    /// Stop recording, and get the recording in a compact binary format.
+    ///
+    /// Returns an empty array if nothing was being recorded.
+    #[wasm_bindgen]
+    pub fn stop_recording(&mut self) -> Vec<u8> {
+        self.recording
+            .take()
+            .map(|recording| recording.to_bytes())
+            .unwrap_or_default()
+    }
+
+    /// Restart the session and replay a recording made with [`stop_recording`](Application::stop_recording).
+    ///
+    /// Live input is ignored until the recording has finished, and the window size is only updated afterwards.
+    #[wasm_bindgen]
+    pub fn start_replay(&mut self, bytes: &[u8]) -> Result<(), JsValue> {
+        let recording = InputRecording::from_bytes(bytes).map_err(|error| JsValue::from(&error))?;
+        self.restart(recording.seed);
+        self.recording = None;
+        self.player = Some(InputPlayer::new(recording));
+        Ok(())
+    }
+
+    /// Check whether a recording is being replayed.
+    #[wasm_bindgen]
+    pub fn is_replaying(&self) -> bool {
+        self.player.is_some()
+    }
+
+    /// Resume audio output. Browsers only allow this after the user has interacted with the page.
+    #[wasm_bindgen]
+    pub fn resume_audio(&mut self) {
+        self.audio.resume();
+    }
+
+    /// Load a sound from encoded audio data, such as the contents of a WAV, MP3 or OGG file.
+    #[wasm_bindgen]
+    pub fn load_sound(&mut self, name: &str, bytes: &[u8]) {
+        self.audio.load_sound(name, bytes);
+    }
+
+    /// Load a sound that is streamed from a URL while it plays, for long music tracks.
+    #[wasm_bindgen]
+    pub fn load_music(&mut self, name: &str, url: &str) {
+        self.audio.load_stream(name, url);
+    }
+
+    /// Play a loaded sound once as a sound effect.
+    #[wasm_bindgen]
+    pub fn play_sound(&mut self, name: &str, volume: f32, pitch: f32) -> Result<(), JsValue> {
+        let sound = self
+            .audio
+            .get_sound(name)
+            .ok_or_else(|| JsValue::from(format!("Sound {} has not been loaded.", name)))?;
+        self.audio
+            .play_sfx(sound, volume, pitch)
+            .map(|_| ())
+            .map_err(|error| JsValue::from(&error))
+    }
+
+    /// Play a loaded sound on a loop as music, crossfading from the current music.
+    #[wasm_bindgen]
+    pub fn play_music(&mut self, name: &str, fade_time: f32) -> Result<(), JsValue> {
+        let sound = self
+            .audio
+            .get_sound(name)
+            .ok_or_else(|| JsValue::from(format!("Sound {} has not been loaded.", name)))?;
+        self.audio
+            .play_music(sound, fade_time)
+            .map(|_| ())
+            .map_err(|error| JsValue::from(&error))
+    }
+
+    /// Set the volume of an audio bus, one of `master`, `music` or `sfx`.
+    #[wasm_bindgen]
+    pub fn set_volume(&mut self, bus: &str, volume: f32) -> Result<(), JsValue> {
+        let bus = bus.parse::<Bus>().map_err(|error| JsValue::from(&error))?;
+        self.audio.set_bus_volume(bus, volume);
+        Ok(())
+    }
+
+    /// Mute or unmute an audio bus, one of `master`, `music` or `sfx`.
+    #[wasm_bindgen]
+    pub fn set_muted(&mut self, bus: &str, muted: bool) -> Result<(), JsValue> {
+        let bus = bus.parse::<Bus>().map_err(|error| JsValue::from(&error))?;
+        self.audio.set_bus_muted(bus, muted);
+        Ok(())
     }
 
     /// Renders a new frame.
@@ -141,19 +379,66 @@ impl Application {
     /// Called every frame, and draws its output onto the [Canvas](web_sys::HtmlCanvasElement).
     #[wasm_bindgen]
     pub fn render(&mut self, delta_time: u32) {
+        // Use the input of the next recorded frame when replaying, or poll any connected gamepads
+        let delta_time = match self.player.as_mut().and_then(|player| player.next_frame()) {
+            Some(frame) => {
+                let frame = frame.clone();
+                for event in frame.events {
+                    self.apply_event(event);
+                }
+                let gamepads = frame
+                    .gamepads
+                    .unwrap_or_else(|| self.input.gamepads.states().to_vec());
+                self.input.update_gamepads(gamepads);
+                frame.delta_time
+            }
+            None => {
+                // Catch up with the window size once a replay has finished
+                if let Some(mut player) = self.player.take() {
+                    for event in player.take_live_events() {
+                        self.apply_event(event);
+                    }
+                }
+                self.input.poll_gamepads();
+                if let Some(recording) = self.recording.as_mut() {
+                    recording.end_frame(delta_time, self.input.gamepads.states());
+                }
+                delta_time
+            }
+        };
+
+        // Finish loading any fetched assets, and add loaded Textures to the Renderer list
+        self.assets.update(&mut LoadContext {
+            gl: Some(&self.renderer.gl),
+            audio: Some(&mut self.audio),
+        });
         let renderer = &mut self.renderer;
+        self.loading_textures.retain(|(key, handle)| {
+            if let Some(texture) = handle.get() {
+                renderer.add_texture(key, texture);
+                return false;
+            }
+            if let AssetState::Failed(error) = handle.state() {
+                console_log!("Could not load texture {}: {}", key, error);
+                return false;
+            }
+            true
+        });
         let delta_time = delta_time as f32 / 1000.0;
 
+        // Render into the post-processing stack, if any effects are enabled
+        renderer.begin_post_process();
+
         // Clear the screen
         renderer.clear([0.5, 0.2, 0.3, 1.0]);
 
         // Reset all Components to their initial state
-        if self.input.get_key_state(b'R') {
+        if self.input.is_action_just_pressed("reset") {
             renderer.init_components();
         }
 
         // Pause and Play the "FIRE" particle system
-        if self.input.get_key_state(b'1') {
+        if self.input.is_action_just_pressed("toggle_fire") {
             renderer
                 .get_mut_component::<ParticleSystem>("FIRE")
                 .unwrap()
@@ -161,7 +446,7 @@ impl Application {
         }
 
         // Pause and Play the "DEFAULT" particle system
-        if self.input.get_key_state(b'2') {
+        if self.input.is_action_just_pressed("toggle_default") {
             renderer
                 .get_mut_component::<ParticleSystem>("DEFAULT")
                 .unwrap()
@@ -169,18 +454,16 @@ impl Application {
         }
 
         // Pause and Play the "SMOKE" particle system
-        if self.input.get_key_state(b'3') {
+        if self.input.is_action_just_pressed("toggle_smoke") {
             renderer
                 .get_mut_component::<ParticleSystem>("SMOKE")
                 .unwrap()
                 .toggle_alive();
         }
 
-        // Get the horizontal and vertical axes of movement using the WASD keys
-        let horizontal =
-            self.input.get_key_state(b'D') as i32 - self.input.get_key_state(b'A') as i32;
-        let vertical =
-            self.input.get_key_state(b'S') as i32 - self.input.get_key_state(b'W') as i32;
+        // Get the horizontal and vertical axes of movement
+        let horizontal = self.input.get_axis("move_x");
+        let vertical = self.input.get_axis("move_y");
 
         // Get a mutable reference to the "DEFAULT" particle system
         let simple = renderer
@@ -197,16 +480,25 @@ impl Application {
 
         // Update the position of the "SMOKE" particle system if it is alive
         if smoke.alive {
-            smoke.transform.position +=
-                Vec2::new(horizontal as f32 * delta_time, vertical as f32 * delta_time);
+            smoke.transform.position += Vec2::new(horizontal * delta_time, vertical * delta_time);
         }
 
         // Call the update() function on all components
-        renderer.update_components(delta_time);
+        renderer.update_components_with_rng(delta_time, &mut self.rng);
 
         // Render all components on-screen by issuing the draw call(s)
         renderer.draw_components();
 
+        // Apply post-processing effects and draw the result to the canvas
+        renderer.apply_post_process();
+
+        // Hear positional sounds from the camera unless the listener follows an entity, advance music fades and clean up finished sounds
+        self.audio.follow_camera(&self.renderer.camera);
+        self.audio.update(delta_time);
+
+        // Store this frame's input, so that presses and releases can be detected next frame
+        self.input.advance_frame(delta_time);
+
         // self.renderer.begin_layer();
         // self.renderer.add_quad(&renderer::Quad::default());
         // self.renderer.use_texture("MAGENTA");
@@ -214,3 +506,92 @@ impl Application {
         // self.renderer.delete_layer();
     }
 }
+
+impl Application {
+    /// Get the [`Rng`] of the [`Application`], which is seeded again whenever a recording is made or replayed.
+    ///
+    /// Random values should come from it, so that replays follow the recorded session exactly.
+    pub fn rng(&mut self) -> &mut Rng {
+        &mut self.rng
+    }
+
+    /// Record an [`InputEvent`] if recording, and apply it unless a recording is being replayed.
+    ///
+    /// While replaying, input and resizing only come from the recording.
+    fn handle_event(&mut self, event: InputEvent) {
+        if let Some(recording) = self.recording.as_mut() {
+            recording.push_event(event.clone());
+        }
+        match self.player.as_mut() {
+            Some(player) => player.hold_live_event(event),
+            None => self.apply_event(event),
+        }
+    }
+
+    /// Apply an [`InputEvent`] to the [`InputManager`] or [`Renderer`].
+    fn apply_event(&mut self, event: InputEvent) {
+        match event {
+            InputEvent::Key { key_code, is_down } => {
+                if is_down {
+                    self.input.key_down(key_code);
+                } else {
+                    self.input.key_up(key_code);
+                }
+            }
+            InputEvent::Text(text) => self.input.text_input(&text),
+            InputEvent::MouseMove { mouse_x, mouse_y } => {
+                let (x, y) = self
+                    .renderer
+                    .camera_at(mouse_x as f32, mouse_y as f32)
+                    .screen_to_world_coordinates(mouse_x as f32, mouse_y as f32);
+                self.input.mouse_position = Vec2::new(x, y);
+            }
+            InputEvent::MouseButton { button, is_down } => {
+                if is_down {
+                    self.input.mouse_button_down(button);
+                } else {
+                    self.input.mouse_button_up(button);
+                }
+            }
+            InputEvent::MouseWheel { delta_x, delta_y } => {
+                self.input.mouse_wheel(delta_x, delta_y);
+            }
+            InputEvent::TouchStart {
+                id,
+                touch_x,
+                touch_y,
+            } => self.input.pointers.pointer_down(id, touch_x, touch_y),
+            InputEvent::TouchMove {
+                id,
+                touch_x,
+                touch_y,
+            } => self.input.pointers.pointer_move(id, touch_x, touch_y),
+            InputEvent::TouchEnd {
+                id,
+                touch_x,
+                touch_y,
+            } => self.input.pointers.pointer_up(id, touch_x, touch_y),
+            InputEvent::TouchCancel { id } => self.input.pointers.pointer_cancel(id),
+            InputEvent::Resize { width, height } => self.renderer.resize(width, height),
+        }
+    }
+
+    /// Restart the session from a known state, seeding [`Random`] values.
+    fn restart(&mut self, seed: u64) {
+        self.seed(seed);
+        self.input.reset();
+        self.renderer.init_components();
+    }
+
+    /// Seed the [`Application`]'s [`Rng`], and the shared one used for values generated without an [`Rng`].
+    fn seed(&mut self, seed: u64) {
+        self.rng = Rng::new_with_seed(seed);
+        seed_random(self.rng.next_u64());
+    }
+
+    /// Get a new seed from the browser.
+    fn random_seed() -> u64 {
+        let half = || (js_sys::Math::random() * u32::MAX as f64) as u64;
+        half() << 32 | half()
+    }
+}
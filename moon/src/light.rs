@@ -0,0 +1,377 @@
+//! The [`Light`] and [`Lighting`] structs, used for 2D lighting.
+
+use std::collections::BTreeMap;
+
+use web_sys::WebGlUniformLocation;
+
+use crate::shadow::{self, Occluder, ShadowMode};
+use crate::{Color32, Shader, Vec2, GL};
+
+/// Maximum [`Lights`](Light) drawn in a single pass.
+///
+/// Scenes with more [`Lights`](Light) are drawn in multiple, additively blended passes.
+pub const MAX_LIGHTS: usize = 16;
+
+/// The shape of a [`Light`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum LightKind {
+    /// A [`Light`] that shines equally in all directions.
+    Point,
+    /// A [`Light`] that shines in a cone.
+    Spot {
+        /// Direction the cone points in.
+        direction: Vec2,
+        /// Angle from the direction, in radians, inside which the [`Light`] is at full strength.
+        inner_angle: f32,
+        /// Angle from the direction, in radians, outside which the [`Light`] has no effect.
+        outer_angle: f32,
+    },
+}
+
+/// A [`Light`] illuminates the area around it.
+#[derive(Debug, Clone)]
+pub struct Light {
+    /// The shape of the [`Light`].
+    pub kind: LightKind,
+    /// Position of the [`Light`].
+    pub position: Vec2,
+    /// Height of the [`Light`] above the scene, used with normal maps.
+    pub height: f32,
+    /// Color of the [`Light`].
+    pub color: Color32,
+    /// Brightness multiplier of the [`Light`].
+    pub intensity: f32,
+    /// Distance at which the [`Light`] has no effect.
+    pub radius: f32,
+    /// Exponent controlling how quickly the [`Light`] fades with distance.
+    pub falloff: f32,
+}
+
+impl Default for Light {
+    fn default() -> Self {
+        Self {
+            kind: LightKind::Point,
+            position: Vec2::zeros(),
+            height: 1.0,
+            color: Color32::WHITE,
+            intensity: 1.0,
+            radius: 5.0,
+            falloff: 2.0,
+        }
+    }
+}
+
+impl Light {
+    /// Create a new point [`Light`] with a given position, radius and color.
+    pub fn new_point(pos_x: f32, pos_y: f32, radius: f32, color: Color32) -> Self {
+        Self {
+            position: Vec2::new(pos_x, pos_y),
+            radius,
+            color,
+            ..Default::default()
+        }
+    }
+
+    /// Create a new spot [`Light`] with a given position, radius, color, direction and cone angle.
+    ///
+    /// The edge of the cone is softened over a tenth of its angle.
+    pub fn new_spot(
+        pos_x: f32,
+        pos_y: f32,
+        radius: f32,
+        color: Color32,
+        direction: Vec2,
+        angle: f32,
+    ) -> Self {
+        Self {
+            kind: LightKind::Spot {
+                direction: direction.normalize(),
+                inner_angle: angle * 0.9,
+                outer_angle: angle,
+            },
+            ..Self::new_point(pos_x, pos_y, radius, color)
+        }
+    }
+
+    /// Get how strongly the [`Light`] affects a given [`Vec2`], from `0.0` to `1.0`.
+    ///
+    /// This matches the calculation done on the GPU, ignoring normal maps.
+    pub fn attenuation(&self, point: Vec2) -> f32 {
+        if self.radius <= 0.0 {
+            return 0.0;
+        }
+        let offset = self.position - point;
+        let distance = offset.norm();
+        let attenuation = (1.0 - distance / self.radius)
+            .clamp(0.0, 1.0)
+            .powf(self.falloff);
+
+        match self.kind {
+            LightKind::Point => attenuation,
+            LightKind::Spot {
+                direction,
+                inner_angle,
+                outer_angle,
+            } => {
+                let cosine = (-offset / distance.max(0.0001)).dot(&direction);
+                attenuation * smoothstep(outer_angle.cos(), inner_angle.cos(), cosine)
+            }
+        }
+    }
+}
+
+fn smoothstep(edge0: f32, edge1: f32, x: f32) -> f32 {
+    let t = ((x - edge0) / (edge1 - edge0)).clamp(0.0, 1.0);
+    t * t * (3.0 - 2.0 * t)
+}
+
+/// The uniform data for up to [`MAX_LIGHTS`] [`Lights`](Light), flattened so it can be sent to WebGL.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct LightBatch {
+    /// Number of [`Lights`](Light) in the batch.
+    pub count: i32,
+    /// Position and height of each [`Light`].
+    pub positions: Vec<f32>,
+    /// Color of each [`Light`], with the intensity stored in the alpha component.
+    pub colors: Vec<f32>,
+    /// Radius, falloff, and the cosines of the inner and outer cone angles of each [`Light`].
+    pub params: Vec<f32>,
+    /// Direction of each [`Light`].
+    pub directions: Vec<f32>,
+}
+
+impl LightBatch {
+    fn push(&mut self, light: &Light) {
+        self.count += 1;
+        self.positions
+            .extend([light.position.x, light.position.y, light.height]);
+        self.colors.extend([
+            light.color.r(),
+            light.color.g(),
+            light.color.b(),
+            light.intensity,
+        ]);
+        match light.kind {
+            LightKind::Point => {
+                // An outer cosine below -1.0 tells the shader not to cut the light off
+                self.params
+                    .extend([light.radius, light.falloff, -2.0, -2.0]);
+                self.directions.extend([0.0, 0.0]);
+            }
+            LightKind::Spot {
+                direction,
+                inner_angle,
+                outer_angle,
+            } => {
+                self.params.extend([
+                    light.radius,
+                    light.falloff,
+                    inner_angle.cos(),
+                    outer_angle.cos(),
+                ]);
+                self.directions.extend([direction.x, direction.y]);
+            }
+        }
+    }
+}
+
+impl From<&Light> for LightBatch {
+    fn from(light: &Light) -> Self {
+        let mut batch = Self::default();
+        batch.push(light);
+        batch
+    }
+}
+
+/// A collection of [`Lights`](Light) and [`Occluders`](Occluder), along with an ambient [`Color32`].
+///
+/// When there are any [`Occluders`](Occluder), [`Lights`](Light) are drawn into a light map with shadows cut out of it, and normal maps are not used.
+#[derive(Debug)]
+pub struct Lighting {
+    /// The [`Color32`] every surface is lit with, with its strength stored in the alpha component.
+    ///
+    /// This is white by default, so scenes without any [`Lights`](Light) are drawn unlit.
+    pub ambient: Color32,
+    /// How the edges of shadows are drawn.
+    pub shadows: ShadowMode,
+    lights: BTreeMap<&'static str, Light>,
+    occluders: BTreeMap<&'static str, Box<dyn Occluder>>,
+}
+
+impl Default for Lighting {
+    fn default() -> Self {
+        Self {
+            ambient: Color32::WHITE,
+            shadows: ShadowMode::default(),
+            lights: BTreeMap::new(),
+            occluders: BTreeMap::new(),
+        }
+    }
+}
+
+impl Lighting {
+    /// Create a new [`Lighting`] with no [`Lights`](Light).
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a [`Light`], that can be retreived later via a string slice.
+    pub fn add_light(&mut self, key: &'static str, light: Light) {
+        self.lights.insert(key, light);
+    }
+
+    /// Remove a [`Light`], returning it if it was found.
+    pub fn remove_light(&mut self, key: &str) -> Option<Light> {
+        self.lights.remove(key)
+    }
+
+    /// Get the requested [`Light`].
+    pub fn get_light(&self, key: &str) -> Option<&Light> {
+        self.lights.get(key)
+    }
+
+    /// Get the requested [`Light`] mutably.
+    pub fn get_mut_light(&mut self, key: &str) -> Option<&mut Light> {
+        self.lights.get_mut(key)
+    }
+
+    /// Get an iterator over all the [`Lights`](Light).
+    pub fn lights(&self) -> impl Iterator<Item = &Light> {
+        self.lights.values()
+    }
+
+    /// Get the number of [`Lights`](Light).
+    pub fn len(&self) -> usize {
+        self.lights.len()
+    }
+
+    /// Check if there are no [`Lights`](Light).
+    pub fn is_empty(&self) -> bool {
+        self.lights.is_empty()
+    }
+
+    /// Add an [`Occluder`], that can be retreived later via a string slice.
+    pub fn add_occluder(&mut self, key: &'static str, occluder: Box<dyn Occluder>) {
+        self.occluders.insert(key, occluder);
+    }
+
+    /// Remove an [`Occluder`], returning it if it was found.
+    pub fn remove_occluder(&mut self, key: &str) -> Option<Box<dyn Occluder>> {
+        self.occluders.remove(key)
+    }
+
+    /// Get an iterator over all the [`Occluders`](Occluder).
+    pub fn occluders(&self) -> impl Iterator<Item = &dyn Occluder> {
+        self.occluders.values().map(|occluder| occluder.as_ref())
+    }
+
+    /// Check if there are any [`Occluders`](Occluder), and shadows need to be drawn.
+    pub fn has_occluders(&self) -> bool {
+        !self.occluders.is_empty()
+    }
+
+    /// Get the [`Color32`] a point is lit with, including the ambient light and any shadows.
+    ///
+    /// This matches the light map drawn on the GPU when there are [`Occluders`](Occluder), ignoring normal maps.
+    pub fn light_at(&self, point: Vec2) -> Color32 {
+        let ambient = self.ambient * self.ambient.a();
+        let outlines: Vec<Vec<Vec2>> = self
+            .occluders()
+            .map(|occluder| occluder.outline())
+            .collect();
+        self.lights().fold(
+            Color32(ambient.r(), ambient.g(), ambient.b(), 1.0),
+            |color, light| {
+                let strength = light.intensity
+                    * light.attenuation(point)
+                    * shadow::light_visibility(light, self.shadows, &outlines, point);
+                color + light.color * strength
+            },
+        )
+    }
+
+    /// Split the [`Lights`](Light) into [`LightBatches`](LightBatch) of at most [`MAX_LIGHTS`].
+    ///
+    /// There is always at least one batch, so the ambient light is drawn even without any [`Lights`](Light).
+    pub fn batches(&self) -> Vec<LightBatch> {
+        let lights: Vec<&Light> = self.lights.values().collect();
+        if lights.is_empty() {
+            return vec![LightBatch::default()];
+        }
+        lights
+            .chunks(MAX_LIGHTS)
+            .map(|chunk| {
+                let mut batch = LightBatch::default();
+                for light in chunk {
+                    batch.push(light);
+                }
+                batch
+            })
+            .collect()
+    }
+}
+
+/// Uniform locations used for lighting on a [`Shader`].
+#[derive(Debug, Default)]
+pub(crate) struct LightUniforms {
+    ambient: Option<WebGlUniformLocation>,
+    count: Option<WebGlUniformLocation>,
+    positions: Option<WebGlUniformLocation>,
+    colors: Option<WebGlUniformLocation>,
+    params: Option<WebGlUniformLocation>,
+    directions: Option<WebGlUniformLocation>,
+    use_normal_map: Option<WebGlUniformLocation>,
+    normal_map: Option<WebGlUniformLocation>,
+}
+
+impl LightUniforms {
+    /// Get the lighting uniform locations of a [`Shader`].
+    pub(crate) fn new(gl: &GL, program: &Shader) -> Self {
+        Self {
+            ambient: program.get_uniform_location(gl, "uAmbient"),
+            count: program.get_uniform_location(gl, "uLightCount"),
+            positions: program.get_uniform_location(gl, "uLightPosition"),
+            colors: program.get_uniform_location(gl, "uLightColor"),
+            params: program.get_uniform_location(gl, "uLightParams"),
+            directions: program.get_uniform_location(gl, "uLightDirection"),
+            use_normal_map: program.get_uniform_location(gl, "uUseNormalMap"),
+            normal_map: program.get_uniform_location(gl, "uTex1"),
+        }
+    }
+
+    /// Set the initial values of the uniforms, with normal maps read from texture slot 1.
+    pub(crate) fn init(&self, gl: &GL) {
+        gl.uniform1i(self.normal_map.as_ref(), 1);
+        gl.uniform1i(self.use_normal_map.as_ref(), 0);
+        gl.uniform4f(self.ambient.as_ref(), 1.0, 1.0, 1.0, 1.0);
+        gl.uniform1i(self.count.as_ref(), 0);
+    }
+
+    /// Upload a [`LightBatch`] and the ambient [`Color32`].
+    pub(crate) fn set(&self, gl: &GL, batch: &LightBatch, ambient: Color32) {
+        gl.uniform4f(
+            self.ambient.as_ref(),
+            ambient.r(),
+            ambient.g(),
+            ambient.b(),
+            ambient.a(),
+        );
+        gl.uniform1i(self.count.as_ref(), batch.count);
+        if batch.count > 0 {
+            gl.uniform3fv_with_f32_array(self.positions.as_ref(), &batch.positions);
+            gl.uniform4fv_with_f32_array(self.colors.as_ref(), &batch.colors);
+            gl.uniform4fv_with_f32_array(self.params.as_ref(), &batch.params);
+            gl.uniform2fv_with_f32_array(self.directions.as_ref(), &batch.directions);
+        }
+    }
+
+    /// Set the texture unit the normal map is read from.
+    pub(crate) fn set_normal_map_unit(&self, gl: &GL, unit: u32) {
+        gl.uniform1i(self.normal_map.as_ref(), unit as i32);
+    }
+
+    /// Enable or disable sampling a normal map.
+    pub(crate) fn set_use_normal_map(&self, gl: &GL, enabled: bool) {
+        gl.uniform1i(self.use_normal_map.as_ref(), enabled as i32);
+    }
+}
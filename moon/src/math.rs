@@ -11,6 +11,9 @@ pub type Vec3 = nalgebra::Vector3<f32>;
 /// An alias to [`nalgebra::Vector4<f32>`].
 pub type Vec4 = nalgebra::Vector4<f32>;
 
+/// An alias to [`nalgebra::Matrix3<f32>`].
+pub type Mat3 = nalgebra::Matrix3<f32>;
+
 /// An alias to [`nalgebra::Matrix4<f32>`].
 pub type Mat4 = nalgebra::Matrix4<f32>;
 
@@ -27,7 +30,7 @@ pub type Ortho = nalgebra::Orthographic3<f32>;
 /// use moon_engine::math::Color32;
 /// let color = Color32::default();
 /// ```
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Copy, Debug, PartialEq)]
 pub struct Color32(pub f32, pub f32, pub f32, pub f32);
 
 impl Default for Color32 {
@@ -260,67 +263,93 @@ use std::ops::{Add, Mul};
 
 pub use nalgebra::clamp;
 
+use crate::random::{with_rng, Rng};
+
 /// Trait for generating random values
-pub trait Random {
+///
+/// Values are generated from an [`Rng`], so they follow its seed, such as the one owned by the [`Application`](crate::Application).
+/// The methods without an [`Rng`] use a shared one instead, which can be seeded with [`seed_random`](crate::random::seed_random).
+pub trait Random: Sized {
+    /// Get a random value from an [`Rng`].
+    fn random_with(rng: &mut Rng) -> Self;
+    /// Get a random value from an [`Rng`], expanded to another range.
+    fn random_range_max_with(rng: &mut Rng, max: Self) -> Self;
+    /// Get a random value from an [`Rng`], expanded to another range.
+    fn random_range_with(rng: &mut Rng, min: Self, max: Self) -> Self;
+
     /// Get a random value.
-    fn random() -> Self;
+    fn random() -> Self {
+        with_rng(Self::random_with)
+    }
     /// Get a random value, expanded to another range.
-    fn random_range_max(max: Self) -> Self;
+    fn random_range_max(max: Self) -> Self {
+        with_rng(|rng| Self::random_range_max_with(rng, max))
+    }
     /// Get a random value, expanded to another range.
-    fn random_range(min: Self, max: Self) -> Self;
+    fn random_range(min: Self, max: Self) -> Self {
+        with_rng(|rng| Self::random_range_with(rng, min, max))
+    }
 }
 
 impl Random for f32 {
-    fn random() -> Self {
-        js_sys::Math::random() as f32
+    fn random_with(rng: &mut Rng) -> Self {
+        rng.next_f32()
     }
 
-    fn random_range_max(max: Self) -> Self {
-        f32::random() * max
+    fn random_range_max_with(rng: &mut Rng, max: Self) -> Self {
+        rng.next_f32() * max
     }
 
-    fn random_range(min: Self, max: Self) -> Self {
-        f32::random() * (max - min) + min
+    fn random_range_with(rng: &mut Rng, min: Self, max: Self) -> Self {
+        rng.next_f32() * (max - min) + min
     }
 }
 
 impl Random for Vec2 {
-    fn random() -> Self {
-        Vec2::new(f32::random(), f32::random())
+    fn random_with(rng: &mut Rng) -> Self {
+        Vec2::new(f32::random_with(rng), f32::random_with(rng))
     }
 
-    fn random_range_max(max: Self) -> Self {
-        Vec2::new(f32::random_range_max(max.x), f32::random_range_max(max.y))
+    fn random_range_max_with(rng: &mut Rng, max: Self) -> Self {
+        Vec2::new(
+            f32::random_range_max_with(rng, max.x),
+            f32::random_range_max_with(rng, max.y),
+        )
     }
 
-    fn random_range(min: Self, max: Self) -> Self {
+    fn random_range_with(rng: &mut Rng, min: Self, max: Self) -> Self {
         Vec2::new(
-            f32::random_range(min.x, max.x),
-            f32::random_range(min.y, max.y),
+            f32::random_range_with(rng, min.x, max.x),
+            f32::random_range_with(rng, min.y, max.y),
         )
     }
 }
 
 impl Random for Color32 {
-    fn random() -> Self {
-        Color32(f32::random(), f32::random(), f32::random(), 1.0)
+    fn random_with(rng: &mut Rng) -> Self {
+        Color32(
+            f32::random_with(rng),
+            f32::random_with(rng),
+            f32::random_with(rng),
+            1.0,
+        )
     }
 
-    fn random_range_max(max: Self) -> Self {
+    fn random_range_max_with(rng: &mut Rng, max: Self) -> Self {
         Color32(
-            f32::random_range_max(max.0),
-            f32::random_range_max(max.1),
-            f32::random_range_max(max.2),
-            f32::random_range_max(max.3),
+            f32::random_range_max_with(rng, max.0),
+            f32::random_range_max_with(rng, max.1),
+            f32::random_range_max_with(rng, max.2),
+            f32::random_range_max_with(rng, max.3),
         )
     }
 
-    fn random_range(min: Self, max: Self) -> Self {
+    fn random_range_with(rng: &mut Rng, min: Self, max: Self) -> Self {
         Color32(
-            f32::random_range(min.0, max.0),
-            f32::random_range(min.1, max.1),
-            f32::random_range(min.2, max.2),
-            f32::random_range(min.3, max.3),
+            f32::random_range_with(rng, min.0, max.0),
+            f32::random_range_with(rng, min.1, max.1),
+            f32::random_range_with(rng, min.2, max.2),
+            f32::random_range_with(rng, min.3, max.3),
         )
     }
 }
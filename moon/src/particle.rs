@@ -4,6 +4,7 @@ use std::f32::consts::PI;
 
 use crate::component::Component;
 use crate::math::*;
+use crate::random::{with_rng, Rng};
 use crate::renderer::Quad;
 use crate::transform::Transform2D;
 
@@ -108,19 +109,20 @@ impl Default for Particle {
 
 impl Component for Particle {
     fn init(&mut self) {
-        self.transform.rotation = f32::random_range_max(PI);
-        self.color = self.color_start;
-        self.alive = true;
-        self.age = 0.0;
+        with_rng(|rng| self.init_with_rng(rng));
     }
 
     fn update(&mut self, delta_time: f32) {
+        with_rng(|rng| self.update_with_rng(delta_time, rng));
+    }
+
+    fn update_with_rng(&mut self, delta_time: f32, rng: &mut Rng) {
         self.age += delta_time;
         if self.age > self.lifetime {
             self.alive = false;
         } else {
             self.transform.position += self.velocity * delta_time;
-            self.transform.rotation += f32::random_range(-1.0, 1.0) * delta_time;
+            self.transform.rotation += f32::random_range_with(rng, -1.0, 1.0) * delta_time;
             self.color = Color32::lerp(self.color_start, self.color_end, self.age / self.lifetime);
         }
     }
@@ -136,23 +138,47 @@ impl Component for Particle {
 
 impl From<&ParticleProps> for Particle {
     fn from(properties: &ParticleProps) -> Self {
+        with_rng(|rng| Self::new_from_emission_with_rng(properties, rng))
+    }
+}
+
+impl Particle {
+    /// Create a new [`Particle`] from a [`ParticleProps`], using an [`Rng`] for its random variation.
+    pub fn new_from_emission_with_rng(properties: &ParticleProps, rng: &mut Rng) -> Self {
         Self {
             transform: Transform2D::new_with_scale(properties.size.x, properties.size.y),
             lifetime: properties.lifetime,
             velocity: {
                 properties.velocity
-                    + Vec2::random_range(
+                    + Vec2::random_range_with(
+                        rng,
                         -properties.velocity_modifier,
                         properties.velocity_modifier,
                     )
             },
             color_start: properties.color_start
-                + Color32::random_range(properties.color_modifier, properties.color_modifier),
+                + Color32::random_range_with(
+                    rng,
+                    properties.color_modifier,
+                    properties.color_modifier,
+                ),
             color_end: properties.color_end
-                + Color32::random_range(properties.color_modifier, properties.color_modifier),
+                + Color32::random_range_with(
+                    rng,
+                    properties.color_modifier,
+                    properties.color_modifier,
+                ),
             ..Default::default()
         }
     }
+
+    /// Start the [`Particle`] with a random rotation, using an [`Rng`].
+    fn init_with_rng(&mut self, rng: &mut Rng) {
+        self.transform.rotation = f32::random_range_max_with(rng, PI);
+        self.color = self.color_start;
+        self.alive = true;
+        self.age = 0.0;
+    }
 }
 
 /// A [`ParticleSystem`] deals with the emission, and creation of [`Particles`](Particle).
@@ -186,15 +212,19 @@ impl Component for ParticleSystem {
     }
 
     fn update(&mut self, delta_time: f32) {
+        with_rng(|rng| self.update_with_rng(delta_time, rng));
+    }
+
+    fn update_with_rng(&mut self, delta_time: f32, rng: &mut Rng) {
         // Do not update if inactive
         if !self.alive {
             return;
         }
 
-        self.emit_many(self.emission.burst_count);
+        self.emit_many_with_rng(self.emission.burst_count, rng);
         for particle in self.particles.iter_mut() {
             if particle.alive {
-                particle.update(delta_time);
+                particle.update_with_rng(delta_time, rng);
             }
         }
     }
@@ -252,11 +282,16 @@ impl ParticleSystem {
 
     /// Emit a single [`Particle`], according to the defined [`ParticleProps`] for emission.
     pub fn emit(&mut self) {
+        with_rng(|rng| self.emit_with_rng(rng));
+    }
+
+    /// Emit a single [`Particle`], using an [`Rng`] for its random variation.
+    pub fn emit_with_rng(&mut self, rng: &mut Rng) {
         if self.index >= MAX_PARTICLES {
             self.index = 0;
         }
 
-        let mut new_particle = Particle::from(&self.emission);
+        let mut new_particle = Particle::new_from_emission_with_rng(&self.emission, rng);
         new_particle.transform = self.transform + new_particle.transform;
 
         let particle = self.particles.get_mut(self.index);
@@ -268,9 +303,9 @@ impl ParticleSystem {
             particle.color_start = new_particle.color_start;
             particle.color_end = new_particle.color_end;
 
-            particle.init();
+            particle.init_with_rng(rng);
         } else {
-            new_particle.init();
+            new_particle.init_with_rng(rng);
             self.particles.push(new_particle);
         }
         self.index += 1;
@@ -278,8 +313,13 @@ impl ParticleSystem {
 
     /// Emit multiple [`Particles`](Particle), according to the defined [`ParticleProps`] for emission.
     pub fn emit_many(&mut self, count: u32) {
+        with_rng(|rng| self.emit_many_with_rng(count, rng));
+    }
+
+    /// Emit multiple [`Particles`](Particle), using an [`Rng`] for their random variation.
+    pub fn emit_many_with_rng(&mut self, count: u32, rng: &mut Rng) {
         for _ in 0..count {
-            self.emit()
+            self.emit_with_rng(rng)
         }
     }
 }
@@ -0,0 +1,260 @@
+//! The [`PointerInput`] struct, which tracks [`Pointers`](Pointer) and recognises [`Gestures`](Gesture) made with them.
+
+use std::collections::BTreeMap;
+
+use crate::Vec2;
+
+/// The phase of a [`Pointer`] during the current frame.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PointerPhase {
+    /// The [`Pointer`] touched down this frame.
+    Began,
+    /// The [`Pointer`] moved this frame.
+    Moved,
+    /// The [`Pointer`] is down, but did not move this frame.
+    Stationary,
+    /// The [`Pointer`] was lifted this frame.
+    Ended,
+    /// The [`Pointer`] was interrupted by the browser this frame.
+    Cancelled,
+}
+
+/// A single touch point, or other pointing device.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Pointer {
+    /// Identifier of the [`Pointer`], unique while it is down.
+    pub id: i32,
+    /// Screen-Space position of the [`Pointer`].
+    pub position: Vec2,
+    /// Screen-Space position the [`Pointer`] touched down at.
+    pub start_position: Vec2,
+    /// Phase of the [`Pointer`] during the current frame.
+    pub phase: PointerPhase,
+    /// How long the [`Pointer`] has been down, in seconds.
+    pub duration: f32,
+}
+
+/// A [`Gesture`] recognised from the movement of [`Pointers`](Pointer).
+///
+/// All positions are in Screen-Space.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Gesture {
+    /// A single [`Pointer`] was quickly pressed and lifted without moving.
+    Tap {
+        /// Position of the tap.
+        position: Vec2,
+    },
+    /// A single [`Pointer`] was moved while down.
+    Drag {
+        /// Current position of the [`Pointer`].
+        position: Vec2,
+        /// Movement since the last [`Gesture::Drag`].
+        delta: Vec2,
+    },
+    /// Two [`Pointers`](Pointer) were moved towards or away from each other.
+    Pinch {
+        /// Point halfway between the [`Pointers`](Pointer).
+        center: Vec2,
+        /// Ratio of the current distance between the [`Pointers`](Pointer) to the previous one.
+        scale: f32,
+    },
+}
+
+/// Thresholds used when recognising [`Gestures`](Gesture).
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct GestureSettings {
+    /// Maximum time a [`Pointer`] can be down for a [`Gesture::Tap`], in seconds.
+    pub tap_max_duration: f32,
+    /// Distance, in pixels, a [`Pointer`] has to move before it starts a [`Gesture::Drag`].
+    pub drag_threshold: f32,
+}
+
+impl Default for GestureSettings {
+    fn default() -> Self {
+        Self {
+            tap_max_duration: 0.3,
+            drag_threshold: 10.0,
+        }
+    }
+}
+
+/// Tracks every [`Pointer`] that is down, and the [`Gestures`](Gesture) made with them during the current frame.
+///
+/// # Examples
+/// ```
+/// use moon_engine::pointer::{Gesture, PointerInput};
+/// use moon_engine::Vec2;
+///
+/// let mut pointers = PointerInput::new();
+///
+/// pointers.pointer_down(0, 10.0, 10.0);
+/// pointers.pointer_up(0, 11.0, 10.0);
+///
+/// assert_eq!(pointers.gestures(), &[Gesture::Tap { position: Vec2::new(11.0, 10.0) }]);
+/// ```
+#[derive(Debug, Default)]
+pub struct PointerInput {
+    /// Thresholds used when recognising [`Gestures`](Gesture).
+    pub settings: GestureSettings,
+    pointers: BTreeMap<i32, Pointer>,
+    gestures: Vec<Gesture>,
+    dragging: Option<i32>,
+    multi_touch: bool,
+    pinch_distance: Option<f32>,
+}
+
+impl PointerInput {
+    /// Create a new [`PointerInput`] with default [`GestureSettings`].
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// A [`Pointer`] touched down.
+    pub fn pointer_down(&mut self, id: i32, x: f32, y: f32) {
+        let position = Vec2::new(x, y);
+        self.pointers.insert(
+            id,
+            Pointer {
+                id,
+                position,
+                start_position: position,
+                phase: PointerPhase::Began,
+                duration: 0.0,
+            },
+        );
+        match self.active().count() {
+            1 => self.multi_touch = false,
+            _ => {
+                self.multi_touch = true;
+                self.dragging = None;
+                self.pinch_distance = self.pinch().map(|(_, distance)| distance);
+            }
+        }
+    }
+
+    /// A [`Pointer`] moved.
+    pub fn pointer_move(&mut self, id: i32, x: f32, y: f32) {
+        let pointer = match self.pointers.get_mut(&id) {
+            Some(pointer) if Self::is_down(pointer) => pointer,
+            _ => return,
+        };
+        let position = Vec2::new(x, y);
+        let delta = position - pointer.position;
+        if delta == Vec2::zeros() {
+            return;
+        }
+        pointer.position = position;
+        if pointer.phase != PointerPhase::Began {
+            pointer.phase = PointerPhase::Moved;
+        }
+        let start_position = pointer.start_position;
+
+        if self.multi_touch {
+            if let (Some((center, distance)), Some(previous)) = (self.pinch(), self.pinch_distance)
+            {
+                if previous > 0.0 {
+                    self.gestures.push(Gesture::Pinch {
+                        center,
+                        scale: distance / previous,
+                    });
+                }
+                self.pinch_distance = Some(distance);
+            }
+        } else if self.dragging == Some(id) {
+            self.gestures.push(Gesture::Drag { position, delta });
+        } else if (position - start_position).norm() > self.settings.drag_threshold {
+            self.dragging = Some(id);
+            self.gestures.push(Gesture::Drag {
+                position,
+                delta: position - start_position,
+            });
+        }
+    }
+
+    /// A [`Pointer`] was lifted.
+    pub fn pointer_up(&mut self, id: i32, x: f32, y: f32) {
+        self.pointer_move(id, x, y);
+        let pointer = match self.pointers.get_mut(&id) {
+            Some(pointer) if Self::is_down(pointer) => pointer,
+            _ => return,
+        };
+        pointer.phase = PointerPhase::Ended;
+        let pointer = *pointer;
+
+        if !self.multi_touch
+            && self.dragging != Some(id)
+            && pointer.duration <= self.settings.tap_max_duration
+        {
+            self.gestures.push(Gesture::Tap {
+                position: pointer.position,
+            });
+        }
+        self.end(id);
+    }
+
+    /// A [`Pointer`] was interrupted, and should be ignored.
+    pub fn pointer_cancel(&mut self, id: i32) {
+        if let Some(pointer) = self.pointers.get_mut(&id) {
+            pointer.phase = PointerPhase::Cancelled;
+            self.end(id);
+        }
+    }
+
+    /// Get the requested [`Pointer`], if it is down or was lifted this frame.
+    pub fn get_pointer(&self, id: i32) -> Option<&Pointer> {
+        self.pointers.get(&id)
+    }
+
+    /// Get an iterator over every [`Pointer`] that is down or was lifted this frame.
+    pub fn pointers(&self) -> impl Iterator<Item = &Pointer> {
+        self.pointers.values()
+    }
+
+    /// Get the [`Gestures`](Gesture) recognised during the current frame, in the order they were made.
+    pub fn gestures(&self) -> &[Gesture] {
+        &self.gestures
+    }
+
+    /// Move on to the next frame.
+    ///
+    /// Lifted [`Pointers`](Pointer) are removed, the others become stationary, and the list of [`Gestures`](Gesture) is cleared.
+    pub fn advance_frame(&mut self, delta_time: f32) {
+        self.pointers.retain(|_, pointer| Self::is_down(pointer));
+        for pointer in self.pointers.values_mut() {
+            pointer.phase = PointerPhase::Stationary;
+            pointer.duration += delta_time;
+        }
+        self.gestures.clear();
+    }
+
+    fn is_down(pointer: &Pointer) -> bool {
+        !matches!(pointer.phase, PointerPhase::Ended | PointerPhase::Cancelled)
+    }
+
+    fn active(&self) -> impl Iterator<Item = &Pointer> {
+        self.pointers
+            .values()
+            .filter(|pointer| Self::is_down(pointer))
+    }
+
+    /// Get the center of, and distance between, the first two active [`Pointers`](Pointer).
+    fn pinch(&self) -> Option<(Vec2, f32)> {
+        let mut active = self.active();
+        let (first, second) = (active.next()?, active.next()?);
+        Some((
+            (first.position + second.position) / 2.0,
+            (first.position - second.position).norm(),
+        ))
+    }
+
+    fn end(&mut self, id: i32) {
+        if self.dragging == Some(id) {
+            self.dragging = None;
+        }
+        match self.active().count() {
+            0 => self.multi_touch = false,
+            1 => self.pinch_distance = None,
+            _ => self.pinch_distance = self.pinch().map(|(_, distance)| distance),
+        }
+    }
+}
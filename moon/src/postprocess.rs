@@ -0,0 +1,338 @@
+//! The [`PostProcess`] stack, and the [`PostEffect`]s it applies.
+
+use std::rc::Rc;
+
+use wasm_bindgen::JsCast;
+use web_sys::{WebGlTexture, WebGlUniformLocation};
+
+use crate::framebuffer::RenderTarget;
+use crate::gl::Bind;
+use crate::mesh::Mesh;
+use crate::texture::Texture;
+use crate::{Shader, GL};
+
+/// A full-screen effect that is applied by the [`PostProcess`] stack.
+///
+/// Each variant stores the parameters of the effect, which can be changed between frames.
+#[derive(Debug, Clone)]
+pub enum PostEffect {
+    /// Makes bright areas glow.
+    Bloom {
+        /// Luminance above which pixels start to glow.
+        threshold: f32,
+        /// Strength of the glow.
+        intensity: f32,
+        /// Distance between samples, in pixels.
+        radius: f32,
+    },
+    /// Remaps colors using a Look-Up Table (LUT).
+    ///
+    /// The LUT [`Texture`] is a horizontal strip of `size` slices, each `size` pixels square.
+    ColorGrading {
+        /// The LUT [`Texture`].
+        lut: Rc<Texture>,
+        /// Number of slices in the LUT.
+        size: f32,
+        /// How much of the graded color to mix in.
+        intensity: f32,
+    },
+    /// Darkens the edges of the screen.
+    Vignette {
+        /// Distance from the center at which darkening starts.
+        radius: f32,
+        /// Width of the transition from the inner to outer edge.
+        softness: f32,
+        /// Strength of the darkening.
+        intensity: f32,
+    },
+    /// Simulates an old CRT display with scanlines and screen curvature.
+    Crt {
+        /// Number of scanlines across the screen.
+        scanlines: f32,
+        /// How dark the scanlines are.
+        scanline_intensity: f32,
+        /// How much the screen bulges outwards.
+        curvature: f32,
+    },
+    /// A separable gaussian blur, applied in a horizontal and a vertical pass.
+    GaussianBlur {
+        /// Distance between samples, in pixels.
+        radius: f32,
+    },
+}
+
+impl PostEffect {
+    /// A Bloom [`PostEffect`] preset.
+    pub const fn bloom() -> Self {
+        Self::Bloom {
+            threshold: 0.7,
+            intensity: 1.0,
+            radius: 2.0,
+        }
+    }
+
+    /// A Color Grading [`PostEffect`] preset, using a given LUT [`Texture`] and its size.
+    pub fn color_grading(lut: Rc<Texture>, size: f32) -> Self {
+        Self::ColorGrading {
+            lut,
+            size,
+            intensity: 1.0,
+        }
+    }
+
+    /// A Vignette [`PostEffect`] preset.
+    pub const fn vignette() -> Self {
+        Self::Vignette {
+            radius: 0.75,
+            softness: 0.45,
+            intensity: 0.8,
+        }
+    }
+
+    /// A CRT [`PostEffect`] preset.
+    pub const fn crt() -> Self {
+        Self::Crt {
+            scanlines: 240.0,
+            scanline_intensity: 0.3,
+            curvature: 0.05,
+        }
+    }
+
+    /// A Gaussian Blur [`PostEffect`] preset.
+    pub const fn gaussian_blur() -> Self {
+        Self::GaussianBlur { radius: 1.0 }
+    }
+
+    /// Get the number of full-screen passes the [`PostEffect`] needs.
+    pub fn passes(&self) -> usize {
+        match self {
+            Self::GaussianBlur { .. } => 2,
+            _ => 1,
+        }
+    }
+
+    fn fragment_source(&self) -> &'static str {
+        match self {
+            Self::Bloom { .. } => include_str!("../res/shader/bloom.frag.glsl"),
+            Self::ColorGrading { .. } => include_str!("../res/shader/lut.frag.glsl"),
+            Self::Vignette { .. } => include_str!("../res/shader/vignette.frag.glsl"),
+            Self::Crt { .. } => include_str!("../res/shader/crt.frag.glsl"),
+            Self::GaussianBlur { .. } => include_str!("../res/shader/blur.frag.glsl"),
+        }
+    }
+
+    /// Set the uniforms of the [`PostEffect`] for a given pass.
+    fn set_uniforms(&self, gl: &GL, shader: &Shader, pass: usize) {
+        let uniform = |name: &str| shader.get_uniform_location(gl, name);
+        match self {
+            Self::Bloom {
+                threshold,
+                intensity,
+                radius,
+            } => {
+                gl.uniform1f(uniform("uThreshold").as_ref(), *threshold);
+                gl.uniform1f(uniform("uIntensity").as_ref(), *intensity);
+                gl.uniform1f(uniform("uRadius").as_ref(), *radius);
+            }
+            Self::ColorGrading {
+                lut,
+                size,
+                intensity,
+            } => {
+                gl.active_texture(GL::TEXTURE1);
+                gl.bind_texture(GL::TEXTURE_2D, lut.raw());
+                gl.uniform1i(uniform("uTex1").as_ref(), 1);
+                gl.uniform1f(uniform("uLutSize").as_ref(), *size);
+                gl.uniform1f(uniform("uIntensity").as_ref(), *intensity);
+            }
+            Self::Vignette {
+                radius,
+                softness,
+                intensity,
+            } => {
+                gl.uniform1f(uniform("uRadius").as_ref(), *radius);
+                gl.uniform1f(uniform("uSoftness").as_ref(), *softness);
+                gl.uniform1f(uniform("uIntensity").as_ref(), *intensity);
+            }
+            Self::Crt {
+                scanlines,
+                scanline_intensity,
+                curvature,
+            } => {
+                gl.uniform1f(uniform("uScanlines").as_ref(), *scanlines);
+                gl.uniform1f(uniform("uScanlineIntensity").as_ref(), *scanline_intensity);
+                gl.uniform1f(uniform("uCurvature").as_ref(), *curvature);
+            }
+            Self::GaussianBlur { radius } => {
+                let (x, y) = if pass == 0 { (1.0, 0.0) } else { (0.0, 1.0) };
+                gl.uniform2f(uniform("uDirection").as_ref(), x, y);
+                gl.uniform1f(uniform("uRadius").as_ref(), *radius);
+            }
+        }
+    }
+}
+
+/// A single [`PostEffect`] in the [`PostProcess`] stack, along with its [`Shader`].
+#[derive(Debug)]
+struct PostPass {
+    name: &'static str,
+    effect: PostEffect,
+    shader: Shader,
+    texture: Option<WebGlUniformLocation>,
+    resolution: Option<WebGlUniformLocation>,
+    enabled: bool,
+}
+
+/// An ordered stack of [`PostEffects`](PostEffect), applied to the whole screen after drawing.
+///
+/// The scene is rendered into an off-screen [`RenderTarget`], and each pass then reads from one target and writes into the other. The final pass writes to the canvas.
+#[derive(Debug, Default)]
+pub struct PostProcess {
+    passes: Vec<PostPass>,
+    targets: Vec<RenderTarget>,
+    quad: Option<Mesh>,
+}
+
+impl PostProcess {
+    /// Create a new, empty [`PostProcess`] stack.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a [`PostEffect`] to the end of the stack.
+    pub fn push(&mut self, gl: &GL, name: &'static str, effect: PostEffect) {
+        let vertex_shader = Shader::create_vertex(gl, include_str!("../res/shader/post.vert.glsl"))
+            .expect("Could not create Vertex Shader!");
+        let fragment_shader = Shader::create_fragment(gl, effect.fragment_source())
+            .expect("Could not create Fragment Shader!");
+        let shader =
+            Shader::new_with_vertex_and_fragment(gl, vertex_shader, fragment_shader, Some(name));
+
+        self.passes.push(PostPass {
+            name,
+            effect,
+            texture: shader.get_uniform_location(gl, "uTex0"),
+            resolution: shader.get_uniform_location(gl, "uResolution"),
+            shader,
+            enabled: true,
+        });
+    }
+
+    /// Remove a [`PostEffect`] from the stack, returning it if it was found.
+    pub fn remove(&mut self, name: &str) -> Option<PostEffect> {
+        let index = self.passes.iter().position(|pass| pass.name == name)?;
+        Some(self.passes.remove(index).effect)
+    }
+
+    /// Remove all [`PostEffects`](PostEffect) from the stack.
+    pub fn clear(&mut self) {
+        self.passes.clear();
+    }
+
+    /// Get a mutable reference to a [`PostEffect`], so that its parameters can be changed.
+    pub fn get_mut_effect(&mut self, name: &str) -> Option<&mut PostEffect> {
+        self.passes
+            .iter_mut()
+            .find(|pass| pass.name == name)
+            .map(|pass| &mut pass.effect)
+    }
+
+    /// Enable or disable a [`PostEffect`] without removing it from the stack.
+    pub fn set_enabled(&mut self, name: &str, enabled: bool) {
+        if let Some(pass) = self.passes.iter_mut().find(|pass| pass.name == name) {
+            pass.enabled = enabled;
+        }
+    }
+
+    /// Check whether any [`PostEffect`] in the stack is enabled.
+    pub fn is_active(&self) -> bool {
+        self.passes.iter().any(|pass| pass.enabled)
+    }
+
+    /// Prepare the stack for a new frame.
+    ///
+    /// If any [`PostEffect`] is enabled, the off-screen [`RenderTarget`] is bound so the scene is rendered into it.
+    pub fn begin(&mut self, gl: &GL) {
+        if !self.is_active() {
+            return;
+        }
+
+        let (width, height) = (
+            gl.drawing_buffer_width() as u32,
+            gl.drawing_buffer_height() as u32,
+        );
+        if self.targets.is_empty() {
+            self.targets.push(RenderTarget::new(gl, width, height));
+            self.targets.push(RenderTarget::new(gl, width, height));
+        }
+        for target in self.targets.iter_mut() {
+            target.resize(gl, width, height);
+        }
+        if self.quad.is_none() {
+            self.quad = Some(Mesh::quad_with_side(gl, 2.0));
+        }
+
+        self.targets[0].bind(gl);
+    }
+
+    /// Apply every enabled [`PostEffect`] in order, writing the final result to the canvas.
+    pub fn apply(&mut self, gl: &GL) {
+        if !self.is_active() || self.targets.len() < 2 {
+            return;
+        }
+        let quad = match self.quad.as_ref() {
+            Some(quad) => quad,
+            None => return,
+        };
+
+        let steps: Vec<(&PostPass, usize)> = self
+            .passes
+            .iter()
+            .filter(|pass| pass.enabled)
+            .flat_map(|pass| (0..pass.effect.passes()).map(move |index| (pass, index)))
+            .collect();
+
+        // Remember the bound texture, so that drawing can continue as normal afterwards
+        gl.active_texture(GL::TEXTURE0);
+        let previous = gl
+            .get_parameter(GL::TEXTURE_BINDING_2D)
+            .ok()
+            .and_then(|texture| texture.dyn_into::<WebGlTexture>().ok());
+
+        gl.disable(GL::BLEND);
+        let mut source = 0;
+        for (step, (pass, index)) in steps.iter().enumerate() {
+            let input = &self.targets[source];
+            let output = &self.targets[1 - source];
+            if step + 1 == steps.len() {
+                output.unbind(gl);
+            } else {
+                output.bind(gl);
+            }
+
+            pass.shader.bind(gl);
+            gl.active_texture(GL::TEXTURE0);
+            gl.bind_texture(GL::TEXTURE_2D, input.texture().raw());
+            gl.uniform1i(pass.texture.as_ref(), 0);
+            gl.uniform2f(
+                pass.resolution.as_ref(),
+                input.width as f32,
+                input.height as f32,
+            );
+            pass.effect.set_uniforms(gl, &pass.shader, *index);
+
+            quad.setup(gl);
+            gl.draw_elements_with_i32(
+                GL::TRIANGLES,
+                quad.indices.len() as i32,
+                GL::UNSIGNED_INT,
+                0,
+            );
+            source = 1 - source;
+        }
+        gl.enable(GL::BLEND);
+
+        gl.active_texture(GL::TEXTURE0);
+        gl.bind_texture(GL::TEXTURE_2D, previous.as_ref());
+    }
+}
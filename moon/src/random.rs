@@ -0,0 +1,219 @@
+//! The [`Rng`] struct, a seedable random number generator.
+//!
+//! Every [`Random`](crate::Random) value can be generated from an [`Rng`].
+//! The [`Application`](crate::Application) owns the engine's [`Rng`] and passes it to [`Components`](crate::component::Component) every frame, while values generated without one come from a shared [`Rng`] seeded with [`seed_random`].
+
+use std::cell::RefCell;
+use std::f32::consts::TAU;
+
+use crate::math::Random;
+use crate::Vec2;
+
+/// Multiplier of the underlying linear congruential generator.
+const MULTIPLIER: u64 = 6364136223846793005;
+/// Default stream of an [`Rng`].
+const DEFAULT_STREAM: u64 = 1442695040888963407;
+
+thread_local! {
+    static SHARED_RNG: RefCell<Rng> = RefCell::new(Rng::default());
+}
+
+/// Seed the shared [`Rng`], so that the same sequence of values is produced every time.
+pub fn seed_random(seed: u64) {
+    with_rng(|rng| *rng = Rng::new_with_seed(seed));
+}
+
+/// Borrow the shared [`Rng`], used for values generated without one.
+///
+/// # Panics
+/// Panics if called again from inside the closure.
+pub fn with_rng<R>(f: impl FnOnce(&mut Rng) -> R) -> R {
+    SHARED_RNG.with(|rng| f(&mut rng.borrow_mut()))
+}
+
+/// A small, fast and seedable random number generator, using PCG32.
+///
+/// Two [`Rngs`](Rng) created with the same seed produce the same sequence of values, on every platform.
+///
+/// # Examples
+/// ```
+/// use moon_engine::random::Rng;
+/// use moon_engine::Vec2;
+///
+/// let mut rng = Rng::new_with_seed(42);
+///
+/// let value: f32 = rng.random();
+/// assert!((0.0..1.0).contains(&value));
+///
+/// let point = rng.point_in_circle(Vec2::zeros(), 2.0);
+/// assert!(point.norm() <= 2.0);
+///
+/// // The same seed always gives the same values
+/// assert_eq!(Rng::new_with_seed(7).next_u64(), Rng::new_with_seed(7).next_u64());
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Rng {
+    state: u64,
+    increment: u64,
+}
+
+impl Default for Rng {
+    fn default() -> Self {
+        Self::new_with_seed(0x853C_49E6_748F_EA9B)
+    }
+}
+
+impl Rng {
+    /// Create a new [`Rng`] with a given seed.
+    pub fn new_with_seed(seed: u64) -> Self {
+        Self::new_with_seed_and_stream(seed, DEFAULT_STREAM)
+    }
+
+    /// Create a new [`Rng`] with a given seed and stream.
+    ///
+    /// [`Rngs`](Rng) with the same seed but different streams produce unrelated sequences.
+    pub fn new_with_seed_and_stream(seed: u64, stream: u64) -> Self {
+        let mut rng = Self {
+            state: 0,
+            increment: (stream << 1) | 1,
+        };
+        rng.next_u32();
+        rng.state = rng.state.wrapping_add(seed);
+        rng.next_u32();
+        rng
+    }
+
+    /// Get a random [`u32`].
+    pub fn next_u32(&mut self) -> u32 {
+        let old = self.state;
+        self.state = old.wrapping_mul(MULTIPLIER).wrapping_add(self.increment);
+        let xorshifted = (((old >> 18) ^ old) >> 27) as u32;
+        xorshifted.rotate_right((old >> 59) as u32)
+    }
+
+    /// Get a random [`u64`].
+    pub fn next_u64(&mut self) -> u64 {
+        (self.next_u32() as u64) << 32 | self.next_u32() as u64
+    }
+
+    /// Get a random [`f32`] between 0 (inclusive) and 1 (exclusive).
+    pub fn next_f32(&mut self) -> f32 {
+        // The top 24 bits fill the mantissa exactly
+        (self.next_u32() >> 8) as f32 / (1u32 << 24) as f32
+    }
+
+    /// Get a random [`u32`] below `max`, without bias.
+    ///
+    /// # Panics
+    /// Panics if `max` is zero.
+    pub fn below(&mut self, max: u32) -> u32 {
+        assert!(max > 0, "Cannot pick a value below zero.");
+        // Reject the values that would make lower results more likely
+        let threshold = max.wrapping_neg() % max;
+        loop {
+            let value = self.next_u32();
+            if value >= threshold {
+                return value % max;
+            }
+        }
+    }
+
+    /// Get a random [`u64`] below `max`, without bias.
+    ///
+    /// # Panics
+    /// Panics if `max` is zero.
+    pub fn below_u64(&mut self, max: u64) -> u64 {
+        assert!(max > 0, "Cannot pick a value below zero.");
+        let threshold = max.wrapping_neg() % max;
+        loop {
+            let value = self.next_u64();
+            if value >= threshold {
+                return value % max;
+            }
+        }
+    }
+
+    /// Get a random index into a slice of a given length.
+    ///
+    /// Lengths that fit in a [`u32`] use a single [`u32`], so the same indices are picked on every platform.
+    ///
+    /// # Panics
+    /// Panics if `len` is zero.
+    pub fn index(&mut self, len: usize) -> usize {
+        match u32::try_from(len) {
+            Ok(len) => self.below(len) as usize,
+            Err(_) => self.below_u64(len as u64) as usize,
+        }
+    }
+
+    /// Get `true` with a given probability, between 0 and 1.
+    pub fn chance(&mut self, probability: f32) -> bool {
+        self.next_f32() < probability
+    }
+
+    /// Get a random [`Random`] value.
+    pub fn random<T: Random>(&mut self) -> T {
+        T::random_with(self)
+    }
+
+    /// Get a random [`Random`] value in a range.
+    pub fn range<T: Random>(&mut self, min: T, max: T) -> T {
+        T::random_range_with(self, min, max)
+    }
+
+    /// Get a random direction as a unit [`Vec2`].
+    pub fn unit_vector(&mut self) -> Vec2 {
+        let angle = self.next_f32() * TAU;
+        Vec2::new(angle.cos(), angle.sin())
+    }
+
+    /// Get a random point inside a circle, uniformly distributed over its area.
+    pub fn point_in_circle(&mut self, center: Vec2, radius: f32) -> Vec2 {
+        center + self.unit_vector() * radius * self.next_f32().sqrt()
+    }
+
+    /// Get a random point inside a rectangle, given its minimum and maximum corners.
+    pub fn point_in_rect(&mut self, min: Vec2, max: Vec2) -> Vec2 {
+        Vec2::random_range_with(self, min, max)
+    }
+
+    /// Get a random element of a slice, or [`None`] if it is empty.
+    pub fn choose<'a, T>(&mut self, items: &'a [T]) -> Option<&'a T> {
+        if items.is_empty() {
+            None
+        } else {
+            items.get(self.index(items.len()))
+        }
+    }
+
+    /// Get a random index into a slice of weights, where each index is picked in proportion to its weight.
+    ///
+    /// Negative weights count as zero. Returns [`None`] if no weight is positive.
+    pub fn weighted_choice(&mut self, weights: &[f32]) -> Option<usize> {
+        let total: f32 = weights.iter().map(|weight| weight.max(0.0)).sum();
+        if total <= 0.0 {
+            return None;
+        }
+        let mut target = self.next_f32() * total;
+        let mut last = None;
+        for (index, weight) in weights.iter().enumerate() {
+            if *weight <= 0.0 {
+                continue;
+            }
+            if target < *weight {
+                return Some(index);
+            }
+            target -= weight;
+            last = Some(index);
+        }
+        // Rounding can leave a tiny remainder, which belongs to the last weight
+        last
+    }
+
+    /// Shuffle a slice in place.
+    pub fn shuffle<T>(&mut self, items: &mut [T]) {
+        for index in (1..items.len()).rev() {
+            items.swap(index, self.index(index + 1));
+        }
+    }
+}
@@ -1,17 +1,24 @@
 //! The [`Renderer`] annd [`Quad`] structs. Used for rendering.
 
+use std::cell::{Cell, RefCell};
 use std::collections::BTreeMap;
 use std::fmt;
 use std::rc::Rc;
 use web_sys::WebGlUniformLocation;
 
 use crate::component::Component;
+use crate::framebuffer::RenderTarget;
+use crate::light::{LightBatch, LightUniforms, Lighting};
+use crate::postprocess::{PostEffect, PostProcess};
+use crate::random::Rng;
+use crate::shadow::ShadowPass;
+use crate::transform::Transform2D;
 use crate::{gl, mesh, texture, Color32};
-use crate::{Camera, Shader, Transform, GL};
+use crate::{Camera, Mat3, Shader, Transform, Vec2, GL};
 
 use gl::Bind;
 use mesh::{Mesh, Vertex};
-use texture::{SubTexture, Texture};
+use texture::{SubTexture, Texture, TextureId, TextureSlots};
 
 /// Maximum [`Quad`]s in a single batch.
 pub const MAX_BATCH_QUADS: i32 = 1000;
@@ -19,38 +26,24 @@ const MAX_BATCH_VERTICES: i32 = MAX_BATCH_QUADS * 4;
 const MAX_BATCH_INDICES: i32 = MAX_BATCH_QUADS * 6;
 
 /// A [`Quad`] is a simple mesh definition with four [`Vertices`](Vertex).
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct Quad([Vertex; 4]);
 
+const UNIT_QUAD_POSITIONS: [[f32; 2]; 4] = [[-0.5, 0.5], [-0.5, -0.5], [0.5, -0.5], [0.5, 0.5]];
+const UNIT_QUAD_UVS: [[f32; 2]; 4] = [[0.0, 0.0], [0.0, 1.0], [1.0, 1.0], [1.0, 0.0]];
+// Rotated quads have always had their UVs flipped vertically, with the bottom-left corner at (0, 0)
+const ROTATED_QUAD_UVS: [[f32; 2]; 4] = [[0.0, 1.0], [0.0, 0.0], [1.0, 0.0], [1.0, 1.0]];
+
 impl Default for Quad {
     fn default() -> Self {
-        Self([
-            Vertex {
-                position: [-0.5, 0.5],
-                uv: [0.0, 0.0],
-                ..Default::default()
-            },
-            Vertex {
-                position: [-0.5, -0.5],
-                uv: [0.0, 1.0],
-                ..Default::default()
-            },
-            Vertex {
-                position: [0.5, -0.5],
-                uv: [1.0, 1.0],
-                ..Default::default()
-            },
-            Vertex {
-                position: [0.5, 0.5],
-                uv: [1.0, 0.0],
-                ..Default::default()
-            },
-        ])
+        Self::new_from_matrix_and_uvs(&Mat3::identity(), UNIT_QUAD_UVS, Color32::WHITE)
     }
 }
 
 impl Quad {
     /// Create a new [`Quad`] from a given position, rotation, size and color.
+    ///
+    /// Unlike other [`Quads`](Quad), the UVs start at the corner with the lowest Y coordinate, so textures are flipped vertically.
     pub fn new_from_position_and_rotation_and_size_and_color(
         pos_x: f32,
         pos_y: f32,
@@ -59,43 +52,10 @@ impl Quad {
         size_y: f32,
         color: Color32,
     ) -> Self {
-        let size_x = size_x / 2.0;
-        let size_y = size_y / 2.0;
-        let color = <[f32; 4]>::from(color);
-        let (sin_theta, cos_theta) = rotation.sin_cos();
-        let mut points = [
-            [-size_x, -size_y],
-            [-size_x, size_y],
-            [size_x, size_y],
-            [size_x, -size_y],
-        ];
-        for point in points.iter_mut() {
-            let (x, y) = (point[0], point[1]);
-            point[0] = pos_x + x * cos_theta - y * sin_theta;
-            point[1] = pos_y + x * sin_theta + y * cos_theta;
-        }
-        Self([
-            Vertex {
-                position: points[0],
-                uv: [0.0, 0.0],
-                color,
-            },
-            Vertex {
-                position: points[1],
-                uv: [0.0, 1.0],
-                color,
-            },
-            Vertex {
-                position: points[2],
-                uv: [1.0, 1.0],
-                color,
-            },
-            Vertex {
-                position: points[3],
-                uv: [1.0, 0.0],
-                color,
-            },
-        ])
+        let transform = Transform2D::new_with_position_and_rotation_and_scale(
+            pos_x, pos_y, rotation, size_x, size_y,
+        );
+        Self::new_from_matrix_and_uvs(&transform.matrix3(), ROTATED_QUAD_UVS, color)
     }
 
     /// Create a new [`Quad`] from a given position, size and color.
@@ -106,31 +66,12 @@ impl Quad {
         size_y: f32,
         color: Color32,
     ) -> Self {
-        let size_x = size_x / 2.0;
-        let size_y = size_y / 2.0;
-        let color = <[f32; 4]>::from(color);
-        Self([
-            Vertex {
-                position: [pos_x - size_x, pos_y + size_y],
-                uv: [0.0, 0.0],
-                color,
-            },
-            Vertex {
-                position: [pos_x - size_x, pos_y - size_y],
-                uv: [0.0, 1.0],
-                color,
-            },
-            Vertex {
-                position: [pos_x + size_x, pos_y - size_y],
-                uv: [1.0, 1.0],
-                color,
-            },
-            Vertex {
-                position: [pos_x + size_x, pos_y + size_y],
-                uv: [1.0, 0.0],
-                color,
-            },
-        ])
+        Self::new_from_transform2d_and_color(
+            &Transform2D::new_with_position_and_rotation_and_scale(
+                pos_x, pos_y, 0.0, size_x, size_y,
+            ),
+            color,
+        )
     }
 
     /// Create a new [`Quad`] from a given position and size.
@@ -146,52 +87,62 @@ impl Quad {
         size_y: f32,
         sprite: &SubTexture,
     ) -> Self {
-        let uv = sprite.get_uv_coords();
-        let size_x = size_x / 2.0;
-        let size_y = size_y / 2.0;
-        Self([
-            Vertex {
-                position: [pos_x - size_x, pos_y + size_y],
-                uv: uv[0],
-                ..Default::default()
-            },
-            Vertex {
-                position: [pos_x - size_x, pos_y - size_y],
-                uv: uv[1],
-                ..Default::default()
-            },
-            Vertex {
-                position: [pos_x + size_x, pos_y - size_y],
-                uv: uv[2],
-                ..Default::default()
-            },
-            Vertex {
-                position: [pos_x + size_x, pos_y + size_y],
-                uv: uv[3],
-                ..Default::default()
-            },
-        ])
+        Self::new_from_transform2d_and_sprite(
+            &Transform2D::new_with_position_and_rotation_and_scale(
+                pos_x, pos_y, 0.0, size_x, size_y,
+            ),
+            sprite,
+        )
     }
 
-    /// Create a new [`Quad`] using a given [`Transform`] for its position and scale.
+    /// Create a new [`Quad`] using a given [`Transform`] for its position, rotation and scale.
     pub fn new_from_transform(transform: Transform) -> Self {
-        Self::new_from_position_and_size(
-            transform.position.x,
-            transform.position.x,
-            transform.scale.x,
-            transform.scale.y,
-        )
+        Self::new_from_transform2d(&Transform2D::from(&transform))
     }
 
-    /// Create a new [`Quad`] using a given [`Transform`] for its position and scale, and a reference to [`SubTexture`].
+    /// Create a new [`Quad`] using a given [`Transform`] for its position, rotation and scale, and a reference to [`SubTexture`].
     pub fn new_from_transform_and_sprite(transform: Transform, sprite: &SubTexture) -> Self {
-        Self::new_from_position_and_size_and_sprite(
-            transform.position.x,
-            transform.position.x,
-            transform.scale.x,
-            transform.scale.y,
-            sprite,
-        )
+        Self::new_from_transform2d_and_sprite(&Transform2D::from(&transform), sprite)
+    }
+
+    /// Create a new [`Quad`] using a given [`Transform2D`].
+    ///
+    /// The [`Quad`] starts out with a side of 1, so the scale of the [`Transform2D`] is its size.
+    pub fn new_from_transform2d(transform: &Transform2D) -> Self {
+        Self::new_from_transform2d_and_color(transform, Color32::WHITE)
+    }
+
+    /// Create a new [`Quad`] using a given [`Transform2D`] and color.
+    pub fn new_from_transform2d_and_color(transform: &Transform2D, color: Color32) -> Self {
+        Self::new_from_matrix_and_uvs(&transform.matrix3(), UNIT_QUAD_UVS, color)
+    }
+
+    /// Create a new [`Quad`] using a given [`Transform2D`], and a reference to a [`SubTexture`].
+    pub fn new_from_transform2d_and_sprite(transform: &Transform2D, sprite: &SubTexture) -> Self {
+        Self::new_from_matrix_and_uvs(&transform.matrix3(), sprite.get_uv_coords(), Color32::WHITE)
+    }
+
+    /// Create a new [`Quad`] from its four [`Vertices`](Vertex).
+    ///
+    /// The vertices go around the [`Quad`], and are drawn as the triangles 0, 2, 1 and 0, 3, 2.
+    pub fn new_from_vertices(vertices: [Vertex; 4]) -> Self {
+        Self(vertices)
+    }
+
+    /// Transform the corners of a unit [`Quad`] by a [`Mat3`].
+    fn new_from_matrix_and_uvs(matrix: &Mat3, uvs: [[f32; 2]; 4], color: Color32) -> Self {
+        let color = <[f32; 4]>::from(color);
+        let mut vertices = [Vertex::default(); 4];
+        for (index, vertex) in vertices.iter_mut().enumerate() {
+            let [x, y] = UNIT_QUAD_POSITIONS[index];
+            let position = matrix.transform_point(&Vec2::new(x, y).into());
+            *vertex = Vertex {
+                position: [position.x, position.y],
+                uv: uvs[index],
+                color,
+            };
+        }
+        Self(vertices)
     }
 
     /// Get the [`Vertices`](Vertex) of the [`Quad`] as a [`Vec`].
@@ -200,23 +151,38 @@ impl Quad {
     }
 }
 
+/// Texture units kept free for creating textures, post-processing and shadows, which the [`Renderer`] does not assign.
+pub const RESERVED_TEXTURE_UNITS: u32 = 2;
+
 /// The [`Renderer`] is responsible for drawing on the screen. It handles the [`Camera`] and [`Shader`]s.
 pub struct Renderer {
     /// The [`WebGl2RenderingContext`](web_sys::WebGl2RenderingContext) used by the [`Renderer`].
     pub gl: GL,
     /// The [`Shader`] used by the [`Renderer`].
     pub program: Shader,
-    /// The [`Camera`] used by the [`Renderer`].
+    /// The main [`Camera`] used by the [`Renderer`].
     pub camera: Camera,
+    cameras: Vec<(&'static str, Camera)>,
     batches: Vec<Mesh>,
     /// [`Components`](Component) that can be added to the [`Renderer`].
     pub components: BTreeMap<&'static str, Box<dyn Component>>,
     textures: BTreeMap<&'static str, Rc<Texture>>,
+    texture_slots: RefCell<TextureSlots>,
+    current_texture: Cell<Option<TextureId>>,
+    current_normal_map: Cell<Option<TextureId>>,
+    render_targets: BTreeMap<&'static str, RenderTarget>,
+    /// The [`PostProcess`] stack, applied after drawing.
+    pub post_process: PostProcess,
     u_time: Option<WebGlUniformLocation>,
+    u_texture: Option<WebGlUniformLocation>,
     u_color: Option<WebGlUniformLocation>,
     u_model_matrix: Option<WebGlUniformLocation>,
     u_view_matrix: Option<WebGlUniformLocation>,
     u_projection_matrix: Option<WebGlUniformLocation>,
+    /// The [`Lighting`] used by the [`Renderer`].
+    pub lighting: Lighting,
+    light_uniforms: LightUniforms,
+    shadow_pass: Option<ShadowPass>,
 }
 
 impl Default for Renderer {
@@ -225,13 +191,18 @@ impl Default for Renderer {
         let program = Shader::new(&gl);
         Self {
             camera: Camera::default(),
+            cameras: Vec::new(),
             batches: Vec::new(),
             components: BTreeMap::new(),
             u_time: program.get_uniform_location(&gl, "uTime"),
+            u_texture: program.get_uniform_location(&gl, "uTex0"),
             u_color: program.get_uniform_location(&gl, "uColor"),
             u_model_matrix: program.get_uniform_location(&gl, "uModel"),
             u_view_matrix: program.get_uniform_location(&gl, "uView"),
             u_projection_matrix: program.get_uniform_location(&gl, "uProj"),
+            lighting: Lighting::new(),
+            shadow_pass: None,
+            light_uniforms: LightUniforms::new(&gl, &program),
             program,
             textures: {
                 let mut textues = BTreeMap::<&str, Rc<Texture>>::new();
@@ -240,6 +211,21 @@ impl Default for Renderer {
                 textues.insert("CHECKERBOARD", Rc::new(Texture::checkerboard(&gl)));
                 textues
             },
+            texture_slots: {
+                let units = gl
+                    .get_parameter(GL::MAX_TEXTURE_IMAGE_UNITS)
+                    .ok()
+                    .and_then(|units| units.as_f64())
+                    .unwrap_or(16.0) as u32;
+                RefCell::new(TextureSlots::new_with_units(
+                    RESERVED_TEXTURE_UNITS,
+                    units.saturating_sub(RESERVED_TEXTURE_UNITS).max(1),
+                ))
+            },
+            current_texture: Cell::new(None),
+            current_normal_map: Cell::new(None),
+            render_targets: BTreeMap::new(),
+            post_process: PostProcess::new(),
             gl,
         }
     }
@@ -251,13 +237,20 @@ impl fmt::Debug for Renderer {
             .field("gl", &self.gl)
             .field("program", &self.program)
             .field("camera", &self.camera)
+            .field("cameras", &self.cameras)
             .field("batches", &self.batches)
             .field("textures", &self.textures)
+            .field("texture_slots", &self.texture_slots)
+            .field("render_targets", &self.render_targets)
+            .field("post_process", &self.post_process)
             .field("u_time", &self.u_time)
+            .field("u_texture", &self.u_texture)
             .field("u_color", &self.u_color)
             .field("u_model_matrix", &self.u_model_matrix)
             .field("u_view_matrix", &self.u_view_matrix)
             .field("u_projection_matrix", &self.u_projection_matrix)
+            .field("lighting", &self.lighting)
+            .field("shadow_pass", &self.shadow_pass)
             .finish()
     }
 }
@@ -279,10 +272,12 @@ impl Renderer {
         Self {
             camera,
             u_time: program.get_uniform_location(&gl, "uTime"),
+            u_texture: program.get_uniform_location(&gl, "uTex0"),
             u_color: program.get_uniform_location(&gl, "uColor"),
             u_model_matrix: program.get_uniform_location(&gl, "uModel"),
             u_view_matrix: program.get_uniform_location(&gl, "uView"),
             u_projection_matrix: program.get_uniform_location(&gl, "uProj"),
+            light_uniforms: LightUniforms::new(&gl, &program),
             program,
             gl,
             ..Default::default()
@@ -299,16 +294,65 @@ impl Renderer {
         let gl = &self.gl;
 
         self.u_time = program.get_uniform_location(gl, "uTime");
+        self.u_texture = program.get_uniform_location(gl, "uTex0");
         self.u_color = program.get_uniform_location(gl, "uColor");
         self.u_model_matrix = program.get_uniform_location(gl, "uModel");
         self.u_view_matrix = program.get_uniform_location(gl, "uView");
         self.u_projection_matrix = program.get_uniform_location(gl, "uProj");
+        self.light_uniforms = LightUniforms::new(gl, &program);
         self.program = program;
     }
 
+    /// Add another [`Camera`] to the [`Renderer`], that can be retreived later via a string slice.
+    ///
+    /// Every [`Camera`] is drawn after the main one, in the order they were added.
+    pub fn add_camera(&mut self, key: &'static str, mut camera: Camera) {
+        let size = self.camera.screen_size();
+        camera.set_width_and_height(size.x, size.y);
+        self.remove_camera(key);
+        self.cameras.push((key, camera));
+    }
+
+    /// Remove a [`Camera`] that was added, returning it if it was found.
+    pub fn remove_camera(&mut self, key: &str) -> Option<Camera> {
+        let index = self.cameras.iter().position(|(name, _)| *name == key)?;
+        Some(self.cameras.remove(index).1)
+    }
+
+    /// Get a [`Camera`] that was added.
+    pub fn get_camera(&self, key: &str) -> Option<&Camera> {
+        self.cameras
+            .iter()
+            .find(|(name, _)| *name == key)
+            .map(|(_, camera)| camera)
+    }
+
+    /// Get a [`Camera`] that was added, mutably.
+    pub fn get_mut_camera(&mut self, key: &str) -> Option<&mut Camera> {
+        self.cameras
+            .iter_mut()
+            .find(|(name, _)| *name == key)
+            .map(|(_, camera)| camera)
+    }
+
+    /// Get the [`Camera`] drawn on top at a position in screen co-ordinates.
+    ///
+    /// This is the last added [`Camera`] whose [`Viewport`](crate::camera::Viewport) contains the position, or the main [`Camera`] if there are none.
+    pub fn camera_at(&self, screen_x: f32, screen_y: f32) -> &Camera {
+        self.cameras
+            .iter()
+            .rev()
+            .map(|(_, camera)| camera)
+            .find(|camera| camera.contains_screen_point(screen_x, screen_y))
+            .unwrap_or(&self.camera)
+    }
+
     /// Handle screen resizes.
     pub fn resize(&mut self, width: f32, height: f32) {
         self.camera.set_width_and_height(width, height);
+        for (_, camera) in self.cameras.iter_mut() {
+            camera.set_width_and_height(width, height);
+        }
         self.gl.viewport(0, 0, width as i32, height as i32);
         self.gl.uniform_matrix4fv_with_f32_array(
             self.u_projection_matrix.as_ref(),
@@ -332,31 +376,85 @@ impl Renderer {
         gl.uniform_matrix4fv_with_f32_array(
             self.u_view_matrix.as_ref(),
             false,
-            self.camera.transform.matrix_slice(),
+            self.camera.view_matrix().as_slice(),
         );
         gl.uniform_matrix4fv_with_f32_array(
             self.u_projection_matrix.as_ref(),
             false,
             self.camera.projection(),
         );
+        self.light_uniforms.init(gl);
     }
 
     /// Add a [`Texture`] to the [`Renderer`].
     ///
     /// The renderer stores [`Texture`]s that can be retreived later, via a string slice.
-    pub fn add_texture(&mut self, key: &'static str, texture: Texture) {
-        self.textures.insert(key, Rc::new(texture));
+    ///
+    /// A shared [`Texture`], such as one loaded by an [`AssetServer`](crate::asset::AssetServer), can also be added.
+    pub fn add_texture(&mut self, key: &'static str, texture: impl Into<Rc<Texture>>) {
+        self.textures.insert(key, texture.into());
     }
 
     /// Use the requested [`Texture`].
     ///
-    /// Sets the currently bound [`Texture`] to the one that matches the key. If no such texture is found, a default MAGENTA one is found.
+    /// Binds the [`Texture`] that matches the key to a texture unit, and samples from it when drawing. If no such texture is found, a default MAGENTA one is used.
     pub fn use_texture(&self, key: &str) {
-        let gl = &self.gl;
-        self.textures
+        let texture = self
+            .textures
             .get(key)
-            .unwrap_or_else(|| self.textures.get("MAGENTA").unwrap())
-            .bind(gl);
+            .unwrap_or_else(|| self.textures.get("MAGENTA").unwrap());
+        let unit = self.bind_pinned_texture(texture, &self.current_texture);
+        self.gl.uniform1i(self.u_texture.as_ref(), unit as i32);
+    }
+
+    /// Bind a [`Texture`] to a texture unit, returning the unit.
+    ///
+    /// A [`Texture`] that is already bound keeps its unit. Otherwise it replaces the least recently used one.
+    pub fn bind_texture(&self, texture: &Texture) -> u32 {
+        let (unit, needs_binding) = self.texture_slots.borrow_mut().assign(texture.id());
+        if needs_binding {
+            let gl = &self.gl;
+            gl.active_texture(GL::TEXTURE0 + unit);
+            gl.bind_texture(GL::TEXTURE_2D, texture.raw());
+            gl.active_texture(GL::TEXTURE0);
+        }
+        unit
+    }
+
+    /// Bind a [`Texture`] that stays in use across draws, pinning its unit in place of the previous one.
+    ///
+    /// This keeps the unit a sampler uniform points at from being replaced by other [`Textures`](Texture).
+    fn bind_pinned_texture(&self, texture: &Texture, current: &Cell<Option<TextureId>>) -> u32 {
+        if let Some(previous) = current.replace(None) {
+            self.texture_slots.borrow_mut().unpin(previous);
+        }
+        let unit = self.bind_texture(texture);
+        self.texture_slots.borrow_mut().pin(texture.id());
+        current.set(Some(texture.id()));
+        unit
+    }
+
+    /// Get the texture unit the requested [`Texture`] is bound to, if any.
+    pub fn texture_unit(&self, key: &str) -> Option<u32> {
+        let texture = self.textures.get(key)?;
+        self.texture_slots.borrow().unit_of(texture.id())
+    }
+
+    /// Get the key of the [`Texture`] bound to each texture unit in use, for debugging.
+    ///
+    /// Textures that were bound without being added to the [`Renderer`] are left out.
+    pub fn bound_textures(&self) -> BTreeMap<u32, &'static str> {
+        self.texture_slots
+            .borrow()
+            .mapping()
+            .into_iter()
+            .filter_map(|(unit, id)| {
+                self.textures
+                    .iter()
+                    .find(|(_, texture)| texture.id() == id)
+                    .map(|(key, _)| (unit, *key))
+            })
+            .collect()
     }
 
     /// Get the requested [`Texture`], or MAGENTA if none is found.
@@ -368,6 +466,86 @@ impl Renderer {
         )
     }
 
+    /// Use the requested [`Texture`] as a normal map for lighting.
+    ///
+    /// The normal map is bound to a texture unit like any other [`Texture`], and is used until [`disable_normal_map`](Renderer::disable_normal_map) is called.
+    pub fn use_normal_map(&self, key: &str) {
+        if let Some(texture) = self.textures.get(key) {
+            let unit = self.bind_pinned_texture(texture, &self.current_normal_map);
+            self.light_uniforms.set_normal_map_unit(&self.gl, unit);
+            self.light_uniforms.set_use_normal_map(&self.gl, true);
+        }
+    }
+
+    /// Stop using a normal map for lighting.
+    pub fn disable_normal_map(&self) {
+        if let Some(previous) = self.current_normal_map.replace(None) {
+            self.texture_slots.borrow_mut().unpin(previous);
+        }
+        self.light_uniforms.set_use_normal_map(&self.gl, false);
+    }
+
+    /// Add a [`RenderTarget`] to the [`Renderer`].
+    ///
+    /// The color attachment of the [`RenderTarget`] is also added as a [`Texture`] with the same key, so it can be used with [`use_texture`](Renderer::use_texture).
+    pub fn add_render_target(&mut self, key: &'static str, target: RenderTarget) {
+        self.textures.insert(key, target.texture());
+        self.render_targets.insert(key, target);
+    }
+
+    /// Get the requested [`RenderTarget`], if it exists.
+    pub fn get_render_target(&self, key: &str) -> Option<&RenderTarget> {
+        self.render_targets.get(key)
+    }
+
+    /// Use the requested [`RenderTarget`].
+    ///
+    /// Subsequent draw calls will render into the [`RenderTarget`] that matches the key. If no such target is found, the canvas is used instead.
+    pub fn use_render_target(&self, key: &str) {
+        match self.render_targets.get(key) {
+            Some(target) => target.bind(&self.gl),
+            None => self.use_canvas(),
+        }
+    }
+
+    /// Stop using any [`RenderTarget`], and draw to the canvas again.
+    pub fn use_canvas(&self) {
+        let gl = &self.gl;
+        gl.bind_framebuffer(GL::FRAMEBUFFER, None);
+        gl.viewport(0, 0, gl.drawing_buffer_width(), gl.drawing_buffer_height());
+    }
+
+    /// Resize the requested [`RenderTarget`], and update its [`Texture`] to match.
+    pub fn resize_render_target(&mut self, key: &'static str, width: u32, height: u32) {
+        if let Some(target) = self.render_targets.get_mut(key) {
+            target.resize(&self.gl, width, height);
+            self.textures.insert(key, target.texture());
+        }
+    }
+
+    /// Add a [`PostEffect`] to the end of the [`PostProcess`] stack.
+    pub fn add_post_effect(&mut self, name: &'static str, effect: PostEffect) {
+        self.post_process.push(&self.gl, name, effect);
+    }
+
+    /// Begin rendering a frame that will be post-processed.
+    ///
+    /// This should be called before clearing the screen. If no [`PostEffect`] is enabled, this does nothing.
+    pub fn begin_post_process(&mut self) {
+        self.post_process.begin(&self.gl);
+    }
+
+    /// Apply the [`PostProcess`] stack, and draw the result to the canvas.
+    ///
+    /// This should be called after [`draw_components`](Renderer::draw_components).
+    pub fn apply_post_process(&mut self) {
+        if !self.post_process.is_active() {
+            return;
+        }
+        self.post_process.apply(&self.gl);
+        self.program.bind(&self.gl);
+    }
+
     /// Clear the batch queue and start a new batch.
     pub fn begin_draw(&mut self) {
         let gl = &self.gl;
@@ -443,23 +621,74 @@ impl Renderer {
     }
 
     /// Draw all batched geometry.
+    ///
+    /// If there are any [`Occluders`](crate::shadow::Occluder), it is drawn unlit, and lit along with the [`Components`](Component) by [`draw_components`](Renderer::draw_components).
     pub fn end_draw(&mut self) {
         let gl = &self.gl;
         gl.uniform_matrix4fv_with_f32_array(
             self.u_view_matrix.as_ref(),
             false,
-            self.camera.transform.matrix_slice(),
+            self.camera.view_matrix().as_slice(),
         );
         self.program.bind(gl);
-        for batch in self.batches.iter() {
-            batch.setup(gl);
-            gl.draw_elements_with_i32(
-                GL::TRIANGLES,
-                batch.indices.len() as i32,
-                GL::UNSIGNED_INT,
-                0,
-            );
+        self.draw_meshes_lit(&self.batches);
+    }
+
+    /// Draw [`Meshes`](Mesh) with every [`Light`](crate::light::Light) in the [`Lighting`].
+    ///
+    /// If there are more lights than fit in a single pass, the [`Meshes`](Mesh) are drawn again with additive blending.
+    /// If there are any [`Occluders`](crate::shadow::Occluder), the [`Meshes`](Mesh) are drawn unlit, and lit later by [`draw_shadows`](Renderer::draw_shadows).
+    fn draw_meshes_lit(&self, meshes: &[Mesh]) {
+        let gl = &self.gl;
+        let (batches, ambient) = if self.lighting.has_occluders() {
+            (vec![LightBatch::default()], Color32::WHITE)
+        } else {
+            (self.lighting.batches(), self.lighting.ambient)
+        };
+        for (pass, batch) in batches.iter().enumerate() {
+            if pass == 0 {
+                self.light_uniforms.set(gl, batch, ambient);
+            } else {
+                gl.blend_func(GL::SRC_ALPHA, GL::ONE);
+                self.light_uniforms.set(gl, batch, Color32::ZEROES);
+            }
+            for mesh in meshes {
+                if pass == 0 {
+                    mesh.setup(gl);
+                } else {
+                    mesh.bind(gl);
+                }
+                gl.draw_elements_with_i32(
+                    GL::TRIANGLES,
+                    mesh.indices.len() as i32,
+                    GL::UNSIGNED_INT,
+                    0,
+                );
+            }
         }
+        if batches.len() > 1 {
+            gl.blend_func(GL::SRC_ALPHA, GL::ONE_MINUS_SRC_ALPHA);
+        }
+    }
+
+    /// Draw shadowed [`Lights`](crate::light::Light) over everything drawn so far, if there are any [`Occluders`](crate::shadow::Occluder).
+    fn draw_shadows(&mut self, camera: Option<usize>) {
+        if !self.lighting.has_occluders() {
+            return;
+        }
+        let camera = match camera {
+            Some(index) => &self.cameras[index].1,
+            None => &self.camera,
+        };
+        let gl = &self.gl;
+        let shadow_pass = self.shadow_pass.get_or_insert_with(|| ShadowPass::new(gl));
+        shadow_pass.draw(
+            gl,
+            &self.lighting,
+            &camera.view_matrix(),
+            camera.projection(),
+        );
+        self.program.bind(gl);
     }
 
     /// Clear the screen with a given Color.
@@ -488,6 +717,13 @@ impl Renderer {
         }
     }
 
+    /// Update the [`Components`](Component) of the [`Renderer`], using an [`Rng`] for any random values.
+    pub fn update_components_with_rng(&mut self, delta_time: f32, rng: &mut Rng) {
+        for component in self.components.values_mut() {
+            component.update_with_rng(delta_time, rng)
+        }
+    }
+
     /// Get a [`Components`](Component) using a key, and ty to cast it to a given type.
     pub fn get_component<T: 'static + Component>(&self, key: &'static str) -> Result<&T, String> {
         self.components
@@ -512,13 +748,57 @@ impl Renderer {
     }
 
     /// Draw the [`Components`](Component) of the [`Renderer`].
+    ///
+    /// They are drawn through the main [`Camera`] first, followed by every other [`Camera`] in the order they were added.
     pub fn draw_components(&mut self) {
+        self.draw_components_with_camera(None);
+        for index in 0..self.cameras.len() {
+            self.draw_components_with_camera(Some(index));
+        }
+        let gl = &self.gl;
+        gl.viewport(0, 0, gl.drawing_buffer_width(), gl.drawing_buffer_height());
+    }
+
+    /// Get the main [`Camera`], or the [`Camera`] at an index in the list of other [`Cameras`](Camera).
+    fn camera_by_index(&self, index: Option<usize>) -> &Camera {
+        match index {
+            Some(index) => &self.cameras[index].1,
+            None => &self.camera,
+        }
+    }
+
+    /// Draw the [`Components`](Component) on the render layers of a [`Camera`], inside its [`Viewport`](crate::camera::Viewport).
+    fn draw_components_with_camera(&mut self, index: Option<usize>) {
         let gl = &self.gl;
+        let camera = self.camera_by_index(index);
+
+        let (x, y, width, height) = camera.viewport_rect();
+        gl.viewport(x, y, width, height);
+        if let Some(color) = camera.clear_color {
+            gl.enable(GL::SCISSOR_TEST);
+            gl.scissor(x, y, width, height);
+            gl.clear_color(color.r(), color.g(), color.b(), color.a());
+            gl.clear(GL::COLOR_BUFFER_BIT);
+            gl.disable(GL::SCISSOR_TEST);
+        }
+
+        gl.uniform_matrix4fv_with_f32_array(
+            self.u_view_matrix.as_ref(),
+            false,
+            camera.view_matrix().as_slice(),
+        );
+        gl.uniform_matrix4fv_with_f32_array(
+            self.u_projection_matrix.as_ref(),
+            false,
+            camera.projection(),
+        );
         let mut layers: Vec<Vec<Quad>> = self
             .components
             .values()
+            .filter(|component| camera.draws_layer(component.render_layer()))
             .filter_map(|component| component.get_quads())
             .collect();
+        let mut meshes = Vec::with_capacity(layers.len());
         for layer in layers.iter_mut() {
             let mut mesh = Mesh::new(
                 gl,
@@ -531,13 +811,9 @@ impl Renderer {
                 let mut indices = vec![last, last + 2, last + 1, last, last + 3, last + 2];
                 mesh.indices.append(&mut indices);
             }
-            mesh.setup(gl);
-            gl.draw_elements_with_i32(
-                GL::TRIANGLES,
-                mesh.indices.len() as i32,
-                GL::UNSIGNED_INT,
-                0,
-            );
+            meshes.push(mesh);
         }
+        self.draw_meshes_lit(&meshes);
+        self.draw_shadows(index);
     }
 }
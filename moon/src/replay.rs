@@ -0,0 +1,503 @@
+//! The [`InputRecording`] struct, used to record and replay the input fed into an [`Application`](crate::Application).
+
+use crate::gamepad::GamepadState;
+
+/// Identifies the start of a serialized [`InputRecording`].
+const MAGIC: &[u8; 4] = b"MOON";
+/// Version of the serialized format.
+const VERSION: u8 = 1;
+
+/// A single input event, as received from the browser.
+#[derive(Debug, Clone, PartialEq)]
+pub enum InputEvent {
+    /// A keyboard key was pressed or released.
+    Key {
+        /// Key code of the key.
+        key_code: u8,
+        /// Whether the key was pressed.
+        is_down: bool,
+    },
+    /// Text was typed.
+    Text(String),
+    /// The mouse moved, in screen co-ordinates.
+    MouseMove {
+        /// Horizontal position of the mouse.
+        mouse_x: i32,
+        /// Vertical position of the mouse.
+        mouse_y: i32,
+    },
+    /// A mouse button was pressed or released.
+    MouseButton {
+        /// Index of the mouse button.
+        button: u8,
+        /// Whether the button was pressed.
+        is_down: bool,
+    },
+    /// The mouse wheel moved.
+    MouseWheel {
+        /// Horizontal movement of the wheel.
+        delta_x: f32,
+        /// Vertical movement of the wheel.
+        delta_y: f32,
+    },
+    /// A touch point touched down.
+    TouchStart {
+        /// Identifier of the touch point.
+        id: i32,
+        /// Horizontal position of the touch point.
+        touch_x: f32,
+        /// Vertical position of the touch point.
+        touch_y: f32,
+    },
+    /// A touch point moved.
+    TouchMove {
+        /// Identifier of the touch point.
+        id: i32,
+        /// Horizontal position of the touch point.
+        touch_x: f32,
+        /// Vertical position of the touch point.
+        touch_y: f32,
+    },
+    /// A touch point was lifted.
+    TouchEnd {
+        /// Identifier of the touch point.
+        id: i32,
+        /// Horizontal position of the touch point.
+        touch_x: f32,
+        /// Vertical position of the touch point.
+        touch_y: f32,
+    },
+    /// A touch point was interrupted.
+    TouchCancel {
+        /// Identifier of the touch point.
+        id: i32,
+    },
+    /// The window was resized.
+    Resize {
+        /// New width of the window.
+        width: f32,
+        /// New height of the window.
+        height: f32,
+    },
+}
+
+/// The input of a single frame.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct RecordedFrame {
+    /// Time since the previous frame, in milliseconds.
+    pub delta_time: u32,
+    /// [`InputEvents`](InputEvent) received before the frame, in order.
+    pub events: Vec<InputEvent>,
+    /// Raw [`GamepadStates`](GamepadState) polled during the frame, if they changed since the previous frame.
+    pub gamepads: Option<Vec<GamepadState>>,
+}
+
+/// A recording of the input of a session, that can be replayed to reproduce it exactly.
+///
+/// Along with the input of every frame, the seed used for [`Random`](crate::Random) values is stored.
+///
+/// # Examples
+/// ```
+/// use moon_engine::replay::{InputEvent, InputRecording};
+///
+/// let mut recording = InputRecording::new(42);
+/// recording.push_event(InputEvent::Key { key_code: b'W', is_down: true });
+/// recording.end_frame(16, &[]);
+///
+/// let bytes = recording.to_bytes();
+/// assert_eq!(InputRecording::from_bytes(&bytes), Ok(recording));
+/// ```
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct InputRecording {
+    /// Seed used for [`Random`](crate::Random) values during the session.
+    pub seed: u64,
+    /// Every recorded frame, in order.
+    pub frames: Vec<RecordedFrame>,
+    pending: Vec<InputEvent>,
+    last_gamepads: Vec<GamepadState>,
+}
+
+impl InputRecording {
+    /// Create a new, empty [`InputRecording`] with a given seed.
+    pub fn new(seed: u64) -> Self {
+        Self {
+            seed,
+            ..Default::default()
+        }
+    }
+
+    /// Record an [`InputEvent`], which will be part of the next frame.
+    pub fn push_event(&mut self, event: InputEvent) {
+        self.pending.push(event);
+    }
+
+    /// Finish recording a frame, along with its delta time and the raw [`GamepadStates`](GamepadState) polled during it.
+    pub fn end_frame(&mut self, delta_time: u32, gamepads: &[GamepadState]) {
+        let gamepads = if gamepads == self.last_gamepads.as_slice() {
+            None
+        } else {
+            self.last_gamepads = gamepads.to_vec();
+            Some(gamepads.to_vec())
+        };
+        self.frames.push(RecordedFrame {
+            delta_time,
+            events: std::mem::take(&mut self.pending),
+            gamepads,
+        });
+    }
+
+    /// Serialize the [`InputRecording`] into a compact binary format.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut writer = Writer::default();
+        writer.bytes.extend_from_slice(MAGIC);
+        writer.u8(VERSION);
+        writer.u64(self.seed);
+        writer.u32(self.frames.len() as u32);
+        for frame in self.frames.iter() {
+            writer.u32(frame.delta_time);
+            writer.u32(frame.events.len() as u32);
+            for event in frame.events.iter() {
+                writer.event(event);
+            }
+            match &frame.gamepads {
+                Some(gamepads) => {
+                    writer.u8(1);
+                    writer.u32(gamepads.len() as u32);
+                    for gamepad in gamepads {
+                        writer.gamepad(gamepad);
+                    }
+                }
+                None => writer.u8(0),
+            }
+        }
+        writer.bytes
+    }
+
+    /// Deserialize an [`InputRecording`] from the format written by [`to_bytes`](InputRecording::to_bytes).
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, String> {
+        let mut reader = Reader { bytes, offset: 0 };
+        if reader.take(MAGIC.len())? != MAGIC {
+            return Err(String::from("Not an input recording."));
+        }
+        let version = reader.u8()?;
+        if version != VERSION {
+            return Err(format!("Unsupported recording version {}.", version));
+        }
+        let mut recording = Self::new(reader.u64()?);
+        let frame_count = reader.u32()?;
+        for _ in 0..frame_count {
+            let delta_time = reader.u32()?;
+            let event_count = reader.u32()?;
+            let events = (0..event_count)
+                .map(|_| reader.event())
+                .collect::<Result<Vec<InputEvent>, String>>()?;
+            let gamepads = match reader.u8()? {
+                0 => None,
+                _ => {
+                    let count = reader.u32()?;
+                    Some(
+                        (0..count)
+                            .map(|_| reader.gamepad())
+                            .collect::<Result<Vec<GamepadState>, String>>()?,
+                    )
+                }
+            };
+            if let Some(gamepads) = &gamepads {
+                recording.last_gamepads = gamepads.clone();
+            }
+            recording.frames.push(RecordedFrame {
+                delta_time,
+                events,
+                gamepads,
+            });
+        }
+        if reader.offset != bytes.len() {
+            return Err(String::from("Unexpected data after the last frame."));
+        }
+        Ok(recording)
+    }
+}
+
+/// Plays back the frames of an [`InputRecording`] in order.
+///
+/// Live input that arrives during playback is not applied, so that the session follows the recording exactly.
+#[derive(Debug, Clone)]
+pub struct InputPlayer {
+    recording: InputRecording,
+    frame: usize,
+    live_resize: Option<InputEvent>,
+}
+
+impl InputPlayer {
+    /// Create a new [`InputPlayer`], starting at the first frame of an [`InputRecording`].
+    pub fn new(recording: InputRecording) -> Self {
+        Self {
+            recording,
+            frame: 0,
+            live_resize: None,
+        }
+    }
+
+    /// Get the seed used for [`Random`](crate::Random) values during the recording.
+    pub fn seed(&self) -> u64 {
+        self.recording.seed
+    }
+
+    /// Get the next [`RecordedFrame`], or [`None`] when the recording has finished.
+    pub fn next_frame(&mut self) -> Option<&RecordedFrame> {
+        let frame = self.recording.frames.get(self.frame)?;
+        self.frame += 1;
+        Some(frame)
+    }
+
+    /// Hold on to a live [`InputEvent`] that arrived during playback, instead of applying it.
+    ///
+    /// Only the latest [`Resize`](InputEvent::Resize) is kept, so the real size can be restored once playback has finished. All other live input is dropped.
+    pub fn hold_live_event(&mut self, event: InputEvent) {
+        if let InputEvent::Resize { .. } = event {
+            self.live_resize = Some(event);
+        }
+    }
+
+    /// Take the live [`InputEvents`](InputEvent) held during playback, to be applied once it has finished.
+    pub fn take_live_events(&mut self) -> Vec<InputEvent> {
+        self.live_resize.take().into_iter().collect()
+    }
+
+    /// Check whether every frame has been played.
+    pub fn is_finished(&self) -> bool {
+        self.frame >= self.recording.frames.len()
+    }
+}
+
+#[derive(Default)]
+struct Writer {
+    bytes: Vec<u8>,
+}
+
+impl Writer {
+    fn u8(&mut self, value: u8) {
+        self.bytes.push(value);
+    }
+
+    fn u32(&mut self, value: u32) {
+        self.bytes.extend_from_slice(&value.to_le_bytes());
+    }
+
+    fn i32(&mut self, value: i32) {
+        self.bytes.extend_from_slice(&value.to_le_bytes());
+    }
+
+    fn u64(&mut self, value: u64) {
+        self.bytes.extend_from_slice(&value.to_le_bytes());
+    }
+
+    fn f32(&mut self, value: f32) {
+        self.bytes.extend_from_slice(&value.to_le_bytes());
+    }
+
+    fn string(&mut self, value: &str) {
+        self.u32(value.len() as u32);
+        self.bytes.extend_from_slice(value.as_bytes());
+    }
+
+    fn event(&mut self, event: &InputEvent) {
+        match event {
+            InputEvent::Key { key_code, is_down } => {
+                self.u8(0);
+                self.u8(*key_code);
+                self.u8(*is_down as u8);
+            }
+            InputEvent::Text(text) => {
+                self.u8(1);
+                self.string(text);
+            }
+            InputEvent::MouseMove { mouse_x, mouse_y } => {
+                self.u8(2);
+                self.i32(*mouse_x);
+                self.i32(*mouse_y);
+            }
+            InputEvent::MouseButton { button, is_down } => {
+                self.u8(3);
+                self.u8(*button);
+                self.u8(*is_down as u8);
+            }
+            InputEvent::MouseWheel { delta_x, delta_y } => {
+                self.u8(4);
+                self.f32(*delta_x);
+                self.f32(*delta_y);
+            }
+            InputEvent::TouchStart {
+                id,
+                touch_x,
+                touch_y,
+            } => {
+                self.u8(5);
+                self.i32(*id);
+                self.f32(*touch_x);
+                self.f32(*touch_y);
+            }
+            InputEvent::TouchMove {
+                id,
+                touch_x,
+                touch_y,
+            } => {
+                self.u8(6);
+                self.i32(*id);
+                self.f32(*touch_x);
+                self.f32(*touch_y);
+            }
+            InputEvent::TouchEnd {
+                id,
+                touch_x,
+                touch_y,
+            } => {
+                self.u8(7);
+                self.i32(*id);
+                self.f32(*touch_x);
+                self.f32(*touch_y);
+            }
+            InputEvent::TouchCancel { id } => {
+                self.u8(8);
+                self.i32(*id);
+            }
+            InputEvent::Resize { width, height } => {
+                self.u8(9);
+                self.f32(*width);
+                self.f32(*height);
+            }
+        }
+    }
+
+    fn gamepad(&mut self, gamepad: &GamepadState) {
+        self.u32(gamepad.index);
+        self.string(&gamepad.id);
+        self.u8(gamepad.connected as u8);
+        self.u8(gamepad.standard_mapping as u8);
+        self.u32(gamepad.buttons.len() as u32);
+        for button in gamepad.buttons.iter() {
+            self.f32(*button);
+        }
+        self.u32(gamepad.axes.len() as u32);
+        for axis in gamepad.axes.iter() {
+            self.f32(*axis);
+        }
+    }
+}
+
+struct Reader<'a> {
+    bytes: &'a [u8],
+    offset: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, count: usize) -> Result<&'a [u8], String> {
+        let bytes = self
+            .offset
+            .checked_add(count)
+            .and_then(|end| self.bytes.get(self.offset..end))
+            .ok_or_else(|| String::from("Unexpected end of recording."))?;
+        self.offset += count;
+        Ok(bytes)
+    }
+
+    fn array<const N: usize>(&mut self) -> Result<[u8; N], String> {
+        let mut array = [0; N];
+        array.copy_from_slice(self.take(N)?);
+        Ok(array)
+    }
+
+    fn u8(&mut self) -> Result<u8, String> {
+        Ok(self.take(1)?[0])
+    }
+
+    fn bool(&mut self) -> Result<bool, String> {
+        Ok(self.u8()? != 0)
+    }
+
+    fn u32(&mut self) -> Result<u32, String> {
+        Ok(u32::from_le_bytes(self.array()?))
+    }
+
+    fn i32(&mut self) -> Result<i32, String> {
+        Ok(i32::from_le_bytes(self.array()?))
+    }
+
+    fn u64(&mut self) -> Result<u64, String> {
+        Ok(u64::from_le_bytes(self.array()?))
+    }
+
+    fn f32(&mut self) -> Result<f32, String> {
+        Ok(f32::from_le_bytes(self.array()?))
+    }
+
+    fn string(&mut self) -> Result<String, String> {
+        let length = self.u32()? as usize;
+        String::from_utf8(self.take(length)?.to_vec())
+            .map_err(|_| String::from("Invalid text in recording."))
+    }
+
+    fn event(&mut self) -> Result<InputEvent, String> {
+        Ok(match self.u8()? {
+            0 => InputEvent::Key {
+                key_code: self.u8()?,
+                is_down: self.bool()?,
+            },
+            1 => InputEvent::Text(self.string()?),
+            2 => InputEvent::MouseMove {
+                mouse_x: self.i32()?,
+                mouse_y: self.i32()?,
+            },
+            3 => InputEvent::MouseButton {
+                button: self.u8()?,
+                is_down: self.bool()?,
+            },
+            4 => InputEvent::MouseWheel {
+                delta_x: self.f32()?,
+                delta_y: self.f32()?,
+            },
+            5 => InputEvent::TouchStart {
+                id: self.i32()?,
+                touch_x: self.f32()?,
+                touch_y: self.f32()?,
+            },
+            6 => InputEvent::TouchMove {
+                id: self.i32()?,
+                touch_x: self.f32()?,
+                touch_y: self.f32()?,
+            },
+            7 => InputEvent::TouchEnd {
+                id: self.i32()?,
+                touch_x: self.f32()?,
+                touch_y: self.f32()?,
+            },
+            8 => InputEvent::TouchCancel { id: self.i32()? },
+            9 => InputEvent::Resize {
+                width: self.f32()?,
+                height: self.f32()?,
+            },
+            tag => return Err(format!("Unknown input event {}.", tag)),
+        })
+    }
+
+    fn gamepad(&mut self) -> Result<GamepadState, String> {
+        let index = self.u32()?;
+        let id = self.string()?;
+        let connected = self.bool()?;
+        let standard_mapping = self.bool()?;
+        let buttons = (0..self.u32()?)
+            .map(|_| self.f32())
+            .collect::<Result<Vec<f32>, String>>()?;
+        let axes = (0..self.u32()?)
+            .map(|_| self.f32())
+            .collect::<Result<Vec<f32>, String>>()?;
+        Ok(GamepadState {
+            index,
+            id,
+            connected,
+            standard_mapping,
+            buttons,
+            axes,
+        })
+    }
+}
@@ -0,0 +1,278 @@
+//! The [`SceneGraph`] struct, and the [`EntityId`] handles used to refer to its entities.
+
+use std::cell::Cell;
+
+use crate::transform::Transform2D;
+use crate::Mat3;
+
+/// A handle to an entity in a [`SceneGraph`].
+///
+/// Handles to removed entities are never reused, so they can be safely kept around.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct EntityId {
+    index: u32,
+    generation: u32,
+}
+
+#[derive(Debug)]
+struct Node {
+    local: Transform2D,
+    parent: Option<EntityId>,
+    children: Vec<EntityId>,
+    world: Cell<Mat3>,
+    dirty: Cell<bool>,
+}
+
+#[derive(Debug, Default)]
+struct Slot {
+    generation: u32,
+    node: Option<Node>,
+}
+
+/// A [`SceneGraph`] stores a hierarchy of entities, each with a local [`Transform2D`] relative to its parent.
+///
+/// World matrices are calculated when they are requested, and cached until the entity or one of its ancestors is changed.
+#[derive(Debug, Default)]
+pub struct SceneGraph {
+    slots: Vec<Slot>,
+    free: Vec<u32>,
+}
+
+impl SceneGraph {
+    /// Create a new, empty [`SceneGraph`].
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Add a new entity with no parent, returning its [`EntityId`].
+    pub fn spawn(&mut self, local: Transform2D) -> EntityId {
+        let node = Node {
+            local,
+            parent: None,
+            children: Vec::new(),
+            world: Cell::new(Mat3::identity()),
+            dirty: Cell::new(true),
+        };
+        match self.free.pop() {
+            Some(index) => {
+                let slot = &mut self.slots[index as usize];
+                slot.node = Some(node);
+                EntityId {
+                    index,
+                    generation: slot.generation,
+                }
+            }
+            None => {
+                self.slots.push(Slot {
+                    generation: 0,
+                    node: Some(node),
+                });
+                EntityId {
+                    index: self.slots.len() as u32 - 1,
+                    generation: 0,
+                }
+            }
+        }
+    }
+
+    /// Add a new entity as a child of another, returning its [`EntityId`].
+    ///
+    /// The local [`Transform2D`] is relative to the parent.
+    pub fn spawn_child(
+        &mut self,
+        parent: EntityId,
+        local: Transform2D,
+    ) -> Result<EntityId, String> {
+        if !self.contains(parent) {
+            return Err(String::from("Parent entity does not exist."));
+        }
+        let child = self.spawn(local);
+        self.attach(child, parent);
+        Ok(child)
+    }
+
+    /// Remove an entity and all of its descendants.
+    pub fn despawn(&mut self, id: EntityId) {
+        if !self.contains(id) {
+            return;
+        }
+        self.detach(id);
+        let mut stack = vec![id];
+        while let Some(id) = stack.pop() {
+            let slot = &mut self.slots[id.index as usize];
+            if let Some(node) = slot.node.take() {
+                stack.extend(node.children);
+                slot.generation = slot.generation.wrapping_add(1);
+                self.free.push(id.index);
+            }
+        }
+    }
+
+    /// Check whether an entity exists in the [`SceneGraph`].
+    pub fn contains(&self, id: EntityId) -> bool {
+        self.node(id).is_some()
+    }
+
+    /// Get the number of entities in the [`SceneGraph`].
+    pub fn len(&self) -> usize {
+        self.slots.len() - self.free.len()
+    }
+
+    /// Check whether the [`SceneGraph`] has no entities.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Get the parent of an entity, if it has one.
+    pub fn parent(&self, id: EntityId) -> Option<EntityId> {
+        self.node(id)?.parent
+    }
+
+    /// Get the children of an entity.
+    pub fn children(&self, id: EntityId) -> &[EntityId] {
+        self.node(id)
+            .map(|node| node.children.as_slice())
+            .unwrap_or(&[])
+    }
+
+    /// Get the local [`Transform2D`] of an entity, relative to its parent.
+    pub fn local(&self, id: EntityId) -> Option<&Transform2D> {
+        self.node(id).map(|node| &node.local)
+    }
+
+    /// Set the local [`Transform2D`] of an entity, relative to its parent.
+    pub fn set_local(&mut self, id: EntityId, local: Transform2D) {
+        if let Some(node) = self.node_mut(id) {
+            node.local = local;
+            self.mark_dirty(id);
+        }
+    }
+
+    /// Get the world [`Mat3`] of an entity, combining its local [`Transform2D`] with those of its ancestors.
+    pub fn world_matrix(&self, id: EntityId) -> Option<Mat3> {
+        let node = self.node(id)?;
+        if node.dirty.get() {
+            let parent = node
+                .parent
+                .and_then(|parent| self.world_matrix(parent))
+                .unwrap_or_else(Mat3::identity);
+            node.world.set(parent * node.local.matrix3());
+            node.dirty.set(false);
+        }
+        Some(node.world.get())
+    }
+
+    /// Get the world [`Transform2D`] of an entity.
+    ///
+    /// Any skew caused by non-uniform scaling of rotated children is lost. Use [`world_matrix`](SceneGraph::world_matrix) if it matters.
+    pub fn world(&self, id: EntityId) -> Option<Transform2D> {
+        let pivot = self.node(id)?.local.pivot;
+        self.world_matrix(id)
+            .map(|matrix| Transform2D::from_matrix3_with_pivot(&matrix, pivot))
+    }
+
+    /// Set the parent of an entity, keeping its local [`Transform2D`]. This moves it in the world.
+    ///
+    /// Passing [`None`] makes it a root entity. Fails if either entity does not exist, or if it would create a cycle.
+    pub fn set_parent(&mut self, id: EntityId, parent: Option<EntityId>) -> Result<(), String> {
+        self.check_parent(id, parent)?;
+        self.detach(id);
+        if let Some(parent) = parent {
+            self.attach(id, parent);
+        }
+        self.mark_dirty(id);
+        Ok(())
+    }
+
+    /// Set the parent of an entity, changing its local [`Transform2D`] so that it stays in the same place in the world.
+    ///
+    /// Passing [`None`] makes it a root entity. Fails if either entity does not exist, or if it would create a cycle.
+    pub fn reparent(&mut self, id: EntityId, parent: Option<EntityId>) -> Result<(), String> {
+        self.check_parent(id, parent)?;
+        let pivot = self
+            .node(id)
+            .map(|node| node.local.pivot)
+            .unwrap_or_default();
+        let world = self.world_matrix(id).unwrap_or_else(Mat3::identity);
+        let parent_world = parent
+            .and_then(|parent| self.world_matrix(parent))
+            .unwrap_or_else(Mat3::identity);
+        let inverse = parent_world
+            .try_inverse()
+            .ok_or_else(|| String::from("Parent transform cannot be inverted."))?;
+
+        self.set_parent(id, parent)?;
+        self.set_local(
+            id,
+            Transform2D::from_matrix3_with_pivot(&(inverse * world), pivot),
+        );
+        Ok(())
+    }
+
+    /// Get an iterator over the [`EntityIds`](EntityId) of every entity.
+    pub fn entities(&self) -> impl Iterator<Item = EntityId> + '_ {
+        self.slots.iter().enumerate().filter_map(|(index, slot)| {
+            slot.node.as_ref().map(|_| EntityId {
+                index: index as u32,
+                generation: slot.generation,
+            })
+        })
+    }
+
+    fn node(&self, id: EntityId) -> Option<&Node> {
+        self.slots
+            .get(id.index as usize)
+            .filter(|slot| slot.generation == id.generation)
+            .and_then(|slot| slot.node.as_ref())
+    }
+
+    fn node_mut(&mut self, id: EntityId) -> Option<&mut Node> {
+        self.slots
+            .get_mut(id.index as usize)
+            .filter(|slot| slot.generation == id.generation)
+            .and_then(|slot| slot.node.as_mut())
+    }
+
+    fn check_parent(&self, id: EntityId, parent: Option<EntityId>) -> Result<(), String> {
+        if !self.contains(id) {
+            return Err(String::from("Entity does not exist."));
+        }
+        let mut ancestor = parent;
+        while let Some(current) = ancestor {
+            if !self.contains(current) {
+                return Err(String::from("Parent entity does not exist."));
+            }
+            if current == id {
+                return Err(String::from("An entity cannot be its own ancestor."));
+            }
+            ancestor = self.parent(current);
+        }
+        Ok(())
+    }
+
+    fn attach(&mut self, id: EntityId, parent: EntityId) {
+        if let Some(node) = self.node_mut(parent) {
+            node.children.push(id);
+        }
+        if let Some(node) = self.node_mut(id) {
+            node.parent = Some(parent);
+        }
+    }
+
+    fn detach(&mut self, id: EntityId) {
+        let parent = self.node_mut(id).and_then(|node| node.parent.take());
+        if let Some(node) = parent.and_then(|parent| self.node_mut(parent)) {
+            node.children.retain(|child| *child != id);
+        }
+    }
+
+    fn mark_dirty(&self, id: EntityId) {
+        let mut stack = vec![id];
+        while let Some(id) = stack.pop() {
+            if let Some(node) = self.node(id) {
+                node.dirty.set(true);
+                stack.extend(node.children.iter().copied());
+            }
+        }
+    }
+}
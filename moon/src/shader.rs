@@ -98,6 +98,21 @@ impl Shader {
         Self { name, program }
     }
 
+    /// Create a new Shader with a custom Vertex and Fragment Shader.
+    pub fn new_with_vertex_and_fragment(
+        gl: &GL,
+        vertex_shader: WebGlShader,
+        fragment_shader: WebGlShader,
+        name: Option<&'static str>,
+    ) -> Self {
+        let name = name.unwrap_or("Custom Shader");
+
+        let program =
+            Shader::program_with_vertex_and_fragment(gl, &vertex_shader, &fragment_shader).ok();
+
+        Self { name, program }
+    }
+
     /// Create a fragment `WebGlShader`.
     pub fn create_fragment(gl: &GL, source: &str) -> Result<WebGlShader, String> {
         Self::create_with_type(gl, ShaderType::FRAGMENT, source)
@@ -0,0 +1,416 @@
+//! The [`Occluder`] trait, and functions for generating shadow geometry.
+
+use std::f32::consts::{SQRT_2, TAU};
+use std::fmt;
+
+use wasm_bindgen::JsCast;
+use web_sys::{WebGlBuffer, WebGlTexture, WebGlVertexArrayObject};
+
+use crate::collider::{Circle, Polygon, AABB};
+use crate::framebuffer::{bound_framebuffer, RenderTarget};
+use crate::gl::Bind;
+use crate::light::{Light, LightBatch, Lighting};
+use crate::mesh::Mesh;
+use crate::texture::Texture;
+use crate::{gl, Mat4, Point, Shader, GL};
+
+/// Number of segments used to approximate the outline of a [`Circle`].
+pub const CIRCLE_OCCLUDER_SEGMENTS: usize = 16;
+
+/// An [`Occluder`] blocks [`Lights`](crate::light::Light), casting shadows behind it.
+pub trait Occluder: fmt::Debug {
+    /// Get the outline of the [`Occluder`] as a list of [`Points`](Point), in counter-clockwise order.
+    fn outline(&self) -> Vec<Point>;
+}
+
+impl Occluder for AABB {
+    fn outline(&self) -> Vec<Point> {
+        vec![
+            self.min,
+            Point::new(self.max.x, self.min.y),
+            self.max,
+            Point::new(self.min.x, self.max.y),
+        ]
+    }
+}
+
+impl Occluder for Circle {
+    fn outline(&self) -> Vec<Point> {
+        (0..CIRCLE_OCCLUDER_SEGMENTS)
+            .map(|segment| {
+                let angle = segment as f32 / CIRCLE_OCCLUDER_SEGMENTS as f32 * TAU;
+                self.origin + Point::new(angle.cos(), angle.sin()) * self.radius
+            })
+            .collect()
+    }
+}
+
+impl Occluder for Polygon {
+    fn outline(&self) -> Vec<Point> {
+        if self.signed_area() < 0.0 {
+            self.points.iter().rev().copied().collect()
+        } else {
+            self.points.clone()
+        }
+    }
+}
+
+/// How the edges of shadows are drawn.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub enum ShadowMode {
+    /// Shadows with sharp edges.
+    #[default]
+    Hard,
+    /// Shadows with a penumbra, made by casting shadows from several points around each [`Light`](crate::light::Light).
+    Soft {
+        /// Radius of the area the [`Light`](crate::light::Light) is emitted from.
+        source_radius: f32,
+        /// Number of points to cast shadows from.
+        samples: u32,
+    },
+}
+
+/// Generate the shadow cast by an outline, as a list of triangles.
+///
+/// Every edge facing away from the light is projected away from it, until the far edge of its shadow is at least `range` from the light, so the [`Occluder`] itself is left lit.
+/// The outline should be in counter-clockwise order, as returned by [`Occluder::outline`].
+/// Edges in line with the light, including those touching it, have no area to shadow and are skipped.
+pub fn shadow_geometry(light: Point, range: f32, outline: &[Point]) -> Vec<Point> {
+    let count = outline.len();
+    let mut triangles = Vec::new();
+    if count < 2 {
+        return triangles;
+    }
+    for index in 0..count {
+        let (a, b) = (outline[index], outline[(index + 1) % count]);
+        let normal = Point::new(b.y - a.y, a.x - b.x);
+        // Distance from the light to the line through the edge
+        let distance = normal.dot(&(a - light)) / normal.norm();
+        if distance.is_nan() || distance <= 0.0 {
+            continue;
+        }
+        // Scale both ends by the same amount, so the far edge stays parallel to the edge at `range` or more
+        let scale = (range / distance).max(1.0);
+        if !scale.is_finite() {
+            continue;
+        }
+        let far_a = light + (a - light) * scale;
+        let far_b = light + (b - light) * scale;
+        triangles.extend([a, b, far_b, a, far_b, far_a]);
+    }
+    triangles
+}
+
+/// Get the points shadows are cast from, for a given [`ShadowMode`].
+///
+/// Soft shadows use points evenly spaced around the light's position.
+pub fn shadow_samples(light: Point, mode: ShadowMode) -> Vec<Point> {
+    match mode {
+        ShadowMode::Soft {
+            source_radius,
+            samples,
+        } if samples > 1 => (0..samples)
+            .map(|sample| {
+                let angle = sample as f32 / samples as f32 * TAU;
+                light + Point::new(angle.cos(), angle.sin()) * source_radius
+            })
+            .collect(),
+        _ => vec![light],
+    }
+}
+
+/// Get how far the shadows cast from a point must reach, to cover all of the [`light_bounds`] of a [`Light`].
+pub fn shadow_range(light: &Light, sample: Point) -> f32 {
+    light.radius * SQRT_2 + (sample - light.position).norm()
+}
+
+/// Get two triangles covering the square around a [`Light`] that it can reach.
+///
+/// Only this area of the light map is drawn into by the [`Light`], so everything outside of it is left to the ambient light.
+pub fn light_bounds(light: &Light) -> [Point; 6] {
+    let (x, y, radius) = (light.position.x, light.position.y, light.radius);
+    [
+        Point::new(x - radius, y - radius),
+        Point::new(x + radius, y - radius),
+        Point::new(x + radius, y + radius),
+        Point::new(x - radius, y - radius),
+        Point::new(x + radius, y + radius),
+        Point::new(x - radius, y + radius),
+    ]
+}
+
+/// Get how much of a [`Light`] reaches a point past a list of outlines, from `0.0` to `1.0`.
+///
+/// Each shadow sample the point is hidden from removes an equal share, and points outside the [`light_bounds`] are never reached.
+/// This matches the mask drawn on the GPU.
+pub fn light_visibility(
+    light: &Light,
+    mode: ShadowMode,
+    outlines: &[Vec<Point>],
+    point: Point,
+) -> f32 {
+    let inside_bounds = (point - light.position).abs().max() <= light.radius;
+    if !inside_bounds {
+        return 0.0;
+    }
+    let samples = shadow_samples(light.position, mode);
+    let hidden = samples
+        .iter()
+        .filter(|sample| {
+            outlines.iter().any(|outline| {
+                shadow_geometry(**sample, shadow_range(light, **sample), outline)
+                    .chunks(3)
+                    .any(|triangle| in_triangle(point, triangle))
+            })
+        })
+        .count();
+    1.0 - hidden as f32 / samples.len() as f32
+}
+
+fn in_triangle(point: Point, triangle: &[Point]) -> bool {
+    let side = |a: Point, b: Point| (b.x - a.x) * (point.y - a.y) - (b.y - a.y) * (point.x - a.x);
+    let sides = [
+        side(triangle[0], triangle[1]),
+        side(triangle[1], triangle[2]),
+        side(triangle[2], triangle[0]),
+    ];
+    sides.iter().all(|side| *side >= 0.0) || sides.iter().all(|side| *side <= 0.0)
+}
+
+/// Draws [`Lights`](crate::light::Light) into a light map with shadows cut out, and multiplies it onto the scene.
+#[derive(Debug)]
+pub(crate) struct ShadowPass {
+    light_map: Option<RenderTarget>,
+    mask: Option<RenderTarget>,
+    light_shader: Shader,
+    solid_shader: Shader,
+    copy_shader: Shader,
+    quad: Mesh,
+    triangles: TriangleStream,
+}
+
+/// A dynamic vertex buffer that triangles are streamed into, reused between draws.
+#[derive(Debug)]
+struct TriangleStream {
+    vao: Option<WebGlVertexArrayObject>,
+    vbo: Option<WebGlBuffer>,
+    capacity: usize,
+}
+
+impl Drop for TriangleStream {
+    fn drop(&mut self) {
+        let gl = gl::get_context();
+
+        gl.delete_buffer(self.vbo.as_ref());
+        gl.delete_vertex_array(self.vao.as_ref());
+    }
+}
+
+impl TriangleStream {
+    fn new(gl: &GL) -> Self {
+        let vao = gl.create_vertex_array();
+        let vbo = gl.create_buffer();
+        gl.bind_vertex_array(vao.as_ref());
+        gl.bind_buffer(GL::ARRAY_BUFFER, vbo.as_ref());
+        gl.vertex_attrib_pointer_with_i32(0, 2, GL::FLOAT, false, 2 * 4, 0);
+        gl.enable_vertex_attrib_array(0);
+        gl.bind_vertex_array(None);
+        Self {
+            vao,
+            vbo,
+            capacity: 0,
+        }
+    }
+
+    /// Upload a list of triangles into the buffer and draw them, growing the buffer if they do not fit.
+    fn draw(&mut self, gl: &GL, triangles: &[Point]) {
+        let positions: Vec<f32> = triangles
+            .iter()
+            .flat_map(|point| [point.x, point.y])
+            .collect();
+        let bytes = unsafe {
+            std::slice::from_raw_parts(
+                positions.as_ptr() as *const u8,
+                positions.len() * std::mem::size_of::<f32>(),
+            )
+        };
+
+        gl.bind_vertex_array(self.vao.as_ref());
+        gl.bind_buffer(GL::ARRAY_BUFFER, self.vbo.as_ref());
+        if triangles.len() > self.capacity {
+            self.capacity = triangles.len().next_power_of_two();
+            gl.buffer_data_with_i32(
+                GL::ARRAY_BUFFER,
+                (self.capacity * 2 * std::mem::size_of::<f32>()) as i32,
+                GL::DYNAMIC_DRAW,
+            );
+        }
+        gl.buffer_sub_data_with_i32_and_u8_array(GL::ARRAY_BUFFER, 0, bytes);
+        gl.draw_arrays(GL::TRIANGLES, 0, triangles.len() as i32);
+    }
+}
+
+impl ShadowPass {
+    /// Create a new [`ShadowPass`], compiling the [`Shaders`](Shader) it needs.
+    pub(crate) fn new(gl: &GL) -> Self {
+        let create = |vertex: &str, fragment: &str, name: &'static str| {
+            let vertex_shader =
+                Shader::create_vertex(gl, vertex).expect("Could not create Vertex Shader!");
+            let fragment_shader =
+                Shader::create_fragment(gl, fragment).expect("Could not create Fragment Shader!");
+            Shader::new_with_vertex_and_fragment(gl, vertex_shader, fragment_shader, Some(name))
+        };
+        let light_vertex = include_str!("../res/shader/light.vert.glsl");
+        let post_vertex = include_str!("../res/shader/post.vert.glsl");
+
+        Self {
+            light_map: None,
+            mask: None,
+            light_shader: create(
+                light_vertex,
+                include_str!("../res/shader/light.frag.glsl"),
+                "Light Shader",
+            ),
+            solid_shader: create(
+                light_vertex,
+                include_str!("../res/shader/solid.frag.glsl"),
+                "Shadow Shader",
+            ),
+            copy_shader: create(
+                post_vertex,
+                include_str!("../res/shader/copy.frag.glsl"),
+                "Copy Shader",
+            ),
+            quad: Mesh::quad_with_side(gl, 2.0),
+            triangles: TriangleStream::new(gl),
+        }
+    }
+
+    /// Draw the light map, and multiply it onto the currently bound framebuffer.
+    pub(crate) fn draw(&mut self, gl: &GL, lighting: &Lighting, view: &Mat4, projection: &[f32]) {
+        // Remember the current state, so that drawing can continue as normal afterwards
+        gl.active_texture(GL::TEXTURE0);
+        let previous_texture = gl
+            .get_parameter(GL::TEXTURE_BINDING_2D)
+            .ok()
+            .and_then(|texture| texture.dyn_into::<WebGlTexture>().ok());
+        let previous_framebuffer = bound_framebuffer(gl);
+        let viewport = gl
+            .get_parameter(GL::VIEWPORT)
+            .ok()
+            .and_then(|viewport| viewport.dyn_into::<js_sys::Int32Array>().ok())
+            .map(|viewport| viewport.to_vec())
+            .unwrap_or_else(|| vec![0, 0, gl.drawing_buffer_width(), gl.drawing_buffer_height()]);
+        let (width, height) = (viewport[2].max(1) as u32, viewport[3].max(1) as u32);
+
+        let light_map = self
+            .light_map
+            .get_or_insert_with(|| RenderTarget::new(gl, width, height));
+        light_map.resize(gl, width, height);
+        let mask = self
+            .mask
+            .get_or_insert_with(|| RenderTarget::new(gl, width, height));
+        mask.resize(gl, width, height);
+
+        // The light map starts out as the ambient light
+        let ambient = lighting.ambient * lighting.ambient.a();
+        light_map.bind(gl);
+        gl.clear_color(ambient.r(), ambient.g(), ambient.b(), 1.0);
+        gl.clear(GL::COLOR_BUFFER_BIT);
+
+        let outlines: Vec<Vec<Point>> = lighting
+            .occluders()
+            .map(|occluder| occluder.outline())
+            .collect();
+
+        for light in lighting.lights() {
+            // The mask starts out lit only where the light can reach, so the rest of it adds nothing to the light map
+            mask.bind(gl);
+            gl.clear_color(0.0, 0.0, 0.0, 1.0);
+            gl.clear(GL::COLOR_BUFFER_BIT);
+
+            let bounds = light_bounds(light);
+            self.solid_shader.bind(gl);
+            Self::set_view_projection(gl, &self.solid_shader, view, projection);
+            let color = self.solid_shader.get_uniform_location(gl, "uColor");
+            gl.uniform4f(color.as_ref(), 1.0, 1.0, 1.0, 1.0);
+            gl.blend_func(GL::ONE, GL::ZERO);
+            self.triangles.draw(gl, &bounds);
+
+            // Each shadow sample subtracts an equal share from the mask
+            let samples = shadow_samples(light.position, lighting.shadows);
+            let share = 1.0 / samples.len() as f32;
+            gl.uniform4f(color.as_ref(), share, share, share, 0.0);
+            gl.blend_equation(GL::FUNC_REVERSE_SUBTRACT);
+            gl.blend_func(GL::ONE, GL::ONE);
+            for sample in samples {
+                let triangles: Vec<Point> = outlines
+                    .iter()
+                    .flat_map(|outline| {
+                        shadow_geometry(sample, shadow_range(light, sample), outline)
+                    })
+                    .collect();
+                if !triangles.is_empty() {
+                    self.triangles.draw(gl, &triangles);
+                }
+            }
+            gl.blend_equation(GL::FUNC_ADD);
+
+            // Multiply the light by the mask
+            gl.blend_func(GL::DST_COLOR, GL::ZERO);
+            self.light_shader.bind(gl);
+            Self::set_view_projection(gl, &self.light_shader, view, projection);
+            let batch = LightBatch::from(light);
+            let uniform = |name: &str| self.light_shader.get_uniform_location(gl, name);
+            gl.uniform3fv_with_f32_array(uniform("uLightPosition").as_ref(), &batch.positions);
+            gl.uniform4fv_with_f32_array(uniform("uLightColor").as_ref(), &batch.colors);
+            gl.uniform4fv_with_f32_array(uniform("uLightParams").as_ref(), &batch.params);
+            gl.uniform2fv_with_f32_array(uniform("uLightDirection").as_ref(), &batch.directions);
+            self.triangles.draw(gl, &bounds);
+
+            // Add the masked light to the light map
+            light_map.bind(gl);
+            gl.blend_func(GL::ONE, GL::ONE);
+            self.copy_shader.bind(gl);
+            Self::draw_texture(gl, &self.copy_shader, &self.quad, &mask.texture());
+        }
+
+        gl.bind_framebuffer(GL::FRAMEBUFFER, previous_framebuffer.as_ref());
+        gl.viewport(viewport[0], viewport[1], viewport[2], viewport[3]);
+
+        // Multiply the scene by the light map
+        gl.blend_func(GL::DST_COLOR, GL::ZERO);
+        self.copy_shader.bind(gl);
+        Self::draw_texture(gl, &self.copy_shader, &self.quad, &light_map.texture());
+
+        gl.blend_func(GL::SRC_ALPHA, GL::ONE_MINUS_SRC_ALPHA);
+        gl.active_texture(GL::TEXTURE0);
+        gl.bind_texture(GL::TEXTURE_2D, previous_texture.as_ref());
+    }
+
+    fn set_view_projection(gl: &GL, shader: &Shader, view: &Mat4, projection: &[f32]) {
+        gl.uniform_matrix4fv_with_f32_array(
+            shader.get_uniform_location(gl, "uView").as_ref(),
+            false,
+            view.as_slice(),
+        );
+        gl.uniform_matrix4fv_with_f32_array(
+            shader.get_uniform_location(gl, "uProj").as_ref(),
+            false,
+            projection,
+        );
+    }
+
+    fn draw_texture(gl: &GL, shader: &Shader, quad: &Mesh, texture: &Texture) {
+        gl.active_texture(GL::TEXTURE0);
+        gl.bind_texture(GL::TEXTURE_2D, texture.raw());
+        gl.uniform1i(shader.get_uniform_location(gl, "uTex0").as_ref(), 0);
+        quad.setup(gl);
+        gl.draw_elements_with_i32(
+            GL::TRIANGLES,
+            quad.indices.len() as i32,
+            GL::UNSIGNED_INT,
+            0,
+        );
+    }
+}
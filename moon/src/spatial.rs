@@ -0,0 +1,166 @@
+//! Positional audio, with the [`Emitter`] and [`Listener`] structs.
+//!
+//! Sounds played by an [`Emitter`] get quieter with distance from the [`Listener`], and are panned towards the side they are on.
+
+use crate::camera::Camera;
+use crate::transform::Transform2D;
+use crate::Vec2;
+
+/// How the volume of an [`Emitter`] falls off with distance, matching the Web Audio distance models.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Rolloff {
+    /// Falls off in a straight line, reaching silence at the maximum distance.
+    Linear,
+    /// Falls off inversely with distance, scaled by a rolloff factor.
+    Inverse(f32),
+    /// Falls off exponentially with distance, using a rolloff factor as the exponent.
+    Exponential(f32),
+}
+
+impl Default for Rolloff {
+    fn default() -> Self {
+        Rolloff::Inverse(1.0)
+    }
+}
+
+impl Rolloff {
+    /// Get the volume of a sound at a distance, between 0 and 1.
+    ///
+    /// Sounds closer than the reference distance are at full volume, and sounds further than the maximum distance are silent.
+    pub fn attenuation(&self, distance: f32, reference_distance: f32, max_distance: f32) -> f32 {
+        if distance > max_distance {
+            return 0.0;
+        }
+        if distance <= reference_distance {
+            return 1.0;
+        }
+        match *self {
+            Rolloff::Linear => {
+                let range = max_distance - reference_distance;
+                1.0 - (distance - reference_distance) / range
+            }
+            Rolloff::Inverse(factor) => {
+                reference_distance / (reference_distance + factor * (distance - reference_distance))
+            }
+            Rolloff::Exponential(factor) => (distance / reference_distance).powf(-factor),
+        }
+    }
+}
+
+/// The point sounds are heard from, usually following the active [`Camera`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Listener {
+    /// Position of the [`Listener`] in the world.
+    pub position: Vec2,
+    /// Rotation of the [`Listener`], in radians.
+    pub rotation: f32,
+    /// Horizontal distance at which sounds are panned completely to one side.
+    pub pan_width: f32,
+}
+
+impl Default for Listener {
+    fn default() -> Self {
+        Self {
+            position: Vec2::zeros(),
+            rotation: 0.0,
+            pan_width: 10.0,
+        }
+    }
+}
+
+impl Listener {
+    /// Create a new [`Listener`] at a position.
+    pub fn new_with_position(x: f32, y: f32) -> Self {
+        Self {
+            position: Vec2::new(x, y),
+            ..Default::default()
+        }
+    }
+
+    /// Create a new [`Listener`] that hears what a [`Camera`] sees.
+    ///
+    /// Sounds at the left and right edges of the view are panned completely to that side.
+    pub fn new_from_camera(camera: &Camera) -> Self {
+        Self {
+            position: camera.position(),
+            rotation: camera.rotation(),
+            pan_width: camera.view_size().x / 2.0,
+        }
+    }
+
+    /// Create a new [`Listener`] at the position and rotation of a [`Transform2D`].
+    pub fn new_from_transform2d(transform: &Transform2D, pan_width: f32) -> Self {
+        Self {
+            position: transform.position,
+            rotation: transform.rotation,
+            pan_width,
+        }
+    }
+
+    /// Get the position of a point relative to the [`Listener`], taking its rotation into account.
+    pub fn relative_position(&self, position: Vec2) -> Vec2 {
+        let offset = position - self.position;
+        let (sin, cos) = (-self.rotation).sin_cos();
+        Vec2::new(
+            offset.x * cos - offset.y * sin,
+            offset.x * sin + offset.y * cos,
+        )
+    }
+}
+
+/// Settings for a sound played at a position in the world.
+///
+/// # Examples
+/// ```
+/// use moon_engine::spatial::{Emitter, Listener, Rolloff};
+/// use moon_engine::Vec2;
+///
+/// let emitter = Emitter {
+///     rolloff: Rolloff::Linear,
+///     ..Default::default()
+/// };
+/// let listener = Listener::default();
+///
+/// assert_eq!(emitter.gain(&listener, Vec2::new(0.5, 0.0)), 1.0);
+/// assert_eq!(emitter.gain(&listener, Vec2::new(100.0, 0.0)), 0.0);
+/// assert_eq!(emitter.pan(&listener, Vec2::new(-100.0, 0.0)), -1.0);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Emitter {
+    /// Distance within which the sound is at full volume.
+    pub reference_distance: f32,
+    /// Distance beyond which the sound is silent.
+    pub max_distance: f32,
+    /// How the volume falls off between the reference and maximum distances.
+    pub rolloff: Rolloff,
+}
+
+impl Default for Emitter {
+    fn default() -> Self {
+        Self {
+            reference_distance: 1.0,
+            max_distance: 20.0,
+            rolloff: Rolloff::default(),
+        }
+    }
+}
+
+impl Emitter {
+    /// Get the volume of a sound at a position, as heard by a [`Listener`].
+    pub fn gain(&self, listener: &Listener, position: Vec2) -> f32 {
+        let distance = (position - listener.position).norm();
+        self.rolloff
+            .attenuation(distance, self.reference_distance, self.max_distance)
+    }
+
+    /// Get the stereo pan of a sound at a position, as heard by a [`Listener`].
+    ///
+    /// The pan ranges from -1 for fully left to 1 for fully right.
+    pub fn pan(&self, listener: &Listener, position: Vec2) -> f32 {
+        if listener.pan_width <= 0.0 {
+            return 0.0;
+        }
+        let offset = listener.relative_position(position);
+        (offset.x / listener.pan_width).clamp(-1.0, 1.0)
+    }
+}
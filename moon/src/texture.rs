@@ -1,34 +1,407 @@
 //! The [`Texture`] and [`SubTexture`] structs.
 
+use std::cell::Cell;
 use std::rc::Rc;
+use std::sync::atomic::{AtomicU32, Ordering};
 
 use wasm_bindgen::JsCast;
 use web_sys::HtmlImageElement;
 use web_sys::WebGlTexture;
 
+use crate::framebuffer::{bound_framebuffer, read_framebuffer};
 use crate::gl::Bind;
+use crate::image::Image;
 use crate::Color32;
 use crate::{gl, GL};
 
+/// `TEXTURE_MAX_ANISOTROPY_EXT` from the `EXT_texture_filter_anisotropic` extension.
+const TEXTURE_MAX_ANISOTROPY: u32 = 0x84FE;
+/// `MAX_TEXTURE_MAX_ANISOTROPY_EXT` from the `EXT_texture_filter_anisotropic` extension.
+const MAX_TEXTURE_MAX_ANISOTROPY: u32 = 0x84FF;
+
+/// How a [`Texture`] is sampled between texels.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TextureFilter {
+    /// Use the nearest texel, keeping pixel art crisp.
+    Nearest,
+    /// Blend the nearest texels together.
+    Linear,
+}
+
+/// How a [`Texture`] is sampled outside of the 0 to 1 UV range.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TextureWrap {
+    /// Tile the [`Texture`].
+    Repeat,
+    /// Tile the [`Texture`], mirroring every other copy.
+    MirroredRepeat,
+    /// Stretch the texels at the edges.
+    ClampToEdge,
+}
+
+impl TextureWrap {
+    /// Get the matching WebGL wrap mode.
+    pub fn to_gl(self) -> u32 {
+        match self {
+            TextureWrap::Repeat => GL::REPEAT,
+            TextureWrap::MirroredRepeat => GL::MIRRORED_REPEAT,
+            TextureWrap::ClampToEdge => GL::CLAMP_TO_EDGE,
+        }
+    }
+}
+
+/// How the texels of a [`Texture`] are stored.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TextureFormat {
+    /// Four 8-bit channels.
+    Rgba8,
+    /// A single 8-bit channel, sampled as red.
+    R8,
+    /// Four 16-bit floating point channels, for HDR colors and data.
+    ///
+    /// Pixels are given as little-endian half floats. Rendering into these, and generating mipmaps, needs the `EXT_color_buffer_float` extension.
+    Rgba16F,
+}
+
+impl TextureFormat {
+    /// Get the sized internal format used to store the texels.
+    pub fn internal_format(self) -> u32 {
+        match self {
+            TextureFormat::Rgba8 => GL::RGBA8,
+            TextureFormat::R8 => GL::R8,
+            TextureFormat::Rgba16F => GL::RGBA16F,
+        }
+    }
+
+    /// Get the format of the pixels given when uploading.
+    pub fn format(self) -> u32 {
+        match self {
+            TextureFormat::R8 => GL::RED,
+            _ => GL::RGBA,
+        }
+    }
+
+    /// Get the type of each channel of the pixels given when uploading.
+    pub fn data_type(self) -> u32 {
+        match self {
+            TextureFormat::Rgba16F => GL::HALF_FLOAT,
+            _ => GL::UNSIGNED_BYTE,
+        }
+    }
+
+    /// Get the number of bytes each pixel takes when uploading.
+    pub fn bytes_per_pixel(self) -> usize {
+        match self {
+            TextureFormat::Rgba8 => 4,
+            TextureFormat::R8 => 1,
+            TextureFormat::Rgba16F => 8,
+        }
+    }
+}
+
+/// Settings for how a [`Texture`] is stored and sampled.
+///
+/// # Examples
+/// ```
+/// use moon_engine::texture::{TextureFilter, TextureOptions, TextureWrap};
+/// use moon_engine::gl::GL;
+///
+/// let options = TextureOptions {
+///     mipmaps: Some(TextureFilter::Linear),
+///     wrap_s: TextureWrap::ClampToEdge,
+///     ..TextureOptions::new_with_filter(TextureFilter::Linear)
+/// };
+///
+/// assert_eq!(options.min_filter_to_gl(), GL::LINEAR_MIPMAP_LINEAR);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TextureOptions {
+    /// Filter used when the [`Texture`] is drawn smaller than its size.
+    pub min_filter: TextureFilter,
+    /// Filter used when the [`Texture`] is drawn larger than its size.
+    pub mag_filter: TextureFilter,
+    /// Filter used between mipmap levels, or [`None`] to not use mipmaps.
+    pub mipmaps: Option<TextureFilter>,
+    /// Wrap mode along the horizontal axis.
+    pub wrap_s: TextureWrap,
+    /// Wrap mode along the vertical axis.
+    pub wrap_t: TextureWrap,
+    /// Maximum anisotropy used when sampling at an angle, where `1.0` turns it off.
+    ///
+    /// This is clamped to what the device supports, and ignored without the `EXT_texture_filter_anisotropic` extension.
+    pub anisotropy: f32,
+    /// How the texels are stored, which can not be changed after creation.
+    pub format: TextureFormat,
+    /// Whether an sRGB encoded [`Image`] is stored as `SRGB8_ALPHA8`, so it is converted to linear values when sampled.
+    ///
+    /// This is off by default, so colors are sampled exactly as they were authored.
+    pub srgb: bool,
+}
+
+impl Default for TextureOptions {
+    fn default() -> Self {
+        Self {
+            min_filter: TextureFilter::Nearest,
+            mag_filter: TextureFilter::Nearest,
+            mipmaps: None,
+            wrap_s: TextureWrap::Repeat,
+            wrap_t: TextureWrap::Repeat,
+            anisotropy: 1.0,
+            format: TextureFormat::Rgba8,
+            srgb: false,
+        }
+    }
+}
+
+impl TextureOptions {
+    /// Create new [`TextureOptions`] with the same minification and magnification filter.
+    pub fn new_with_filter(filter: TextureFilter) -> Self {
+        Self {
+            min_filter: filter,
+            mag_filter: filter,
+            ..Default::default()
+        }
+    }
+
+    /// Create new [`TextureOptions`] with the same wrap mode along both axes.
+    pub fn new_with_wrap(wrap: TextureWrap) -> Self {
+        Self {
+            wrap_s: wrap,
+            wrap_t: wrap,
+            ..Default::default()
+        }
+    }
+
+    /// Get the WebGL minification filter, combining the minification and mipmap filters.
+    pub fn min_filter_to_gl(&self) -> u32 {
+        match (self.min_filter, self.mipmaps) {
+            (TextureFilter::Nearest, None) => GL::NEAREST,
+            (TextureFilter::Linear, None) => GL::LINEAR,
+            (TextureFilter::Nearest, Some(TextureFilter::Nearest)) => GL::NEAREST_MIPMAP_NEAREST,
+            (TextureFilter::Nearest, Some(TextureFilter::Linear)) => GL::NEAREST_MIPMAP_LINEAR,
+            (TextureFilter::Linear, Some(TextureFilter::Nearest)) => GL::LINEAR_MIPMAP_NEAREST,
+            (TextureFilter::Linear, Some(TextureFilter::Linear)) => GL::LINEAR_MIPMAP_LINEAR,
+        }
+    }
+
+    /// Get the WebGL magnification filter.
+    pub fn mag_filter_to_gl(&self) -> u32 {
+        match self.mag_filter {
+            TextureFilter::Nearest => GL::NEAREST,
+            TextureFilter::Linear => GL::LINEAR,
+        }
+    }
+
+    /// Set the sampling parameters of the [`Texture`] bound to `TEXTURE_2D`.
+    fn apply(&self, gl: &GL) {
+        let min_filter = self.min_filter_to_gl();
+        gl.tex_parameteri(GL::TEXTURE_2D, GL::TEXTURE_MIN_FILTER, min_filter as i32);
+        let mag_filter = self.mag_filter_to_gl();
+        gl.tex_parameteri(GL::TEXTURE_2D, GL::TEXTURE_MAG_FILTER, mag_filter as i32);
+        gl.tex_parameteri(
+            GL::TEXTURE_2D,
+            GL::TEXTURE_WRAP_S,
+            self.wrap_s.to_gl() as i32,
+        );
+        gl.tex_parameteri(
+            GL::TEXTURE_2D,
+            GL::TEXTURE_WRAP_T,
+            self.wrap_t.to_gl() as i32,
+        );
+        if let Ok(Some(_)) = gl.get_extension("EXT_texture_filter_anisotropic") {
+            let max = gl
+                .get_parameter(MAX_TEXTURE_MAX_ANISOTROPY)
+                .ok()
+                .and_then(|max| max.as_f64())
+                .unwrap_or(1.0) as f32;
+            let anisotropy = self.anisotropy.clamp(1.0, max.max(1.0));
+            gl.tex_parameterf(GL::TEXTURE_2D, TEXTURE_MAX_ANISOTROPY, anisotropy);
+        }
+    }
+}
+
+/// Counter used to give every [`Texture`] a unique [`TextureId`].
+static NEXT_TEXTURE_ID: AtomicU32 = AtomicU32::new(0);
+
+/// A unique identifier for a [`Texture`], used to track which texture unit it is bound to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct TextureId(pub u32);
+
+/// A texture unit in use, when it was last used, and how many times it is pinned.
+#[derive(Debug, Clone, Copy)]
+struct SlotEntry {
+    texture: TextureId,
+    last_used: u64,
+    pins: u32,
+}
+
+/// Tracks which [`Texture`] is bound to each of a range of texture units.
+///
+/// When every unit is in use, the least recently used [`Texture`] that is not pinned is replaced.
+///
+/// # Examples
+/// ```
+/// use moon_engine::texture::{TextureId, TextureSlots};
+///
+/// let mut slots = TextureSlots::new_with_units(2, 2);
+///
+/// assert_eq!(slots.assign(TextureId(10)), (2, true));
+/// assert_eq!(slots.assign(TextureId(11)), (3, true));
+/// assert_eq!(slots.assign(TextureId(10)), (2, false));
+/// // Texture 11 was used least recently, so it is replaced
+/// assert_eq!(slots.assign(TextureId(12)), (3, true));
+///
+/// // Pinned textures are kept, even when used least recently
+/// slots.pin(TextureId(10));
+/// assert_eq!(slots.assign(TextureId(13)), (3, true));
+/// ```
+#[derive(Debug, Clone)]
+pub struct TextureSlots {
+    first_unit: u32,
+    units: Vec<Option<SlotEntry>>,
+    clock: u64,
+}
+
+impl TextureSlots {
+    /// Create new [`TextureSlots`], managing a number of texture units starting from the first one.
+    pub fn new_with_units(first_unit: u32, count: u32) -> Self {
+        assert!(count > 0);
+        Self {
+            first_unit,
+            units: vec![None; count as usize],
+            clock: 0,
+        }
+    }
+
+    /// Get the number of texture units that are managed.
+    pub fn capacity(&self) -> u32 {
+        self.units.len() as u32
+    }
+
+    /// Assign a texture unit to a [`Texture`], returning the unit and whether the [`Texture`] needs to be bound to it.
+    pub fn assign(&mut self, texture: TextureId) -> (u32, bool) {
+        self.clock += 1;
+        if let Some(index) = self.index_of(texture) {
+            if let Some(entry) = self.units[index].as_mut() {
+                entry.last_used = self.clock;
+            }
+            return (self.first_unit + index as u32, false);
+        }
+        // Use an empty unit if there is one, otherwise replace the least recently used.
+        // Pinned units are only replaced when every unit is pinned.
+        let index = self
+            .units
+            .iter()
+            .position(Option::is_none)
+            .unwrap_or_else(|| {
+                self.units
+                    .iter()
+                    .enumerate()
+                    .min_by_key(|(_, entry)| {
+                        entry.map_or((false, 0), |entry| (entry.pins > 0, entry.last_used))
+                    })
+                    .map_or(0, |(index, _)| index)
+            });
+        self.units[index] = Some(SlotEntry {
+            texture,
+            last_used: self.clock,
+            pins: 0,
+        });
+        (self.first_unit + index as u32, true)
+    }
+
+    /// Get the texture unit a [`Texture`] is bound to, if any.
+    pub fn unit_of(&self, texture: TextureId) -> Option<u32> {
+        self.index_of(texture)
+            .map(|index| self.first_unit + index as u32)
+    }
+
+    /// Pin an assigned [`Texture`], so its texture unit is not replaced while it is still in use.
+    ///
+    /// Each call should be matched by a call to [`unpin`](TextureSlots::unpin).
+    pub fn pin(&mut self, texture: TextureId) {
+        if let Some(entry) = self.entry_mut(texture) {
+            entry.pins += 1;
+        }
+    }
+
+    /// Undo a previous call to [`pin`](TextureSlots::pin).
+    pub fn unpin(&mut self, texture: TextureId) {
+        if let Some(entry) = self.entry_mut(texture) {
+            entry.pins = entry.pins.saturating_sub(1);
+        }
+    }
+
+    /// Check whether a [`Texture`] is pinned to its texture unit.
+    pub fn is_pinned(&self, texture: TextureId) -> bool {
+        self.index_of(texture)
+            .and_then(|index| self.units[index])
+            .is_some_and(|entry| entry.pins > 0)
+    }
+
+    /// Stop tracking a [`Texture`], freeing its texture unit.
+    pub fn release(&mut self, texture: TextureId) {
+        if let Some(index) = self.index_of(texture) {
+            self.units[index] = None;
+        }
+    }
+
+    /// Stop tracking every [`Texture`], so they are bound again when next used.
+    pub fn clear(&mut self) {
+        self.units.fill(None);
+    }
+
+    /// Get every texture unit in use, and the [`TextureId`] bound to it.
+    pub fn mapping(&self) -> Vec<(u32, TextureId)> {
+        self.units
+            .iter()
+            .enumerate()
+            .filter_map(|(index, entry)| {
+                entry.map(|entry| (self.first_unit + index as u32, entry.texture))
+            })
+            .collect()
+    }
+
+    fn entry_mut(&mut self, texture: TextureId) -> Option<&mut SlotEntry> {
+        let index = self.index_of(texture)?;
+        self.units[index].as_mut()
+    }
+
+    fn index_of(&self, texture: TextureId) -> Option<usize> {
+        self.units
+            .iter()
+            .position(|entry| entry.is_some_and(|entry| entry.texture == texture))
+    }
+}
+
 /// A [`Texture`] stores an Image that can be used while rendering, or to store data.
 #[derive(Debug)]
 pub struct Texture {
     texture: Option<WebGlTexture>,
+    id: TextureId,
+    options: Cell<TextureOptions>,
     /// Width of the [`Texture`].
     pub width: u32,
     /// Height of the [`Texture`].
     pub height: u32,
-    /// Slot the [`Texture`] will occupy.
+    /// Slot the [`Texture`] will occupy when bound with [`Bind`].
+    ///
+    /// Textures used through the [`Renderer`](crate::renderer::Renderer) are assigned texture units automatically instead.
     pub slot: u32,
+    /// Whether the color values have been multiplied by their alpha, and need a blend function of `ONE, ONE_MINUS_SRC_ALPHA`.
+    pub premultiplied: bool,
 }
 
 impl Default for Texture {
     fn default() -> Self {
         Self {
             texture: None,
+            id: TextureId(NEXT_TEXTURE_ID.fetch_add(1, Ordering::Relaxed)),
+            options: Cell::new(TextureOptions::default()),
             width: 1,
             height: 1,
             slot: 0,
+            premultiplied: false,
         }
     }
 }
@@ -46,6 +419,9 @@ impl Bind for Texture {
 
 impl Drop for Texture {
     fn drop(&mut self) {
+        if self.texture.is_none() {
+            return;
+        }
         let gl = gl::get_context();
 
         gl.delete_texture(self.texture.as_ref());
@@ -53,34 +429,46 @@ impl Drop for Texture {
 }
 
 impl Texture {
+    /// Get the underlying [`WebGlTexture`], if any.
+    pub(crate) fn raw(&self) -> Option<&WebGlTexture> {
+        self.texture.as_ref()
+    }
+
+    /// Get the unique [`TextureId`] of the [`Texture`].
+    pub fn id(&self) -> TextureId {
+        self.id
+    }
+
     /// Create a new [`Texture`] using an [`HtmlImageElement`].
     pub fn new(gl: &GL, image: &HtmlImageElement) -> Self {
+        Self::new_with_options(gl, image, TextureOptions::default())
+    }
+
+    /// Create a new [`Texture`] using an [`HtmlImageElement`] and [`TextureOptions`].
+    pub fn new_with_options(gl: &GL, image: &HtmlImageElement, options: TextureOptions) -> Self {
         let (width, height) = (image.width(), image.height());
 
-        let texture = gl.create_texture();
-        gl.active_texture(GL::TEXTURE0);
-        gl.bind_texture(GL::TEXTURE_2D, texture.as_ref());
-        gl.tex_parameteri(GL::TEXTURE_2D, GL::TEXTURE_MIN_FILTER, GL::NEAREST as i32);
-        gl.tex_parameteri(GL::TEXTURE_2D, GL::TEXTURE_MAG_FILTER, GL::NEAREST as i32);
-        gl.tex_parameteri(GL::TEXTURE_2D, GL::TEXTURE_WRAP_S, GL::REPEAT as i32);
-        gl.tex_parameteri(GL::TEXTURE_2D, GL::TEXTURE_WRAP_T, GL::REPEAT as i32);
+        let texture = Self::create(gl, &options);
         // Flip the Y-axis so the image displays the right way up
         gl.pixel_storei(GL::UNPACK_FLIP_Y_WEBGL, 1);
         gl.tex_image_2d_with_u32_and_u32_and_html_image_element(
             GL::TEXTURE_2D,
             0,
-            GL::RGBA as i32,
-            GL::RGBA,
-            GL::UNSIGNED_BYTE,
+            options.format.internal_format() as i32,
+            options.format.format(),
+            options.format.data_type(),
             image,
         )
         .expect("Failed to load texture");
-        gl.generate_mipmap(GL::TEXTURE_2D);
+        if options.mipmaps.is_some() {
+            gl.generate_mipmap(GL::TEXTURE_2D);
+        }
 
         Self {
             width,
             height,
             texture,
+            options: Cell::new(options),
             ..Default::default()
         }
     }
@@ -103,36 +491,316 @@ impl Texture {
 
     /// Create a new [`Texture`] using a slice of [`u8`]s.
     pub fn new_from_pixels(gl: &GL, width: u32, height: u32, pixels: &[u8]) -> Self {
-        assert!(pixels.len() == (width * height * 4) as usize);
-        let texture = gl.create_texture();
-        gl.active_texture(GL::TEXTURE0);
-        gl.bind_texture(GL::TEXTURE_2D, texture.as_ref());
-        gl.tex_parameteri(GL::TEXTURE_2D, GL::TEXTURE_MIN_FILTER, GL::NEAREST as i32);
-        gl.tex_parameteri(GL::TEXTURE_2D, GL::TEXTURE_MAG_FILTER, GL::NEAREST as i32);
-        gl.tex_parameteri(GL::TEXTURE_2D, GL::TEXTURE_WRAP_S, GL::REPEAT as i32);
-        gl.tex_parameteri(GL::TEXTURE_2D, GL::TEXTURE_WRAP_T, GL::REPEAT as i32);
-        gl.tex_image_2d_with_i32_and_i32_and_i32_and_format_and_type_and_opt_u8_array(
-            GL::TEXTURE_2D,
-            0,
-            GL::RGBA as i32,
-            width as i32,
-            height as i32,
-            0,
-            GL::RGBA,
-            GL::UNSIGNED_BYTE,
+        Self::new_from_pixels_and_options(gl, width, height, pixels, TextureOptions::default())
+    }
+
+    /// Create a new [`Texture`] using a slice of [`u8`]s in the format given by the [`TextureOptions`].
+    pub fn new_from_pixels_and_options(
+        gl: &GL,
+        width: u32,
+        height: u32,
+        pixels: &[u8],
+        options: TextureOptions,
+    ) -> Self {
+        assert!(
+            pixels.len() == width as usize * height as usize * options.format.bytes_per_pixel()
+        );
+        let texture = Self::create(gl, &options);
+        Self::allocate(
+            gl,
+            width,
+            height,
+            &options,
+            options.format.internal_format(),
             Some(pixels),
+        );
+
+        Self {
+            width,
+            height,
+            texture,
+            options: Cell::new(options),
+            ..Default::default()
+        }
+    }
+
+    /// Create a new [`Texture`] from a decoded [`Image`].
+    pub fn new_from_image(gl: &GL, image: &Image) -> Self {
+        Self::new_from_image_and_options(gl, image, TextureOptions::default())
+    }
+
+    /// Create a new [`Texture`] from a decoded [`Image`] and [`TextureOptions`].
+    ///
+    /// The [`Image`] is always RGBA, so the format of the [`TextureOptions`] is replaced with [`TextureFormat::Rgba8`].
+    /// sRGB images use an `SRGB8_ALPHA8` internal format if [`srgb`](TextureOptions::srgb) is set.
+    pub fn new_from_image_and_options(gl: &GL, image: &Image, options: TextureOptions) -> Self {
+        let options = TextureOptions {
+            format: TextureFormat::Rgba8,
+            ..options
+        };
+        let internal_format = if options.srgb && image.srgb {
+            GL::SRGB8_ALPHA8
+        } else {
+            GL::RGBA8
+        };
+        let texture = Self::create(gl, &options);
+        // Images are stored top row first, so flip them like an HtmlImageElement
+        gl.pixel_storei(GL::UNPACK_FLIP_Y_WEBGL, 1);
+        Self::allocate(
+            gl,
+            image.width,
+            image.height,
+            &options,
+            internal_format,
+            Some(&image.pixels),
+        );
+
+        Self {
+            width: image.width,
+            height: image.height,
+            texture,
+            options: Cell::new(options),
+            premultiplied: image.premultiplied,
+            ..Default::default()
+        }
+    }
+
+    /// Create a new [`Texture`] from an encoded PNG, QOI or BMP image.
+    pub fn new_from_bytes(gl: &GL, bytes: &[u8]) -> Result<Self, String> {
+        Ok(Self::new_from_image(gl, &Image::decode(bytes)?))
+    }
+
+    /// Create a new empty [`Texture`] with a given width and height.
+    ///
+    /// The contents of the [`Texture`] are left undefined, which is useful when it will be rendered into.
+    pub fn new_empty(gl: &GL, width: u32, height: u32) -> Self {
+        Self::new_empty_with_options(
+            gl,
+            width,
+            height,
+            TextureOptions::new_with_wrap(TextureWrap::ClampToEdge),
         )
-        .expect("Failed to generate texture");
-        gl.generate_mipmap(GL::TEXTURE_2D);
+    }
+
+    /// Create a new empty [`Texture`] with a given width, height and [`TextureOptions`].
+    pub fn new_empty_with_options(
+        gl: &GL,
+        width: u32,
+        height: u32,
+        options: TextureOptions,
+    ) -> Self {
+        let texture = Self::create(gl, &options);
+        Self::allocate(
+            gl,
+            width,
+            height,
+            &options,
+            options.format.internal_format(),
+            None,
+        );
 
         Self {
             width,
             height,
             texture,
+            options: Cell::new(options),
             ..Default::default()
         }
     }
 
+    /// Create a [`WebGlTexture`], bind it and apply the [`TextureOptions`].
+    fn create(gl: &GL, options: &TextureOptions) -> Option<WebGlTexture> {
+        let texture = gl.create_texture();
+        gl.active_texture(GL::TEXTURE0);
+        gl.bind_texture(GL::TEXTURE_2D, texture.as_ref());
+        options.apply(gl);
+        texture
+    }
+
+    /// Allocate storage for the bound [`WebGlTexture`], optionally filling it with pixels.
+    fn allocate(
+        gl: &GL,
+        width: u32,
+        height: u32,
+        options: &TextureOptions,
+        internal_format: u32,
+        pixels: Option<&[u8]>,
+    ) {
+        let format = options.format;
+        // Rows of single channel textures are not padded to four bytes
+        gl.pixel_storei(GL::UNPACK_ALIGNMENT, 1);
+        let result = match (format, pixels) {
+            (TextureFormat::Rgba16F, Some(pixels)) => gl
+                .tex_image_2d_with_i32_and_i32_and_i32_and_format_and_type_and_opt_array_buffer_view(
+                    GL::TEXTURE_2D,
+                    0,
+                    internal_format as i32,
+                    width as i32,
+                    height as i32,
+                    0,
+                    format.format(),
+                    format.data_type(),
+                    Some(&half_floats(pixels)),
+                ),
+            _ => gl.tex_image_2d_with_i32_and_i32_and_i32_and_format_and_type_and_opt_u8_array(
+                GL::TEXTURE_2D,
+                0,
+                internal_format as i32,
+                width as i32,
+                height as i32,
+                0,
+                format.format(),
+                format.data_type(),
+                pixels,
+            ),
+        };
+        result.expect("Failed to generate texture");
+        if options.mipmaps.is_some() {
+            gl.generate_mipmap(GL::TEXTURE_2D);
+        }
+    }
+
+    /// Get the [`TextureOptions`] of the [`Texture`].
+    pub fn options(&self) -> TextureOptions {
+        self.options.get()
+    }
+
+    /// Change the sampling [`TextureOptions`] of the [`Texture`].
+    ///
+    /// Mipmaps are generated if they were not used before. The [`TextureFormat`] can not be changed after creation.
+    pub fn set_options(&self, gl: &GL, options: TextureOptions) -> Result<(), String> {
+        let previous = self.options.get();
+        if options.format != previous.format {
+            return Err(format!(
+                "Can not change the format of a texture from {:?} to {:?}.",
+                previous.format, options.format
+            ));
+        }
+        gl.active_texture(GL::TEXTURE0);
+        gl.bind_texture(GL::TEXTURE_2D, self.texture.as_ref());
+        options.apply(gl);
+        if options.mipmaps.is_some() && previous.mipmaps.is_none() {
+            gl.generate_mipmap(GL::TEXTURE_2D);
+        }
+        self.options.set(options);
+        Ok(())
+    }
+
+    /// Replace a region of the [`Texture`] with new pixels, in the format given by its [`TextureOptions`].
+    ///
+    /// The region is measured in texels from the top-left corner, and the pixels are given top row first, like an [`Image`]. Mipmaps are regenerated if they are used.
+    pub fn update_region(
+        &self,
+        gl: &GL,
+        x: u32,
+        y: u32,
+        width: u32,
+        height: u32,
+        pixels: &[u8],
+    ) -> Result<(), String> {
+        self.check_region(x, y, width, height)?;
+        let format = self.options.get().format;
+        let expected = width as usize * height as usize * format.bytes_per_pixel();
+        if pixels.len() != expected {
+            return Err(format!(
+                "Expected {} bytes of pixels for a {}x{} region, found {}.",
+                expected,
+                width,
+                height,
+                pixels.len()
+            ));
+        }
+
+        gl.active_texture(GL::TEXTURE0);
+        gl.bind_texture(GL::TEXTURE_2D, self.texture.as_ref());
+        gl.pixel_storei(GL::UNPACK_FLIP_Y_WEBGL, 1);
+        gl.pixel_storei(GL::UNPACK_ALIGNMENT, 1);
+        let (x, y) = (x as i32, (self.height - y - height) as i32);
+        let result = match format {
+            TextureFormat::Rgba16F => gl
+                .tex_sub_image_2d_with_i32_and_i32_and_u32_and_type_and_opt_array_buffer_view(
+                    GL::TEXTURE_2D,
+                    0,
+                    x,
+                    y,
+                    width as i32,
+                    height as i32,
+                    format.format(),
+                    format.data_type(),
+                    Some(&half_floats(pixels)),
+                ),
+            _ => gl.tex_sub_image_2d_with_i32_and_i32_and_u32_and_type_and_opt_u8_array(
+                GL::TEXTURE_2D,
+                0,
+                x,
+                y,
+                width as i32,
+                height as i32,
+                format.format(),
+                format.data_type(),
+                Some(pixels),
+            ),
+        };
+        result.map_err(|error| format!("Failed to update texture: {:?}", error))?;
+        if self.options.get().mipmaps.is_some() {
+            gl.generate_mipmap(GL::TEXTURE_2D);
+        }
+        Ok(())
+    }
+
+    /// Replace every pixel of the [`Texture`].
+    pub fn update(&self, gl: &GL, pixels: &[u8]) -> Result<(), String> {
+        self.update_region(gl, 0, 0, self.width, self.height, pixels)
+    }
+
+    /// Read every pixel of the [`Texture`] back as RGBA values, top row first.
+    pub fn read_pixels(&self, gl: &GL) -> Result<Vec<u8>, String> {
+        self.read_region(gl, 0, 0, self.width, self.height)
+    }
+
+    /// Read a region of the [`Texture`] back as RGBA values, top row first.
+    ///
+    /// Single channel textures are read with the channel in red. Floating point textures can not be read back.
+    pub fn read_region(
+        &self,
+        gl: &GL,
+        x: u32,
+        y: u32,
+        width: u32,
+        height: u32,
+    ) -> Result<Vec<u8>, String> {
+        self.check_region(x, y, width, height)?;
+        if self.options.get().format == TextureFormat::Rgba16F {
+            return Err(String::from("Can not read back floating point textures."));
+        }
+        // Textures can only be read through a framebuffer they are attached to
+        let previous = bound_framebuffer(gl);
+        let framebuffer = gl.create_framebuffer();
+        gl.bind_framebuffer(GL::FRAMEBUFFER, framebuffer.as_ref());
+        gl.framebuffer_texture_2d(
+            GL::FRAMEBUFFER,
+            GL::COLOR_ATTACHMENT0,
+            GL::TEXTURE_2D,
+            self.texture.as_ref(),
+            0,
+        );
+        let pixels = read_framebuffer(gl, framebuffer.as_ref(), x, y, width, height, self.height);
+        gl.bind_framebuffer(GL::FRAMEBUFFER, previous.as_ref());
+        gl.delete_framebuffer(framebuffer.as_ref());
+        pixels
+    }
+
+    /// Check that a region lies within the [`Texture`].
+    fn check_region(&self, x: u32, y: u32, width: u32, height: u32) -> Result<(), String> {
+        if x.saturating_add(width) > self.width || y.saturating_add(height) > self.height {
+            return Err(format!(
+                "Region {}x{} at ({}, {}) is outside of a {}x{} texture.",
+                width, height, x, y, self.width, self.height
+            ));
+        }
+        Ok(())
+    }
+
     /// A colored [`Texture`].
     ///
     /// Create a single pixel sized [`Texture`] with the specified [`Color32`].
@@ -168,6 +836,15 @@ impl Texture {
     }
 }
 
+/// Convert little-endian half floats into a typed array that WebGL can upload.
+fn half_floats(pixels: &[u8]) -> js_sys::Uint16Array {
+    let halves: Vec<u16> = pixels
+        .chunks_exact(2)
+        .map(|half| u16::from_le_bytes([half[0], half[1]]))
+        .collect();
+    js_sys::Uint16Array::from(&halves[..])
+}
+
 /// A [`SubTexture`] is a part of a full [`Texture`].
 ///
 /// It stores the UV co-ordinates of the part of the Texture it occupies.
@@ -0,0 +1,195 @@
+//! Generation of colliders from the solid tiles of a grid, used by [`Tilemaps`](crate::tilemap::Tilemap).
+//!
+//! Solid tiles are merged into as few rectangles as a greedy search finds, or traced into outlines around each solid region.
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use crate::tilemap::Tile;
+
+/// Decides which tiles of a layer are solid.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub enum Solidity {
+    /// Every tile is solid.
+    #[default]
+    AnyTile,
+    /// Only tiles with one of these IDs are solid.
+    Tiles(BTreeSet<u32>),
+}
+
+impl Solidity {
+    /// Check if a tile is solid. Empty tiles never are.
+    pub fn is_solid(&self, tile: Option<Tile>) -> bool {
+        match (self, tile) {
+            (_, None) => false,
+            (Solidity::AnyTile, Some(_)) => true,
+            (Solidity::Tiles(ids), Some(tile)) => ids.contains(&tile.id),
+        }
+    }
+}
+
+/// A rectangle of tiles, with the position of its top-left tile and its size in tiles.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct TileRect {
+    /// Column of the left-most tiles.
+    pub x: u32,
+    /// Row of the top-most tiles.
+    pub y: u32,
+    /// Number of columns.
+    pub width: u32,
+    /// Number of rows.
+    pub height: u32,
+}
+
+/// Merge the solid tiles of a grid into rectangles that cover each of them once.
+///
+/// Starting from the top-left, each rectangle is grown as far right as it can go, and then as far down as its whole width allows.
+///
+/// # Examples
+/// ```
+/// use moon_engine::tilecollider::{merge_rectangles, TileRect};
+///
+/// // An L shape is covered by two rectangles
+/// let solid = |x: u32, y: u32| x == 0 || y == 2;
+/// assert_eq!(
+///     merge_rectangles(3, 3, solid),
+///     [
+///         TileRect { x: 0, y: 0, width: 1, height: 3 },
+///         TileRect { x: 1, y: 2, width: 2, height: 1 },
+///     ]
+/// );
+/// ```
+pub fn merge_rectangles(
+    width: u32,
+    height: u32,
+    solid: impl Fn(u32, u32) -> bool,
+) -> Vec<TileRect> {
+    let mut covered = vec![false; width as usize * height as usize];
+    let index = |x: u32, y: u32| y as usize * width as usize + x as usize;
+    let free = |covered: &[bool], x: u32, y: u32| !covered[index(x, y)] && solid(x, y);
+
+    let mut rectangles = Vec::new();
+    for y in 0..height {
+        for x in 0..width {
+            if !free(&covered, x, y) {
+                continue;
+            }
+            let mut rect_width = 1;
+            while x + rect_width < width && free(&covered, x + rect_width, y) {
+                rect_width += 1;
+            }
+            let mut rect_height = 1;
+            while y + rect_height < height
+                && (x..x + rect_width).all(|column| free(&covered, column, y + rect_height))
+            {
+                rect_height += 1;
+            }
+            for row in y..y + rect_height {
+                for column in x..x + rect_width {
+                    covered[index(column, row)] = true;
+                }
+            }
+            rectangles.push(TileRect {
+                x,
+                y,
+                width: rect_width,
+                height: rect_height,
+            });
+        }
+    }
+    rectangles
+}
+
+/// Trace the outlines of the solid regions of a grid, as corners of tiles.
+///
+/// Straight runs of edges are joined, so each outline only has a point where it turns.
+/// Outer edges go around with a positive signed area, like a [`Polygon`](crate::collider::Polygon) in counter-clockwise order, and the edges of holes go the opposite way.
+/// Tiles that only touch at a corner are given separate outlines.
+pub fn trace_outlines(
+    width: u32,
+    height: u32,
+    solid: impl Fn(u32, u32) -> bool,
+) -> Vec<Vec<(i32, i32)>> {
+    let is_solid = |x: i32, y: i32| {
+        x >= 0 && y >= 0 && x < width as i32 && y < height as i32 && solid(x as u32, y as u32)
+    };
+
+    // Every edge between a solid and an empty tile, directed so that the solid tile is on its left
+    let mut edges: BTreeMap<(i32, i32), Vec<(i32, i32)>> = BTreeMap::new();
+    for y in 0..height as i32 {
+        for x in 0..width as i32 {
+            if !is_solid(x, y) {
+                continue;
+            }
+            let sides = [
+                ((0, -1), (x, y), (x + 1, y)),
+                ((1, 0), (x + 1, y), (x + 1, y + 1)),
+                ((0, 1), (x + 1, y + 1), (x, y + 1)),
+                ((-1, 0), (x, y + 1), (x, y)),
+            ];
+            for ((dx, dy), start, end) in sides {
+                if !is_solid(x + dx, y + dy) {
+                    edges.entry(start).or_default().push(end);
+                }
+            }
+        }
+    }
+
+    let mut outlines = Vec::new();
+    while let Some((&start, _)) = edges.iter().next() {
+        let mut corners = vec![start];
+        let mut current = start;
+        let mut direction = (0, 0);
+        loop {
+            let next = take_edge(&mut edges, current, direction);
+            direction = (next.0 - current.0, next.1 - current.1);
+            current = next;
+            if current == start {
+                break;
+            }
+            corners.push(current);
+        }
+        outlines.push(remove_collinear(corners));
+    }
+    outlines
+}
+
+/// Remove the edge to follow from a corner, preferring to turn towards the solid side where two outlines touch.
+fn take_edge(
+    edges: &mut BTreeMap<(i32, i32), Vec<(i32, i32)>>,
+    corner: (i32, i32),
+    direction: (i32, i32),
+) -> (i32, i32) {
+    let ends = edges
+        .get_mut(&corner)
+        .expect("Every edge of an outline is followed by another");
+    // The cross product is positive for edges that turn left
+    let turn = |end: &(i32, i32)| {
+        let next = (end.0 - corner.0, end.1 - corner.1);
+        direction.0 * next.1 - direction.1 * next.0
+    };
+    let (index, _) = ends
+        .iter()
+        .enumerate()
+        .max_by_key(|(_, end)| turn(end))
+        .expect("Corners without edges are removed");
+    let end = ends.swap_remove(index);
+    if ends.is_empty() {
+        edges.remove(&corner);
+    }
+    end
+}
+
+/// Remove the corners of an outline that are between two edges going the same way.
+fn remove_collinear(corners: Vec<(i32, i32)>) -> Vec<(i32, i32)> {
+    let count = corners.len();
+    (0..count)
+        .filter(|&index| {
+            let previous = corners[(index + count - 1) % count];
+            let (corner, next) = (corners[index], corners[(index + 1) % count]);
+            let incoming = (corner.0 - previous.0, corner.1 - previous.1);
+            let outgoing = (next.0 - corner.0, next.1 - corner.1);
+            incoming.0 * outgoing.1 - incoming.1 * outgoing.0 != 0
+        })
+        .map(|index| corners[index])
+        .collect()
+}
@@ -0,0 +1,1171 @@
+//! Import of maps made with the [Tiled](https://www.mapeditor.org) level editor, saved as JSON or XML (TMX).
+//!
+//! A [`TiledMap`] is parsed first, and then used to create [`Tilesets`](Tileset), [`Tilemaps`](Tilemap), and entities in a [`SceneGraph`].
+//! Only finite orthogonal maps with embedded tilesets are supported.
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::rc::Rc;
+
+use crate::asset::{Asset, AssetData, LoadContext};
+use crate::collider::{Circle, Collider, Polygon, AABB};
+use crate::inflate::{gzip_decompress_with_limit, zlib_decompress_with_limit};
+use crate::json::JsonValue;
+use crate::scene::{EntityId, SceneGraph};
+use crate::texture::{SubTexture, Texture};
+use crate::tilemap::{Tile, TileAnimation, TileFlags, Tilemap, Tileset};
+use crate::transform::Transform2D;
+use crate::xml::XmlElement;
+use crate::{Color32, Point, Vec2};
+
+/// Bit of a global tile ID that flips the tile horizontally.
+pub const FLIPPED_HORIZONTALLY: u32 = 0x8000_0000;
+/// Bit of a global tile ID that flips the tile vertically.
+pub const FLIPPED_VERTICALLY: u32 = 0x4000_0000;
+/// Bit of a global tile ID that flips the tile across its diagonal.
+pub const FLIPPED_DIAGONALLY: u32 = 0x2000_0000;
+/// Bits of a global tile ID that are used for flags, rather than the ID itself.
+const FLAG_BITS: u32 = 0xF000_0000;
+
+/// Split a global tile ID into the ID itself and its [`TileFlags`].
+///
+/// # Examples
+/// ```
+/// use moon_engine::tiled::{decode_gid, FLIPPED_HORIZONTALLY};
+/// use moon_engine::tilemap::TileFlags;
+///
+/// assert_eq!(decode_gid(FLIPPED_HORIZONTALLY | 7), (7, TileFlags::FLIP_HORIZONTAL));
+/// ```
+pub fn decode_gid(gid: u32) -> (u32, TileFlags) {
+    let mut flags = TileFlags::NONE;
+    for (bit, flag) in [
+        (FLIPPED_HORIZONTALLY, TileFlags::FLIP_HORIZONTAL),
+        (FLIPPED_VERTICALLY, TileFlags::FLIP_VERTICAL),
+        (FLIPPED_DIAGONALLY, TileFlags::FLIP_DIAGONAL),
+    ] {
+        if gid & bit != 0 {
+            flags = flags | flag;
+        }
+    }
+    (gid & !FLAG_BITS, flags)
+}
+
+/// The value of a custom property.
+#[derive(Debug, Clone, PartialEq)]
+pub enum PropertyValue {
+    /// A `bool` property.
+    Bool(bool),
+    /// An `int` property.
+    Int(i64),
+    /// A `float` property.
+    Float(f64),
+    /// A `string` property.
+    String(String),
+    /// A `color` property.
+    Color(Color32),
+    /// A `file` property, with the path of the file.
+    File(String),
+    /// An `object` property, with the ID of the object.
+    Object(u32),
+    /// A property of a custom class, with the values of its members.
+    Class(Properties),
+}
+
+impl PropertyValue {
+    /// Get the value as a [`bool`].
+    pub fn as_bool(&self) -> Option<bool> {
+        match self {
+            PropertyValue::Bool(value) => Some(*value),
+            _ => None,
+        }
+    }
+
+    /// Get the value as an [`f64`], if it is a number.
+    pub fn as_f64(&self) -> Option<f64> {
+        match self {
+            PropertyValue::Int(value) => Some(*value as f64),
+            PropertyValue::Float(value) => Some(*value),
+            _ => None,
+        }
+    }
+
+    /// Get the value as a string slice, if it is a string or a file.
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            PropertyValue::String(value) | PropertyValue::File(value) => Some(value),
+            _ => None,
+        }
+    }
+}
+
+/// Custom properties, by name.
+pub type Properties = BTreeMap<String, PropertyValue>;
+
+/// A tileset of a [`TiledMap`], made from a single sprite sheet.
+#[derive(Debug, Clone, Default)]
+pub struct TiledTileset {
+    /// Global ID of the first tile.
+    pub first_gid: u32,
+    /// Name of the tileset.
+    pub name: String,
+    /// Path of the sprite sheet, relative to the map.
+    pub image: String,
+    /// Width of each tile, in pixels.
+    pub tile_width: u32,
+    /// Height of each tile, in pixels.
+    pub tile_height: u32,
+    /// Number of tiles.
+    pub tile_count: u32,
+    /// Number of columns of tiles in the sprite sheet.
+    pub columns: u32,
+    /// Pixels around the edges of the sprite sheet.
+    pub margin: u32,
+    /// Pixels between neighbouring tiles.
+    pub spacing: u32,
+    /// Animations of tiles, by their IDs in the tileset.
+    pub animations: BTreeMap<u32, TileAnimation>,
+    /// Custom properties of tiles, by their IDs in the tileset.
+    pub tile_properties: BTreeMap<u32, Properties>,
+    /// Custom properties of the tileset.
+    pub properties: Properties,
+}
+
+impl TiledTileset {
+    /// Check if a global tile ID belongs to the tileset.
+    pub fn contains(&self, gid: u32) -> bool {
+        gid >= self.first_gid && gid - self.first_gid < self.tile_count
+    }
+
+    /// Get the IDs of the tiles with a `bool` property set to `true`, such as to make a [`Solidity`](crate::tilecollider::Solidity).
+    pub fn tiles_with_property(&self, name: &str) -> BTreeSet<u32> {
+        self.tile_properties
+            .iter()
+            .filter(|(_, properties)| {
+                properties.get(name).and_then(PropertyValue::as_bool) == Some(true)
+            })
+            .map(|(id, _)| *id)
+            .collect()
+    }
+}
+
+/// A layer of tiles in a [`TiledMap`].
+#[derive(Debug, Clone, Default)]
+pub struct TiledTileLayer {
+    /// Name of the layer.
+    pub name: String,
+    /// Whether the layer is visible, including the groups it is in.
+    pub visible: bool,
+    /// Tint color of the layer, with its opacity as the alpha.
+    pub color: Color32,
+    /// Offset of the layer, in pixels.
+    pub offset: Vec2,
+    /// Global tile IDs, row by row, with zero for empty tiles.
+    pub gids: Vec<u32>,
+    /// Custom properties of the layer.
+    pub properties: Properties,
+}
+
+/// The shape of a [`TiledObject`], with points relative to its position in pixels.
+#[derive(Debug, Clone, PartialEq)]
+pub enum TiledShape {
+    /// A rectangle, extending right and down from the position.
+    Rectangle,
+    /// An ellipse inside the rectangle of the object.
+    Ellipse,
+    /// A single point.
+    Point,
+    /// A closed polygon.
+    Polygon(Vec<Vec2>),
+    /// An open line made of segments.
+    Polyline(Vec<Vec2>),
+}
+
+/// An object in an object layer of a [`TiledMap`].
+#[derive(Debug, Clone)]
+pub struct TiledObject {
+    /// Unique ID of the object.
+    pub id: u32,
+    /// Name of the object.
+    pub name: String,
+    /// Class of the object, which is called its type in older versions of Tiled.
+    pub class: String,
+    /// Position of the object, in pixels.
+    ///
+    /// This is the top-left corner of most objects, and the bottom-left corner of tile objects.
+    pub position: Vec2,
+    /// Size of the object, in pixels.
+    pub size: Vec2,
+    /// Clockwise rotation of the object around its position, in degrees.
+    pub rotation: f32,
+    /// Whether the object is visible.
+    pub visible: bool,
+    /// Global tile ID drawn by a tile object, including its flip bits.
+    pub gid: Option<u32>,
+    /// Shape of the object.
+    pub shape: TiledShape,
+    /// Custom properties of the object.
+    pub properties: Properties,
+}
+
+/// A layer of objects in a [`TiledMap`].
+#[derive(Debug, Clone, Default)]
+pub struct TiledObjectLayer {
+    /// Name of the layer.
+    pub name: String,
+    /// Whether the layer is visible, including the groups it is in.
+    pub visible: bool,
+    /// Offset of the layer, in pixels.
+    pub offset: Vec2,
+    /// Objects in the layer.
+    pub objects: Vec<TiledObject>,
+    /// Custom properties of the layer.
+    pub properties: Properties,
+}
+
+/// A layer of a [`TiledMap`]. Group layers are flattened into the layers they contain.
+#[derive(Debug, Clone)]
+pub enum TiledLayer {
+    /// A layer of tiles.
+    Tiles(TiledTileLayer),
+    /// A layer of objects.
+    Objects(TiledObjectLayer),
+}
+
+/// The collider of a [`MapObject`], in world co-ordinates.
+#[derive(Debug, Clone)]
+pub enum ObjectCollider {
+    /// A point object.
+    Point(Point),
+    /// A rectangle or tile object that is not rotated.
+    Rectangle(AABB),
+    /// An ellipse object, approximated by a [`Circle`] with its average radius.
+    Circle(Circle),
+    /// A polygon object, or a rotated rectangle or tile object.
+    Polygon(Polygon),
+}
+
+impl Collider for ObjectCollider {
+    fn get_bounding_box(&self) -> AABB {
+        match self {
+            ObjectCollider::Point(collider) => collider.get_bounding_box(),
+            ObjectCollider::Rectangle(collider) => collider.get_bounding_box(),
+            ObjectCollider::Circle(collider) => collider.get_bounding_box(),
+            ObjectCollider::Polygon(collider) => collider.get_bounding_box(),
+        }
+    }
+
+    fn get_center(&self) -> Point {
+        match self {
+            ObjectCollider::Point(collider) => collider.get_center(),
+            ObjectCollider::Rectangle(collider) => collider.get_center(),
+            ObjectCollider::Circle(collider) => collider.get_center(),
+            ObjectCollider::Polygon(collider) => collider.get_center(),
+        }
+    }
+}
+
+/// An entity spawned for a [`TiledObject`] by [`TiledMap::spawn_objects`].
+#[derive(Debug, Clone)]
+pub struct MapObject {
+    /// The entity in the [`SceneGraph`], whose parent is an entity for its layer.
+    pub entity: EntityId,
+    /// Name of the layer the object is in.
+    pub layer: String,
+    /// Unique ID of the object in the map.
+    pub id: u32,
+    /// Name of the object.
+    pub name: String,
+    /// Class of the object.
+    pub class: String,
+    /// Whether the object and its layer are visible.
+    pub visible: bool,
+    /// The collider of the object in world co-ordinates, unless it is a polyline or has no size.
+    pub collider: Option<ObjectCollider>,
+    /// For tile objects, the index of the tileset in the [`TiledMap`], and the [`Tile`] in it.
+    pub tile: Option<(usize, Tile)>,
+    /// Custom properties of the object.
+    pub properties: Properties,
+}
+
+/// A map made with the Tiled level editor.
+///
+/// # Examples
+/// ```
+/// use moon_engine::tiled::TiledMap;
+/// use moon_engine::tilemap::{Tile, Tileset};
+/// use moon_engine::Vec2;
+///
+/// let map = TiledMap::parse(r#"{
+///     "width": 2, "height": 1, "tilewidth": 16, "tileheight": 16,
+///     "tilesets": [{"firstgid": 1, "tilecount": 4, "columns": 2, "tilewidth": 16, "tileheight": 16}],
+///     "layers": [{"type": "tilelayer", "name": "Ground", "data": [0, 3]}]
+/// }"#).unwrap();
+///
+/// let tilemap = map.create_tilemap(0, Tileset::default(), Vec2::new(1.0, 1.0));
+/// assert_eq!(tilemap.get_tile(0, 1, 0), Some(Tile::new(2)));
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct TiledMap {
+    /// Width of the map, in tiles.
+    pub width: u32,
+    /// Height of the map, in tiles.
+    pub height: u32,
+    /// Width of each tile, in pixels.
+    pub tile_width: u32,
+    /// Height of each tile, in pixels.
+    pub tile_height: u32,
+    /// Tilesets used by the map, ordered by their first global tile ID.
+    pub tilesets: Vec<TiledTileset>,
+    /// Layers of the map, from the bottom up.
+    pub layers: Vec<TiledLayer>,
+    /// Custom properties of the map.
+    pub properties: Properties,
+}
+
+impl TiledMap {
+    /// Parse a map saved as JSON or XML, detecting which from its first character.
+    pub fn parse(text: &str) -> Result<Self, String> {
+        if text.trim_start().starts_with('<') {
+            Self::parse_xml(text)
+        } else {
+            Self::parse_json(text)
+        }
+    }
+
+    /// Parse a map saved as JSON.
+    pub fn parse_json(text: &str) -> Result<Self, String> {
+        let root = JsonValue::parse(text)?;
+        let json = Json(&root);
+        json.check_map()?;
+        let mut map = TiledMap {
+            width: json.required("width")?,
+            height: json.required("height")?,
+            tile_width: json.required("tilewidth")?,
+            tile_height: json.required("tileheight")?,
+            properties: json.properties(),
+            ..Default::default()
+        };
+        for tileset in json.array("tilesets") {
+            map.tilesets.push(tileset.tileset()?);
+        }
+        let parent = Group::default();
+        for layer in json.array("layers") {
+            layer.layers(&parent, &mut map)?;
+        }
+        map.finish()
+    }
+
+    /// Parse a map saved as XML, in the TMX format.
+    pub fn parse_xml(text: &str) -> Result<Self, String> {
+        let root = XmlElement::parse(text)?;
+        if root.name != "map" {
+            return Err(String::from("Expected a <map> element."));
+        }
+        let xml = Xml(&root);
+        xml.check_map()?;
+        let mut map = TiledMap {
+            width: xml.required("width")?,
+            height: xml.required("height")?,
+            tile_width: xml.required("tilewidth")?,
+            tile_height: xml.required("tileheight")?,
+            properties: xml.properties(),
+            ..Default::default()
+        };
+        for tileset in root.children_named("tileset") {
+            map.tilesets.push(Xml(tileset).tileset()?);
+        }
+        xml.layers(&Group::default(), &mut map)?;
+        map.finish()
+    }
+
+    /// Get the index of the tileset a global tile ID belongs to.
+    pub fn tileset_index(&self, gid: u32) -> Option<usize> {
+        let (gid, _) = decode_gid(gid);
+        self.tilesets
+            .iter()
+            .position(|tileset| tileset.contains(gid))
+    }
+
+    /// Get the size of a tile in pixels.
+    pub fn tile_size(&self) -> Vec2 {
+        Vec2::new(self.tile_width as f32, self.tile_height as f32)
+    }
+
+    /// Get the layers of tiles.
+    pub fn tile_layers(&self) -> impl Iterator<Item = &TiledTileLayer> {
+        self.layers.iter().filter_map(|layer| match layer {
+            TiledLayer::Tiles(layer) => Some(layer),
+            TiledLayer::Objects(_) => None,
+        })
+    }
+
+    /// Get the layers of objects.
+    pub fn object_layers(&self) -> impl Iterator<Item = &TiledObjectLayer> {
+        self.layers.iter().filter_map(|layer| match layer {
+            TiledLayer::Objects(layer) => Some(layer),
+            TiledLayer::Tiles(_) => None,
+        })
+    }
+
+    /// Create a [`Tileset`] of [`SubTextures`](SubTexture) from one of the tilesets, with its [`TileAnimations`](TileAnimation).
+    ///
+    /// The [`Texture`] should be the sprite sheet of the tileset, at its original size.
+    pub fn create_tileset(&self, index: usize, texture: Rc<Texture>) -> Result<Tileset, String> {
+        let tiled = self
+            .tilesets
+            .get(index)
+            .ok_or_else(|| format!("The map has no tileset {}.", index))?;
+        if tiled.columns == 0 {
+            return Err(format!(
+                "Tileset {} is a collection of images, which is not supported.",
+                tiled.name
+            ));
+        }
+        if tiled.tile_width == 0 || tiled.tile_height == 0 {
+            return Err(format!("Tileset {} has tiles with no size.", tiled.name));
+        }
+        // Only allocate the tiles that can fit in the image
+        let rows = texture
+            .height
+            .checked_sub(tiled.margin)
+            .and_then(|rest| rest.checked_sub(tiled.tile_height))
+            .map_or(0, |rest| {
+                rest / tiled.tile_height.saturating_add(tiled.spacing) + 1
+            });
+        if tiled.tile_count as u64 > tiled.columns as u64 * rows as u64 {
+            return Err(format!(
+                "Tileset {} has {} tiles, but its {}x{} image only fits {} rows of {}.",
+                tiled.name, tiled.tile_count, texture.width, texture.height, rows, tiled.columns
+            ));
+        }
+
+        let (width, height) = (texture.width as f32, texture.height as f32);
+        let tiles = (0..tiled.tile_count)
+            .map(|id| {
+                let (left, top) = (id % tiled.columns, id / tiled.columns);
+                // Get the start and end of a tile along an axis, if it fits in the image
+                let span = |index: u32, size: u32, limit: u32| {
+                    let start = (index as u64)
+                        .checked_mul(size as u64 + tiled.spacing as u64)?
+                        .checked_add(tiled.margin as u64)?;
+                    let end = start + size as u64;
+                    (end <= limit as u64).then_some((start, end))
+                };
+                let outside = || format!("Tile {} is outside of its image.", id);
+                let (left, right) =
+                    span(left, tiled.tile_width, texture.width).ok_or_else(outside)?;
+                let (top, bottom) =
+                    span(top, tiled.tile_height, texture.height).ok_or_else(outside)?;
+                // Textures are flipped when uploaded, so V goes up from the bottom of the image
+                let uv = Color32(
+                    left as f32 / width,
+                    right as f32 / width,
+                    1.0 - bottom as f32 / height,
+                    1.0 - top as f32 / height,
+                );
+                Ok(SubTexture::new_with_coords(Rc::clone(&texture), uv))
+            })
+            .collect::<Result<_, String>>()?;
+        let mut tileset = Tileset::new_from_tiles(tiles, tiled.columns);
+        for (id, animation) in &tiled.animations {
+            tileset.set_animation(*id, Some(animation.clone()));
+        }
+        Ok(tileset)
+    }
+
+    /// Create a [`Tilemap`] with every tile layer, containing the tiles from one of the tilesets.
+    ///
+    /// A [`Tilemap`] is drawn with a single [`Texture`], so maps with several tilesets need one [`Tilemap`] for each.
+    /// Tiles are sized in world units, which also scales the offsets of layers.
+    pub fn create_tilemap(&self, index: usize, tileset: Tileset, tile_size: Vec2) -> Tilemap {
+        let scale = tile_size.component_div(&self.tile_size());
+        let mut tilemap = Tilemap::new_with_size(tileset, self.width, self.height, tile_size);
+        for layer in self.tile_layers() {
+            let layer_index = tilemap.add_layer(&layer.name);
+            if let Some(tile_layer) = tilemap.layer_mut(layer_index) {
+                tile_layer.visible = layer.visible;
+                tile_layer.color = layer.color;
+                tile_layer.offset = layer.offset.component_mul(&scale);
+            }
+            for (position, raw_gid) in layer.gids.iter().enumerate() {
+                if self.tileset_index(*raw_gid) != Some(index) {
+                    continue;
+                }
+                let (gid, flags) = decode_gid(*raw_gid);
+                let tile = Tile::new_with_flags(gid - self.tilesets[index].first_gid, flags);
+                let (x, y) = (position as u32 % self.width, position as u32 / self.width);
+                tilemap.set_tile(layer_index, x as i32, y as i32, Some(tile));
+            }
+        }
+        tilemap
+    }
+
+    /// Spawn an entity for each object layer and each object in it, returning the objects with their colliders.
+    ///
+    /// Objects are scaled by the same amount as the tiles of a [`Tilemap`] with the given tile size.
+    pub fn spawn_objects(&self, scene: &mut SceneGraph, tile_size: Vec2) -> Vec<MapObject> {
+        let scale = tile_size.component_div(&self.tile_size());
+        let mut spawned = Vec::new();
+        for layer in self.object_layers() {
+            let offset = layer.offset.component_mul(&scale);
+            let parent = scene.spawn(Transform2D::new_with_position(offset.x, offset.y));
+            for object in &layer.objects {
+                let position = object.position.component_mul(&scale);
+                let local = Transform2D {
+                    position,
+                    rotation: object.rotation.to_radians(),
+                    ..Default::default()
+                };
+                let entity = scene
+                    .spawn_child(parent, local)
+                    .expect("The layer entity was just spawned");
+                spawned.push(MapObject {
+                    entity,
+                    layer: layer.name.clone(),
+                    id: object.id,
+                    name: object.name.clone(),
+                    class: object.class.clone(),
+                    visible: layer.visible && object.visible,
+                    collider: object_collider(object, layer.offset, scale),
+                    tile: object.gid.and_then(|raw_gid| {
+                        let index = self.tileset_index(raw_gid)?;
+                        let (gid, flags) = decode_gid(raw_gid);
+                        let id = gid - self.tilesets[index].first_gid;
+                        Some((index, Tile::new_with_flags(id, flags)))
+                    }),
+                    properties: object.properties.clone(),
+                });
+            }
+        }
+        spawned
+    }
+
+    /// Sort the tilesets, and check that every layer has a tile for each position.
+    fn finish(mut self) -> Result<Self, String> {
+        self.tilesets.sort_by_key(|tileset| tileset.first_gid);
+        let count = self.width as usize * self.height as usize;
+        for layer in self.tile_layers() {
+            if layer.gids.len() != count {
+                return Err(format!(
+                    "Layer {} has {} tiles, but the map has {}.",
+                    layer.name,
+                    layer.gids.len(),
+                    count
+                ));
+            }
+        }
+        Ok(self)
+    }
+}
+
+impl Asset for TiledMap {
+    fn from_data(path: &str, data: AssetData, context: &mut LoadContext) -> Result<Self, String> {
+        TiledMap::parse(&String::from_data(path, data, context)?)
+            .map_err(|error| format!("{}: {}", path, error))
+    }
+}
+
+/// Get the collider of an object, in world co-ordinates.
+fn object_collider(object: &TiledObject, offset: Vec2, scale: Vec2) -> Option<ObjectCollider> {
+    let (sin, cos) = object.rotation.to_radians().sin_cos();
+    let transform = |point: Vec2| {
+        let rotated = Vec2::new(point.x * cos - point.y * sin, point.x * sin + point.y * cos);
+        (offset + object.position + rotated).component_mul(&scale)
+    };
+    let (width, height) = (object.size.x, object.size.y);
+    match &object.shape {
+        TiledShape::Point => Some(ObjectCollider::Point(transform(Vec2::zeros()))),
+        TiledShape::Ellipse => {
+            let center = transform(object.size / 2.0);
+            let radius = (width * scale.x + height * scale.y) / 4.0;
+            Some(ObjectCollider::Circle(Circle::new_position_and_size(
+                center.x, center.y, radius,
+            )))
+        }
+        TiledShape::Polygon(points) => Some(ObjectCollider::Polygon(Polygon::new(
+            points.iter().map(|point| transform(*point)).collect(),
+        ))),
+        TiledShape::Polyline(_) => None,
+        TiledShape::Rectangle if width <= 0.0 || height <= 0.0 => None,
+        TiledShape::Rectangle => {
+            // Tile objects are placed by their bottom-left corner
+            let top = if object.gid.is_some() { -height } else { 0.0 };
+            let corners = [
+                Vec2::new(0.0, top),
+                Vec2::new(width, top),
+                Vec2::new(width, top + height),
+                Vec2::new(0.0, top + height),
+            ]
+            .map(transform);
+            if object.rotation == 0.0 {
+                Some(ObjectCollider::Rectangle(AABB {
+                    min: corners[0],
+                    max: corners[2],
+                }))
+            } else {
+                Some(ObjectCollider::Polygon(Polygon::new(corners.to_vec())))
+            }
+        }
+    }
+}
+
+/// Visibility, color and offset inherited from the group layers a layer is in.
+#[derive(Debug, Clone, Copy)]
+struct Group {
+    visible: bool,
+    color: Color32,
+    offset: Vec2,
+}
+
+impl Default for Group {
+    fn default() -> Self {
+        Self {
+            visible: true,
+            color: Color32::WHITE,
+            offset: Vec2::zeros(),
+        }
+    }
+}
+
+impl Group {
+    /// Combine the group with the properties of a layer inside it.
+    fn nest(&self, visible: bool, opacity: f32, tint: Option<Color32>, offset: Vec2) -> Self {
+        let tint = tint.unwrap_or(Color32::WHITE);
+        Self {
+            visible: self.visible && visible,
+            color: Color32(
+                self.color.r() * tint.r(),
+                self.color.g() * tint.g(),
+                self.color.b() * tint.b(),
+                self.color.a() * tint.a() * opacity,
+            ),
+            offset: self.offset + offset,
+        }
+    }
+}
+
+/// Parse a color written as `#RRGGBB` or `#AARRGGBB`.
+fn parse_color(text: &str) -> Option<Color32> {
+    let hex = text.strip_prefix('#').unwrap_or(text);
+    let value = u32::from_str_radix(hex, 16).ok()?;
+    let [a, r, g, b] = match hex.len() {
+        6 => (value | 0xFF00_0000).to_be_bytes(),
+        8 => value.to_be_bytes(),
+        _ => return None,
+    };
+    Some(Color32::from(&[r, g, b, a]))
+}
+
+/// Parse a property value written as text, such as an XML attribute.
+fn parse_property(kind: &str, text: &str, members: Properties) -> Option<PropertyValue> {
+    Some(match kind {
+        "bool" => PropertyValue::Bool(text == "true"),
+        "int" => PropertyValue::Int(text.parse().ok()?),
+        "float" => PropertyValue::Float(text.parse().ok()?),
+        "color" => PropertyValue::Color(parse_color(text).unwrap_or(Color32::ZEROES)),
+        "file" => PropertyValue::File(text.to_owned()),
+        "object" => PropertyValue::Object(text.parse().ok()?),
+        "class" => PropertyValue::Class(members),
+        _ => PropertyValue::String(text.to_owned()),
+    })
+}
+
+/// Decode the global tile IDs of a layer, stored as CSV or base64 with optional compression.
+fn decode_layer_data(
+    text: &str,
+    encoding: &str,
+    compression: &str,
+    tile_count: usize,
+) -> Result<Vec<u32>, String> {
+    match encoding {
+        "csv" => text
+            .split(',')
+            .map(str::trim)
+            .filter(|value| !value.is_empty())
+            .map(|value| {
+                value
+                    .parse()
+                    .map_err(|_| format!("Invalid tile ID {} in layer data.", value))
+            })
+            .collect(),
+        "base64" => {
+            let bytes = decode_base64(text)?;
+            // Each tile is stored as four bytes
+            let limit = tile_count.saturating_mul(4);
+            let bytes = match compression {
+                "" => bytes,
+                "zlib" => zlib_decompress_with_limit(&bytes, limit)?,
+                "gzip" => gzip_decompress_with_limit(&bytes, limit)?,
+                _ => return Err(format!("{} compression is not supported.", compression)),
+            };
+            if bytes.len() % 4 != 0 {
+                return Err(String::from("Layer data is not a whole number of tiles."));
+            }
+            Ok(bytes
+                .chunks_exact(4)
+                .map(|gid| u32::from_le_bytes([gid[0], gid[1], gid[2], gid[3]]))
+                .collect())
+        }
+        _ => Err(format!("{} encoding is not supported.", encoding)),
+    }
+}
+
+/// Decode base64 text, ignoring whitespace.
+fn decode_base64(text: &str) -> Result<Vec<u8>, String> {
+    let mut bytes = Vec::with_capacity(text.len() * 3 / 4);
+    let (mut buffer, mut bits) = (0u32, 0u32);
+    for character in text.bytes() {
+        let value = match character {
+            b'A'..=b'Z' => character - b'A',
+            b'a'..=b'z' => character - b'a' + 26,
+            b'0'..=b'9' => character - b'0' + 52,
+            b'+' => 62,
+            b'/' => 63,
+            b'=' => break,
+            _ if character.is_ascii_whitespace() => continue,
+            _ => return Err(String::from("Invalid base64 data.")),
+        };
+        buffer = buffer << 6 | value as u32;
+        bits += 6;
+        if bits >= 8 {
+            bits -= 8;
+            bytes.push((buffer >> bits) as u8);
+        }
+    }
+    Ok(bytes)
+}
+
+/// Reads map data from a JSON value.
+#[derive(Clone, Copy)]
+struct Json<'a>(&'a JsonValue);
+
+impl<'a> Json<'a> {
+    fn number(self, key: &str) -> Option<f64> {
+        self.0.get(key).and_then(JsonValue::as_f64)
+    }
+
+    fn float(self, key: &str) -> f32 {
+        self.number(key).unwrap_or(0.0) as f32
+    }
+
+    fn unsigned(self, key: &str) -> u32 {
+        self.number(key).unwrap_or(0.0) as u32
+    }
+
+    fn required(self, key: &str) -> Result<u32, String> {
+        self.number(key)
+            .map(|value| value as u32)
+            .ok_or_else(|| format!("The map has no {}.", key))
+    }
+
+    fn string(self, key: &str) -> &'a str {
+        self.0.get(key).and_then(JsonValue::as_str).unwrap_or("")
+    }
+
+    fn boolean(self, key: &str, default: bool) -> bool {
+        self.0
+            .get(key)
+            .and_then(JsonValue::as_bool)
+            .unwrap_or(default)
+    }
+
+    fn array(self, key: &str) -> impl Iterator<Item = Json<'a>> {
+        self.0
+            .get(key)
+            .and_then(JsonValue::as_array)
+            .unwrap_or(&[])
+            .iter()
+            .map(Json)
+    }
+
+    fn points(self, key: &str) -> Vec<Vec2> {
+        self.array(key)
+            .map(|point| Vec2::new(point.float("x"), point.float("y")))
+            .collect()
+    }
+
+    fn check_map(self) -> Result<(), String> {
+        check_map(
+            self.0.get("orientation").and_then(JsonValue::as_str),
+            self.boolean("infinite", false),
+        )
+    }
+
+    fn properties(self) -> Properties {
+        self.array("properties")
+            .filter_map(|property| {
+                let value = property.0.get("value")?;
+                let value = match (property.string("type"), value) {
+                    ("color", JsonValue::String(text)) => {
+                        PropertyValue::Color(parse_color(text).unwrap_or(Color32::ZEROES))
+                    }
+                    ("file", JsonValue::String(text)) => PropertyValue::File(text.clone()),
+                    ("object", JsonValue::Number(id)) => PropertyValue::Object(*id as u32),
+                    ("float", JsonValue::Number(value)) => PropertyValue::Float(*value),
+                    _ => json_property(value)?,
+                };
+                Some((property.string("name").to_owned(), value))
+            })
+            .collect()
+    }
+
+    fn tileset(self) -> Result<TiledTileset, String> {
+        if let Some(source) = self.0.get("source").and_then(JsonValue::as_str) {
+            return Err(external_tileset(source));
+        }
+        let mut tileset = TiledTileset {
+            first_gid: self.unsigned("firstgid"),
+            name: self.string("name").to_owned(),
+            image: self.string("image").to_owned(),
+            tile_width: self.unsigned("tilewidth"),
+            tile_height: self.unsigned("tileheight"),
+            tile_count: self.unsigned("tilecount"),
+            columns: self.unsigned("columns"),
+            margin: self.unsigned("margin"),
+            spacing: self.unsigned("spacing"),
+            properties: self.properties(),
+            ..Default::default()
+        };
+        for tile in self.array("tiles") {
+            let id = tile.unsigned("id");
+            let frames: Vec<(u32, f32)> = tile
+                .array("animation")
+                .map(|frame| (frame.unsigned("tileid"), frame.float("duration") / 1000.0))
+                .collect();
+            if !frames.is_empty() {
+                tileset.animations.insert(id, TileAnimation::new(frames));
+            }
+            let properties = tile.properties();
+            if !properties.is_empty() {
+                tileset.tile_properties.insert(id, properties);
+            }
+        }
+        Ok(tileset)
+    }
+
+    /// Add the layer to the map, or the layers inside it if it is a group.
+    fn layers(self, parent: &Group, map: &mut TiledMap) -> Result<(), String> {
+        let tint = self.0.get("tintcolor").and_then(JsonValue::as_str);
+        let group = parent.nest(
+            self.boolean("visible", true),
+            self.number("opacity").unwrap_or(1.0) as f32,
+            tint.and_then(parse_color),
+            Vec2::new(self.float("offsetx"), self.float("offsety")),
+        );
+        let name = self.string("name").to_owned();
+        match self.string("type") {
+            "group" => {
+                for layer in self.array("layers") {
+                    layer.layers(&group, map)?;
+                }
+            }
+            "tilelayer" => {
+                if self.0.get("chunks").is_some() {
+                    return Err(String::from("Infinite maps are not supported."));
+                }
+                let gids = match self.0.get("data") {
+                    Some(JsonValue::String(text)) => decode_layer_data(
+                        text,
+                        "base64",
+                        self.string("compression"),
+                        map.width as usize * map.height as usize,
+                    )?,
+                    Some(JsonValue::Array(gids)) => gids
+                        .iter()
+                        .map(|gid| gid.as_f64().unwrap_or(0.0) as u32)
+                        .collect(),
+                    _ => return Err(format!("Layer {} has no data.", name)),
+                };
+                map.layers.push(TiledLayer::Tiles(TiledTileLayer {
+                    name,
+                    visible: group.visible,
+                    color: group.color,
+                    offset: group.offset,
+                    gids,
+                    properties: self.properties(),
+                }));
+            }
+            "objectgroup" => {
+                let objects = self.array("objects").map(Json::object).collect();
+                map.layers.push(TiledLayer::Objects(TiledObjectLayer {
+                    name,
+                    visible: group.visible,
+                    offset: group.offset,
+                    objects,
+                    properties: self.properties(),
+                }));
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+
+    fn object(self) -> TiledObject {
+        let shape = if self.boolean("point", false) {
+            TiledShape::Point
+        } else if self.boolean("ellipse", false) {
+            TiledShape::Ellipse
+        } else if self.0.get("polygon").is_some() {
+            TiledShape::Polygon(self.points("polygon"))
+        } else if self.0.get("polyline").is_some() {
+            TiledShape::Polyline(self.points("polyline"))
+        } else {
+            TiledShape::Rectangle
+        };
+        let class = match self.string("class") {
+            "" => self.string("type"),
+            class => class,
+        };
+        TiledObject {
+            id: self.unsigned("id"),
+            name: self.string("name").to_owned(),
+            class: class.to_owned(),
+            position: Vec2::new(self.float("x"), self.float("y")),
+            size: Vec2::new(self.float("width"), self.float("height")),
+            rotation: self.float("rotation"),
+            visible: self.boolean("visible", true),
+            gid: self.number("gid").map(|gid| gid as u32),
+            shape,
+            properties: self.properties(),
+        }
+    }
+}
+
+/// Convert a JSON value with no declared type, such as a member of a class, into a property.
+fn json_property(value: &JsonValue) -> Option<PropertyValue> {
+    Some(match value {
+        JsonValue::Bool(value) => PropertyValue::Bool(*value),
+        JsonValue::Number(value) => match value.fract() == 0.0 {
+            true => PropertyValue::Int(*value as i64),
+            false => PropertyValue::Float(*value),
+        },
+        JsonValue::String(value) => PropertyValue::String(value.clone()),
+        JsonValue::Object(members) => PropertyValue::Class(
+            members
+                .iter()
+                .filter_map(|(name, member)| Some((name.clone(), json_property(member)?)))
+                .collect(),
+        ),
+        _ => return None,
+    })
+}
+
+fn check_map(orientation: Option<&str>, infinite: bool) -> Result<(), String> {
+    if orientation.is_some_and(|orientation| orientation != "orthogonal") {
+        return Err(String::from("Only orthogonal maps are supported."));
+    }
+    if infinite {
+        return Err(String::from("Infinite maps are not supported."));
+    }
+    Ok(())
+}
+
+fn external_tileset(source: &str) -> String {
+    format!(
+        "The external tileset {} is not supported, embed it in the map instead.",
+        source
+    )
+}
+
+/// Reads map data from an XML element.
+#[derive(Clone, Copy)]
+struct Xml<'a>(&'a XmlElement);
+
+impl<'a> Xml<'a> {
+    fn float(self, name: &str) -> f32 {
+        self.0
+            .attribute(name)
+            .and_then(|value| value.parse().ok())
+            .unwrap_or(0.0)
+    }
+
+    fn unsigned(self, name: &str) -> u32 {
+        self.0
+            .attribute(name)
+            .and_then(|value| value.parse().ok())
+            .unwrap_or(0)
+    }
+
+    fn required(self, name: &str) -> Result<u32, String> {
+        self.0
+            .attribute(name)
+            .and_then(|value| value.parse().ok())
+            .ok_or_else(|| format!("The map has no {}.", name))
+    }
+
+    fn string(self, name: &str) -> &'a str {
+        self.0.attribute(name).unwrap_or("")
+    }
+
+    fn boolean(self, name: &str, default: bool) -> bool {
+        match self.0.attribute(name) {
+            Some(value) => value == "1" || value == "true",
+            None => default,
+        }
+    }
+
+    fn points(self) -> Vec<Vec2> {
+        self.string("points")
+            .split_whitespace()
+            .filter_map(|point| {
+                let (x, y) = point.split_once(',')?;
+                Some(Vec2::new(x.parse().ok()?, y.parse().ok()?))
+            })
+            .collect()
+    }
+
+    fn check_map(self) -> Result<(), String> {
+        check_map(
+            self.0.attribute("orientation"),
+            self.boolean("infinite", false),
+        )
+    }
+
+    fn properties(self) -> Properties {
+        self.0
+            .child("properties")
+            .into_iter()
+            .flat_map(|properties| properties.children_named("property"))
+            .filter_map(|property| {
+                let property = Xml(property);
+                let text = property.0.attribute("value").unwrap_or(&property.0.text);
+                let value = parse_property(property.string("type"), text, property.properties())?;
+                Some((property.string("name").to_owned(), value))
+            })
+            .collect()
+    }
+
+    fn tileset(self) -> Result<TiledTileset, String> {
+        if let Some(source) = self.0.attribute("source") {
+            return Err(external_tileset(source));
+        }
+        let image = self.0.child("image").map(Xml);
+        let mut tileset = TiledTileset {
+            first_gid: self.unsigned("firstgid"),
+            name: self.string("name").to_owned(),
+            image: image.map_or("", |image| image.string("source")).to_owned(),
+            tile_width: self.unsigned("tilewidth"),
+            tile_height: self.unsigned("tileheight"),
+            tile_count: self.unsigned("tilecount"),
+            columns: self.unsigned("columns"),
+            margin: self.unsigned("margin"),
+            spacing: self.unsigned("spacing"),
+            properties: self.properties(),
+            ..Default::default()
+        };
+        for tile in self.0.children_named("tile").map(Xml) {
+            let id = tile.unsigned("id");
+            let frames: Vec<(u32, f32)> = tile
+                .0
+                .child("animation")
+                .into_iter()
+                .flat_map(|animation| animation.children_named("frame"))
+                .map(|frame| {
+                    let frame = Xml(frame);
+                    (frame.unsigned("tileid"), frame.float("duration") / 1000.0)
+                })
+                .collect();
+            if !frames.is_empty() {
+                tileset.animations.insert(id, TileAnimation::new(frames));
+            }
+            let properties = tile.properties();
+            if !properties.is_empty() {
+                tileset.tile_properties.insert(id, properties);
+            }
+        }
+        Ok(tileset)
+    }
+
+    /// Add the layers inside the element to the map, flattening groups.
+    fn layers(self, parent: &Group, map: &mut TiledMap) -> Result<(), String> {
+        for child in &self.0.children {
+            let layer = Xml(child);
+            let group = parent.nest(
+                layer.boolean("visible", true),
+                layer
+                    .0
+                    .attribute("opacity")
+                    .and_then(|opacity| opacity.parse().ok())
+                    .unwrap_or(1.0),
+                layer.0.attribute("tintcolor").and_then(parse_color),
+                Vec2::new(layer.float("offsetx"), layer.float("offsety")),
+            );
+            let name = layer.string("name").to_owned();
+            match child.name.as_str() {
+                "group" => layer.layers(&group, map)?,
+                "layer" => {
+                    let data = child
+                        .child("data")
+                        .ok_or_else(|| format!("Layer {} has no data.", name))?;
+                    if data.child("chunk").is_some() {
+                        return Err(String::from("Infinite maps are not supported."));
+                    }
+                    let data = Xml(data);
+                    let gids = match data.string("encoding") {
+                        "" => data
+                            .0
+                            .children_named("tile")
+                            .map(|tile| Xml(tile).unsigned("gid"))
+                            .collect(),
+                        encoding => decode_layer_data(
+                            &data.0.text,
+                            encoding,
+                            data.string("compression"),
+                            map.width as usize * map.height as usize,
+                        )?,
+                    };
+                    map.layers.push(TiledLayer::Tiles(TiledTileLayer {
+                        name,
+                        visible: group.visible,
+                        color: group.color,
+                        offset: group.offset,
+                        gids,
+                        properties: layer.properties(),
+                    }));
+                }
+                "objectgroup" => {
+                    let objects = child.children_named("object").map(Xml::object).collect();
+                    map.layers.push(TiledLayer::Objects(TiledObjectLayer {
+                        name,
+                        visible: group.visible,
+                        offset: group.offset,
+                        objects,
+                        properties: layer.properties(),
+                    }));
+                }
+                _ => {}
+            }
+        }
+        Ok(())
+    }
+
+    fn object(element: &XmlElement) -> TiledObject {
+        let object = Xml(element);
+        let shape = if element.child("point").is_some() {
+            TiledShape::Point
+        } else if element.child("ellipse").is_some() {
+            TiledShape::Ellipse
+        } else if let Some(polygon) = element.child("polygon") {
+            TiledShape::Polygon(Xml(polygon).points())
+        } else if let Some(polyline) = element.child("polyline") {
+            TiledShape::Polyline(Xml(polyline).points())
+        } else {
+            TiledShape::Rectangle
+        };
+        let class = match object.string("class") {
+            "" => object.string("type"),
+            class => class,
+        };
+        TiledObject {
+            id: object.unsigned("id"),
+            name: object.string("name").to_owned(),
+            class: class.to_owned(),
+            position: Vec2::new(object.float("x"), object.float("y")),
+            size: Vec2::new(object.float("width"), object.float("height")),
+            rotation: object.float("rotation"),
+            visible: object.boolean("visible", true),
+            gid: element.attribute("gid").and_then(|gid| gid.parse().ok()),
+            shape,
+            properties: object.properties(),
+        }
+    }
+}
@@ -0,0 +1,765 @@
+//! The [`Tilemap`] component, which draws layers of tiles from a [`Tileset`].
+//!
+//! Tiles are grouped into square chunks, whose [`Quads`](Quad) are built once and reused until a tile in them changes.
+//! The reused [`Quads`](Quad) are still batched and uploaded with every other [`Component`] each frame.
+//! Layers can also be given colliders, generated from their solid tiles and cached in the same way.
+
+use std::cell::RefCell;
+use std::collections::{BTreeMap, BTreeSet};
+use std::ops::BitOr;
+use std::rc::Rc;
+
+use crate::collider::{Polygon, AABB};
+use crate::component::Component;
+use crate::mesh::Vertex;
+use crate::renderer::Quad;
+use crate::texture::{SubTexture, Texture};
+use crate::tilecollider::{merge_rectangles, trace_outlines, Solidity};
+use crate::{Color32, Vec2};
+
+/// Number of tiles along each side of a chunk, unless set otherwise.
+pub const DEFAULT_CHUNK_SIZE: u32 = 16;
+
+/// Flags that flip and rotate how a [`Tile`] is drawn, matching the ones used by Tiled.
+///
+/// The diagonal flip is applied first, followed by the horizontal and vertical flips.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct TileFlags(pub u8);
+
+impl TileFlags {
+    /// Draw the [`Tile`] as it is.
+    pub const NONE: TileFlags = TileFlags(0);
+    /// Mirror the [`Tile`] from left to right.
+    pub const FLIP_HORIZONTAL: TileFlags = TileFlags(1);
+    /// Mirror the [`Tile`] from top to bottom.
+    pub const FLIP_VERTICAL: TileFlags = TileFlags(2);
+    /// Mirror the [`Tile`] across the diagonal from its top-left to its bottom-right corner.
+    pub const FLIP_DIAGONAL: TileFlags = TileFlags(4);
+
+    /// Get the flags that rotate a [`Tile`] clockwise by a number of quarter turns.
+    pub fn new_with_rotation(quarter_turns: i32) -> Self {
+        match quarter_turns.rem_euclid(4) {
+            0 => TileFlags::NONE,
+            1 => TileFlags::FLIP_DIAGONAL | TileFlags::FLIP_HORIZONTAL,
+            2 => TileFlags::FLIP_HORIZONTAL | TileFlags::FLIP_VERTICAL,
+            _ => TileFlags::FLIP_DIAGONAL | TileFlags::FLIP_VERTICAL,
+        }
+    }
+
+    /// Check whether every flag in `other` is set.
+    pub fn contains(self, other: TileFlags) -> bool {
+        self.0 & other.0 == other.0
+    }
+
+    /// Reorder the UV co-ordinates of the corners of a [`Quad`], going around from its bottom-left corner.
+    pub fn apply(self, uvs: [[f32; 2]; 4]) -> [[f32; 2]; 4] {
+        let permute = |uvs: [[f32; 2]; 4], order: [usize; 4]| order.map(|index| uvs[index]);
+        let mut uvs = uvs;
+        if self.contains(TileFlags::FLIP_DIAGONAL) {
+            uvs = permute(uvs, [2, 1, 0, 3]);
+        }
+        if self.contains(TileFlags::FLIP_HORIZONTAL) {
+            uvs = permute(uvs, [3, 2, 1, 0]);
+        }
+        if self.contains(TileFlags::FLIP_VERTICAL) {
+            uvs = permute(uvs, [1, 0, 3, 2]);
+        }
+        uvs
+    }
+}
+
+impl BitOr for TileFlags {
+    type Output = Self;
+
+    fn bitor(self, rhs: Self) -> Self {
+        TileFlags(self.0 | rhs.0)
+    }
+}
+
+/// A single tile of a [`Tilemap`], referring to a tile in its [`Tileset`].
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct Tile {
+    /// Index of the tile in the [`Tileset`].
+    pub id: u32,
+    /// How the tile is flipped and rotated.
+    pub flags: TileFlags,
+}
+
+impl Tile {
+    /// Create a new [`Tile`] with an ID.
+    pub fn new(id: u32) -> Self {
+        Self {
+            id,
+            flags: TileFlags::NONE,
+        }
+    }
+
+    /// Create a new [`Tile`] with an ID and [`TileFlags`].
+    pub fn new_with_flags(id: u32, flags: TileFlags) -> Self {
+        Self { id, flags }
+    }
+}
+
+/// A looping animation of a tile, which shows other tiles of the [`Tileset`] in turn.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TileAnimation {
+    /// The tile shown by each frame, and how long it is shown for in seconds.
+    pub frames: Vec<(u32, f32)>,
+}
+
+impl TileAnimation {
+    /// Create a new [`TileAnimation`] from its frames, as tile IDs and durations in seconds.
+    pub fn new(frames: Vec<(u32, f32)>) -> Self {
+        Self { frames }
+    }
+
+    /// Get the time taken to show every frame once, in seconds.
+    pub fn duration(&self) -> f32 {
+        self.frames.iter().map(|(_, duration)| duration).sum()
+    }
+
+    /// Get the index of the frame shown at a time, in seconds.
+    pub fn frame_index_at(&self, time: f32) -> Option<usize> {
+        let duration = self.duration();
+        if self.frames.is_empty() || duration <= 0.0 {
+            return (!self.frames.is_empty()).then_some(0);
+        }
+        let mut time = time.rem_euclid(duration);
+        for (index, (_, frame_duration)) in self.frames.iter().enumerate() {
+            if time < *frame_duration {
+                return Some(index);
+            }
+            time -= frame_duration;
+        }
+        Some(self.frames.len() - 1)
+    }
+
+    /// Get the ID of the tile shown at a time, in seconds.
+    pub fn tile_at(&self, time: f32) -> Option<u32> {
+        self.frame_index_at(time).map(|index| self.frames[index].0)
+    }
+}
+
+/// The [`SubTextures`](SubTexture) that the tiles of a [`Tilemap`] are drawn with.
+///
+/// Tiles are numbered from the top-left of the sprite sheet, going along each row.
+#[derive(Debug, Clone, Default)]
+pub struct Tileset {
+    tiles: Vec<SubTexture>,
+    columns: u32,
+    animations: BTreeMap<u32, TileAnimation>,
+}
+
+impl Tileset {
+    /// Create a new [`Tileset`] by dividing a sprite sheet into a grid of tiles.
+    pub fn new_from_spritesheet(texture: Rc<Texture>, columns: u32, rows: u32) -> Self {
+        let cells = SubTexture::create_tiles_from_spritesheet(texture, columns, rows);
+        // Cells are created column by column, starting from the bottom of the sprite sheet
+        let tiles = (0..rows)
+            .flat_map(|row| (0..columns).map(move |column| column * rows + rows - 1 - row))
+            .map(|index| cells[index as usize].clone())
+            .collect();
+        Self::new_from_tiles(tiles, columns)
+    }
+
+    /// Create a new [`Tileset`] from [`SubTextures`](SubTexture) in order, with a number of columns.
+    pub fn new_from_tiles(tiles: Vec<SubTexture>, columns: u32) -> Self {
+        Self {
+            tiles,
+            columns,
+            animations: BTreeMap::new(),
+        }
+    }
+
+    /// Get the [`SubTexture`] of a tile.
+    pub fn get(&self, id: u32) -> Option<&SubTexture> {
+        self.tiles.get(id as usize)
+    }
+
+    /// Get the number of tiles.
+    pub fn len(&self) -> usize {
+        self.tiles.len()
+    }
+
+    /// Check if there are no tiles.
+    pub fn is_empty(&self) -> bool {
+        self.tiles.is_empty()
+    }
+
+    /// Get the number of columns in the sprite sheet.
+    pub fn columns(&self) -> u32 {
+        self.columns
+    }
+
+    /// Get the [`TileAnimation`] of a tile, if it has one.
+    pub fn animation(&self, id: u32) -> Option<&TileAnimation> {
+        self.animations.get(&id)
+    }
+
+    /// Animate a tile, or stop animating it with [`None`].
+    pub fn set_animation(&mut self, id: u32, animation: Option<TileAnimation>) {
+        match animation {
+            Some(animation) => self.animations.insert(id, animation),
+            None => self.animations.remove(&id),
+        };
+    }
+
+    /// Get every animated tile, with its [`TileAnimation`].
+    pub fn animations(&self) -> impl Iterator<Item = (u32, &TileAnimation)> {
+        self.animations
+            .iter()
+            .map(|(id, animation)| (*id, animation))
+    }
+
+    /// Get the ID of the tile to draw in place of a tile at a time, following its [`TileAnimation`].
+    pub fn resolve(&self, id: u32, time: f32) -> u32 {
+        self.animation(id)
+            .and_then(|animation| animation.tile_at(time))
+            .unwrap_or(id)
+    }
+}
+
+/// A grid of [`Tiles`](Tile) in a [`Tilemap`], drawn on top of the layers before it.
+#[derive(Debug, Clone)]
+pub struct TileLayer {
+    /// Name of the [`TileLayer`].
+    pub name: String,
+    /// Whether the [`TileLayer`] is drawn.
+    pub visible: bool,
+    /// Color the tiles are tinted with, including their opacity.
+    pub color: Color32,
+    /// Offset of the [`TileLayer`] from the [`Tilemap`], in world units.
+    pub offset: Vec2,
+    width: u32,
+    height: u32,
+    tiles: Vec<Option<Tile>>,
+}
+
+impl TileLayer {
+    /// Create a new empty [`TileLayer`] with a name and size in tiles.
+    pub fn new_with_size(name: &str, width: u32, height: u32) -> Self {
+        Self {
+            name: name.to_owned(),
+            visible: true,
+            color: Color32::WHITE,
+            offset: Vec2::zeros(),
+            width,
+            height,
+            tiles: vec![None; width as usize * height as usize],
+        }
+    }
+
+    /// Get the width of the [`TileLayer`], in tiles.
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    /// Get the height of the [`TileLayer`], in tiles.
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    /// Get the [`Tile`] at a position, if there is one.
+    pub fn get(&self, x: i32, y: i32) -> Option<Tile> {
+        self.index(x, y).and_then(|index| self.tiles[index])
+    }
+
+    /// Get every [`Tile`] with its position, row by row.
+    pub fn tiles(&self) -> impl Iterator<Item = (u32, u32, Tile)> + '_ {
+        let width = self.width;
+        self.tiles
+            .iter()
+            .enumerate()
+            .filter_map(move |(index, tile)| {
+                tile.map(|tile| (index as u32 % width, index as u32 / width, tile))
+            })
+    }
+
+    fn index(&self, x: i32, y: i32) -> Option<usize> {
+        if x < 0 || y < 0 || x as u32 >= self.width || y as u32 >= self.height {
+            return None;
+        }
+        Some(y as usize * self.width as usize + x as usize)
+    }
+}
+
+/// Identifies a chunk by its layer, and its column and row of chunks.
+type ChunkKey = (usize, u32, u32);
+
+/// A [`Component`] that draws layers of [`Tiles`](Tile) from a [`Tileset`].
+///
+/// Like other [`Components`](Component), it is drawn with the [`Texture`] currently in use, which should be the sprite sheet of the [`Tileset`].
+/// Animated tiles advance when the [`Tilemap`] is updated, and only chunks containing them have their [`Quads`](Quad) rebuilt when their frames change.
+///
+/// # Examples
+/// ```
+/// use moon_engine::tilemap::{Tile, Tilemap, Tileset};
+/// use moon_engine::Vec2;
+///
+/// let mut tilemap = Tilemap::new_with_size(Tileset::default(), 8, 8, Vec2::new(2.0, 2.0));
+/// let ground = tilemap.add_layer("Ground");
+/// tilemap.set_tile(ground, 3, 1, Some(Tile::new(5)));
+///
+/// assert_eq!(tilemap.world_to_tile(Vec2::new(7.5, 2.5)), (3, 1));
+/// assert_eq!(tilemap.tile_at(ground, Vec2::new(7.5, 2.5)), Some(Tile::new(5)));
+/// ```
+#[derive(Debug)]
+pub struct Tilemap {
+    /// The render layer of the [`Tilemap`].
+    pub render_layer: u32,
+    tileset: Tileset,
+    layers: Vec<TileLayer>,
+    width: u32,
+    height: u32,
+    position: Vec2,
+    tile_size: Vec2,
+    chunk_size: u32,
+    chunk_quads: RefCell<BTreeMap<ChunkKey, Vec<Quad>>>,
+    animated_chunks: RefCell<BTreeSet<ChunkKey>>,
+    time: f64,
+    collision: BTreeMap<usize, Solidity>,
+    colliders: RefCell<BTreeMap<usize, Vec<AABB>>>,
+    outlines: RefCell<BTreeMap<usize, Vec<Polygon>>>,
+    collision_version: u64,
+}
+
+impl Tilemap {
+    /// Create a new [`Tilemap`] with no layers, a size in tiles, and the size of each tile in world units.
+    pub fn new_with_size(tileset: Tileset, width: u32, height: u32, tile_size: Vec2) -> Self {
+        Self {
+            render_layer: 0,
+            tileset,
+            layers: Vec::new(),
+            width,
+            height,
+            position: Vec2::zeros(),
+            tile_size,
+            chunk_size: DEFAULT_CHUNK_SIZE,
+            chunk_quads: RefCell::new(BTreeMap::new()),
+            animated_chunks: RefCell::new(BTreeSet::new()),
+            time: 0.0,
+            collision: BTreeMap::new(),
+            colliders: RefCell::new(BTreeMap::new()),
+            outlines: RefCell::new(BTreeMap::new()),
+            collision_version: 0,
+        }
+    }
+
+    /// Get the width of the [`Tilemap`], in tiles.
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    /// Get the height of the [`Tilemap`], in tiles.
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    /// Get the [`Tileset`] the [`Tilemap`] is drawn with.
+    pub fn tileset(&self) -> &Tileset {
+        &self.tileset
+    }
+
+    /// Replace the [`Tileset`], redrawing every tile.
+    pub fn set_tileset(&mut self, tileset: Tileset) {
+        self.tileset = tileset;
+        self.invalidate();
+    }
+
+    /// Get the world position of the top-left corner of the [`Tilemap`].
+    pub fn position(&self) -> Vec2 {
+        self.position
+    }
+
+    /// Move the top-left corner of the [`Tilemap`] to a world position.
+    pub fn set_position(&mut self, position: Vec2) {
+        self.position = position;
+        self.invalidate();
+    }
+
+    /// Get the size of each tile, in world units.
+    pub fn tile_size(&self) -> Vec2 {
+        self.tile_size
+    }
+
+    /// Set the size of each tile, in world units.
+    pub fn set_tile_size(&mut self, tile_size: Vec2) {
+        self.tile_size = tile_size;
+        self.invalidate();
+    }
+
+    /// Get the number of tiles along each side of a chunk.
+    pub fn chunk_size(&self) -> u32 {
+        self.chunk_size
+    }
+
+    /// Set the number of tiles along each side of a chunk.
+    pub fn set_chunk_size(&mut self, chunk_size: u32) {
+        assert!(chunk_size > 0);
+        self.chunk_size = chunk_size;
+        self.invalidate();
+    }
+
+    /// Add an empty [`TileLayer`] on top of the others, returning its index.
+    pub fn add_layer(&mut self, name: &str) -> usize {
+        self.layers
+            .push(TileLayer::new_with_size(name, self.width, self.height));
+        self.layers.len() - 1
+    }
+
+    /// Get the number of [`TileLayers`](TileLayer).
+    pub fn layer_count(&self) -> usize {
+        self.layers.len()
+    }
+
+    /// Get a [`TileLayer`] by its index.
+    pub fn layer(&self, index: usize) -> Option<&TileLayer> {
+        self.layers.get(index)
+    }
+
+    /// Get a [`TileLayer`] by its index mutably, to change how it is drawn.
+    pub fn layer_mut(&mut self, index: usize) -> Option<&mut TileLayer> {
+        self.invalidate_collision(Some(index));
+        self.chunk_quads
+            .get_mut()
+            .retain(|(layer, _, _), _| *layer != index);
+        self.animated_chunks
+            .get_mut()
+            .retain(|(layer, _, _)| *layer != index);
+        self.layers.get_mut(index)
+    }
+
+    /// Get the index of the first [`TileLayer`] with a name.
+    pub fn layer_index(&self, name: &str) -> Option<usize> {
+        self.layers.iter().position(|layer| layer.name == name)
+    }
+
+    /// Get the [`Tile`] at a position on a layer, if there is one.
+    pub fn get_tile(&self, layer: usize, x: i32, y: i32) -> Option<Tile> {
+        self.layers.get(layer)?.get(x, y)
+    }
+
+    /// Set or clear the [`Tile`] at a position on a layer, returning the previous one.
+    ///
+    /// Positions outside of the [`Tilemap`] are ignored. Only the chunk containing the tile is rebuilt.
+    pub fn set_tile(&mut self, layer: usize, x: i32, y: i32, tile: Option<Tile>) -> Option<Tile> {
+        let tile_layer = self.layers.get_mut(layer)?;
+        let index = tile_layer.index(x, y)?;
+        let previous = std::mem::replace(&mut tile_layer.tiles[index], tile);
+        if previous != tile {
+            let chunk = (
+                layer,
+                x as u32 / self.chunk_size,
+                y as u32 / self.chunk_size,
+            );
+            self.chunk_quads.get_mut().remove(&chunk);
+            self.animated_chunks.get_mut().remove(&chunk);
+            if let Some(solidity) = self.collision.get(&layer) {
+                if solidity.is_solid(previous) != solidity.is_solid(tile) {
+                    self.invalidate_collision(Some(layer));
+                }
+            }
+        }
+        previous
+    }
+
+    /// Clear every [`Tile`] on a layer.
+    pub fn clear_layer(&mut self, layer: usize) {
+        if let Some(tile_layer) = self.layer_mut(layer) {
+            tile_layer.tiles.fill(None);
+        }
+    }
+
+    /// Get the tile position containing a world position, which may be outside of the [`Tilemap`].
+    pub fn world_to_tile(&self, position: Vec2) -> (i32, i32) {
+        let local = position - self.position;
+        (
+            (local.x / self.tile_size.x).floor() as i32,
+            (local.y / self.tile_size.y).floor() as i32,
+        )
+    }
+
+    /// Get the world position of the top-left corner of a tile.
+    pub fn tile_to_world(&self, x: i32, y: i32) -> Vec2 {
+        self.position + Vec2::new(x as f32 * self.tile_size.x, y as f32 * self.tile_size.y)
+    }
+
+    /// Get the world position of the center of a tile.
+    pub fn tile_center(&self, x: i32, y: i32) -> Vec2 {
+        self.tile_to_world(x, y) + self.tile_size / 2.0
+    }
+
+    /// Get the [`Tile`] on a layer at a world position, if there is one.
+    pub fn tile_at(&self, layer: usize, position: Vec2) -> Option<Tile> {
+        let (x, y) = self.world_to_tile(position);
+        self.get_tile(layer, x, y)
+    }
+
+    /// Get the time animated tiles are shown at, in seconds.
+    ///
+    /// This is kept as an [`f64`], so frames stay accurate however long the [`Tilemap`] runs.
+    pub fn animation_time(&self) -> f64 {
+        self.time
+    }
+
+    /// Advance animated tiles by a number of seconds, rebuilding the chunks whose tiles change frame.
+    pub fn advance_animations(&mut self, delta_time: f32) {
+        let previous = self.time;
+        self.time += delta_time as f64;
+        let changed = self.tileset.animations().any(|(_, animation)| {
+            animation.frame_index_at(loop_time(previous, animation))
+                != animation.frame_index_at(loop_time(self.time, animation))
+        });
+        if changed {
+            let chunks = self.chunk_quads.get_mut();
+            for key in std::mem::take(self.animated_chunks.get_mut()) {
+                chunks.remove(&key);
+            }
+        }
+    }
+
+    /// Generate colliders for a layer from its solid tiles, or stop generating them with [`None`].
+    pub fn set_collision_layer(&mut self, layer: usize, solidity: Option<Solidity>) {
+        self.invalidate_collision(Some(layer));
+        match solidity {
+            Some(solidity) => self.collision.insert(layer, solidity),
+            None => self.collision.remove(&layer),
+        };
+        self.collision_version += 1;
+    }
+
+    /// Get the [`Solidity`] of a layer that colliders are generated for.
+    pub fn collision_layer(&self, layer: usize) -> Option<&Solidity> {
+        self.collision.get(&layer)
+    }
+
+    /// Check if the [`Tile`] at a position is solid, on any layer that colliders are generated for.
+    pub fn is_solid(&self, x: i32, y: i32) -> bool {
+        self.collision
+            .iter()
+            .any(|(layer, solidity)| solidity.is_solid(self.get_tile(*layer, x, y)))
+    }
+
+    /// Get a number that changes whenever the colliders of any layer change.
+    ///
+    /// Colliders only need to be fetched again when this is different from the last time they were.
+    pub fn collision_version(&self) -> u64 {
+        self.collision_version
+    }
+
+    /// Get the solid tiles of a layer merged into rectangles, in world co-ordinates.
+    ///
+    /// They are generated when first requested, and cached until a tile on the layer changes whether it is solid.
+    /// Layers without a [`Solidity`], or that do not exist, have no colliders.
+    pub fn colliders(&self, layer: usize) -> Vec<AABB> {
+        let (solidity, offset) = match (self.collision.get(&layer), self.layers.get(layer)) {
+            (Some(solidity), Some(tile_layer)) => (solidity, tile_layer.offset),
+            _ => return Vec::new(),
+        };
+        let mut colliders = self.colliders.borrow_mut();
+        let colliders = colliders.entry(layer).or_insert_with(|| {
+            let origin = self.position + offset;
+            let solid =
+                |x: u32, y: u32| solidity.is_solid(self.get_tile(layer, x as i32, y as i32));
+            merge_rectangles(self.width, self.height, solid)
+                .into_iter()
+                .map(|rect| {
+                    let min = Vec2::new(rect.x as f32, rect.y as f32);
+                    let max = min + Vec2::new(rect.width as f32, rect.height as f32);
+                    AABB {
+                        min: origin + min.component_mul(&self.tile_size),
+                        max: origin + max.component_mul(&self.tile_size),
+                    }
+                })
+                .collect()
+        });
+        colliders.clone()
+    }
+
+    /// Get the outlines around each solid region of a layer, in world co-ordinates.
+    ///
+    /// Outer outlines are counter-clockwise, and the outlines of holes are clockwise.
+    /// Outlines can be concave, so they suit [`Occluders`](crate::shadow::Occluder) and collisions with edges rather than [`Polygon`] tests.
+    pub fn outlines(&self, layer: usize) -> Vec<Polygon> {
+        let (solidity, offset) = match (self.collision.get(&layer), self.layers.get(layer)) {
+            (Some(solidity), Some(tile_layer)) => (solidity, tile_layer.offset),
+            _ => return Vec::new(),
+        };
+        let mut outlines = self.outlines.borrow_mut();
+        let outlines = outlines.entry(layer).or_insert_with(|| {
+            let origin = self.position + offset;
+            let solid =
+                |x: u32, y: u32| solidity.is_solid(self.get_tile(layer, x as i32, y as i32));
+            trace_outlines(self.width, self.height, solid)
+                .into_iter()
+                .map(|corners| {
+                    Polygon::new(
+                        corners
+                            .into_iter()
+                            .map(|(x, y)| {
+                                origin
+                                    + Vec2::new(x as f32, y as f32).component_mul(&self.tile_size)
+                            })
+                            .collect(),
+                    )
+                })
+                .collect()
+        });
+        outlines.clone()
+    }
+
+    /// Get the number of chunks whose [`Quads`](Quad) have been built, and are reused until a tile in them changes.
+    pub fn built_chunk_count(&self) -> usize {
+        self.chunk_quads.borrow().len()
+    }
+
+    /// Get the [`Quads`](Quad) of every visible tile that overlaps a rectangle in world co-ordinates.
+    ///
+    /// Whole chunks are included, so this can be used to only draw what a [`Camera`](crate::Camera) can see.
+    pub fn get_quads_in_rect(&self, min: Vec2, max: Vec2) -> Vec<Quad> {
+        let mut quads = Vec::new();
+        if self.width == 0 || self.height == 0 {
+            return quads;
+        }
+        for (index, layer) in self.layers.iter().enumerate() {
+            if !layer.visible {
+                continue;
+            }
+            let (min_x, min_y) = self.world_to_tile(min - layer.offset);
+            let (max_x, max_y) = self.world_to_tile(max - layer.offset);
+            if max_x < 0 || max_y < 0 || min_x >= self.width as i32 || min_y >= self.height as i32 {
+                continue;
+            }
+            let chunk_range = |min: i32, max: i32, tiles: u32| {
+                let last = (tiles - 1) / self.chunk_size;
+                let first = (min.max(0) as u32 / self.chunk_size).min(last);
+                (first, (max as u32 / self.chunk_size).min(last))
+            };
+            let (first_x, last_x) = chunk_range(min_x, max_x, self.width);
+            let (first_y, last_y) = chunk_range(min_y, max_y, self.height);
+            for chunk_y in first_y..=last_y {
+                for chunk_x in first_x..=last_x {
+                    self.extend_with_chunk(&mut quads, (index, chunk_x, chunk_y));
+                }
+            }
+        }
+        quads
+    }
+
+    /// Clear the built [`Quads`](Quad) of every chunk.
+    fn invalidate(&mut self) {
+        self.chunk_quads.get_mut().clear();
+        self.animated_chunks.get_mut().clear();
+        self.invalidate_collision(None);
+    }
+
+    /// Clear the cached colliders of a layer, or of every layer.
+    fn invalidate_collision(&mut self, layer: Option<usize>) {
+        let cached = |key: &usize| layer.is_none_or(|layer| layer == *key);
+        if self.collision.keys().any(cached) {
+            self.colliders.get_mut().retain(|key, _| !cached(key));
+            self.outlines.get_mut().retain(|key, _| !cached(key));
+            self.collision_version += 1;
+        }
+    }
+
+    /// Add the [`Quads`](Quad) of a chunk, building them if they have not been built yet.
+    fn extend_with_chunk(&self, quads: &mut Vec<Quad>, key: ChunkKey) {
+        let mut chunks = self.chunk_quads.borrow_mut();
+        let chunk = chunks.entry(key).or_insert_with(|| self.build_chunk(key));
+        quads.extend(chunk.iter().cloned());
+    }
+
+    fn build_chunk(&self, key: ChunkKey) -> Vec<Quad> {
+        let (layer, chunk_x, chunk_y) = key;
+        let layer = &self.layers[layer];
+        let color = <[f32; 4]>::from(layer.color);
+        let origin = self.position + layer.offset;
+        let (start_x, start_y) = (chunk_x * self.chunk_size, chunk_y * self.chunk_size);
+        let end_x = (start_x + self.chunk_size).min(self.width);
+        let end_y = (start_y + self.chunk_size).min(self.height);
+
+        let mut quads = Vec::new();
+        for y in start_y..end_y {
+            for x in start_x..end_x {
+                let tile = match layer.get(x as i32, y as i32) {
+                    Some(tile) => tile,
+                    None => continue,
+                };
+                let id = match self.tileset.animation(tile.id) {
+                    Some(animation) => {
+                        self.animated_chunks.borrow_mut().insert(key);
+                        self.tileset
+                            .resolve(tile.id, loop_time(self.time, animation))
+                    }
+                    None => tile.id,
+                };
+                let sprite = match self.tileset.get(id) {
+                    Some(sprite) => sprite,
+                    None => continue,
+                };
+                let left = origin.x + x as f32 * self.tile_size.x;
+                let top = origin.y + y as f32 * self.tile_size.y;
+                let (right, bottom) = (left + self.tile_size.x, top + self.tile_size.y);
+                let positions = [[left, bottom], [left, top], [right, top], [right, bottom]];
+                let uvs = tile.flags.apply(sprite.get_uv_coords());
+                let mut vertices = [Vertex::default(); 4];
+                for (index, vertex) in vertices.iter_mut().enumerate() {
+                    *vertex = Vertex {
+                        position: positions[index],
+                        uv: uvs[index],
+                        color,
+                    };
+                }
+                quads.push(Quad::new_from_vertices(vertices));
+            }
+        }
+        quads
+    }
+}
+
+impl Component for Tilemap {
+    fn update(&mut self, delta_time: f32) {
+        self.advance_animations(delta_time);
+    }
+
+    fn render_layer(&self) -> u32 {
+        self.render_layer
+    }
+
+    /// Get the [`Quads`](Quad) of every visible tile, layer by layer.
+    fn get_quads(&self) -> Option<Vec<Quad>> {
+        let mut quads = Vec::new();
+        let chunks_x = self.width.div_ceil(self.chunk_size);
+        let chunks_y = self.height.div_ceil(self.chunk_size);
+        for (index, layer) in self.layers.iter().enumerate() {
+            if !layer.visible {
+                continue;
+            }
+            for chunk_y in 0..chunks_y {
+                for chunk_x in 0..chunks_x {
+                    self.extend_with_chunk(&mut quads, (index, chunk_x, chunk_y));
+                }
+            }
+        }
+        Some(quads)
+    }
+
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+
+    fn as_mut_any(&mut self) -> &mut dyn std::any::Any {
+        self
+    }
+}
+
+/// Wrap a time to the length of a [`TileAnimation`], so it keeps its precision as an [`f32`].
+fn loop_time(time: f64, animation: &TileAnimation) -> f32 {
+    let duration = animation.duration() as f64;
+    if duration > 0.0 {
+        time.rem_euclid(duration) as f32
+    } else {
+        0.0
+    }
+}
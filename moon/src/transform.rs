@@ -1,7 +1,9 @@
 //! The [`Transform`] ans [`Transform2D`] structs.
 
+use std::f32::consts::{PI, TAU};
 use std::ops::Add;
 
+use crate::Mat3;
 use crate::Mat4;
 use crate::Vec2;
 use crate::Vec3;
@@ -97,10 +99,22 @@ impl Transform {
     }
 }
 
+impl From<&Transform> for Transform2D {
+    /// Create a [`Transform2D`] from the `X` and `Y` components of a [`Transform`], and its rotation around the `Z` axis.
+    fn from(transform: &Transform) -> Self {
+        Self {
+            position: transform.position.xy(),
+            rotation: transform.rotation.z,
+            scale: transform.scale.xy(),
+            ..Default::default()
+        }
+    }
+}
+
 /// A 2D counterpart for the [`Transform`].
 ///
-/// A [`Transform2D`] contains Position and Scale [`Vec2`]s and a float for rotation.
-#[derive(Debug, Clone, Copy)]
+/// A [`Transform2D`] contains Position, Scale and Pivot [`Vec2`]s and a float for rotation.
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub struct Transform2D {
     /// Position of the [`Transform2D`].
     pub position: Vec2,
@@ -108,6 +122,10 @@ pub struct Transform2D {
     pub rotation: f32,
     /// Scale of the [`Transform2D`].
     pub scale: Vec2,
+    /// Pivot of the [`Transform2D`], in local co-ordinates.
+    ///
+    /// Rotation and scale happen around the pivot, and it is placed at the position.
+    pub pivot: Vec2,
 }
 
 impl Default for Transform2D {
@@ -116,19 +134,19 @@ impl Default for Transform2D {
             position: Vec2::zeros(),
             rotation: 0.0f32,
             scale: Vec2::from_element(1.0),
+            pivot: Vec2::zeros(),
         }
     }
 }
 
+/// Combine a parent [`Transform2D`] with a child one, placing the child relative to the parent.
+///
+/// The child's position is rotated and scaled by the parent, and their rotations and scales are combined.
 impl Add for Transform2D {
     type Output = Transform2D;
 
     fn add(self, rhs: Self) -> Self::Output {
-        Transform2D {
-            position: self.position + rhs.position,
-            rotation: self.rotation + rhs.rotation,
-            ..rhs
-        }
+        self.combine(&rhs)
     }
 }
 
@@ -149,14 +167,139 @@ impl Transform2D {
         }
     }
 
+    /// Create a new [`Transform2D`] with a given position, rotation and scale.
+    pub fn new_with_position_and_rotation_and_scale(
+        pos_x: f32,
+        pos_y: f32,
+        rotation: f32,
+        scale_x: f32,
+        scale_y: f32,
+    ) -> Self {
+        Self {
+            position: Vec2::new(pos_x, pos_y),
+            rotation,
+            scale: Vec2::new(scale_x, scale_y),
+            ..Default::default()
+        }
+    }
+
+    /// Combine the [`Transform2D`] with a child one, placing the child relative to it.
+    ///
+    /// The result keeps the pivot of the child. Any skew caused by non-uniform scaling of a rotated child is lost.
+    pub fn combine(&self, child: &Self) -> Self {
+        Self::from_matrix3_with_pivot(&(self.matrix3() * child.matrix3()), child.pivot)
+    }
+
     /// Translate a [`Transform2D`] using `X` and `Y` deltas.
     pub fn translate(&mut self, delta_x: f32, delta_y: f32) {
         self.position.x += delta_x;
         self.position.y += delta_y;
     }
 
-    /// Get a [`Mat4`] of the [`Transform2D`].
+    /// Get a [`Mat4`] of the [`Transform2D`], combining its position, rotation, scale and pivot.
+    #[rustfmt::skip]
     pub fn matrix(&self) -> Mat4 {
-        Mat4::new_translation(&Vec3::new(self.position.x, self.position.y, 0.0))
+        let m = self.matrix3();
+        Mat4::new(
+            m[(0, 0)], m[(0, 1)], 0.0, m[(0, 2)],
+            m[(1, 0)], m[(1, 1)], 0.0, m[(1, 2)],
+            0.0, 0.0, 1.0, 0.0,
+            0.0, 0.0, 0.0, 1.0,
+        )
+    }
+
+    /// Get a [`Mat3`] of the [`Transform2D`], combining its position, rotation, scale and pivot.
+    pub fn matrix3(&self) -> Mat3 {
+        Mat3::new_translation(&self.position)
+            * Mat3::new_rotation(self.rotation)
+            * Mat3::new_nonuniform_scaling(&self.scale)
+            * Mat3::new_translation(&-self.pivot)
+    }
+
+    /// Get the inverse [`Mat3`] of the [`Transform2D`], if it can be inverted.
+    ///
+    /// A [`Transform2D`] with a scale of zero on either axis cannot be inverted.
+    pub fn inverse_matrix3(&self) -> Option<Mat3> {
+        if self.scale.x == 0.0 || self.scale.y == 0.0 {
+            return None;
+        }
+        Some(
+            Mat3::new_translation(&self.pivot)
+                * Mat3::new_nonuniform_scaling(&self.scale.map(|scale| 1.0 / scale))
+                * Mat3::new_rotation(-self.rotation)
+                * Mat3::new_translation(&-self.position),
+        )
+    }
+
+    /// Get the inverse of the [`Transform2D`], if it can be inverted.
+    ///
+    /// Rotated [`Transform2Ds`](Transform2D) with a non-uniform scale have a skewed inverse, which cannot be represented. Use [`inverse_matrix3`](Transform2D::inverse_matrix3) if it matters.
+    pub fn inverse(&self) -> Option<Self> {
+        self.inverse_matrix3()
+            .map(|matrix| Self::from_matrix3(&matrix))
+    }
+
+    /// Transform a point from local co-ordinates, applying position, rotation, scale and pivot.
+    pub fn transform_point(&self, point: Vec2) -> Vec2 {
+        self.matrix3().transform_point(&point.into()).coords
+    }
+
+    /// Transform a vector from local co-ordinates, applying rotation and scale, but not position.
+    pub fn transform_vector(&self, vector: Vec2) -> Vec2 {
+        self.matrix3().transform_vector(&vector)
+    }
+
+    /// Transform a point into local co-ordinates. This is the reverse of [`transform_point`](Transform2D::transform_point).
+    pub fn inverse_transform_point(&self, point: Vec2) -> Option<Vec2> {
+        self.inverse_matrix3()
+            .map(|matrix| matrix.transform_point(&point.into()).coords)
+    }
+
+    /// Transform a vector into local co-ordinates. This is the reverse of [`transform_vector`](Transform2D::transform_vector).
+    pub fn inverse_transform_vector(&self, vector: Vec2) -> Option<Vec2> {
+        self.inverse_matrix3()
+            .map(|matrix| matrix.transform_vector(&vector))
+    }
+
+    /// Interpolate between two [`Transform2Ds`](Transform2D), where a `t` of `0.0` is `self` and `1.0` is `other`.
+    ///
+    /// The rotation is interpolated along the shortest path.
+    pub fn lerp(&self, other: &Self, t: f32) -> Self {
+        let delta = (other.rotation - self.rotation + PI).rem_euclid(TAU) - PI;
+        Self {
+            position: self.position.lerp(&other.position, t),
+            rotation: self.rotation + delta * t,
+            scale: self.scale.lerp(&other.scale, t),
+            pivot: self.pivot.lerp(&other.pivot, t),
+        }
+    }
+
+    /// Create a new [`Transform2D`] from a [`Mat3`].
+    ///
+    /// Any skew in the [`Mat3`] cannot be represented, and is lost.
+    pub fn from_matrix3(matrix: &Mat3) -> Self {
+        Self::from_matrix3_with_pivot(matrix, Vec2::zeros())
+    }
+
+    /// Create a new [`Transform2D`] from a [`Mat3`], with a given pivot.
+    ///
+    /// Any skew in the [`Mat3`] cannot be represented, and is lost.
+    pub fn from_matrix3_with_pivot(matrix: &Mat3, pivot: Vec2) -> Self {
+        let scale_x = Vec2::new(matrix[(0, 0)], matrix[(1, 0)]).norm();
+        let determinant = matrix[(0, 0)] * matrix[(1, 1)] - matrix[(0, 1)] * matrix[(1, 0)];
+        Self {
+            // The pivot is the local point that ends up at the position
+            position: matrix.transform_point(&pivot.into()).coords,
+            rotation: matrix[(1, 0)].atan2(matrix[(0, 0)]),
+            scale: Vec2::new(
+                scale_x,
+                if scale_x > 0.0 {
+                    determinant / scale_x
+                } else {
+                    Vec2::new(matrix[(0, 1)], matrix[(1, 1)]).norm()
+                },
+            ),
+            pivot,
+        }
     }
 }
@@ -0,0 +1,222 @@
+//! The [`XmlElement`] struct, and a parser for simple XML documents.
+//!
+//! Namespaces and document type definitions are not interpreted.
+
+use std::collections::BTreeMap;
+
+/// Deepest nesting of elements that will be parsed.
+const MAX_DEPTH: u32 = 128;
+
+/// An element in an XML document, with its attributes, child elements and text.
+///
+/// # Examples
+/// ```
+/// use moon_engine::xml::XmlElement;
+///
+/// let root = XmlElement::parse(r#"<map width="2"><layer name="A &amp; B"/></map>"#).unwrap();
+/// assert_eq!(root.attribute("width"), Some("2"));
+/// assert_eq!(root.child("layer").and_then(|layer| layer.attribute("name")), Some("A & B"));
+/// ```
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct XmlElement {
+    /// Name of the element.
+    pub name: String,
+    /// Attributes of the element, by name.
+    pub attributes: BTreeMap<String, String>,
+    /// Child elements, in order.
+    pub children: Vec<XmlElement>,
+    /// Text directly inside the element, with entities replaced.
+    pub text: String,
+}
+
+impl XmlElement {
+    /// Parse an XML document, returning its root element.
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let mut parser = Parser { text, position: 0 };
+        parser.misc()?;
+        if !parser.rest().starts_with('<') {
+            return Err(parser.error("Expected a root element"));
+        }
+        let root = parser.element(0)?;
+        parser.misc()?;
+        if parser.position != text.len() {
+            return Err(parser.error("Unexpected data after the root element"));
+        }
+        Ok(root)
+    }
+
+    /// Get the value of an attribute.
+    pub fn attribute(&self, name: &str) -> Option<&str> {
+        self.attributes.get(name).map(String::as_str)
+    }
+
+    /// Get the first child element with a name.
+    pub fn child(&self, name: &str) -> Option<&XmlElement> {
+        self.children.iter().find(|child| child.name == name)
+    }
+
+    /// Get every child element with a name.
+    pub fn children_named<'a>(&'a self, name: &'a str) -> impl Iterator<Item = &'a XmlElement> {
+        self.children.iter().filter(move |child| child.name == name)
+    }
+}
+
+/// Reads XML elements from text.
+struct Parser<'a> {
+    text: &'a str,
+    position: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn error(&self, message: &str) -> String {
+        format!("{} at byte {}.", message, self.position)
+    }
+
+    fn rest(&self) -> &'a str {
+        &self.text[self.position..]
+    }
+
+    fn whitespace(&mut self) {
+        let rest = self.rest();
+        self.position += rest.len() - rest.trim_start().len();
+    }
+
+    /// Skip everything up to and including a delimiter.
+    fn skip_past(&mut self, delimiter: &str) -> Result<&'a str, String> {
+        let rest = self.rest();
+        let end = rest
+            .find(delimiter)
+            .ok_or_else(|| self.error(&format!("Expected `{}`", delimiter)))?;
+        self.position += end + delimiter.len();
+        Ok(&rest[..end])
+    }
+
+    /// Skip whitespace, comments, processing instructions and document type declarations.
+    fn misc(&mut self) -> Result<(), String> {
+        loop {
+            self.whitespace();
+            let rest = self.rest();
+            if rest.starts_with("<?") {
+                self.skip_past("?>")?;
+            } else if rest.starts_with("<!--") {
+                self.skip_past("-->")?;
+            } else if rest.starts_with("<!") {
+                self.skip_past(">")?;
+            } else {
+                return Ok(());
+            }
+        }
+    }
+
+    fn name(&mut self) -> Result<&'a str, String> {
+        let rest = self.rest();
+        let length = rest
+            .find(|character: char| {
+                character.is_whitespace() || matches!(character, '/' | '>' | '=')
+            })
+            .unwrap_or(rest.len());
+        if length == 0 {
+            return Err(self.error("Expected a name"));
+        }
+        self.position += length;
+        Ok(&rest[..length])
+    }
+
+    fn element(&mut self, depth: u32) -> Result<XmlElement, String> {
+        if depth > MAX_DEPTH {
+            return Err(self.error("XML is nested too deeply"));
+        }
+        self.position += 1;
+        let mut element = XmlElement {
+            name: self.name()?.to_owned(),
+            ..Default::default()
+        };
+
+        loop {
+            self.whitespace();
+            let rest = self.rest();
+            if rest.starts_with("/>") {
+                self.position += 2;
+                return Ok(element);
+            }
+            if rest.starts_with('>') {
+                self.position += 1;
+                break;
+            }
+            let name = self.name()?;
+            self.whitespace();
+            if !self.rest().starts_with('=') {
+                return Err(self.error("Expected `=`"));
+            }
+            self.position += 1;
+            self.whitespace();
+            let quote = match self.rest().chars().next() {
+                Some(quote @ ('"' | '\'')) => quote,
+                _ => return Err(self.error("Expected a quoted value")),
+            };
+            self.position += 1;
+            let value = self.skip_past(&quote.to_string())?;
+            let value = self.unescape(value)?;
+            element.attributes.insert(name.to_owned(), value);
+        }
+
+        loop {
+            let rest = self.rest();
+            if rest.starts_with("</") {
+                self.position += 2;
+                let name = self.name()?;
+                if name != element.name {
+                    return Err(self.error(&format!("Expected `</{}>`", element.name)));
+                }
+                self.whitespace();
+                self.skip_past(">")?;
+                return Ok(element);
+            } else if rest.starts_with("<!--") {
+                self.skip_past("-->")?;
+            } else if rest.starts_with("<![CDATA[") {
+                self.position += 9;
+                element.text.push_str(self.skip_past("]]>")?);
+            } else if rest.starts_with("<?") {
+                self.skip_past("?>")?;
+            } else if rest.starts_with('<') {
+                element.children.push(self.element(depth + 1)?);
+            } else if rest.is_empty() {
+                return Err(self.error(&format!("Expected `</{}>`", element.name)));
+            } else {
+                let length = rest.find('<').unwrap_or(rest.len());
+                self.position += length;
+                element.text.push_str(&self.unescape(&rest[..length])?);
+            }
+        }
+    }
+
+    /// Replace character and predefined entity references.
+    fn unescape(&self, text: &str) -> Result<String, String> {
+        let mut result = String::with_capacity(text.len());
+        let mut rest = text;
+        while let Some(start) = rest.find('&') {
+            result.push_str(&rest[..start]);
+            let end = rest[start..]
+                .find(';')
+                .ok_or_else(|| self.error("Unterminated entity"))?;
+            let entity = &rest[start + 1..start + end];
+            let character = match entity {
+                "lt" => Some('<'),
+                "gt" => Some('>'),
+                "amp" => Some('&'),
+                "quot" => Some('"'),
+                "apos" => Some('\''),
+                _ => entity
+                    .strip_prefix("#x")
+                    .map(|hex| u32::from_str_radix(hex, 16))
+                    .or_else(|| entity.strip_prefix('#').map(str::parse))
+                    .and_then(Result::ok)
+                    .and_then(char::from_u32),
+            };
+            result.push(character.ok_or_else(|| self.error("Unknown entity"))?);
+            rest = &rest[start + end + 1..];
+        }
+        result.push_str(rest);
+        Ok(result)
+    }
+}
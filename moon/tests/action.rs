@@ -0,0 +1,112 @@
+use moon_engine::action::*;
+use moon_engine::input::InputManager;
+
+#[test]
+fn action_pressed_by_any_binding() {
+    let mut input = InputManager::new();
+    input.actions.bind_action("jump", InputBinding::Key(b' '));
+    input
+        .actions
+        .bind_action("jump", InputBinding::MouseButton(0));
+    assert!(!input.is_action_pressed("jump"));
+    input.mouse_button_down(0);
+    assert!(input.is_action_pressed("jump"));
+    input.mouse_button_up(0);
+    input.key_down(b' ');
+    assert!(input.is_action_pressed("jump"));
+}
+
+#[test]
+fn axis_combines_keys_and_gamepad() {
+    let mut input = InputManager::new();
+    input
+        .actions
+        .bind_axis("move_x", InputBinding::Key(b'D'), InputBinding::Key(b'A'));
+    input.actions.bind_axis(
+        "move_x",
+        InputBinding::GamepadAxis {
+            axis: 0,
+            positive: true,
+        },
+        InputBinding::GamepadAxis {
+            axis: 0,
+            positive: false,
+        },
+    );
+    input.key_down(b'A');
+    assert_eq!(input.get_axis("move_x"), -1.0);
+    input.key_down(b'D');
+    assert_eq!(input.get_axis("move_x"), 0.0);
+    input.key_up(b'D');
+    input.key_up(b'A');
+    input.set_gamepad_axis(0, 0.25);
+    assert_eq!(input.get_axis("move_x"), 0.25);
+    assert_eq!(input.get_axis("missing"), 0.0);
+}
+
+#[test]
+fn rebind_replaces_binding() {
+    let mut actions = ActionMap::new();
+    actions.bind_action("fire", InputBinding::Key(b'F'));
+    actions
+        .rebind(
+            "fire",
+            InputBinding::Key(b'F'),
+            InputBinding::GamepadButton(7),
+        )
+        .unwrap();
+    assert_eq!(
+        actions.action_bindings("fire"),
+        &[InputBinding::GamepadButton(7)]
+    );
+    assert!(actions
+        .rebind("fire", InputBinding::Key(b'F'), InputBinding::Key(b'G'))
+        .is_err());
+}
+
+#[test]
+fn profile_round_trip() {
+    let mut actions = ActionMap::new();
+    actions.bind_action("jump", InputBinding::Key(32));
+    actions.bind_axis(
+        "move_y",
+        InputBinding::Key(b'S'),
+        InputBinding::GamepadAxis {
+            axis: 1,
+            positive: false,
+        },
+    );
+    let profile = actions.to_profile();
+    assert_eq!(ActionMap::from_profile(&profile), Ok(actions));
+}
+
+#[test]
+fn profile_escapes_names() {
+    let mut actions = ActionMap::new();
+    actions.bind_action("jump high", InputBinding::Key(32));
+    actions.bind_action("a=b", InputBinding::Key(b'A'));
+    actions.bind_action("#100%\ttab", InputBinding::Key(b'B'));
+    actions.bind_axis("move x", InputBinding::Key(b'D'), InputBinding::Key(b'A'));
+    let profile = actions.to_profile();
+    assert!(profile.contains("action jump%20high = key:32"));
+    assert_eq!(ActionMap::from_profile(&profile), Ok(actions));
+
+    assert!(ActionMap::from_profile("action jump%2 = key:32").is_err());
+    assert!(ActionMap::from_profile("action jump%zz = key:32").is_err());
+
+    // Empty names have an escape of their own
+    let mut actions = ActionMap::new();
+    actions.bind_action("", InputBinding::Key(32));
+    actions.bind_axis("", InputBinding::Key(b'D'), InputBinding::Key(b'A'));
+    let profile = actions.to_profile();
+    assert!(profile.contains("action % = key:32"));
+    assert_eq!(ActionMap::from_profile(&profile), Ok(actions));
+}
+
+#[test]
+fn invalid_profile_is_rejected() {
+    assert!(ActionMap::from_profile("action jump = key:300").is_err());
+    assert!(ActionMap::from_profile("axis move = axis:0").is_err());
+    assert!(ActionMap::from_profile("jump key:32").is_err());
+    assert!(ActionMap::from_profile("# comment\n\naction jump = pad:1").is_err());
+}
@@ -0,0 +1,152 @@
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use moon_engine::asset::*;
+use moon_engine::audio::Audio;
+
+static DROPPED_LEVELS: AtomicUsize = AtomicUsize::new(0);
+
+#[derive(Debug)]
+struct Level(usize);
+
+impl Asset for Level {
+    fn from_data(_path: &str, data: AssetData, _context: &mut LoadContext) -> Result<Self, String> {
+        Ok(Level(data.into_bytes()?.len()))
+    }
+}
+
+impl Drop for Level {
+    fn drop(&mut self) {
+        DROPPED_LEVELS.fetch_add(1, Ordering::SeqCst);
+    }
+}
+
+fn server() -> AssetServer {
+    let mut source = MemoryAssetSource::new();
+    source.insert("hello.txt", b"Hello");
+    source.insert("level.bin", &[1, 2, 3]);
+    source.insert("bad.txt", &[0xFF, 0xFE]);
+    source.insert("jump.wav", &[0; 16]);
+    AssetServer::new_with_source(Box::new(source))
+}
+
+#[test]
+fn loads_after_update() {
+    let mut assets = server();
+    let text = assets.load::<String>("hello.txt");
+    let bytes = assets.load::<Vec<u8>>("hello.txt");
+    assert_eq!(assets.loading_count(), 2);
+    assert!(text.get().is_none());
+
+    assets.update(&mut LoadContext::default());
+    assert!(assets.is_idle());
+    assert!(text.is_loaded());
+    assert_eq!(text.get().unwrap().as_str(), "Hello");
+    assert_eq!(bytes.get().unwrap().as_slice(), b"Hello");
+    assert_eq!(text.path(), "hello.txt");
+}
+
+#[test]
+fn errors_are_reported() {
+    let mut assets = server();
+    let missing = assets.load::<Vec<u8>>("missing.png");
+    let invalid = assets.load::<String>("bad.txt");
+    let texture = assets.load::<moon_engine::texture::Texture>("hello.txt");
+    assets.update(&mut LoadContext::default());
+    assert!(matches!(missing.state(), AssetState::Failed(_)));
+    assert!(matches!(invalid.state(), AssetState::Failed(_)));
+    assert!(matches!(texture.state(), AssetState::Failed(_)));
+    assert!(invalid.get().is_none());
+}
+
+#[test]
+fn failed_assets_can_be_retried() {
+    let mut assets = server();
+    let failed = assets.load::<String>("bad.txt");
+    assets.update(&mut LoadContext::default());
+    assert!(matches!(failed.state(), AssetState::Failed(_)));
+
+    // A new load is started while the failed handle is still alive
+    let retry = assets.load::<String>("bad.txt");
+    assert_ne!(failed, retry);
+    assert_eq!(retry.state(), AssetState::Loading);
+    assert_eq!(assets.loading_count(), 1);
+    assets.update(&mut LoadContext::default());
+    assert!(matches!(retry.state(), AssetState::Failed(_)));
+}
+
+#[test]
+fn same_path_is_loaded_once() {
+    let mut assets = server();
+    let first = assets.load::<ShaderSource>("hello.txt");
+    let second = assets.load::<ShaderSource>("hello.txt");
+    assert_eq!(first, second);
+    assert_eq!(first.handle_count(), 2);
+    assert_eq!(assets.loading_count(), 1);
+    assert_eq!(assets.asset_count(), 1);
+}
+
+#[test]
+fn freed_when_last_handle_drops() {
+    let mut assets = server();
+    let level = assets.load::<Level>("level.bin");
+    assets.update(&mut LoadContext::default());
+    assert_eq!(level.get().unwrap().0, 3);
+
+    let before = DROPPED_LEVELS.load(Ordering::SeqCst);
+    let copy = level.clone();
+    drop(level);
+    assert_eq!(DROPPED_LEVELS.load(Ordering::SeqCst), before);
+    let id = copy.id();
+    drop(copy);
+    assert_eq!(DROPPED_LEVELS.load(Ordering::SeqCst), before + 1);
+
+    // Loading the path again starts over with a new asset
+    assets.update(&mut LoadContext::default());
+    assert_eq!(assets.asset_count(), 0);
+    let level = assets.load::<Level>("level.bin");
+    assert_ne!(level.id(), id);
+}
+
+#[test]
+fn sounds_are_decoded_by_the_mixer() {
+    let mut assets = server();
+    let orphan = assets.load::<Sound>("hello.txt");
+    assets.update(&mut LoadContext::default());
+    assert!(matches!(orphan.state(), AssetState::Failed(_)));
+
+    let sound = assets.load::<Sound>("jump.wav");
+    let mut audio = Audio::new();
+    assets.update(&mut LoadContext {
+        audio: Some(&mut audio),
+        ..Default::default()
+    });
+    let Sound(id) = *sound.get().unwrap();
+    assert_eq!(audio.get_sound("jump.wav"), Some(id));
+    assert!(audio.is_sound_ready(id));
+}
+
+#[test]
+fn sounds_are_freed_from_the_mixer_with_their_last_handle() {
+    let mut assets = server();
+    let mut audio = Audio::new();
+    let update = |assets: &mut AssetServer, audio: &mut Audio| {
+        assets.update(&mut LoadContext {
+            audio: Some(audio),
+            ..Default::default()
+        })
+    };
+    let sound = assets.load::<Sound>("jump.wav");
+    update(&mut assets, &mut audio);
+    let Sound(id) = *sound.get().unwrap();
+
+    // The asset stays alive while a handle or the value is kept
+    let value = sound.get();
+    drop(sound);
+    update(&mut assets, &mut audio);
+    assert!(audio.is_sound_ready(id));
+
+    drop(value);
+    update(&mut assets, &mut audio);
+    assert!(!audio.is_sound_ready(id));
+    assert_eq!(audio.get_sound("jump.wav"), None);
+}
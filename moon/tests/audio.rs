@@ -0,0 +1,119 @@
+use moon_engine::audio::*;
+
+#[test]
+fn sfx_finishes_after_its_duration() {
+    let mut audio = Audio::new_with_backend(Box::new(NullBackend::new_with_duration(1.0)));
+    let sound = audio.load_sound("HIT", &[]);
+    let normal = audio.play_sfx(sound, 1.0, 1.0).unwrap();
+    let fast = audio.play_sfx(sound, 1.0, 2.0).unwrap();
+    audio.update(0.6);
+    assert!(audio.is_playing(normal));
+    assert!(!audio.is_playing(fast));
+    audio.update(0.6);
+    assert_eq!(audio.voice_count(), 0);
+}
+
+#[test]
+fn streams_finish_when_the_backend_reaches_their_end() {
+    let mut audio = Audio::new_with_backend(Box::new(NullBackend::new_with_duration(1.0)));
+    let sound = audio.load_stream("VOICE", "voice.ogg");
+    let voice = audio.play_sfx(sound, 1.0, 1.0).unwrap();
+    let music = audio.play_music(sound, 0.0).unwrap();
+    audio.update(0.6);
+    assert!(audio.is_playing(voice));
+    audio.update(0.6);
+    assert!(!audio.is_playing(voice));
+    // Looping streams never end
+    assert!(audio.is_playing(music));
+    assert_eq!(audio.voice_count(), 1);
+}
+
+#[test]
+fn sounds_must_be_ready() {
+    let mut audio = Audio::new();
+    assert!(audio.play_sfx(SoundId(3), 1.0, 1.0).is_err());
+    let sound = audio.load_sound("HIT", &[]);
+    assert_eq!(audio.get_sound("HIT"), Some(sound));
+    assert!(audio.is_sound_ready(sound));
+}
+
+#[test]
+fn reloading_a_sound_frees_the_old_one() {
+    let mut audio = Audio::new();
+    let old = audio.load_sound("HIT", &[]);
+    let new = audio.load_stream("HIT", "hit.ogg");
+    assert_ne!(old, new);
+    assert_eq!(audio.get_sound("HIT"), Some(new));
+    assert!(!audio.is_sound_ready(old));
+    assert!(audio.play_sfx(old, 1.0, 1.0).is_err());
+
+    assert!(audio.unload_sound("HIT"));
+    assert!(!audio.unload_sound("HIT"));
+    assert!(!audio.is_sound_ready(new));
+}
+
+#[test]
+fn buses_scale_and_mute() {
+    let mut audio = Audio::new();
+    let sound = audio.load_sound("HIT", &[]);
+    let voice = audio.play_sfx(sound, 0.5, 1.0).unwrap();
+    audio.set_bus_volume(Bus::Master, 0.5);
+    audio.set_bus_volume(Bus::Sfx, 0.8);
+    audio.update(0.0);
+    assert_eq!(audio.voice_gain(voice), Some(0.2));
+
+    audio.set_bus_muted(Bus::Sfx, true);
+    audio.update(0.0);
+    assert_eq!(audio.voice_gain(voice), Some(0.0));
+    assert_eq!(audio.bus(Bus::Sfx).volume, 0.8);
+
+    // Muting music leaves sound effects alone
+    audio.set_bus_muted(Bus::Sfx, false);
+    audio.set_bus_muted(Bus::Music, true);
+    audio.update(0.0);
+    assert_eq!(audio.voice_gain(voice), Some(0.2));
+}
+
+#[test]
+fn music_crossfades() {
+    let mut audio = Audio::new();
+    let first = audio.load_stream("FIRST", "first.ogg");
+    let second = audio.load_stream("SECOND", "second.ogg");
+
+    let old = audio.play_music(first, 0.0).unwrap();
+    audio.update(0.5);
+    assert_eq!(audio.voice_gain(old), Some(1.0));
+
+    let new = audio.play_music(second, 2.0).unwrap();
+    assert_eq!(audio.music(), Some(new));
+    audio.update(1.0);
+    assert_eq!(audio.voice_gain(old), Some(0.5));
+    assert_eq!(audio.voice_gain(new), Some(0.5));
+    audio.update(1.0);
+    assert!(!audio.is_playing(old));
+    assert_eq!(audio.voice_gain(new), Some(1.0));
+
+    // Music loops instead of finishing
+    audio.update(100.0);
+    assert!(audio.is_playing(new));
+}
+
+#[test]
+fn stop_music_fades_out() {
+    let mut audio = Audio::new();
+    let sound = audio.load_sound("SONG", &[]);
+    let music = audio.play_music(sound, 0.0).unwrap();
+    audio.stop_music(1.0);
+    assert_eq!(audio.music(), None);
+    audio.update(0.5);
+    assert_eq!(audio.voice_gain(music), Some(0.5));
+    audio.update(0.5);
+    assert!(!audio.is_playing(music));
+}
+
+#[test]
+fn bus_names() {
+    assert_eq!("music".parse::<Bus>(), Ok(Bus::Music));
+    assert_eq!(Bus::Sfx.to_string(), "sfx");
+    assert!("voice".parse::<Bus>().is_err());
+}
@@ -0,0 +1,266 @@
+use moon_engine::camera::*;
+use moon_engine::collider::AABB;
+use moon_engine::Vec2;
+
+#[test]
+fn follow_without_smoothing() {
+    let mut camera = Camera::new();
+    let mut controller = CameraController::new();
+    controller.smoothing = 0.0;
+    controller.follow(Vec2::new(3.0, -2.0));
+    controller.update(&mut camera, 0.016);
+    assert_eq!(controller.position, Vec2::new(3.0, -2.0))
+}
+
+#[test]
+fn follow_with_smoothing() {
+    let mut camera = Camera::new();
+    let mut controller = CameraController::new();
+    controller.follow(Vec2::new(4.0, 0.0));
+    controller.update(&mut camera, 0.016);
+    assert!(controller.position.x > 0.0 && controller.position.x < 4.0)
+}
+
+#[test]
+fn target_inside_dead_zone() {
+    let mut camera = Camera::new();
+    let mut controller = CameraController::new();
+    controller.smoothing = 0.0;
+    controller.dead_zone = Vec2::new(2.0, 2.0);
+    controller.follow(Vec2::new(0.9, -0.9));
+    controller.update(&mut camera, 0.016);
+    assert_eq!(controller.position, Vec2::zeros())
+}
+
+#[test]
+fn target_outside_dead_zone() {
+    let mut camera = Camera::new();
+    let mut controller = CameraController::new();
+    controller.smoothing = 0.0;
+    controller.dead_zone = Vec2::new(2.0, 2.0);
+    controller.follow(Vec2::new(3.0, 0.0));
+    controller.update(&mut camera, 0.016);
+    assert_eq!(controller.position, Vec2::new(2.0, 0.0))
+}
+
+#[test]
+fn position_clamped_to_bounds() {
+    let mut camera = Camera::new();
+    let mut controller = CameraController::new();
+    controller.smoothing = 0.0;
+    controller.bounds = Some(AABB::new_size(40.0, 40.0));
+    controller.follow(Vec2::new(100.0, 0.0));
+    controller.update(&mut camera, 0.016);
+    let half_width = camera.view_size().x / 2.0;
+    assert!((controller.position.x - (20.0 - half_width)).abs() < 0.0001)
+}
+
+#[test]
+fn small_bounds_are_centered() {
+    let mut camera = Camera::new();
+    let mut controller = CameraController::new();
+    controller.smoothing = 0.0;
+    controller.bounds = Some(AABB::new_position_and_size(5.0, 5.0, 1.0, 1.0));
+    controller.follow(Vec2::new(100.0, 100.0));
+    controller.update(&mut camera, 0.016);
+    assert_eq!(controller.position, Vec2::new(5.0, 5.0))
+}
+
+#[test]
+fn no_shake_without_trauma() {
+    let mut camera = Camera::new();
+    let mut controller = CameraController::new();
+    controller.update(&mut camera, 0.016);
+    assert_eq!(controller.shake_offset(), Vec2::zeros())
+}
+
+#[test]
+fn trauma_decays() {
+    let mut camera = Camera::new();
+    let mut controller = CameraController::new();
+    controller.add_trauma(2.0);
+    assert_eq!(controller.trauma, 1.0);
+    for _ in 0..100 {
+        controller.update(&mut camera, 0.1);
+    }
+    assert_eq!(controller.trauma, 0.0);
+    assert_eq!(controller.shake_offset(), Vec2::zeros())
+}
+
+#[test]
+fn shake_is_deterministic() {
+    let (mut camera_a, mut camera_b) = (Camera::new(), Camera::new());
+    let mut a = CameraController::new_with_seed(7);
+    let mut b = CameraController::new_with_seed(7);
+    a.add_trauma(1.0);
+    b.add_trauma(1.0);
+    for _ in 0..10 {
+        a.update(&mut camera_a, 0.05);
+        b.update(&mut camera_b, 0.05);
+        assert_eq!(a.shake_offset(), b.shake_offset());
+    }
+    assert_ne!(a.shake_offset(), Vec2::zeros())
+}
+
+#[test]
+fn zoom_approaches_target() {
+    let mut camera = Camera::new();
+    let mut controller = CameraController::new();
+    let size = camera.view_size();
+    controller.zoom_to(2.0);
+    for _ in 0..200 {
+        controller.update(&mut camera, 0.05);
+    }
+    assert!((camera.zoom() - 2.0).abs() < 0.001);
+    assert!((camera.view_size().x - size.x / 2.0).abs() < 0.001)
+}
+
+fn assert_round_trip(camera: &Camera) {
+    for (screen_x, screen_y) in [(0.0, 0.0), (640.0, 360.0), (1280.0, 720.0), (100.0, 650.0)] {
+        let (world_x, world_y) = camera.screen_to_world_coordinates(screen_x, screen_y);
+        let (x, y) = camera.world_to_screen_coordinates(world_x, world_y);
+        assert!((x - screen_x).abs() < 0.01, "{} != {}", x, screen_x);
+        assert!((y - screen_y).abs() < 0.01, "{} != {}", y, screen_y);
+    }
+}
+
+#[test]
+fn screen_center_is_camera_position() {
+    let mut camera = Camera::new();
+    camera.set_width_and_height(1280.0, 720.0);
+    camera.focus_on(Vec2::new(3.0, -4.0));
+    let (x, y) = camera.screen_to_world_coordinates(640.0, 360.0);
+    assert!((x - 3.0).abs() < 0.0001 && (y + 4.0).abs() < 0.0001)
+}
+
+#[test]
+fn screen_corner_is_view_edge() {
+    let mut camera = Camera::with_scaling(ScalingMode::FixedWidth(20.0));
+    camera.set_width_and_height(1280.0, 720.0);
+    let (x, y) = camera.screen_to_world_coordinates(0.0, 0.0);
+    let half = camera.view_size() / 2.0;
+    assert!((x + 10.0).abs() < 0.0001);
+    assert!((y + half.y).abs() < 0.0001)
+}
+
+#[test]
+fn round_trip_with_every_scaling_mode() {
+    for scaling in [
+        ScalingMode::FixedWidth(20.0),
+        ScalingMode::FixedHeight(10.0),
+        ScalingMode::Fit {
+            width: 16.0,
+            height: 16.0,
+        },
+        ScalingMode::Fill {
+            width: 16.0,
+            height: 16.0,
+        },
+        ScalingMode::PixelPerfect {
+            width: 320.0,
+            height: 180.0,
+        },
+    ] {
+        let mut camera = Camera::with_scaling(scaling);
+        camera.set_width_and_height(1280.0, 720.0);
+        assert_round_trip(&camera);
+    }
+}
+
+#[test]
+fn round_trip_with_position_zoom_and_rotation() {
+    let mut camera = Camera::new();
+    camera.set_width_and_height(1280.0, 720.0);
+    camera.focus_on(Vec2::new(-7.5, 2.25));
+    camera.set_zoom(2.5);
+    camera.set_rotation(0.7);
+    assert_round_trip(&camera);
+}
+
+#[test]
+fn zoom_shrinks_view() {
+    let mut camera = Camera::new();
+    camera.set_width_and_height(1280.0, 720.0);
+    let size = camera.view_size();
+    camera.set_zoom(2.0);
+    assert!((camera.view_size() - size / 2.0).norm() < 0.0001)
+}
+
+#[test]
+fn fit_shows_whole_area() {
+    let size = ScalingMode::Fit {
+        width: 10.0,
+        height: 10.0,
+    }
+    .view_size(200.0, 100.0);
+    assert_eq!(size, Vec2::new(20.0, 10.0))
+}
+
+#[test]
+fn fill_covers_screen() {
+    let size = ScalingMode::Fill {
+        width: 10.0,
+        height: 10.0,
+    }
+    .view_size(200.0, 100.0);
+    assert_eq!(size, Vec2::new(10.0, 5.0))
+}
+
+#[test]
+fn pixel_perfect_uses_integer_scale() {
+    let size = ScalingMode::PixelPerfect {
+        width: 320.0,
+        height: 180.0,
+    }
+    .view_size(1000.0, 600.0);
+    // 1000 / 320 and 600 / 180 both round down to a scale of 3
+    assert!((size.x - 1000.0 / 3.0).abs() < 0.0001);
+    assert!((size.y - 200.0).abs() < 0.0001)
+}
+
+#[test]
+fn viewport_pixel_rect_from_bottom_left() {
+    let (x, y, width, height) = Viewport::TOP.pixel_rect(1280.0, 720.0);
+    assert_eq!((x, y, width, height), (0, 360, 1280, 360))
+}
+
+#[test]
+fn split_screen_round_trip() {
+    for viewport in [Viewport::LEFT, Viewport::RIGHT] {
+        let mut camera = Camera::with_viewport(viewport);
+        camera.set_width_and_height(1280.0, 720.0);
+        camera.focus_on(Vec2::new(1.0, 2.0));
+        let (x, y) = camera.world_to_screen_coordinates(1.0, 2.0);
+        let center_x = (viewport.x + viewport.width / 2.0) * 1280.0;
+        assert!((x - center_x).abs() < 0.01 && (y - 360.0).abs() < 0.01);
+        let (world_x, world_y) = camera.screen_to_world_coordinates(x, y);
+        assert!((world_x - 1.0).abs() < 0.0001 && (world_y - 2.0).abs() < 0.0001);
+    }
+}
+
+#[test]
+fn viewport_narrows_view() {
+    let mut full = Camera::with_scaling(ScalingMode::FixedHeight(10.0));
+    let mut half = Camera::with_scaling(ScalingMode::FixedHeight(10.0));
+    full.set_width_and_height(1280.0, 720.0);
+    half.set_width_and_height(1280.0, 720.0);
+    half.set_viewport(Viewport::LEFT);
+    assert!((half.view_size().x - full.view_size().x / 2.0).abs() < 0.0001)
+}
+
+#[test]
+fn camera_layer_mask() {
+    let mut camera = Camera::new();
+    assert!(camera.draws_layer(0) && camera.draws_layer(31));
+    camera.layers = 1 << 2;
+    assert!(!camera.draws_layer(0));
+    assert!(camera.draws_layer(2))
+}
+
+#[test]
+fn camera_contains_screen_point() {
+    let mut camera = Camera::with_viewport(Viewport::RIGHT);
+    camera.set_width_and_height(1280.0, 720.0);
+    assert!(camera.contains_screen_point(1000.0, 100.0));
+    assert!(!camera.contains_screen_point(100.0, 100.0))
+}
@@ -76,3 +76,25 @@ fn circle_and_circle_edges() {
     let c2 = Circle::new_position(2.0, 0.0);
     assert_eq!(c1.collide_with(&c2), false)
 }
+
+#[test]
+fn point_in_polygon() {
+    let p = Point::new(0.2, 0.2);
+    let polygon = Polygon::new(vec![
+        Point::new(0.0, 0.0),
+        Point::new(1.0, 0.0),
+        Point::new(0.0, 1.0),
+    ]);
+    assert!(polygon.collide_with(&p))
+}
+
+#[test]
+fn point_not_in_polygon() {
+    let p = Point::new(0.8, 0.8);
+    let polygon = Polygon::new(vec![
+        Point::new(0.0, 0.0),
+        Point::new(1.0, 0.0),
+        Point::new(0.0, 1.0),
+    ]);
+    assert!(!p.collide_with(&polygon))
+}
@@ -0,0 +1,19 @@
+//! Helpers shared by the integration tests.
+
+// Each test crate only uses some of the helpers
+#![allow(dead_code)]
+
+use moon_engine::texture::SubTexture;
+use moon_engine::tilemap::{Tilemap, Tileset};
+use moon_engine::Vec2;
+
+/// Assert that two points are within a small distance of each other.
+pub fn assert_close(a: Vec2, b: Vec2) {
+    assert!((a - b).norm() < 1e-4, "{:?} != {:?}", a, b)
+}
+
+/// Create an empty [`Tilemap`] with a [`Tileset`] of four tiles in two columns, and tiles of a size in world units.
+pub fn tilemap(width: u32, height: u32, tile_size: Vec2) -> Tilemap {
+    let tileset = Tileset::new_from_tiles(vec![SubTexture::default(); 4], 2);
+    Tilemap::new_with_size(tileset, width, height, tile_size)
+}
@@ -0,0 +1,24 @@
+use moon_engine::framebuffer::RenderTarget;
+
+#[test]
+fn default_target_size() {
+    let target = RenderTarget::default();
+    assert_eq!(target.size(), (1, 1));
+    assert!(!target.has_depth_stencil());
+
+    let texture = target.texture();
+    assert_eq!((texture.width, texture.height), target.size());
+}
+
+#[test]
+fn resize_only_when_size_changes() {
+    let target = RenderTarget::default();
+    assert!(!target.needs_resize(1, 1));
+    assert!(target.needs_resize(2, 1));
+    assert!(target.needs_resize(1, 640));
+
+    // Empty sizes are clamped to a single pixel
+    assert!(!target.needs_resize(0, 0));
+    assert!(!target.needs_resize(1, 0));
+    assert!(target.needs_resize(0, 2));
+}
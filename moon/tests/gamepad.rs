@@ -0,0 +1,141 @@
+use moon_engine::action::InputBinding;
+use moon_engine::gamepad::*;
+use moon_engine::input::InputManager;
+use moon_engine::Vec2;
+
+fn standard_gamepad(index: u32) -> GamepadState {
+    GamepadState {
+        index,
+        id: String::from("Fake Gamepad"),
+        connected: true,
+        standard_mapping: true,
+        buttons: vec![0.0; 17],
+        axes: vec![0.0; 4],
+    }
+}
+
+#[test]
+fn dead_zone_remaps_values() {
+    let dead_zone = DeadZone {
+        inner: 0.2,
+        outer: 0.8,
+    };
+    assert_eq!(dead_zone.apply(0.1), 0.0);
+    assert_eq!(dead_zone.apply(-0.9), -1.0);
+    assert!((dead_zone.apply(0.5) - 0.5).abs() < 1e-6);
+}
+
+#[test]
+fn radial_dead_zone_keeps_direction() {
+    let dead_zone = DeadZone {
+        inner: 0.2,
+        outer: 1.0,
+    };
+    assert_eq!(dead_zone.apply_radial(Vec2::new(0.1, 0.1)), Vec2::zeros());
+    let stick = dead_zone.apply_radial(Vec2::new(0.6, 0.0));
+    assert!((stick - Vec2::new(0.5, 0.0)).norm() < 1e-6);
+}
+
+#[test]
+fn connect_and_disconnect_events() {
+    let mut gamepads = Gamepads::new_with_source(Box::new(FakeGamepadSource {
+        gamepads: vec![standard_gamepad(0), standard_gamepad(1)],
+    }));
+    gamepads.poll();
+    assert_eq!(
+        gamepads.events(),
+        &[GamepadEvent::Connected(0), GamepadEvent::Connected(1)]
+    );
+    gamepads.poll();
+    assert!(gamepads.events().is_empty());
+
+    let mut source = FakeGamepadSource {
+        gamepads: vec![standard_gamepad(1)],
+    };
+    source.gamepads[0].connected = false;
+    gamepads.set_source(Box::new(source));
+    gamepads.poll();
+    assert_eq!(
+        gamepads.events(),
+        &[GamepadEvent::Disconnected(0), GamepadEvent::Disconnected(1)]
+    );
+    assert!(gamepads.is_empty());
+}
+
+#[test]
+fn standard_mapping_applies_dead_zones() {
+    let mut state = standard_gamepad(0);
+    state.axes = vec![0.05, 0.0, 0.0, -1.0];
+    state.buttons[StandardButton::South as usize] = 1.0;
+    state.buttons[StandardButton::RightTrigger as usize] = 0.1;
+    let mut gamepads = Gamepads::new_with_source(Box::new(FakeGamepadSource {
+        gamepads: vec![state],
+    }));
+    gamepads.poll();
+    let gamepad = gamepads.get(0).unwrap();
+    assert_eq!(gamepad.left_stick(), Vec2::zeros());
+    assert_eq!(gamepad.right_stick(), Vec2::new(0.0, -1.0));
+    assert_eq!(gamepad.button(StandardButton::South), 1.0);
+    assert_eq!(gamepad.button(StandardButton::RightTrigger), 0.0);
+}
+
+#[test]
+fn gamepads_drive_actions() {
+    let mut state = standard_gamepad(0);
+    state.buttons[StandardButton::South as usize] = 1.0;
+    state.axes[StandardAxis::LeftStickX as usize] = -1.0;
+
+    let mut input = InputManager::new();
+    input.gamepads.set_source(Box::new(FakeGamepadSource {
+        gamepads: vec![state],
+    }));
+    input
+        .actions
+        .bind_action("jump", InputBinding::GamepadButton(0));
+    input.actions.bind_axis(
+        "move_x",
+        InputBinding::GamepadAxis {
+            axis: 0,
+            positive: true,
+        },
+        InputBinding::GamepadAxis {
+            axis: 0,
+            positive: false,
+        },
+    );
+    input.poll_gamepads();
+    assert!(input.is_action_just_pressed("jump"));
+    assert_eq!(input.get_axis("move_x"), -1.0);
+    input.advance_frame(0.1);
+    input.poll_gamepads();
+    assert!(input.is_action_pressed("jump"));
+    assert!(!input.is_action_just_pressed("jump"));
+}
+
+#[test]
+fn manual_gamepad_input_is_merged_with_polled_gamepads() {
+    let mut state = standard_gamepad(0);
+    state.axes[StandardAxis::LeftStickX as usize] = -1.0;
+
+    let mut input = InputManager::new();
+    input.gamepads.set_source(Box::new(FakeGamepadSource {
+        gamepads: vec![state],
+    }));
+    input
+        .actions
+        .bind_action("jump", InputBinding::GamepadButton(0));
+    input.set_gamepad_button(0, 1.0);
+    input.set_gamepad_axis(StandardAxis::LeftStickY as u8, 0.75);
+    input.poll_gamepads();
+    assert!(input.is_action_just_pressed("jump"));
+    let axis = |axis, positive| InputBinding::GamepadAxis { axis, positive };
+    assert_eq!(input.get_binding_value(axis(0, false)), 1.0);
+    assert_eq!(input.get_binding_value(axis(1, true)), 0.75);
+
+    // Values set directly are kept until they are set again
+    input.advance_frame(0.1);
+    input.poll_gamepads();
+    assert!(input.is_action_pressed("jump"));
+    input.set_gamepad_button(0, 0.0);
+    assert!(input.is_binding_just_released(InputBinding::GamepadButton(0)));
+}
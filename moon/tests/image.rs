@@ -0,0 +1,271 @@
+use moon_engine::image::*;
+use moon_engine::inflate::*;
+
+/// Build a PNG file, storing its filtered image data uncompressed.
+fn png(header: [u8; 13], chunks: &[(&[u8; 4], &[u8])], filtered: &[u8]) -> Vec<u8> {
+    let mut zlib = vec![0x78, 0x01, 0x01];
+    zlib.extend_from_slice(&(filtered.len() as u16).to_le_bytes());
+    zlib.extend_from_slice(&(!(filtered.len() as u16)).to_le_bytes());
+    zlib.extend_from_slice(filtered);
+    zlib.extend_from_slice(&adler32(filtered).to_be_bytes());
+
+    let mut bytes = vec![0x89, b'P', b'N', b'G', 0x0D, 0x0A, 0x1A, 0x0A];
+    let mut chunks = chunks.to_vec();
+    chunks.insert(0, (b"IHDR", &header));
+    chunks.push((b"IDAT", &zlib));
+    chunks.push((b"IEND", &[]));
+    for (kind, contents) in chunks {
+        let chunk = [&kind[..], contents].concat();
+        bytes.extend_from_slice(&(contents.len() as u32).to_be_bytes());
+        bytes.extend_from_slice(&chunk);
+        bytes.extend_from_slice(&crc32(&chunk).to_be_bytes());
+    }
+    bytes
+}
+
+fn png_header(width: u32, height: u32, bit_depth: u8, color_type: u8, interlace: u8) -> [u8; 13] {
+    let mut header = [0; 13];
+    header[..4].copy_from_slice(&width.to_be_bytes());
+    header[4..8].copy_from_slice(&height.to_be_bytes());
+    header[8..].copy_from_slice(&[bit_depth, color_type, 0, 0, interlace]);
+    header
+}
+
+/// Build a BMP file with a 40 byte info header.
+fn bmp(width: i32, height: i32, bits: u16, palette: &[u8], data: &[u8]) -> Vec<u8> {
+    let offset = 54 + palette.len() as u32;
+    let mut bytes = b"BM".to_vec();
+    bytes.extend_from_slice(&(offset + data.len() as u32).to_le_bytes());
+    bytes.extend_from_slice(&[0; 4]);
+    bytes.extend_from_slice(&offset.to_le_bytes());
+    bytes.extend_from_slice(&40u32.to_le_bytes());
+    bytes.extend_from_slice(&width.to_le_bytes());
+    bytes.extend_from_slice(&height.to_le_bytes());
+    bytes.extend_from_slice(&1u16.to_le_bytes());
+    bytes.extend_from_slice(&bits.to_le_bytes());
+    bytes.extend_from_slice(&[0; 16]);
+    bytes.extend_from_slice(&(palette.len() as u32 / 4).to_le_bytes());
+    bytes.extend_from_slice(&[0; 4]);
+    bytes.extend_from_slice(palette);
+    bytes.extend_from_slice(data);
+    bytes
+}
+
+#[test]
+fn inflate_compressed_blocks() {
+    // Fixed Huffman codes
+    let fixed = [
+        0x78, 0xDA, 0xCB, 0x48, 0xCD, 0xC9, 0xC9, 0x57, 0xC8, 0x40, 0x27, 0x15, 0x01, 0x70, 0xD5,
+        0x08, 0xD2,
+    ];
+    assert_eq!(
+        zlib_decompress(&fixed).unwrap(),
+        b"hello hello hello hello!"
+    );
+
+    // Dynamic Huffman codes
+    let dynamic = [
+        0x78, 0xDA, 0x75, 0xC9, 0xC5, 0x01, 0x80, 0x40, 0x10, 0x00, 0xB1, 0x96, 0x70, 0x19, 0xAA,
+        0x01, 0x0E, 0x77, 0x97, 0xEA, 0x29, 0x80, 0xDD, 0x6F, 0x62, 0x91, 0x66, 0xB9, 0x29, 0xCA,
+        0xAA, 0x6E, 0xDA, 0xAE, 0x1F, 0xC6, 0x69, 0x5E, 0xD6, 0x6D, 0x3F, 0xCE, 0xEB, 0x7E, 0xDE,
+        0xC4, 0x46, 0x3F, 0x07, 0xB5, 0x5C, 0xB4, 0xF1, 0x50, 0xC2, 0x47, 0xF6, 0x00, 0x91, 0x43,
+        0x24, 0x8D, 0x10, 0x30, 0xE6, 0x6F, 0x1F, 0xD3, 0xF5, 0x64, 0x51,
+    ];
+    let alphabet = "abcdefghijklmnopqrstuvwxyz";
+    let expected: String = (0..10)
+        .map(|i| format!("{}:{};", i, &alphabet[i..]))
+        .collect();
+    assert_eq!(zlib_decompress(&dynamic).unwrap(), expected.as_bytes());
+
+    let mut corrupted = fixed;
+    corrupted[16] ^= 1;
+    assert!(zlib_decompress(&corrupted).is_err());
+    assert!(zlib_decompress(&fixed[..10]).is_err());
+    assert!(zlib_decompress(&[0x78, 0x02, 0, 0, 0, 0]).is_err());
+}
+
+#[test]
+fn png_filters() {
+    #[rustfmt::skip]
+    let filtered = [
+        1, 10, 20, 30, 255, 5, 5, 5, 0,
+        2, 2, 2, 2, 0, 5, 5, 5, 129,
+        3, 6, 11, 16, 128, 4, 4, 4, 193,
+        4, 0, 0, 0, 0, 0, 0, 0, 0,
+    ];
+    let image = Image::decode(&png(png_header(2, 4, 8, 6, 0), &[], &filtered)).unwrap();
+    assert_eq!((image.width, image.height), (2, 4));
+    assert!(!image.srgb);
+    assert_eq!(image.get_pixel(0, 0), Some([10, 20, 30, 255]));
+    assert_eq!(image.get_pixel(1, 0), Some([15, 25, 35, 255]));
+    for y in 1..4 {
+        assert_eq!(image.get_pixel(0, y), Some([12, 22, 32, 255]));
+        assert_eq!(image.get_pixel(1, y), Some([20, 30, 40, 128]));
+    }
+    assert_eq!(image.get_pixel(2, 0), None);
+}
+
+#[test]
+fn png_palettes_and_bit_depths() {
+    // Two bits per palette index, with the first color transparent
+    let palette = [0, 0, 0, 255, 0, 0, 0, 255, 0, 0, 0, 255];
+    let image = Image::decode(&png(
+        png_header(4, 1, 2, 3, 0),
+        &[(b"PLTE", &palette[..9]), (b"tRNS", &[0])],
+        &[0, 0b00_01_10_00],
+    ))
+    .unwrap();
+    assert_eq!(
+        image.pixels,
+        [0, 0, 0, 0, 255, 0, 0, 255, 0, 255, 0, 255, 0, 0, 0, 0]
+    );
+
+    // Sixteen bit grayscale, with a transparent color key and an sRGB chunk
+    let image = Image::decode(&png(
+        png_header(2, 1, 16, 0, 0),
+        &[(b"sRGB", &[0]), (b"tRNS", &[0x12, 0x34])],
+        &[0, 0x12, 0x34, 0xFF, 0xFF],
+    ))
+    .unwrap();
+    assert!(image.srgb);
+    assert_eq!(image.pixels, [0x12, 0x12, 0x12, 0, 255, 255, 255, 255]);
+
+    // One bit grayscale is scaled to the full range
+    let image = Image::decode(&png(png_header(3, 1, 1, 0, 0), &[], &[0, 0b1010_0000])).unwrap();
+    assert_eq!(image.get_pixel(0, 0), Some([255, 255, 255, 255]));
+    assert_eq!(image.get_pixel(1, 0), Some([0, 0, 0, 255]));
+}
+
+#[test]
+fn png_interlacing() {
+    let value = |x: u8, y: u8| (y * 3 + x) * 10;
+    #[rustfmt::skip]
+    let filtered = [
+        0, value(0, 0),
+        0, value(2, 0),
+        0, value(0, 2), value(2, 2),
+        0, value(1, 0),
+        0, value(1, 2),
+        0, value(0, 1), value(1, 1), value(2, 1),
+    ];
+    let image = Image::decode(&png(png_header(3, 3, 8, 0, 1), &[], &filtered)).unwrap();
+    for y in 0..3 {
+        for x in 0..3 {
+            let v = value(x as u8, y as u8);
+            assert_eq!(image.get_pixel(x, y), Some([v, v, v, 255]));
+        }
+    }
+
+    let mut bytes = png(png_header(3, 3, 8, 0, 1), &[], &filtered[..8]);
+    assert!(Image::decode(&bytes).is_err());
+    bytes = png(png_header(3, 3, 8, 0, 1), &[], &filtered);
+    bytes[20] ^= 1;
+    assert_eq!(
+        Image::decode(&bytes),
+        Err(String::from("PNG chunk checksum does not match."))
+    );
+}
+
+#[test]
+fn qoi_operations() {
+    let mut bytes = b"qoif".to_vec();
+    bytes.extend_from_slice(&3u32.to_be_bytes());
+    bytes.extend_from_slice(&2u32.to_be_bytes());
+    bytes.extend_from_slice(&[4, 0]);
+    // RGB, DIFF, INDEX, RGBA, LUMA and RUN
+    bytes.extend_from_slice(&[
+        0xFE, 10, 20, 30, 0x79, 0x09, 0xFF, 1, 2, 3, 4, 0xA4, 0x97, 0xC0,
+    ]);
+    bytes.extend_from_slice(&[0, 0, 0, 0, 0, 0, 0, 1]);
+
+    let image = Image::decode(&bytes).unwrap();
+    assert!(image.srgb);
+    assert_eq!(
+        image.pixels.chunks(4).collect::<Vec<_>>(),
+        [
+            [10, 20, 30, 255],
+            [11, 20, 29, 255],
+            [10, 20, 30, 255],
+            [1, 2, 3, 4],
+            [6, 6, 6, 4],
+            [6, 6, 6, 4],
+        ]
+    );
+    assert!(Image::decode(&bytes[..20]).is_err());
+}
+
+#[test]
+fn bmp_bit_depths() {
+    // Bottom-up rows, padded to four bytes
+    #[rustfmt::skip]
+    let data = [
+        0, 0, 255, 0, 255, 0, 0, 0,
+        255, 0, 0, 255, 255, 255, 0, 0,
+    ];
+    let image = Image::decode(&bmp(2, 2, 24, &[], &data)).unwrap();
+    assert_eq!(image.get_pixel(0, 0), Some([0, 0, 255, 255]));
+    assert_eq!(image.get_pixel(1, 0), Some([255, 255, 255, 255]));
+    assert_eq!(image.get_pixel(0, 1), Some([255, 0, 0, 255]));
+    assert_eq!(image.get_pixel(1, 1), Some([0, 255, 0, 255]));
+
+    // Top-down rows, with an unused alpha channel
+    let image = Image::decode(&bmp(1, -2, 32, &[], &[1, 2, 3, 0, 4, 5, 6, 0])).unwrap();
+    assert_eq!(image.pixels, [3, 2, 1, 255, 6, 5, 4, 255]);
+
+    let palette = [0, 0, 0, 0, 50, 100, 150, 0];
+    let image = Image::decode(&bmp(2, 1, 8, &palette, &[1, 0, 0, 0])).unwrap();
+    assert_eq!(image.pixels, [150, 100, 50, 255, 0, 0, 0, 255]);
+
+    assert!(Image::decode(&bmp(2, 1, 8, &palette, &[2, 0, 0, 0])).is_err());
+    assert!(Image::decode(&bmp(2, 1, 16, &[], &[0; 4])).is_err());
+}
+
+#[test]
+fn premultiply_and_flip() {
+    let mut image = Image::new_from_pixels(1, 2, vec![200, 100, 50, 0, 255, 255, 255, 51]).unwrap();
+    image.flip_vertically();
+    assert_eq!(image.pixels, [255, 255, 255, 51, 200, 100, 50, 0]);
+
+    image.premultiply_alpha();
+    image.premultiply_alpha();
+    assert!(image.premultiplied);
+    assert_eq!(image.pixels, [51, 51, 51, 51, 0, 0, 0, 0]);
+
+    assert!(Image::new_from_pixels(2, 2, vec![0; 4]).is_err());
+    assert_eq!(ImageFormat::detect(b"GIF89a"), None);
+    assert!(Image::decode(b"GIF89a").is_err());
+}
+
+#[test]
+fn oversized_and_malformed_headers() {
+    let huge = u32::MAX;
+    assert!(Image::decode(&png(png_header(huge, huge, 16, 6, 0), &[], &[0; 9])).is_err());
+    assert!(Image::decode(&png(png_header(16384, 16384, 8, 6, 1), &[], &[0; 9])).is_err());
+    // More image data than a 1x1 image can use
+    assert_eq!(
+        Image::decode(&png(png_header(1, 1, 8, 0, 0), &[], &[0; 64])),
+        Err(String::from("Decompressed data is larger than expected."))
+    );
+    assert!(Image::decode(&png(png_header(1, 1, 8, 0, 0), &[], &[0, 7])).is_ok());
+
+    for (width, height) in [(huge, huge), (16385, 1), (16384, 16384)] {
+        let mut qoi = b"qoif".to_vec();
+        qoi.extend_from_slice(&width.to_be_bytes());
+        qoi.extend_from_slice(&height.to_be_bytes());
+        qoi.extend_from_slice(&[4, 0, 0xFF, 1, 2, 3, 4]);
+        assert!(Image::decode(&qoi).is_err());
+    }
+
+    assert!(Image::decode(&bmp(i32::MAX, i32::MIN, 32, &[], &[0; 4])).is_err());
+    assert!(Image::decode(&bmp(16384, 16384, 24, &[], &[0; 4])).is_err());
+    let mut bytes = bmp(1, 1, 8, &[0; 4], &[0; 4]);
+    bytes[14..18].copy_from_slice(&u32::MAX.to_le_bytes());
+    assert!(Image::decode(&bytes).is_err());
+    let mut bytes = bmp(1, 1, 24, &[], &[0; 4]);
+    bytes[10..14].copy_from_slice(&u32::MAX.to_le_bytes());
+    assert!(Image::decode(&bytes).is_err());
+
+    assert!(Image::new_from_pixels(huge, huge, Vec::new()).is_err());
+    let stored = [0x01, 0x03, 0x00, 0xFC, 0xFF, b'a', b'b', b'c'];
+    assert!(inflate_with_limit(&stored, 2).is_err());
+}
@@ -0,0 +1,74 @@
+use moon_engine::action::InputBinding;
+use moon_engine::input::InputManager;
+
+#[test]
+fn just_pressed_lasts_one_frame() {
+    let mut input = InputManager::new();
+    input.key_down(b'1');
+    assert!(input.is_key_just_pressed(b'1'));
+    input.advance_frame(0.1);
+    // Held keys repeat key down events, which should not count as new presses
+    input.key_down(b'1');
+    assert!(input.get_key_state(b'1'));
+    assert!(!input.is_key_just_pressed(b'1'));
+}
+
+#[test]
+fn just_released_after_hold() {
+    let mut input = InputManager::new();
+    input.key_down(b'A');
+    input.advance_frame(0.1);
+    assert!(!input.is_key_just_released(b'A'));
+    input.key_up(b'A');
+    assert!(input.is_key_just_released(b'A'));
+    input.advance_frame(0.1);
+    assert!(!input.is_key_just_released(b'A'));
+}
+
+#[test]
+fn tap_within_a_frame_is_detected() {
+    let mut input = InputManager::new();
+    input.key_down(b' ');
+    input.key_up(b' ');
+    assert!(!input.get_key_state(b' '));
+    assert!(input.is_key_just_pressed(b' '));
+    assert!(input.is_key_just_released(b' '));
+}
+
+#[test]
+fn held_time_accumulates() {
+    let mut input = InputManager::new();
+    input.key_down(b'W');
+    input.advance_frame(0.25);
+    input.advance_frame(0.5);
+    assert_eq!(input.get_key_held_time(b'W'), 0.75);
+    input.key_up(b'W');
+    input.advance_frame(0.25);
+    assert_eq!(input.get_key_held_time(b'W'), 0.0);
+}
+
+#[test]
+fn action_edges() {
+    let mut input = InputManager::new();
+    input.actions.bind_action("toggle", InputBinding::Key(b'1'));
+    input.actions.bind_action("toggle", InputBinding::Key(b'2'));
+    input.key_down(b'1');
+    assert!(input.is_action_just_pressed("toggle"));
+    input.advance_frame(0.1);
+    input.key_down(b'2');
+    assert!(!input.is_action_just_pressed("toggle"));
+    input.key_up(b'1');
+    input.key_up(b'2');
+    assert!(input.is_action_just_released("toggle"));
+}
+
+#[test]
+fn text_input_is_cleared_each_frame() {
+    let mut input = InputManager::new();
+    input.text_input("H");
+    input.text_input("i");
+    assert_eq!(input.get_text_input(), "Hi");
+    assert!(!input.get_key_state(b'H'));
+    input.advance_frame(0.1);
+    assert_eq!(input.get_text_input(), "");
+}
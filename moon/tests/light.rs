@@ -0,0 +1,69 @@
+use moon_engine::light::*;
+use moon_engine::{Color32, Vec2};
+
+#[test]
+fn point_light_at_center() {
+    let light = Light::new_point(1.0, 1.0, 2.0, Color32::WHITE);
+    assert_eq!(light.attenuation(Vec2::new(1.0, 1.0)), 1.0)
+}
+
+#[test]
+fn point_light_outside_radius() {
+    let light = Light::new_point(0.0, 0.0, 2.0, Color32::WHITE);
+    assert_eq!(light.attenuation(Vec2::new(3.0, 0.0)), 0.0)
+}
+
+#[test]
+fn point_light_fades() {
+    let light = Light::new_point(0.0, 0.0, 2.0, Color32::WHITE);
+    let near = light.attenuation(Vec2::new(0.5, 0.0));
+    let far = light.attenuation(Vec2::new(1.5, 0.0));
+    assert!(near > far && far > 0.0)
+}
+
+#[test]
+fn spot_light_in_cone() {
+    let light = Light::new_spot(0.0, 0.0, 5.0, Color32::WHITE, Vec2::x(), 0.5);
+    assert!(light.attenuation(Vec2::new(1.0, 0.0)) > 0.0)
+}
+
+#[test]
+fn spot_light_behind() {
+    let light = Light::new_spot(0.0, 0.0, 5.0, Color32::WHITE, Vec2::x(), 0.5);
+    assert_eq!(light.attenuation(Vec2::new(-1.0, 0.0)), 0.0)
+}
+
+#[test]
+fn batches_without_lights() {
+    let lighting = Lighting::new();
+    let batches = lighting.batches();
+    assert_eq!(batches.len(), 1);
+    assert_eq!(batches[0].count, 0)
+}
+
+#[test]
+fn batches_split_at_max_lights() {
+    const KEYS: [&str; 20] = [
+        "0", "1", "2", "3", "4", "5", "6", "7", "8", "9", "10", "11", "12", "13", "14", "15", "16",
+        "17", "18", "19",
+    ];
+    let mut lighting = Lighting::new();
+    for key in KEYS {
+        lighting.add_light(key, Light::default());
+    }
+    let batches = lighting.batches();
+    assert_eq!(batches.len(), 2);
+    assert_eq!(batches[0].count as usize, MAX_LIGHTS);
+    assert_eq!(batches[0].positions.len(), MAX_LIGHTS * 3);
+    assert_eq!(batches[1].count, 4)
+}
+
+#[test]
+fn occluders_are_tracked() {
+    let mut lighting = Lighting::new();
+    assert!(!lighting.has_occluders());
+    lighting.add_occluder("WALL", Box::new(moon_engine::collider::AABB::default()));
+    assert!(lighting.has_occluders());
+    lighting.remove_occluder("WALL");
+    assert!(!lighting.has_occluders())
+}
@@ -0,0 +1,91 @@
+use moon_engine::input::InputManager;
+use moon_engine::pointer::*;
+use moon_engine::Vec2;
+
+#[test]
+fn pointer_phases() {
+    let mut pointers = PointerInput::new();
+    pointers.pointer_down(3, 0.0, 0.0);
+    assert_eq!(pointers.get_pointer(3).unwrap().phase, PointerPhase::Began);
+    pointers.advance_frame(0.1);
+    assert_eq!(
+        pointers.get_pointer(3).unwrap().phase,
+        PointerPhase::Stationary
+    );
+    pointers.pointer_move(3, 1.0, 0.0);
+    assert_eq!(pointers.get_pointer(3).unwrap().phase, PointerPhase::Moved);
+    pointers.advance_frame(0.1);
+    pointers.pointer_up(3, 1.0, 0.0);
+    assert_eq!(pointers.get_pointer(3).unwrap().phase, PointerPhase::Ended);
+    pointers.advance_frame(0.1);
+    assert!(pointers.get_pointer(3).is_none());
+}
+
+#[test]
+fn slow_press_is_not_a_tap() {
+    let mut pointers = PointerInput::new();
+    pointers.pointer_down(0, 5.0, 5.0);
+    pointers.advance_frame(1.0);
+    pointers.pointer_up(0, 5.0, 5.0);
+    assert!(pointers.gestures().is_empty());
+}
+
+#[test]
+fn drag_after_threshold() {
+    let mut pointers = PointerInput::new();
+    pointers.pointer_down(0, 0.0, 0.0);
+    pointers.pointer_move(0, 5.0, 0.0);
+    assert!(pointers.gestures().is_empty());
+    pointers.pointer_move(0, 20.0, 0.0);
+    pointers.pointer_move(0, 25.0, 5.0);
+    assert_eq!(
+        pointers.gestures(),
+        &[
+            Gesture::Drag {
+                position: Vec2::new(20.0, 0.0),
+                delta: Vec2::new(20.0, 0.0),
+            },
+            Gesture::Drag {
+                position: Vec2::new(25.0, 5.0),
+                delta: Vec2::new(5.0, 5.0),
+            },
+        ]
+    );
+    pointers.pointer_up(0, 25.0, 5.0);
+    assert!(!pointers
+        .gestures()
+        .iter()
+        .any(|gesture| matches!(gesture, Gesture::Tap { .. })));
+}
+
+#[test]
+fn pinch_with_two_pointers() {
+    let mut pointers = PointerInput::new();
+    pointers.pointer_down(0, 0.0, 0.0);
+    pointers.pointer_down(1, 10.0, 0.0);
+    pointers.pointer_move(1, 20.0, 0.0);
+    assert_eq!(
+        pointers.gestures(),
+        &[Gesture::Pinch {
+            center: Vec2::new(10.0, 0.0),
+            scale: 2.0,
+        }]
+    );
+    pointers.pointer_up(1, 20.0, 0.0);
+    pointers.pointer_up(0, 0.0, 0.0);
+    assert_eq!(pointers.gestures().len(), 1);
+}
+
+#[test]
+fn mouse_buttons_and_wheel() {
+    let mut input = InputManager::new();
+    input.mouse_button_down(2);
+    input.mouse_wheel(0.0, 3.0);
+    input.mouse_wheel(1.0, -1.0);
+    assert!(input.is_mouse_button_just_pressed(2));
+    assert_eq!(input.get_wheel_delta(), Vec2::new(1.0, 2.0));
+    input.advance_frame(0.1);
+    input.mouse_button_up(2);
+    assert!(input.is_mouse_button_just_released(2));
+    assert_eq!(input.get_wheel_delta(), Vec2::zeros());
+}
@@ -0,0 +1,157 @@
+use moon_engine::random::*;
+use moon_engine::{Color32, Random, Vec2};
+
+#[test]
+fn same_seed_same_sequence() {
+    let mut first = Rng::new_with_seed(1234);
+    let mut second = Rng::new_with_seed(1234);
+    let mut other = Rng::new_with_seed(1235);
+    let values: Vec<u32> = (0..16).map(|_| first.next_u32()).collect();
+    assert_eq!(
+        values,
+        (0..16).map(|_| second.next_u32()).collect::<Vec<_>>()
+    );
+    assert_ne!(
+        values,
+        (0..16).map(|_| other.next_u32()).collect::<Vec<_>>()
+    );
+}
+
+#[test]
+fn values_stay_in_range() {
+    let mut rng = Rng::new_with_seed(5);
+    for _ in 0..1000 {
+        let value = f32::random_range_with(&mut rng, -2.0, 3.0);
+        assert!((-2.0..3.0).contains(&value));
+        assert!(rng.below(7) < 7);
+        let color: Color32 = rng.random();
+        assert_eq!(color.a(), 1.0);
+    }
+}
+
+#[test]
+fn geometric_helpers() {
+    let mut rng = Rng::new_with_seed(9);
+    for _ in 0..1000 {
+        assert!((rng.unit_vector().norm() - 1.0).abs() < 1e-5);
+        let center = Vec2::new(3.0, -1.0);
+        assert!((rng.point_in_circle(center, 0.5) - center).norm() <= 0.5 + 1e-5);
+        let point = rng.point_in_rect(Vec2::new(-1.0, 2.0), Vec2::new(1.0, 4.0));
+        assert!((-1.0..1.0).contains(&point.x) && (2.0..4.0).contains(&point.y));
+    }
+}
+
+#[test]
+fn weighted_choice_follows_weights() {
+    let mut rng = Rng::new_with_seed(77);
+    assert_eq!(rng.weighted_choice(&[]), None);
+    assert_eq!(rng.weighted_choice(&[0.0, -1.0]), None);
+
+    let mut counts = [0; 3];
+    for _ in 0..3000 {
+        counts[rng.weighted_choice(&[1.0, 0.0, 2.0]).unwrap()] += 1;
+    }
+    assert_eq!(counts[1], 0);
+    assert!(counts[2] > counts[0] * 3 / 2);
+}
+
+#[test]
+fn shuffle_keeps_elements() {
+    let mut rng = Rng::new_with_seed(3);
+    let mut items: Vec<u32> = (0..20).collect();
+    rng.shuffle(&mut items);
+    assert_ne!(items, (0..20).collect::<Vec<_>>());
+    items.sort_unstable();
+    assert_eq!(items, (0..20).collect::<Vec<_>>());
+    assert_eq!(rng.choose::<u32>(&[]), None);
+    assert_eq!(rng.choose(&[4]), Some(&4));
+}
+
+#[test]
+fn shared_rng_is_seedable() {
+    seed_random(11);
+    let shared = f32::random();
+    assert_eq!(shared, Rng::new_with_seed(11).next_f32());
+}
+
+#[test]
+fn index_covers_lengths_beyond_u32() {
+    let mut rng = Rng::new_with_seed(21);
+    for _ in 0..100 {
+        assert!(rng.below_u64(3) < 3);
+    }
+    // Small lengths use the same values as `below`
+    let mut other = Rng::new_with_seed(21);
+    let mut rng = Rng::new_with_seed(21);
+    assert_eq!(rng.index(10), other.below(10) as usize);
+    if let Ok(len) = usize::try_from(u32::MAX as u64 + 10) {
+        assert!(rng.index(len) < len);
+    }
+}
+
+/// A type that only implements the [`Rng`] methods of [`Random`].
+#[derive(Debug, PartialEq)]
+struct Dice(u32);
+
+impl Random for Dice {
+    fn random_with(rng: &mut Rng) -> Self {
+        Dice(rng.below(6) + 1)
+    }
+    fn random_range_max_with(rng: &mut Rng, max: Self) -> Self {
+        Dice(rng.below(max.0) + 1)
+    }
+    fn random_range_with(rng: &mut Rng, min: Self, max: Self) -> Self {
+        Dice(min.0 + rng.below(max.0 - min.0 + 1))
+    }
+}
+
+#[test]
+fn shared_methods_follow_the_seeded_rng() {
+    let rolls = |rng: &mut Rng| {
+        (0..16)
+            .map(|_| {
+                Dice::random_with(rng).0 * 100 + Dice::random_range_with(rng, Dice(2), Dice(3)).0
+            })
+            .collect::<Vec<_>>()
+    };
+    let expected = rolls(&mut Rng::new_with_seed(8));
+
+    seed_random(8);
+    let shared = (0..16)
+        .map(|_| Dice::random().0 * 100 + Dice::random_range(Dice(2), Dice(3)).0)
+        .collect::<Vec<_>>();
+    assert_eq!(shared, expected);
+
+    seed_random(9);
+    let mut rng = Rng::new_with_seed(9);
+    for _ in 0..16 {
+        assert_eq!(
+            Dice::random_range_max(Dice(4)),
+            Dice::random_range_max_with(&mut rng, Dice(4))
+        );
+    }
+}
+
+#[test]
+fn particles_follow_the_rng_they_are_given() {
+    use moon_engine::component::Component;
+    use moon_engine::particle::{ParticleProps, ParticleSystem};
+
+    let positions = |seed| {
+        let mut system = ParticleSystem::new_from_emission(ParticleProps::fire());
+        system.init();
+        let mut rng = Rng::new_with_seed(seed);
+        system.update_with_rng(0.1, &mut rng);
+        system.update_with_rng(0.1, &mut rng);
+        system
+            .get_quads()
+            .unwrap()
+            .iter()
+            .flat_map(|quad| quad.get_vertices())
+            .map(|vertex| vertex.position)
+            .collect::<Vec<[f32; 2]>>()
+    };
+    assert_eq!(positions(8).len(), 10 * 4);
+    assert_eq!(positions(8), positions(8));
+    assert_ne!(positions(8), positions(9));
+}
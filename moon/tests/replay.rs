@@ -0,0 +1,118 @@
+use moon_engine::gamepad::GamepadState;
+use moon_engine::replay::*;
+use moon_engine::{seed_random, Random};
+
+fn sample_recording() -> InputRecording {
+    let mut recording = InputRecording::new(0xDEAD_BEEF);
+    recording.push_event(InputEvent::Resize {
+        width: 800.0,
+        height: 600.0,
+    });
+    recording.push_event(InputEvent::Text(String::from("héllo")));
+    recording.end_frame(16, &[]);
+    recording.push_event(InputEvent::MouseMove {
+        mouse_x: -4,
+        mouse_y: 12,
+    });
+    recording.push_event(InputEvent::TouchStart {
+        id: 3,
+        touch_x: 1.5,
+        touch_y: 2.5,
+    });
+    recording.push_event(InputEvent::TouchCancel { id: 3 });
+    recording.end_frame(
+        17,
+        &[GamepadState {
+            index: 0,
+            id: String::from("Fake Gamepad"),
+            connected: true,
+            standard_mapping: true,
+            buttons: vec![1.0, 0.5],
+            axes: vec![-1.0],
+        }],
+    );
+    recording
+}
+
+#[test]
+fn bytes_round_trip() {
+    let recording = sample_recording();
+    let bytes = recording.to_bytes();
+    assert_eq!(InputRecording::from_bytes(&bytes), Ok(recording));
+}
+
+#[test]
+fn invalid_bytes_are_rejected() {
+    let bytes = sample_recording().to_bytes();
+    assert!(InputRecording::from_bytes(b"NOPE").is_err());
+    assert!(InputRecording::from_bytes(&bytes[..bytes.len() - 1]).is_err());
+
+    // A text length running past the end of the recording
+    let text = "héllo".as_bytes();
+    let start = bytes
+        .windows(text.len())
+        .position(|window| window == text)
+        .unwrap();
+    let mut long_text = bytes.clone();
+    long_text[start - 4..start].copy_from_slice(&u32::MAX.to_le_bytes());
+    assert!(InputRecording::from_bytes(&long_text).is_err());
+
+    let mut extra = bytes;
+    extra.push(0);
+    assert!(InputRecording::from_bytes(&extra).is_err());
+}
+
+#[test]
+fn unchanged_gamepads_are_not_stored() {
+    let mut recording = sample_recording();
+    let gamepads = recording.frames[1].gamepads.clone().unwrap();
+    recording.end_frame(16, &gamepads);
+    assert_eq!(recording.frames[2].gamepads, None);
+    recording.end_frame(16, &[]);
+    assert_eq!(recording.frames[3].gamepads, Some(vec![]));
+}
+
+#[test]
+fn player_returns_frames_in_order() {
+    let mut player = InputPlayer::new(sample_recording());
+    assert_eq!(player.seed(), 0xDEAD_BEEF);
+    assert_eq!(player.next_frame().unwrap().delta_time, 16);
+    assert_eq!(player.next_frame().unwrap().events.len(), 3);
+    assert!(player.is_finished());
+    assert!(player.next_frame().is_none());
+}
+
+#[test]
+fn seeded_random_values_repeat() {
+    seed_random(7);
+    let first: Vec<f32> = (0..8).map(|_| f32::random()).collect();
+    seed_random(7);
+    let second: Vec<f32> = (0..8).map(|_| f32::random()).collect();
+    assert_eq!(first, second);
+    assert!(first.iter().all(|value| (0.0..1.0).contains(value)));
+}
+
+#[test]
+fn player_holds_live_resize_until_finished() {
+    let mut player = InputPlayer::new(sample_recording());
+    player.hold_live_event(InputEvent::Resize {
+        width: 640.0,
+        height: 480.0,
+    });
+    player.hold_live_event(InputEvent::MouseMove {
+        mouse_x: 1,
+        mouse_y: 2,
+    });
+    player.hold_live_event(InputEvent::Resize {
+        width: 1024.0,
+        height: 768.0,
+    });
+    assert_eq!(
+        player.take_live_events(),
+        [InputEvent::Resize {
+            width: 1024.0,
+            height: 768.0
+        }]
+    );
+    assert!(player.take_live_events().is_empty());
+}
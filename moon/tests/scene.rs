@@ -0,0 +1,108 @@
+mod common;
+
+use std::f32::consts::FRAC_PI_2;
+
+use moon_engine::scene::*;
+use moon_engine::transform::Transform2D;
+use moon_engine::Vec2;
+
+use common::assert_close;
+
+#[test]
+fn child_follows_parent() {
+    let mut scene = SceneGraph::new();
+    let parent = scene.spawn(Transform2D::new_with_position(2.0, 0.0));
+    let child = scene
+        .spawn_child(parent, Transform2D::new_with_position(1.0, 0.0))
+        .unwrap();
+    assert_close(scene.world(child).unwrap().position, Vec2::new(3.0, 0.0));
+}
+
+#[test]
+fn child_rotates_and_scales_with_parent() {
+    let mut scene = SceneGraph::new();
+    let parent = scene.spawn(Transform2D {
+        rotation: FRAC_PI_2,
+        scale: Vec2::new(2.0, 2.0),
+        ..Default::default()
+    });
+    let child = scene
+        .spawn_child(parent, Transform2D::new_with_position(1.0, 0.0))
+        .unwrap();
+    let world = scene.world(child).unwrap();
+    assert_close(world.position, Vec2::new(0.0, 2.0));
+    assert_close(world.scale, Vec2::new(2.0, 2.0));
+    assert!((world.rotation - FRAC_PI_2).abs() < 1e-4);
+}
+
+#[test]
+fn moving_parent_updates_cached_child() {
+    let mut scene = SceneGraph::new();
+    let parent = scene.spawn(Transform2D::default());
+    let child = scene
+        .spawn_child(parent, Transform2D::new_with_position(1.0, 1.0))
+        .unwrap();
+    assert_close(scene.world(child).unwrap().position, Vec2::new(1.0, 1.0));
+    scene.set_local(parent, Transform2D::new_with_position(5.0, 0.0));
+    assert_close(scene.world(child).unwrap().position, Vec2::new(6.0, 1.0));
+}
+
+#[test]
+fn reparent_keeps_world_position() {
+    let mut scene = SceneGraph::new();
+    let first = scene.spawn(Transform2D::new_with_position(2.0, 3.0));
+    let second = scene.spawn(Transform2D {
+        position: Vec2::new(-4.0, 1.0),
+        rotation: 0.5,
+        scale: Vec2::new(2.0, 2.0),
+        ..Default::default()
+    });
+    let child = scene
+        .spawn_child(first, Transform2D::new_with_position(1.0, 0.0))
+        .unwrap();
+    let before = scene.world(child).unwrap().position;
+    scene.reparent(child, Some(second)).unwrap();
+    assert_eq!(scene.parent(child), Some(second));
+    assert_close(scene.world(child).unwrap().position, before);
+}
+
+#[test]
+fn reparent_rejects_cycles() {
+    let mut scene = SceneGraph::new();
+    let root = scene.spawn(Transform2D::default());
+    let child = scene.spawn_child(root, Transform2D::default()).unwrap();
+    assert!(scene.set_parent(root, Some(child)).is_err());
+    assert!(scene.set_parent(root, Some(root)).is_err());
+}
+
+#[test]
+fn despawn_removes_descendants() {
+    let mut scene = SceneGraph::new();
+    let root = scene.spawn(Transform2D::default());
+    let child = scene.spawn_child(root, Transform2D::default()).unwrap();
+    let grandchild = scene.spawn_child(child, Transform2D::default()).unwrap();
+    scene.despawn(child);
+    assert!(scene.contains(root));
+    assert!(!scene.contains(child));
+    assert!(!scene.contains(grandchild));
+    assert!(scene.children(root).is_empty());
+
+    let reused = scene.spawn(Transform2D::default());
+    assert!(!scene.contains(child) && !scene.contains(grandchild));
+    assert!(scene.contains(reused));
+    assert_eq!(scene.len(), 2);
+}
+
+#[test]
+fn add_composes_transforms() {
+    let parent = Transform2D {
+        position: Vec2::new(1.0, 0.0),
+        rotation: FRAC_PI_2,
+        scale: Vec2::new(2.0, 2.0),
+        ..Default::default()
+    };
+    let child = Transform2D::new_with_position(1.0, 0.0);
+    let combined = parent + child;
+    assert_close(combined.position, Vec2::new(1.0, 2.0));
+    assert_close(combined.scale, Vec2::new(2.0, 2.0));
+}
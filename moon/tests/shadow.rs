@@ -0,0 +1,166 @@
+use moon_engine::collider::*;
+use moon_engine::light::{Light, Lighting};
+use moon_engine::shadow::*;
+use moon_engine::{Color32, Point};
+
+#[test]
+fn box_casts_shadow_away_from_light() {
+    let wall = AABB::new_position(2.0, 0.0);
+    let triangles = shadow_geometry(Point::zeros(), 10.0, &wall.outline());
+    assert!(!triangles.is_empty());
+    assert!(triangles.iter().all(|point| point.x >= 1.5))
+}
+
+#[test]
+fn box_leaves_near_face_lit() {
+    let wall = AABB::new_position(2.0, 0.0);
+    let triangles = shadow_geometry(Point::zeros(), 10.0, &wall.outline());
+    // The face nearest the light is never extruded
+    assert!(!triangles
+        .chunks(3)
+        .any(|triangle| triangle.iter().all(|point| point.x == 1.5)))
+}
+
+#[test]
+fn shadow_reaches_range() {
+    let wall = AABB::new_position(2.0, 0.0);
+    let triangles = shadow_geometry(Point::zeros(), 10.0, &wall.outline());
+    assert!(triangles.iter().any(|point| point.x > 10.0))
+}
+
+#[test]
+fn polygon_winding_is_normalized() {
+    let clockwise = Polygon::new(vec![
+        Point::new(0.0, 0.0),
+        Point::new(0.0, 1.0),
+        Point::new(1.0, 1.0),
+        Point::new(1.0, 0.0),
+    ]);
+    let outline = clockwise.outline();
+    assert!(Polygon::new(outline).signed_area() > 0.0)
+}
+
+#[test]
+fn circle_outline_segments() {
+    let circle = Circle::new_size(1.0);
+    let outline = circle.outline();
+    assert_eq!(outline.len(), CIRCLE_OCCLUDER_SEGMENTS);
+    assert!(outline
+        .iter()
+        .all(|point| (point.norm() - 1.0).abs() < 0.0001))
+}
+
+#[test]
+fn hard_shadows_have_one_sample() {
+    assert_eq!(shadow_samples(Point::zeros(), ShadowMode::Hard).len(), 1)
+}
+
+#[test]
+fn soft_shadows_have_many_samples() {
+    let mode = ShadowMode::Soft {
+        source_radius: 0.5,
+        samples: 8,
+    };
+    let samples = shadow_samples(Point::zeros(), mode);
+    assert_eq!(samples.len(), 8);
+    assert!(samples
+        .iter()
+        .all(|point| (point.norm() - 0.5).abs() < 0.0001))
+}
+
+#[test]
+fn light_on_vertex_casts_no_nan() {
+    let wall = AABB::new_position(2.0, 0.0);
+    let outline = wall.outline();
+    let triangles = shadow_geometry(outline[0], 10.0, &outline);
+    assert!(triangles
+        .iter()
+        .all(|point| point.x.is_finite() && point.y.is_finite()));
+
+    let mode = ShadowMode::Soft {
+        source_radius: 1.0,
+        samples: 4,
+    };
+    for sample in shadow_samples(Point::new(2.5, 0.5), mode) {
+        let triangles = shadow_geometry(sample, 10.0, &outline);
+        assert!(triangles.iter().all(|point| point.x.is_finite()));
+    }
+}
+
+#[test]
+fn only_ambient_outside_light_radius() {
+    let mut lighting = Lighting::new();
+    lighting.ambient = Color32(0.2, 0.2, 0.2, 1.0);
+    lighting.add_light("lamp", Light::new_point(0.0, 0.0, 5.0, Color32::WHITE));
+    lighting.add_occluder("wall", Box::new(AABB::new_position(2.0, 0.0)));
+    let ambient = Color32(0.2, 0.2, 0.2, 1.0);
+
+    // Outside of the light's square, and inside it but past its radius
+    assert_eq!(lighting.light_at(Point::new(20.0, 20.0)), ambient);
+    assert_eq!(lighting.light_at(Point::new(4.5, 4.5)), ambient);
+    assert_eq!(
+        light_visibility(
+            lighting.get_light("lamp").unwrap(),
+            ShadowMode::Hard,
+            &[],
+            Point::new(6.0, 0.0)
+        ),
+        0.0
+    );
+
+    // Behind the wall, and in front of it
+    assert_eq!(lighting.light_at(Point::new(4.0, 0.0)), ambient);
+    assert!(lighting.light_at(Point::new(-1.0, 0.0)).r() > 0.2);
+}
+
+#[test]
+fn soft_shadows_are_partially_lit() {
+    let mut lighting = Lighting::new();
+    lighting.shadows = ShadowMode::Soft {
+        source_radius: 1.0,
+        samples: 4,
+    };
+    let light = Light::new_point(0.0, 0.0, 10.0, Color32::WHITE);
+    let outlines = vec![AABB::new_position(3.0, 0.0).outline()];
+    // In the umbra directly behind the wall, and at the edge of the penumbra
+    assert_eq!(
+        light_visibility(&light, lighting.shadows, &outlines, Point::new(6.0, 0.0)),
+        0.0
+    );
+    let edge = light_visibility(&light, lighting.shadows, &outlines, Point::new(6.0, 1.4));
+    assert!(edge > 0.0 && edge < 1.0);
+}
+
+#[test]
+fn wide_occluders_near_lights_shadow_their_whole_reach() {
+    let light = Light::new_point(0.0, 0.0, 5.0, Color32::WHITE);
+    let walls = [
+        AABB {
+            min: Point::new(-10.0, 1.0),
+            max: Point::new(10.0, 2.0),
+        },
+        AABB {
+            min: Point::new(-100.0, 0.1),
+            max: Point::new(100.0, 0.2),
+        },
+    ];
+    for wall in walls {
+        let outlines = [wall.outline()];
+        for y in [3.0, 4.0, 4.9] {
+            for x in [-4.9, 0.0, 4.9] {
+                let point = Point::new(x, y);
+                assert_eq!(
+                    light_visibility(&light, ShadowMode::Hard, &outlines, point),
+                    0.0,
+                    "{:?} behind {:?}",
+                    point,
+                    wall
+                );
+            }
+        }
+        assert_eq!(
+            light_visibility(&light, ShadowMode::Hard, &outlines, Point::new(0.0, -3.0)),
+            1.0
+        );
+    }
+}
@@ -0,0 +1,108 @@
+use moon_engine::audio::*;
+use moon_engine::camera::Camera;
+use moon_engine::scene::SceneGraph;
+use moon_engine::spatial::*;
+use moon_engine::transform::Transform2D;
+use moon_engine::Vec2;
+
+#[test]
+fn rolloff_curves() {
+    assert_eq!(Rolloff::Linear.attenuation(6.0, 2.0, 10.0), 0.5);
+    assert_eq!(Rolloff::Inverse(1.0).attenuation(4.0, 2.0, 10.0), 0.5);
+    assert_eq!(Rolloff::Exponential(2.0).attenuation(4.0, 2.0, 10.0), 0.25);
+    for rolloff in [
+        Rolloff::Linear,
+        Rolloff::Inverse(1.0),
+        Rolloff::Exponential(1.0),
+    ] {
+        assert_eq!(rolloff.attenuation(1.0, 2.0, 10.0), 1.0);
+        assert_eq!(rolloff.attenuation(11.0, 2.0, 10.0), 0.0);
+    }
+}
+
+#[test]
+fn pan_follows_listener_rotation() {
+    let emitter = Emitter::default();
+    let mut listener = Listener::new_with_position(1.0, 0.0);
+    listener.pan_width = 4.0;
+    assert_eq!(emitter.pan(&listener, Vec2::new(3.0, 0.0)), 0.5);
+    assert_eq!(emitter.pan(&listener, Vec2::new(-9.0, 0.0)), -1.0);
+
+    // Turned a quarter turn, the sound on the right is now straight ahead
+    listener.rotation = std::f32::consts::FRAC_PI_2;
+    assert!(emitter.pan(&listener, Vec2::new(3.0, 0.0)).abs() < 1e-6);
+}
+
+#[test]
+fn camera_listener_pans_at_view_edges() {
+    let mut camera = Camera::with_width_and_height(800.0, 600.0);
+    camera.focus_on(Vec2::new(5.0, 5.0));
+    let listener = Listener::new_from_camera(&camera);
+    let edge = camera.position() + Vec2::new(camera.view_size().x / 2.0, 0.0);
+    assert_eq!(listener.position, Vec2::new(5.0, 5.0));
+    assert!((Emitter::default().pan(&listener, edge) - 1.0).abs() < 1e-6);
+}
+
+#[test]
+fn positional_voices_are_attenuated_and_panned() {
+    let mut audio = Audio::new();
+    let sound = audio.load_sound("BOOM", &[]);
+    let emitter = Emitter {
+        reference_distance: 1.0,
+        max_distance: 10.0,
+        rolloff: Rolloff::Linear,
+    };
+    let near = audio
+        .play_sfx_at(sound, 1.0, 1.0, emitter, Vec2::new(0.0, 1.0))
+        .unwrap();
+    let far = audio
+        .play_sfx_at(sound, 1.0, 1.0, emitter, Vec2::new(-5.5, 0.0))
+        .unwrap();
+    assert_eq!(audio.voice_gain(near), Some(1.0));
+    assert_eq!(audio.voice_gain(far), Some(0.5));
+    assert!(audio.voice_pan(far).unwrap() < 0.0);
+
+    audio.set_voice_position(near, Vec2::new(20.0, 0.0));
+    audio.update(0.0);
+    assert_eq!(audio.voice_gain(near), Some(0.0));
+    assert_eq!(audio.voice_pan(near), Some(1.0));
+}
+
+#[test]
+fn voices_follow_scene_entities() {
+    let mut scene = SceneGraph::new();
+    let player = scene.spawn(Transform2D::new_with_position(10.0, 0.0));
+    let enemy = scene.spawn(Transform2D::new_with_position(12.0, 0.0));
+
+    let mut audio = Audio::new();
+    let sound = audio.load_sound("GROWL", &[]);
+    let voice = audio
+        .play_sfx_at(sound, 1.0, 1.0, Emitter::default(), Vec2::zeros())
+        .unwrap();
+    audio.attach_to_entity(voice, enemy);
+    audio.set_listener_entity(Some(player));
+    audio.follow_scene(&scene);
+    audio.update(0.0);
+
+    assert_eq!(audio.listener().position, Vec2::new(10.0, 0.0));
+    assert_eq!(audio.voice_gain(voice), Some(0.5));
+    assert_eq!(audio.voice_pan(voice), Some(0.2));
+}
+
+#[test]
+fn camera_only_moves_listener_without_an_entity() {
+    let mut scene = SceneGraph::new();
+    let player = scene.spawn(Transform2D::new_with_position(10.0, 0.0));
+    let mut camera = Camera::with_width_and_height(800.0, 600.0);
+    camera.focus_on(Vec2::new(5.0, 5.0));
+
+    let mut audio = Audio::new();
+    audio.follow_camera(&camera);
+    assert_eq!(audio.listener().position, Vec2::new(5.0, 5.0));
+
+    audio.set_listener_entity(Some(player));
+    audio.follow_scene(&scene);
+    audio.follow_camera(&camera);
+    assert_eq!(audio.listener_entity(), Some(player));
+    assert_eq!(audio.listener().position, Vec2::new(10.0, 0.0));
+}
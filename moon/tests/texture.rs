@@ -0,0 +1,135 @@
+use moon_engine::gl::GL;
+use moon_engine::texture::*;
+
+#[test]
+fn default_options_match_pixel_art() {
+    let options = TextureOptions::default();
+    assert_eq!(options.min_filter_to_gl(), GL::NEAREST);
+    assert_eq!(options.mag_filter_to_gl(), GL::NEAREST);
+    assert_eq!(options.mipmaps, None);
+    assert_eq!(options.wrap_s.to_gl(), GL::REPEAT);
+    assert_eq!(options.format, TextureFormat::Rgba8);
+    assert!(!options.srgb);
+}
+
+#[test]
+fn min_filter_combines_mipmaps() {
+    let filters = [TextureFilter::Nearest, TextureFilter::Linear];
+    let expected = [
+        GL::NEAREST_MIPMAP_NEAREST,
+        GL::NEAREST_MIPMAP_LINEAR,
+        GL::LINEAR_MIPMAP_NEAREST,
+        GL::LINEAR_MIPMAP_LINEAR,
+    ];
+    for (index, (min_filter, mipmaps)) in filters
+        .iter()
+        .flat_map(|min| filters.iter().map(move |mip| (*min, *mip)))
+        .enumerate()
+    {
+        let options = TextureOptions {
+            min_filter,
+            mipmaps: Some(mipmaps),
+            ..Default::default()
+        };
+        assert_eq!(options.min_filter_to_gl(), expected[index]);
+    }
+    let linear = TextureOptions::new_with_filter(TextureFilter::Linear);
+    assert_eq!(linear.min_filter_to_gl(), GL::LINEAR);
+    assert_eq!(linear.mag_filter_to_gl(), GL::LINEAR);
+}
+
+#[test]
+fn wrap_modes_per_axis() {
+    let options = TextureOptions {
+        wrap_t: TextureWrap::MirroredRepeat,
+        ..TextureOptions::new_with_wrap(TextureWrap::ClampToEdge)
+    };
+    assert_eq!(options.wrap_s.to_gl(), GL::CLAMP_TO_EDGE);
+    assert_eq!(options.wrap_t.to_gl(), GL::MIRRORED_REPEAT);
+}
+
+#[test]
+fn formats() {
+    let formats = [
+        TextureFormat::Rgba8,
+        TextureFormat::R8,
+        TextureFormat::Rgba16F,
+    ];
+    let layouts: Vec<_> = formats
+        .iter()
+        .map(|format| {
+            (
+                format.internal_format(),
+                format.format(),
+                format.data_type(),
+                format.bytes_per_pixel(),
+            )
+        })
+        .collect();
+    assert_eq!(
+        layouts,
+        [
+            (GL::RGBA8, GL::RGBA, GL::UNSIGNED_BYTE, 4),
+            (GL::R8, GL::RED, GL::UNSIGNED_BYTE, 1),
+            (GL::RGBA16F, GL::RGBA, GL::HALF_FLOAT, 8),
+        ]
+    );
+}
+
+#[test]
+fn slots_evict_least_recently_used() {
+    let mut slots = TextureSlots::new_with_units(2, 3);
+    assert_eq!(slots.capacity(), 3);
+    for id in 0..3 {
+        assert_eq!(slots.assign(TextureId(id)), (2 + id, true));
+    }
+    slots.assign(TextureId(0));
+    slots.assign(TextureId(2));
+    assert_eq!(slots.assign(TextureId(3)), (3, true));
+    assert_eq!(slots.unit_of(TextureId(1)), None);
+    assert_eq!(slots.assign(TextureId(4)), (2, true));
+    assert_eq!(
+        slots.mapping(),
+        [(2, TextureId(4)), (3, TextureId(3)), (4, TextureId(2))]
+    );
+}
+
+#[test]
+fn slots_release_and_clear() {
+    let mut slots = TextureSlots::new_with_units(0, 2);
+    slots.assign(TextureId(7));
+    slots.assign(TextureId(8));
+    slots.release(TextureId(7));
+    assert_eq!(slots.unit_of(TextureId(7)), None);
+    // A freed unit is used before replacing another texture
+    assert_eq!(slots.assign(TextureId(9)), (0, true));
+    assert_eq!(slots.unit_of(TextureId(8)), Some(1));
+
+    slots.clear();
+    assert!(slots.mapping().is_empty());
+    assert_eq!(slots.assign(TextureId(8)), (0, true));
+}
+
+#[test]
+fn pinned_slots_are_not_evicted() {
+    let mut slots = TextureSlots::new_with_units(2, 2);
+    // A normal map bound before the texture drawn with it
+    slots.assign(TextureId(1));
+    slots.pin(TextureId(1));
+    slots.assign(TextureId(2));
+    assert_eq!(slots.assign(TextureId(3)), (3, true));
+    assert_eq!(slots.assign(TextureId(4)), (3, true));
+    assert_eq!(slots.unit_of(TextureId(1)), Some(2));
+    assert!(slots.is_pinned(TextureId(1)));
+
+    // Once unpinned, it is replaced like any other texture
+    slots.unpin(TextureId(1));
+    assert!(!slots.is_pinned(TextureId(1)));
+    assert_eq!(slots.assign(TextureId(5)), (2, true));
+
+    // When every unit is pinned, the least recently used one is still replaced
+    slots.pin(TextureId(4));
+    slots.pin(TextureId(5));
+    assert_eq!(slots.assign(TextureId(6)), (3, true));
+    assert!(!slots.is_pinned(TextureId(6)));
+}
@@ -0,0 +1,204 @@
+mod common;
+
+use std::collections::BTreeSet;
+
+use moon_engine::collider::{Polygon, AABB};
+use moon_engine::tilecollider::*;
+use moon_engine::tiled::TiledMap;
+use moon_engine::tilemap::{Tile, Tileset};
+use moon_engine::Vec2;
+
+use common::tilemap;
+
+/// Parse a grid drawn with `#` for solid tiles.
+fn grid(rows: &[&str]) -> (u32, u32, impl Fn(u32, u32) -> bool) {
+    let cells: Vec<Vec<bool>> = rows
+        .iter()
+        .map(|row| row.chars().map(|cell| cell == '#').collect())
+        .collect();
+    let (width, height) = (rows[0].len() as u32, rows.len() as u32);
+    (width, height, move |x: u32, y: u32| {
+        cells[y as usize][x as usize]
+    })
+}
+
+fn signed_area(corners: &[(i32, i32)]) -> i32 {
+    let count = corners.len();
+    (0..count)
+        .map(|index| {
+            let (a, b) = (corners[index], corners[(index + 1) % count]);
+            a.0 * b.1 - b.0 * a.1
+        })
+        .sum()
+}
+
+fn assert_aabb(aabb: &AABB, min: (f32, f32), max: (f32, f32)) {
+    assert_eq!(
+        (aabb.min, aabb.max),
+        (Vec2::new(min.0, min.1), Vec2::new(max.0, max.1))
+    );
+}
+
+#[test]
+fn rectangles_cover_each_solid_tile_once() {
+    #[rustfmt::skip]
+    let (width, height, solid) = grid(&[
+        "####..",
+        "####.#",
+        "..#..#",
+        "#.#.##",
+    ]);
+    let rectangles = merge_rectangles(width, height, &solid);
+    assert_eq!(
+        rectangles[0],
+        TileRect {
+            x: 0,
+            y: 0,
+            width: 4,
+            height: 2
+        }
+    );
+    assert_eq!(rectangles.len(), 5);
+
+    let mut covered = BTreeSet::new();
+    for rect in &rectangles {
+        for y in rect.y..rect.y + rect.height {
+            for x in rect.x..rect.x + rect.width {
+                assert!(solid(x, y));
+                assert!(covered.insert((x, y)), "{:?} is covered twice", (x, y));
+            }
+        }
+    }
+    let solid_count = (0..height)
+        .flat_map(|y| (0..width).map(move |x| (x, y)))
+        .filter(|(x, y)| solid(*x, *y))
+        .count();
+    assert_eq!(covered.len(), solid_count);
+
+    assert!(merge_rectangles(3, 2, |_, _| false).is_empty());
+    assert_eq!(merge_rectangles(3, 2, |_, _| true).len(), 1);
+}
+
+#[test]
+fn outlines_of_regions_and_holes() {
+    // An L shape only has a corner where it turns
+    let (width, height, solid) = grid(&["#.", "##"]);
+    let outlines = trace_outlines(width, height, solid);
+    assert_eq!(outlines.len(), 1);
+    assert_eq!(outlines[0].len(), 6);
+    assert_eq!(signed_area(&outlines[0]), 6);
+
+    // A ring has an outer outline, and an inner one going the other way
+    let (width, height, solid) = grid(&["###", "#.#", "###"]);
+    let mut areas: Vec<i32> = trace_outlines(width, height, solid)
+        .iter()
+        .map(|outline| {
+            assert_eq!(outline.len(), 4);
+            signed_area(outline)
+        })
+        .collect();
+    areas.sort();
+    assert_eq!(areas, [-2, 18]);
+
+    // Tiles touching at a corner are kept apart
+    let (width, height, solid) = grid(&["#.", ".#"]);
+    let outlines = trace_outlines(width, height, solid);
+    assert_eq!(outlines.len(), 2);
+    assert!(outlines.iter().all(|outline| outline.len() == 4));
+}
+
+#[test]
+fn tilemap_colliders_in_world_coordinates() {
+    let mut tilemap = tilemap(4, 3, Vec2::new(2.0, 1.0));
+    tilemap.set_position(Vec2::new(10.0, 20.0));
+    let ground = tilemap.add_layer("Ground");
+    for x in 0..4 {
+        tilemap.set_tile(ground, x, 2, Some(Tile::new(0)));
+    }
+    tilemap.set_tile(ground, 3, 1, Some(Tile::new(1)));
+    // Decoration is not solid
+    tilemap.set_tile(ground, 0, 0, Some(Tile::new(3)));
+
+    assert!(tilemap.colliders(ground).is_empty());
+    tilemap.set_collision_layer(ground, Some(Solidity::Tiles(BTreeSet::from([0, 1]))));
+    assert!(tilemap.is_solid(3, 1));
+    assert!(!tilemap.is_solid(0, 0));
+
+    let colliders = tilemap.colliders(ground);
+    assert_eq!(colliders.len(), 2);
+    assert_aabb(&colliders[0], (16.0, 21.0), (18.0, 23.0));
+    assert_aabb(&colliders[1], (10.0, 22.0), (16.0, 23.0));
+
+    let outlines: Vec<Polygon> = tilemap.outlines(ground);
+    assert_eq!(outlines.len(), 1);
+    assert_eq!(outlines[0].points.len(), 6);
+    assert!(outlines[0].signed_area() > 0.0);
+    assert!(outlines[0].points.contains(&Vec2::new(18.0, 21.0)));
+
+    tilemap.layer_mut(ground).unwrap().offset = Vec2::new(0.0, -1.0);
+    assert_aabb(&tilemap.colliders(ground)[1], (10.0, 21.0), (16.0, 22.0));
+
+    tilemap.set_collision_layer(ground, None);
+    assert!(tilemap.colliders(ground).is_empty());
+    assert!(!tilemap.is_solid(3, 1));
+
+    // Layers that do not exist yet have no colliders
+    tilemap.set_collision_layer(5, Some(Solidity::AnyTile));
+    assert!(tilemap.colliders(5).is_empty());
+    assert!(tilemap.outlines(5).is_empty());
+}
+
+#[test]
+fn colliders_follow_tile_changes() {
+    let mut tilemap = tilemap(3, 3, Vec2::new(2.0, 1.0));
+    let walls = tilemap.add_layer("Walls");
+    tilemap.set_collision_layer(walls, Some(Solidity::AnyTile));
+    tilemap.set_tile(walls, 0, 0, Some(Tile::new(0)));
+    tilemap.set_tile(walls, 1, 0, Some(Tile::new(0)));
+    assert_eq!(tilemap.colliders(walls).len(), 1);
+
+    // Changing a solid tile into another solid tile keeps the colliders
+    let version = tilemap.collision_version();
+    tilemap.set_tile(walls, 1, 0, Some(Tile::new(2)));
+    assert_eq!(tilemap.collision_version(), version);
+
+    tilemap.set_tile(walls, 0, 1, Some(Tile::new(0)));
+    assert_ne!(tilemap.collision_version(), version);
+    assert_eq!(tilemap.colliders(walls).len(), 2);
+    tilemap.set_tile(walls, 1, 1, Some(Tile::new(0)));
+    assert_eq!(tilemap.colliders(walls).len(), 1);
+    assert_aabb(&tilemap.colliders(walls)[0], (0.0, 0.0), (4.0, 2.0));
+
+    // Changes to other layers do not affect the colliders
+    let decoration = tilemap.add_layer("Decoration");
+    let version = tilemap.collision_version();
+    tilemap.set_tile(decoration, 2, 2, Some(Tile::new(0)));
+    assert_eq!(tilemap.collision_version(), version);
+
+    tilemap.clear_layer(walls);
+    assert_ne!(tilemap.collision_version(), version);
+    assert!(tilemap.colliders(walls).is_empty());
+    assert!(tilemap.outlines(walls).is_empty());
+}
+
+#[test]
+fn solid_tiles_from_tiled_properties() {
+    let map = TiledMap::parse(
+        r#"<map width="3" height="1" tilewidth="8" tileheight="8">
+            <tileset firstgid="1" name="terrain" tilewidth="8" tileheight="8" tilecount="4" columns="2">
+             <tile id="1"><properties><property name="solid" type="bool" value="true"/></properties></tile>
+             <tile id="2"><properties><property name="solid" type="bool" value="false"/></properties></tile>
+            </tileset>
+            <layer name="Ground"><data encoding="csv">2,2,3</data></layer>
+        </map>"#,
+    )
+    .unwrap();
+    let solid = map.tilesets[0].tiles_with_property("solid");
+    assert_eq!(solid, BTreeSet::from([1]));
+
+    let mut tilemap = map.create_tilemap(0, Tileset::default(), Vec2::new(1.0, 1.0));
+    tilemap.set_collision_layer(0, Some(Solidity::Tiles(solid)));
+    let colliders = tilemap.colliders(0);
+    assert_eq!(colliders.len(), 1);
+    assert_aabb(&colliders[0], (0.0, 0.0), (2.0, 1.0));
+}
@@ -0,0 +1,423 @@
+mod common;
+
+use std::f32::consts::FRAC_PI_2;
+use std::rc::Rc;
+
+use moon_engine::collider::Polygon;
+use moon_engine::inflate::{gzip_decompress, gzip_decompress_with_limit};
+use moon_engine::json::JsonValue;
+use moon_engine::scene::SceneGraph;
+use moon_engine::texture::Texture;
+use moon_engine::tiled::*;
+use moon_engine::tilemap::{Tile, TileAnimation, TileFlags, Tileset};
+use moon_engine::xml::XmlElement;
+use moon_engine::{Color32, Vec2};
+
+use common::assert_close;
+
+const JSON_MAP: &str = r##"{
+    "width": 2, "height": 2, "tilewidth": 16, "tileheight": 8, "orientation": "orthogonal",
+    "properties": [
+        {"name": "gravity", "type": "float", "value": 9.5},
+        {"name": "music", "type": "file", "value": "level1.ogg"}
+    ],
+    "tilesets": [
+        {"firstgid": 5, "name": "props", "tilewidth": 16, "tileheight": 8, "tilecount": 4, "columns": 2},
+        {
+            "firstgid": 1, "name": "terrain", "tilewidth": 16, "tileheight": 8, "tilecount": 4, "columns": 2,
+            "image": "terrain.png",
+            "tiles": [
+                {"id": 1, "animation": [{"tileid": 1, "duration": 100}, {"tileid": 2, "duration": 300}]},
+                {"id": 3, "properties": [{"name": "solid", "type": "bool", "value": true}]}
+            ]
+        }
+    ],
+    "layers": [
+        {"type": "tilelayer", "name": "Ground", "width": 2, "height": 2, "data": [1, 2147483650, 6, 3]},
+        {"type": "group", "name": "Decor", "offsetx": 4, "opacity": 0.5, "layers": [
+            {
+                "type": "tilelayer", "name": "Props", "offsety": 8, "tintcolor": "#ff0000", "visible": false,
+                "encoding": "base64", "compression": "zlib", "data": "eJxjZGBgYGJgaABSDMxADAAExACH"
+            }
+        ]},
+        {"type": "imagelayer", "name": "Sky", "image": "sky.png"},
+        {"type": "objectgroup", "name": "Things", "offsetx": 16, "objects": [
+            {"id": 1, "name": "spawn", "point": true, "x": 8, "y": 4,
+                "properties": [{"name": "facing", "type": "string", "value": "left"}]},
+            {"id": 2, "name": "wall", "type": "solid", "x": 0, "y": 0, "width": 32, "height": 8},
+            {"id": 3, "name": "door", "class": "door", "x": 0, "y": 16, "width": 16, "height": 8, "rotation": 90},
+            {"id": 4, "name": "coin", "gid": 2147483654, "x": 16, "y": 16, "width": 16, "height": 8},
+            {"id": 5, "name": "pond", "ellipse": true, "x": 0, "y": 0, "width": 16, "height": 8},
+            {"id": 6, "name": "ramp", "x": 0, "y": 8, "polygon": [{"x": 0, "y": 0}, {"x": 16, "y": 0}, {"x": 16, "y": -8}]},
+            {"id": 7, "name": "path", "visible": false, "x": 0, "y": 0, "polyline": [{"x": 0, "y": 0}, {"x": 8, "y": 8}]}
+        ]}
+    ]
+}"##;
+
+const XML_MAP: &str = r##"<?xml version="1.0" encoding="UTF-8"?>
+<map version="1.10" orientation="orthogonal" width="2" height="2" tilewidth="16" tileheight="8" infinite="0">
+ <properties>
+  <property name="gravity" type="float" value="9.5"/>
+  <property name="music" type="file" value="level1.ogg"/>
+ </properties>
+ <tileset firstgid="5" name="props" tilewidth="16" tileheight="8" tilecount="4" columns="2">
+  <image source="props.png" width="32" height="16"/>
+ </tileset>
+ <tileset firstgid="1" name="terrain" tilewidth="16" tileheight="8" tilecount="4" columns="2">
+  <image source="terrain.png" width="32" height="16"/>
+  <tile id="1">
+   <animation><frame tileid="1" duration="100"/><frame tileid="2" duration="300"/></animation>
+  </tile>
+  <tile id="3">
+   <properties><property name="solid" type="bool" value="true"/></properties>
+  </tile>
+ </tileset>
+ <layer id="1" name="Ground" width="2" height="2">
+  <data encoding="csv">
+1,2147483650,
+6,3
+</data>
+ </layer>
+ <group name="Decor" offsetx="4" opacity="0.5">
+  <layer name="Props" offsety="8" tintcolor="#ff0000" visible="0" width="2" height="2">
+   <data encoding="base64" compression="gzip">
+    H4sIAAAAAAACA2NkYGBgYmBoAFIMzEAMAPrrCNoQAAAA
+   </data>
+  </layer>
+ </group>
+ <imagelayer name="Sky"><image source="sky.png"/></imagelayer>
+ <!-- Objects are placed in pixels -->
+ <objectgroup name="Things" offsetx="16">
+  <object id="1" name="spawn" x="8" y="4">
+   <properties><property name="facing" value="left"/></properties>
+   <point/>
+  </object>
+  <object id="2" name="wall" type="solid" x="0" y="0" width="32" height="8"/>
+  <object id="3" name="door" class="door" x="0" y="16" width="16" height="8" rotation="90"/>
+  <object id="4" name="coin" gid="2147483654" x="16" y="16" width="16" height="8"/>
+  <object id="5" name="pond" x="0" y="0" width="16" height="8"><ellipse/></object>
+  <object id="6" name="ramp" x="0" y="8"><polygon points="0,0 16,0 16,-8"/></object>
+  <object id="7" name="path" x="0" y="0" visible="0"><polyline points="0,0 8,8"/></object>
+ </objectgroup>
+</map>"##;
+
+/// Check a map parsed from either [`JSON_MAP`] or [`XML_MAP`].
+fn check_map(map: &TiledMap) {
+    assert_eq!((map.width, map.height), (2, 2));
+    assert_eq!(map.tile_size(), Vec2::new(16.0, 8.0));
+    assert_eq!(map.properties["gravity"], PropertyValue::Float(9.5));
+    assert_eq!(map.properties["music"].as_str(), Some("level1.ogg"));
+
+    let names: Vec<&str> = map
+        .tilesets
+        .iter()
+        .map(|tileset| &tileset.name[..])
+        .collect();
+    assert_eq!(names, ["terrain", "props"]);
+    assert_eq!(map.tilesets[0].image, "terrain.png");
+    assert_eq!(map.tileset_index(2 | FLIPPED_VERTICALLY), Some(0));
+    assert_eq!(map.tileset_index(6), Some(1));
+    assert_eq!(map.tileset_index(0), None);
+    assert_eq!(map.tileset_index(9), None);
+    assert_eq!(
+        map.tilesets[0].animations[&1],
+        TileAnimation::new(vec![(1, 0.1), (2, 0.3)])
+    );
+    assert_eq!(
+        map.tilesets[0].tile_properties[&3]["solid"],
+        PropertyValue::Bool(true)
+    );
+
+    let layers: Vec<&TiledTileLayer> = map.tile_layers().collect();
+    assert_eq!(layers.len(), 2);
+    assert_eq!(layers[0].gids, [1, 0x8000_0002, 6, 3]);
+    assert!(layers[0].visible);
+    assert_eq!(layers[0].color, Color32::WHITE);
+    // Groups add their offset and opacity to the layers inside them
+    assert_eq!(layers[1].name, "Props");
+    assert_eq!(layers[1].gids, [1, 0x8000_0002, 0, 3]);
+    assert!(!layers[1].visible);
+    assert_eq!(layers[1].offset, Vec2::new(4.0, 8.0));
+    assert_eq!(layers[1].color, Color32(1.0, 0.0, 0.0, 0.5));
+
+    let objects = &map.object_layers().next().unwrap().objects;
+    assert_eq!(objects.len(), 7);
+    assert_eq!(objects[0].shape, TiledShape::Point);
+    assert_eq!(
+        objects[0].properties["facing"],
+        PropertyValue::String(String::from("left"))
+    );
+    assert_eq!(
+        (&objects[1].class[..], &objects[2].class[..]),
+        ("solid", "door")
+    );
+    assert_eq!(objects[3].gid, Some(0x8000_0006));
+    assert_eq!(objects[4].shape, TiledShape::Ellipse);
+    assert_eq!(
+        objects[6].shape,
+        TiledShape::Polyline(vec![Vec2::zeros(), Vec2::new(8.0, 8.0)])
+    );
+}
+
+#[test]
+fn json_and_xml_documents() {
+    let value = JsonValue::parse(r#" {"a": [true, null, -1.5e2], "b": "\"\u00e9\ud83c\udf19\n"} "#)
+        .unwrap();
+    assert_eq!(
+        value.get("a").and_then(|a| a.index(2)?.as_f64()),
+        Some(-150.0)
+    );
+    assert!(value.get("a").and_then(|a| a.index(1)).unwrap().is_null());
+    assert_eq!(value.get("b").and_then(JsonValue::as_str), Some("\"é🌙\n"));
+    assert_eq!(JsonValue::parse("[]"), Ok(JsonValue::Array(Vec::new())));
+    for invalid in [
+        "",
+        "[1,]",
+        "{\"a\" 1}",
+        "\"open",
+        "[1] 2",
+        "tru",
+        "\"\\ud83c\"",
+    ] {
+        assert!(JsonValue::parse(invalid).is_err(), "{}", invalid);
+    }
+
+    let root = XmlElement::parse(
+        "<?xml version=\"1.0\"?><!DOCTYPE a><a x='1 &lt; 2'>one <!-- two --><b/>&#x41;<![CDATA[<c>]]></a>",
+    )
+    .unwrap();
+    assert_eq!(root.attribute("x"), Some("1 < 2"));
+    assert_eq!(root.text, "one A<c>");
+    assert_eq!(root.children_named("b").count(), 1);
+    for invalid in [
+        "<a>",
+        "<a></b>",
+        "<a x=1/>",
+        "<a/><b/>",
+        "<a>&bad;</a>",
+        "text",
+    ] {
+        assert!(XmlElement::parse(invalid).is_err(), "{}", invalid);
+    }
+}
+
+#[test]
+fn gids_and_compression() {
+    assert_eq!(decode_gid(5), (5, TileFlags::NONE));
+    assert_eq!(
+        decode_gid(5 | FLIPPED_VERTICALLY | FLIPPED_DIAGONALLY),
+        (5, TileFlags::FLIP_VERTICAL | TileFlags::FLIP_DIAGONAL)
+    );
+    // The hexagonal rotation bit is ignored
+    assert_eq!(decode_gid(0x1000_0005), (5, TileFlags::NONE));
+
+    // With a file name in the header
+    let mut gzip = vec![
+        0x1F, 0x8B, 0x08, 0x08, 0x00, 0x00, 0x00, 0x00, 0x02, 0xFF, 0x61, 0x2E, 0x74, 0x78, 0x74,
+        0x00, 0x2B, 0xC9, 0xCC, 0x49, 0x2D, 0x56, 0x28, 0x41, 0x90, 0x00, 0x2B, 0x33, 0x9D, 0x6B,
+        0x11, 0x00, 0x00, 0x00,
+    ];
+    assert_eq!(gzip_decompress(&gzip).unwrap(), b"tiles tiles tiles");
+    assert!(gzip_decompress_with_limit(&gzip, 17).is_ok());
+    assert!(gzip_decompress_with_limit(&gzip, 16).is_err());
+    gzip[30] ^= 1;
+    assert!(gzip_decompress(&gzip).is_err());
+    assert!(gzip_decompress(&gzip[..12]).is_err());
+}
+
+#[test]
+fn json_and_xml_maps() {
+    check_map(&TiledMap::parse(JSON_MAP).unwrap());
+    check_map(&TiledMap::parse(XML_MAP).unwrap());
+
+    // Tile data can also be stored as separate elements
+    let map = TiledMap::parse_xml(
+        r#"<map width="2" height="1" tilewidth="8" tileheight="8">
+            <layer name="A"><data><tile gid="3"/><tile/></data></layer>
+        </map>"#,
+    )
+    .unwrap();
+    assert_eq!(map.tile_layers().next().unwrap().gids, [3, 0]);
+}
+
+#[test]
+fn tilemaps_from_each_tileset() {
+    let map = TiledMap::parse(JSON_MAP).unwrap();
+    let terrain = map.create_tilemap(0, Tileset::default(), Vec2::new(1.0, 0.5));
+    assert_eq!((terrain.width(), terrain.height()), (2, 2));
+    assert_eq!(terrain.layer_count(), 2);
+    assert_eq!(terrain.get_tile(0, 0, 0), Some(Tile::new(0)));
+    assert_eq!(
+        terrain.get_tile(0, 1, 0),
+        Some(Tile::new_with_flags(1, TileFlags::FLIP_HORIZONTAL))
+    );
+    assert_eq!(terrain.get_tile(0, 0, 1), None);
+    assert_eq!(terrain.get_tile(0, 1, 1), Some(Tile::new(2)));
+
+    let props_layer = terrain.layer(1).unwrap();
+    assert_eq!(props_layer.name, "Props");
+    assert!(!props_layer.visible);
+    assert_eq!(props_layer.offset, Vec2::new(0.25, 0.5));
+
+    // Tiles of other tilesets are left out, and IDs start from zero in each tileset
+    let props = map.create_tilemap(1, Tileset::default(), Vec2::new(1.0, 0.5));
+    let tiles: Vec<_> = props.layer(0).unwrap().tiles().collect();
+    assert_eq!(tiles, [(0, 1, Tile::new(1))]);
+}
+
+#[test]
+fn objects_with_colliders() {
+    let map = TiledMap::parse(XML_MAP).unwrap();
+    let mut scene = SceneGraph::new();
+    let objects = map.spawn_objects(&mut scene, Vec2::new(1.0, 0.5));
+    assert_eq!(objects.len(), 7);
+    assert_eq!(scene.len(), 8);
+    assert!(objects.iter().all(|object| object.layer == "Things"));
+    let layer = scene.parent(objects[0].entity).unwrap();
+    assert_eq!(scene.children(layer).len(), 7);
+
+    let polygon = |collider: &Option<ObjectCollider>| -> Polygon {
+        match collider {
+            Some(ObjectCollider::Polygon(polygon)) => polygon.clone(),
+            other => panic!("Expected a polygon, found {:?}", other),
+        }
+    };
+
+    match objects[0].collider {
+        Some(ObjectCollider::Point(point)) => assert_close(point, Vec2::new(1.5, 0.25)),
+        ref other => panic!("Expected a point, found {:?}", other),
+    }
+    assert_eq!(objects[0].properties["facing"].as_str(), Some("left"));
+
+    match objects[1].collider {
+        Some(ObjectCollider::Rectangle(aabb)) => {
+            assert_close(aabb.min, Vec2::new(1.0, 0.0));
+            assert_close(aabb.max, Vec2::new(3.0, 0.5));
+        }
+        ref other => panic!("Expected a rectangle, found {:?}", other),
+    }
+
+    // Rotated rectangles turn around their top-left corner
+    let door = polygon(&objects[2].collider);
+    let expected = [(1.0, 1.0), (1.0, 2.0), (0.5, 2.0), (0.5, 1.0)];
+    for (point, (x, y)) in door.points.iter().zip(expected) {
+        assert_close(*point, Vec2::new(x, y));
+    }
+    let door = scene.world(objects[2].entity).unwrap();
+    assert_close(door.position, Vec2::new(1.0, 1.0));
+    assert!((door.rotation - FRAC_PI_2).abs() < 1e-4);
+
+    // Tile objects are placed by their bottom-left corner
+    assert_eq!(
+        objects[3].tile,
+        Some((1, Tile::new_with_flags(1, TileFlags::FLIP_HORIZONTAL)))
+    );
+    match objects[3].collider {
+        Some(ObjectCollider::Rectangle(aabb)) => {
+            assert_close(aabb.min, Vec2::new(2.0, 0.5));
+            assert_close(aabb.max, Vec2::new(3.0, 1.0));
+        }
+        ref other => panic!("Expected a rectangle, found {:?}", other),
+    }
+
+    match objects[4].collider {
+        Some(ObjectCollider::Circle(circle)) => {
+            assert_close(circle.origin, Vec2::new(1.5, 0.25));
+            assert!((circle.radius - 0.375).abs() < 1e-4);
+        }
+        ref other => panic!("Expected a circle, found {:?}", other),
+    }
+
+    let ramp = polygon(&objects[5].collider);
+    let expected = [(1.0, 0.5), (2.0, 0.5), (2.0, 0.0)];
+    for (point, (x, y)) in ramp.points.iter().zip(expected) {
+        assert_close(*point, Vec2::new(x, y));
+    }
+
+    assert!(objects[6].collider.is_none());
+    assert!(!objects[6].visible && objects[5].visible);
+}
+
+#[test]
+fn tilesets_with_margins_and_spacing() {
+    let mut map = TiledMap::parse(JSON_MAP).unwrap();
+    map.tilesets[0].margin = 1;
+    map.tilesets[0].spacing = 2;
+    let mut texture = Texture::default();
+    (texture.width, texture.height) = (36, 20);
+    let texture = Rc::new(texture);
+    // Textures cannot be deleted without a WebGL context, so this one is never dropped
+    std::mem::forget(Rc::clone(&texture));
+
+    let tileset = map.create_tileset(0, Rc::clone(&texture)).unwrap();
+    assert_eq!((tileset.len(), tileset.columns()), (4, 2));
+    let uvs = tileset.get(3).unwrap().get_uv_coords();
+    assert_close(Vec2::from(uvs[0]), Vec2::new(19.0 / 36.0, 0.05));
+    assert_close(Vec2::from(uvs[2]), Vec2::new(35.0 / 36.0, 0.45));
+    assert_eq!(tileset.resolve(1, 0.2), 2);
+
+    // Tiles that do not fit in the image
+    for (tile_count, margin, spacing) in [(5, 1, 2), (u32::MAX, 1, 2), (4, u32::MAX, 2), (4, 1, 4)]
+    {
+        map.tilesets[0].tile_count = tile_count;
+        map.tilesets[0].margin = margin;
+        map.tilesets[0].spacing = spacing;
+        assert!(map.create_tileset(0, Rc::clone(&texture)).is_err());
+    }
+    map.tilesets[0].spacing = u32::MAX;
+    map.tilesets[0].margin = 0;
+    map.tilesets[0].tile_count = 1;
+    assert!(map.create_tileset(0, Rc::clone(&texture)).is_ok());
+    map.tilesets[0].tile_count = 2;
+    assert!(map.create_tileset(0, Rc::clone(&texture)).is_err());
+
+    map.tilesets[0].columns = 0;
+    assert!(map.create_tileset(0, Rc::clone(&texture)).is_err());
+    assert!(map.create_tileset(2, texture).is_err());
+}
+
+#[test]
+fn unsupported_maps() {
+    let map = |extra: &str, layer: &str| {
+        format!(
+            r#"{{"width": 2, "height": 1, "tilewidth": 8, "tileheight": 8, {} "layers": [{}]}}"#,
+            extra, layer
+        )
+    };
+    assert!(TiledMap::parse(&map("", r#"{"type": "tilelayer", "data": [0, 0]}"#)).is_ok());
+    let zlib = |data| {
+        format!(
+            r#"{{"type": "tilelayer", "data": "{}", "compression": "zlib"}}"#,
+            data
+        )
+    };
+    assert!(TiledMap::parse(&map("", &zlib("eJxjZGBgYAJiAAAYAAQ="))).is_ok());
+    for (extra, layer) in [
+        ("", r#"{"type": "tilelayer", "data": [0]}"#),
+        ("", r#"{"type": "tilelayer"}"#),
+        (r#""infinite": true,"#, ""),
+        (r#""orientation": "isometric","#, ""),
+        (
+            r#""tilesets": [{"firstgid": 1, "source": "terrain.tsj"}],"#,
+            "",
+        ),
+        (
+            "",
+            r#"{"type": "tilelayer", "data": "AAAA", "compression": "zstd"}"#,
+        ),
+    ] {
+        assert!(TiledMap::parse(&map(extra, layer)).is_err(), "{}", extra);
+    }
+    // Compressed data larger than the map is not inflated
+    for layer in [
+        zlib("eJxjZGBgYAJiZiAGAAA0AAc="),
+        String::from(
+            r#"{"type": "tilelayer", "data": "H4sIAAAAAAACA2NkYGBgAmJmIAYAkyLgsAwAAAA=", "compression": "gzip"}"#,
+        ),
+    ] {
+        let error = TiledMap::parse(&map("", &layer)).unwrap_err();
+        assert!(error.contains("larger than expected"), "{}", error);
+    }
+    assert!(TiledMap::parse(r#"{"width": 2}"#).is_err());
+    assert!(TiledMap::parse("<tileset/>").is_err());
+}
@@ -0,0 +1,184 @@
+mod common;
+
+use moon_engine::component::Component;
+use moon_engine::tilemap::*;
+use moon_engine::{Color32, Vec2};
+
+use common::tilemap;
+
+const CORNERS: [[f32; 2]; 4] = [[0.0, 0.0], [0.0, 1.0], [1.0, 1.0], [1.0, 0.0]];
+
+#[test]
+fn flags_flip_and_rotate() {
+    assert_eq!(
+        TileFlags::FLIP_HORIZONTAL.apply(CORNERS),
+        [[1.0, 0.0], [1.0, 1.0], [0.0, 1.0], [0.0, 0.0]]
+    );
+    assert_eq!(
+        TileFlags::FLIP_VERTICAL.apply(CORNERS),
+        [[0.0, 1.0], [0.0, 0.0], [1.0, 0.0], [1.0, 1.0]]
+    );
+    // A quarter turn clockwise moves the bottom-left corner to the top-left
+    let quarter = TileFlags::new_with_rotation(1);
+    assert_eq!(quarter.apply(CORNERS)[1], CORNERS[0]);
+    for turns in 0..4 {
+        let once = TileFlags::new_with_rotation(turns);
+        let twice = TileFlags::new_with_rotation(turns + 1);
+        assert_eq!(quarter.apply(once.apply(CORNERS)), twice.apply(CORNERS));
+    }
+    assert_eq!(
+        TileFlags::new_with_rotation(-2),
+        TileFlags::new_with_rotation(2)
+    );
+}
+
+#[test]
+fn tiles_and_coordinates() {
+    let mut tilemap = tilemap(4, 3, Vec2::new(2.0, 2.0));
+    tilemap.set_position(Vec2::new(-4.0, 10.0));
+    let layer = tilemap.add_layer("Ground");
+    assert_eq!(tilemap.layer_index("Ground"), Some(layer));
+
+    assert_eq!(tilemap.set_tile(layer, 1, 2, Some(Tile::new(3))), None);
+    assert_eq!(
+        tilemap.set_tile(layer, 1, 2, Some(Tile::new(1))),
+        Some(Tile::new(3))
+    );
+    assert_eq!(tilemap.set_tile(layer, 4, 0, Some(Tile::new(1))), None);
+    assert_eq!(tilemap.get_tile(layer, 4, 0), None);
+    assert_eq!(tilemap.get_tile(1, 1, 2), None);
+
+    assert_eq!(tilemap.world_to_tile(Vec2::new(-1.0, 15.9)), (1, 2));
+    assert_eq!(tilemap.world_to_tile(Vec2::new(-4.5, 9.0)), (-1, -1));
+    assert_eq!(tilemap.tile_to_world(1, 2), Vec2::new(-2.0, 14.0));
+    assert_eq!(tilemap.tile_center(1, 2), Vec2::new(-1.0, 15.0));
+    assert_eq!(
+        tilemap.tile_at(layer, Vec2::new(-1.0, 15.9)),
+        Some(Tile::new(1))
+    );
+    assert_eq!(
+        tilemap.layer(layer).unwrap().tiles().collect::<Vec<_>>(),
+        [(1, 2, Tile::new(1))]
+    );
+}
+
+#[test]
+fn quads_follow_tiles_and_layers() {
+    let mut tilemap = tilemap(2, 2, Vec2::new(2.0, 2.0));
+    let back = tilemap.add_layer("Back");
+    let front = tilemap.add_layer("Front");
+    tilemap.set_tile(back, 0, 0, Some(Tile::new(0)));
+    tilemap.set_tile(
+        front,
+        1,
+        1,
+        Some(Tile::new_with_flags(2, TileFlags::FLIP_HORIZONTAL)),
+    );
+    // Tiles missing from the tileset are skipped
+    tilemap.set_tile(front, 0, 1, Some(Tile::new(9)));
+    let layer = tilemap.layer_mut(front).unwrap();
+    layer.offset = Vec2::new(0.5, 0.0);
+    layer.color = Color32(1.0, 1.0, 1.0, 0.5);
+
+    let quads = tilemap.get_quads().unwrap();
+    assert_eq!(quads.len(), 2);
+    let positions: Vec<_> = quads[1].get_vertices().iter().map(|v| v.position).collect();
+    assert_eq!(positions, [[2.5, 4.0], [2.5, 2.0], [4.5, 2.0], [4.5, 4.0]]);
+    assert_eq!(quads[1].get_vertices()[0].uv, [1.0, 0.0]);
+    assert_eq!(quads[1].get_vertices()[0].color, [1.0, 1.0, 1.0, 0.5]);
+
+    tilemap.layer_mut(back).unwrap().visible = false;
+    assert_eq!(tilemap.get_quads().unwrap().len(), 1);
+}
+
+#[test]
+fn chunk_quads_are_reused_until_changed() {
+    let mut tilemap = tilemap(10, 10, Vec2::new(2.0, 2.0));
+    tilemap.set_chunk_size(4);
+    let layer = tilemap.add_layer("Ground");
+    for x in 0..10 {
+        tilemap.set_tile(layer, x, 9, Some(Tile::new(1)));
+    }
+    assert_eq!(tilemap.get_quads().unwrap().len(), 10);
+    assert_eq!(tilemap.built_chunk_count(), 9);
+
+    tilemap.set_tile(layer, 5, 5, Some(Tile::new(0)));
+    assert_eq!(tilemap.built_chunk_count(), 8);
+    // Setting the same tile again keeps the chunk
+    tilemap.get_quads();
+    tilemap.set_tile(layer, 5, 5, Some(Tile::new(0)));
+    assert_eq!(tilemap.built_chunk_count(), 9);
+
+    tilemap.clear_layer(layer);
+    assert_eq!(tilemap.built_chunk_count(), 0);
+    assert!(tilemap.get_quads().unwrap().is_empty());
+}
+
+#[test]
+fn quads_in_rect_are_culled_by_chunk() {
+    let mut tilemap = tilemap(8, 8, Vec2::new(2.0, 2.0));
+    tilemap.set_chunk_size(2);
+    let layer = tilemap.add_layer("Ground");
+    for y in 0..8 {
+        for x in 0..8 {
+            tilemap.set_tile(layer, x, y, Some(Tile::new(0)));
+        }
+    }
+    // Tiles are 2 units wide, so this covers tiles 1 to 2 along each axis
+    let quads = tilemap.get_quads_in_rect(Vec2::new(3.0, 3.0), Vec2::new(5.0, 5.0));
+    assert_eq!(quads.len(), 16);
+    assert_eq!(tilemap.built_chunk_count(), 4);
+
+    let outside = tilemap.get_quads_in_rect(Vec2::new(-10.0, -10.0), Vec2::new(-1.0, 40.0));
+    assert!(outside.is_empty());
+    let everything = tilemap.get_quads_in_rect(Vec2::new(-10.0, -10.0), Vec2::new(100.0, 100.0));
+    assert_eq!(everything.len(), 64);
+}
+
+#[test]
+fn animated_tiles() {
+    let animation = TileAnimation::new(vec![(1, 0.5), (3, 0.25)]);
+    assert_eq!(animation.duration(), 0.75);
+    assert_eq!(animation.tile_at(0.0), Some(1));
+    assert_eq!(animation.tile_at(0.6), Some(3));
+    assert_eq!(animation.tile_at(0.8), Some(1));
+    assert_eq!(TileAnimation::new(Vec::new()).tile_at(1.0), None);
+
+    let mut tilemap = tilemap(32, 16, Vec2::new(2.0, 2.0));
+    let mut tileset = tilemap.tileset().clone();
+    tileset.set_animation(0, Some(animation));
+    assert_eq!(tileset.resolve(0, 0.6), 3);
+    assert_eq!(tileset.resolve(2, 0.6), 2);
+    tilemap.set_tileset(tileset);
+    let layer = tilemap.add_layer("Water");
+    tilemap.set_tile(layer, 0, 0, Some(Tile::new(0)));
+    tilemap.set_tile(layer, 20, 0, Some(Tile::new(2)));
+    tilemap.get_quads();
+    assert_eq!(tilemap.built_chunk_count(), 2);
+
+    // Only the chunk with the animated tile is rebuilt, and only when its frame changes
+    tilemap.update(0.25);
+    assert_eq!(tilemap.built_chunk_count(), 2);
+    tilemap.update(0.3);
+    assert_eq!(tilemap.built_chunk_count(), 1);
+    assert!((tilemap.animation_time() - 0.55).abs() < 1e-6);
+    tilemap.get_quads();
+    assert_eq!(tilemap.built_chunk_count(), 2);
+
+    // Frames still change after running for a long time
+    tilemap.update(75_000_000.0);
+    tilemap.get_quads();
+    tilemap.update(0.6);
+    assert_eq!(tilemap.built_chunk_count(), 1);
+}
+
+#[test]
+fn empty_tilemaps_have_no_quads() {
+    for (width, height) in [(0, 0), (0, 4), (4, 0)] {
+        let mut tilemap = tilemap(width, height, Vec2::new(2.0, 2.0));
+        tilemap.add_layer("Ground");
+        let quads = tilemap.get_quads_in_rect(Vec2::new(-10.0, -10.0), Vec2::new(10.0, 10.0));
+        assert!(quads.is_empty());
+        assert!(tilemap.get_quads().unwrap().is_empty());
+    }
+}
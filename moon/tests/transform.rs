@@ -0,0 +1,119 @@
+mod common;
+
+use std::f32::consts::{FRAC_PI_2, PI};
+
+use moon_engine::renderer::Quad;
+use moon_engine::transform::{Transform, Transform2D};
+use moon_engine::{Vec2, Vec3, Vec4};
+
+use common::assert_close;
+
+#[test]
+fn transform_point_applies_scale_rotation_and_position() {
+    let transform =
+        Transform2D::new_with_position_and_rotation_and_scale(1.0, 2.0, FRAC_PI_2, 2.0, 3.0);
+    assert_close(
+        transform.transform_point(Vec2::new(1.0, 1.0)),
+        Vec2::new(-2.0, 4.0),
+    );
+    assert_close(
+        transform.transform_vector(Vec2::new(1.0, 0.0)),
+        Vec2::new(0.0, 2.0),
+    );
+}
+
+#[test]
+fn pivot_is_placed_at_position() {
+    let transform = Transform2D {
+        position: Vec2::new(5.0, 5.0),
+        rotation: 1.0,
+        scale: Vec2::new(2.0, 0.5),
+        pivot: Vec2::new(0.5, -0.5),
+    };
+    assert_close(
+        transform.transform_point(Vec2::new(0.5, -0.5)),
+        Vec2::new(5.0, 5.0),
+    );
+}
+
+#[test]
+fn inverse_round_trip() {
+    let transform = Transform2D {
+        position: Vec2::new(-3.0, 2.0),
+        rotation: 0.7,
+        scale: Vec2::new(2.0, 0.5),
+        pivot: Vec2::new(1.0, 0.0),
+    };
+    let point = Vec2::new(4.0, -1.5);
+    let local = transform.inverse_transform_point(point).unwrap();
+    assert_close(transform.transform_point(local), point);
+    assert!(Transform2D::new_with_scale(0.0, 1.0).inverse().is_none());
+}
+
+#[test]
+fn matrix_matches_matrix3() {
+    let transform = Transform2D::new_with_position_and_rotation_and_scale(1.0, -2.0, 0.3, 2.0, 1.5);
+    let point = transform.transform_point(Vec2::new(0.5, 0.25));
+    let homogeneous = transform.matrix() * Vec4::new(0.5, 0.25, 0.0, 1.0);
+    assert_close(homogeneous.xy(), point);
+}
+
+#[test]
+fn lerp_takes_shortest_rotation() {
+    let from = Transform2D::new_with_position_and_rotation_and_scale(0.0, 0.0, PI - 0.1, 1.0, 1.0);
+    let to = Transform2D::new_with_position_and_rotation_and_scale(2.0, 4.0, -PI + 0.1, 3.0, 1.0);
+    let half = from.lerp(&to, 0.5);
+    assert_close(half.position, Vec2::new(1.0, 2.0));
+    assert_close(half.scale, Vec2::new(2.0, 1.0));
+    assert!((half.rotation - PI).abs() < 1e-4);
+}
+
+#[test]
+fn quad_from_transform_uses_y_position() {
+    let quad = Quad::new_from_transform(Transform::new_with_position(Vec3::new(1.0, 5.0, 0.0)));
+    let vertices = quad.get_vertices();
+    let center = vertices.iter().fold(Vec2::zeros(), |sum, vertex| {
+        sum + Vec2::new(vertex.position[0], vertex.position[1])
+    }) / 4.0;
+    assert_close(center, Vec2::new(1.0, 5.0));
+}
+
+#[test]
+fn quad_from_transform2d_is_rotated() {
+    let transform =
+        Transform2D::new_with_position_and_rotation_and_scale(0.0, 0.0, FRAC_PI_2, 2.0, 1.0);
+    let vertices = Quad::new_from_transform2d(&transform).get_vertices();
+    let corner = Vec2::new(vertices[0].position[0], vertices[0].position[1]);
+    assert_close(corner, Vec2::new(-0.5, -1.0));
+}
+
+#[test]
+fn rotated_quad_keeps_its_uv_layout() {
+    let uv_at = |vertices: &[moon_engine::mesh::Vertex], corner: Vec2| {
+        vertices
+            .iter()
+            .find(|vertex| {
+                (Vec2::new(vertex.position[0], vertex.position[1]) - corner).norm() < 1e-4
+            })
+            .map(|vertex| vertex.uv)
+            .unwrap()
+    };
+    let white = moon_engine::Color32::WHITE;
+
+    // The corner at the lowest local Y coordinate is rotated, and keeps the first UV
+    let rotated = Quad::new_from_position_and_rotation_and_size_and_color(
+        0.0, 0.0, FRAC_PI_2, 2.0, 1.0, white,
+    )
+    .get_vertices();
+    assert_eq!(uv_at(&rotated, Vec2::new(0.5, -1.0)), [0.0, 0.0]);
+    assert_eq!(uv_at(&rotated, Vec2::new(-0.5, 1.0)), [1.0, 1.0]);
+
+    let unrotated =
+        Quad::new_from_position_and_rotation_and_size_and_color(0.0, 0.0, 0.0, 2.0, 1.0, white)
+            .get_vertices();
+    assert_eq!(uv_at(&unrotated, Vec2::new(-1.0, -0.5)), [0.0, 0.0]);
+
+    // Quads without a rotation start at the corner with the highest Y coordinate
+    let quad = Quad::new_from_position_and_size_and_color(0.0, 0.0, 2.0, 1.0, white).get_vertices();
+    assert_eq!(uv_at(&quad, Vec2::new(-1.0, 0.5)), [0.0, 0.0]);
+}
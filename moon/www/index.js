@@ -16,6 +16,9 @@ function init() {
 
     canvas.addEventListener("keydown", event => {
         app.input(event.which, true);
+        if (event.key.length === 1) {
+            app.text_input(event.key);
+        }
     });
     canvas.addEventListener("keyup", event => {
         app.input(event.which, false);
@@ -23,6 +26,37 @@ function init() {
     canvas.addEventListener("mousemove", event => {
         app.mouse_move(event.clientX, event.clientY);
     }, false);
+    canvas.addEventListener("mousedown", event => {
+        app.mouse_button(event.button, true);
+    });
+    canvas.addEventListener("mouseup", event => {
+        app.mouse_button(event.button, false);
+    });
+    canvas.addEventListener("wheel", event => {
+        app.mouse_wheel(event.deltaX, event.deltaY);
+    }, { passive: true });
+    canvas.addEventListener("touchstart", event => {
+        event.preventDefault();
+        for (const touch of event.changedTouches) {
+            app.touch_start(touch.identifier, touch.clientX, touch.clientY);
+        }
+    }, { passive: false });
+    canvas.addEventListener("touchmove", event => {
+        event.preventDefault();
+        for (const touch of event.changedTouches) {
+            app.touch_move(touch.identifier, touch.clientX, touch.clientY);
+        }
+    }, { passive: false });
+    canvas.addEventListener("touchend", event => {
+        for (const touch of event.changedTouches) {
+            app.touch_end(touch.identifier, touch.clientX, touch.clientY);
+        }
+    });
+    canvas.addEventListener("touchcancel", event => {
+        for (const touch of event.changedTouches) {
+            app.touch_cancel(touch.identifier);
+        }
+    });
 
     app.resize(window.innerWidth, window.innerHeight);
     function render() {